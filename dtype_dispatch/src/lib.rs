@@ -113,7 +113,7 @@ macro_rules! build_dtype_macros {
                 Self::$variant(inner) => {
                   if std::any::TypeId::of::<T>() == std::any::TypeId::of::<$t>() {
                     unsafe {
-                      Some(std::mem::transmute::<_, &$container<T>>(inner))
+                      Some(std::mem::transmute::<&$container<$t>, &$container<T>>(inner))
                     }
                   } else {
                     None
@@ -129,7 +129,7 @@ macro_rules! build_dtype_macros {
                 Self::$variant(inner) => {
                   if std::any::TypeId::of::<T>() == std::any::TypeId::of::<$t>() {
                     unsafe {
-                      Some(std::mem::transmute::<_, &mut $container<T>>(inner))
+                      Some(std::mem::transmute::<&mut $container<$t>, &mut $container<T>>(inner))
                     }
                   } else {
                     None
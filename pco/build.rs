@@ -3,20 +3,19 @@ use std::env;
 const DISMISS_WARNINGS_VAR: &str = "PCO_DISMISS_BUILD_WARNINGS";
 
 fn main() {
-  if cfg!(target_arch = "x86_64")
+  // Build scripts are compiled for the host, so we check the target's
+  // configuration via env vars instead of cfg!, e.g. for wasm builds.
+  let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap_or("".to_string());
+  let target_features = env::var("CARGO_CFG_TARGET_FEATURE").unwrap_or("".to_string());
+  let target_features = target_features.split(',').collect::<Vec<_>>();
+  if target_arch == "x86_64"
     && env::var("PROFILE").unwrap_or("".to_string()) == "release"
     && env::var(DISMISS_WARNINGS_VAR).unwrap_or("".to_string()) != "1"
   {
-    let mut missing_instructions = Vec::new();
-    if !cfg!(target_feature = "bmi1") {
-      missing_instructions.push("bmi1");
-    }
-    if !cfg!(target_feature = "bmi2") {
-      missing_instructions.push("bmi2");
-    }
-    if !cfg!(target_feature = "avx2") {
-      missing_instructions.push("avx2");
-    }
+    let missing_instructions = ["bmi1", "bmi2", "avx2"]
+      .into_iter()
+      .filter(|instruction| !target_features.contains(instruction))
+      .collect::<Vec<_>>();
 
     if !missing_instructions.is_empty() {
       println!(
@@ -49,9 +49,9 @@ mod tests {
     let table_size = 1 << encoder.size_log();
 
     // DECODE
-    compressed.extend(&vec![0; 100]);
+    compressed.extend(&[0; 100]);
     let mut reader = BitReader::new(&compressed, expected_byte_len, 0);
-    let decoder = Decoder::new(spec, &vec![]);
+    let decoder = Decoder::new(spec, &[]);
     let mut decoded = Vec::new();
     let mut state_idx = final_state - table_size;
     for _ in 0..symbols.len() {
@@ -101,9 +101,7 @@ mod tests {
     };
     let mut symbols = Vec::new();
     for _ in 0..100 {
-      for _ in 0..7 {
-        symbols.push(0);
-      }
+      symbols.extend(vec![0; 7]);
       symbols.push(1);
     }
     // let the symbols be A and B
@@ -0,0 +1,263 @@
+use crate::ans;
+use crate::chunk_config::{ChunkConfig, ModeSpec};
+use crate::constants::{Bitlen, MAX_ANS_BITS};
+use crate::data_types::{Float, Latent, Number};
+use crate::errors::{PcoError, PcoResult};
+use crate::macros::match_latent_enum;
+use crate::metadata::format_version::FormatVersion;
+use crate::metadata::{Bin, ChunkLatentVarMeta, ChunkMeta, DynBins, DynLatent, Mode};
+use crate::wrapped::chunk_compressor;
+use half::f16;
+
+/// A mode, delta encoding, and set of bins learned from one dataset, which
+/// can be applied verbatim to compress others.
+///
+/// Compressing with a layout skips mode, delta, and bin analysis entirely,
+/// trading some compression ratio for speed and predictability.
+/// Create one with [`learn_layout`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct BinLayout {
+  pub(crate) mode_spec: ModeSpec,
+  pub(crate) meta: ChunkMeta,
+}
+
+impl BinLayout {
+  /// Returns the chunk metadata that every chunk compressed with this layout
+  /// will have.
+  pub fn meta(&self) -> &ChunkMeta {
+    &self.meta
+  }
+}
+
+// the spec that reproduces exactly this mode
+pub(crate) fn mode_spec_for(mode: Mode) -> ModeSpec {
+  match mode {
+    Mode::Classic => ModeSpec::Classic,
+    Mode::IntMult(base) => match_latent_enum!(
+      base,
+      DynLatent<L>(base) => { ModeSpec::TryIntMult(base.to_u64()) }
+    ),
+    Mode::FloatMult(base) => {
+      let base_f64 = match base {
+        DynLatent::U16(base) => f16::from_latent_ordered(base).to_f64(),
+        DynLatent::U32(base) => f32::from_latent_ordered(base).to_f64(),
+        DynLatent::U64(base) => f64::from_latent_ordered(base),
+      };
+      ModeSpec::TryFloatMult(base_f64)
+    }
+    Mode::FloatQuant(k) => ModeSpec::TryFloatQuant(k),
+  }
+}
+
+pub(crate) fn bin_upper<L: Latent>(bin: &Bin<L>) -> L {
+  if bin.offset_bits >= L::BITS {
+    return L::MAX;
+  }
+  let span = (L::ONE << bin.offset_bits) - L::ONE;
+  if L::MAX - bin.lower < span {
+    L::MAX
+  } else {
+    bin.lower + span
+  }
+}
+
+fn escape_bin<L: Latent>(lower: L, upper: L) -> Bin<L> {
+  let diff = upper - lower;
+  let offset_bits = if diff == L::ZERO {
+    0
+  } else {
+    L::BITS - diff.leading_zeros()
+  };
+  Bin {
+    weight: 0,
+    lower,
+    offset_bits,
+  }
+}
+
+// Adds bins of minimal weight over every gap between the learned bins, so
+// that any latent can be encoded, even ones the sample never saw.
+pub(crate) fn cover_latent_space<L: Latent>(
+  bins: &[Bin<L>],
+  ans_size_log: Bitlen,
+) -> (Vec<Bin<L>>, Bitlen) {
+  let mut covering = Vec::with_capacity(2 * bins.len() + 1);
+  let mut next_uncovered = Some(L::ZERO);
+  for bin in bins {
+    let Some(uncovered) = next_uncovered else {
+      break;
+    };
+    if bin.lower > uncovered {
+      covering.push(escape_bin(uncovered, bin.lower - L::ONE));
+    }
+    covering.push(*bin);
+    let upper = bin_upper(bin);
+    next_uncovered = (upper < L::MAX).then(|| upper + L::ONE);
+  }
+  if let Some(uncovered) = next_uncovered {
+    covering.push(escape_bin(uncovered, L::MAX));
+  }
+
+  if covering.len() == 1 {
+    covering[0].weight = 1;
+    return (covering, 0);
+  }
+
+  // learned bins get double their weight and escapes get 1, so escapes
+  // take up little of the table
+  let counts = covering
+    .iter()
+    .map(|bin| (2 * bin.weight).max(1))
+    .collect::<Vec<_>>();
+  let total_count = counts.iter().map(|&count| count as usize).sum();
+  let (ans_size_log, weights) = ans::quantize_weights(
+    counts,
+    total_count,
+    (ans_size_log + 1).min(MAX_ANS_BITS),
+  );
+  for (bin, weight) in covering.iter_mut().zip(weights) {
+    bin.weight = weight;
+  }
+  (covering, ans_size_log)
+}
+
+/// Learns a [`BinLayout`] from a representative sample of numbers,
+/// compressing it with the given configuration.
+///
+/// The layout covers every possible number, so it can compress any data of
+/// the same type, but compresses best on data distributed like the sample.
+/// It only uses delta encodings the default format version supports.
+///
+/// Will return an error if the config is invalid or the sample is empty.
+pub fn learn_layout<T: Number>(sample: &[T], config: &ChunkConfig) -> PcoResult<BinLayout> {
+  let cc = chunk_compressor::new(sample, config, &FormatVersion::default())?;
+  let meta = cc.meta();
+  let per_latent_var = meta.per_latent_var.as_ref().map(|_, var_meta| {
+    match_latent_enum!(
+      &var_meta.bins,
+      DynBins<L>(bins) => {
+        let (bins, ans_size_log) = cover_latent_space(bins, var_meta.ans_size_log);
+        ChunkLatentVarMeta {
+          ans_size_log,
+          bins: DynBins::new(bins).unwrap(),
+        }
+      }
+    )
+  });
+  if per_latent_var
+    .as_ref()
+    .enumerated()
+    .iter()
+    .any(|(_, var_meta)| var_meta.ans_size_log > MAX_ANS_BITS)
+  {
+    return Err(PcoError::invalid_argument(
+      "sample has too many sparse bins to learn a layout from",
+    ));
+  }
+
+  Ok(BinLayout {
+    mode_spec: mode_spec_for(meta.mode),
+    meta: ChunkMeta {
+      mode: meta.mode,
+      delta_encoding: meta.delta_encoding,
+      per_latent_var,
+    },
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  use super::*;
+  use crate::standalone::{compress_with_layout, simple_decompress};
+  use crate::DeltaSpec;
+
+  #[test]
+  fn test_cover_latent_space() {
+    let bins = vec![
+      Bin {
+        weight: 3,
+        lower: 10_u32,
+        offset_bits: 2,
+      },
+      Bin {
+        weight: 1,
+        lower: 100,
+        offset_bits: 4,
+      },
+    ];
+    let (covering, ans_size_log) = cover_latent_space(&bins, 2);
+    assert_eq!(covering.len(), 5);
+    assert_eq!(
+      covering.iter().map(|bin| bin.weight).sum::<u32>(),
+      1 << ans_size_log
+    );
+
+    // escape bins may overlap the next bin, but must leave no gaps
+    assert_eq!(covering[0].lower, 0);
+    for pair in covering.windows(2) {
+      assert!(pair[0].lower < pair[1].lower);
+      assert!(bin_upper(&pair[0]) >= pair[1].lower - 1);
+    }
+    assert_eq!(bin_upper(covering.last().unwrap()), u32::MAX);
+    assert!(covering.iter().all(|bin| bin.weight > 0));
+  }
+
+  #[test]
+  fn test_stale_layout_roundtrips() -> PcoResult<()> {
+    let config = ChunkConfig::default();
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let sample = (0..5000)
+      .map(|_| rng.gen_range(0..1000_i64) * 7)
+      .collect::<Vec<_>>();
+    let layout = learn_layout(&sample, &config)?;
+    assert!(matches!(
+      layout.meta().mode,
+      Mode::IntMult(_)
+    ));
+
+    // new data drifts out of the sample's range and breaks its pattern
+    let mut nums = (0..3000)
+      .map(|_| rng.gen_range(0..2000_i64) * 7 + 1000)
+      .collect::<Vec<_>>();
+    nums.extend([i64::MIN, i64::MAX, -3, 0]);
+    let compressed = compress_with_layout(&nums, &layout, &config)?;
+    assert_eq!(simple_decompress::<i64>(&compressed)?, nums);
+    Ok(())
+  }
+
+  #[test]
+  fn test_stale_delta_layout_roundtrips() -> PcoResult<()> {
+    let config = ChunkConfig::default().with_delta_spec(DeltaSpec::TryConsecutive(1));
+    let sample = (0..2000).map(|i| i as f64 * 0.5).collect::<Vec<_>>();
+    let layout = learn_layout(&sample, &config)?;
+
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let nums = (0..2000)
+      .map(|_| rng.gen_range(0..1 << 24) as f64 * -0.3)
+      .chain([f64::NAN, f64::INFINITY, -0.0])
+      .collect::<Vec<_>>();
+    let compressed = compress_with_layout(&nums, &layout, &config)?;
+    let decompressed = simple_decompress::<f64>(&compressed)?;
+    assert_eq!(
+      decompressed.iter().map(|x| x.to_bits()).collect::<Vec<_>>(),
+      nums.iter().map(|x| x.to_bits()).collect::<Vec<_>>(),
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_layout_type_mismatch() -> PcoResult<()> {
+    let config = ChunkConfig::default();
+    let layout = learn_layout(&(0..1000).collect::<Vec<u64>>(), &config)?;
+    let result = compress_with_layout(&[1_u32, 2, 3], &layout, &config);
+    assert!(matches!(
+      result.unwrap_err().kind,
+      crate::errors::ErrorKind::InvalidArgument
+    ));
+    Ok(())
+  }
+}
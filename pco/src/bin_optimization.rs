@@ -13,6 +13,7 @@ type Partitioning = Vec<(usize, usize)>;
 
 const SINGLE_BIN_SPEEDUP_WORTH_IN_BITS_PER_NUM: f32 = 0.1;
 const TRIVIAL_OFFSET_SPEEDUP_WORTH_IN_BITS_PER_NUM: f32 = 0.1;
+const WIDE_OFFSET_SLOWDOWN_COST_IN_BITS_PER_NUM: f32 = 0.5;
 
 /// Fast approximate base-2 logarithm for **positive, finite, non-denormal** `x`.
 /// Inspired by `log2_raw` from the `fast-math` crate by Huon Wilson.
@@ -21,6 +22,8 @@ const TRIVIAL_OFFSET_SPEEDUP_WORTH_IN_BITS_PER_NUM: f32 = 0.1;
 fn log2_approx(x: f32) -> f32 {
   const Z: f32 = 0.674; // cutoff for local approximation in [z, 2z]
   const SIGNIF_MASK: u32 = 0x7FFFFF;
+  // f32::to_bits isn't const until Rust 1.83, which is newer than our MSRV
+  #[allow(unknown_lints, unnecessary_transmutes)]
   const Z_SIGNIF: u32 = unsafe { mem::transmute::<f32, u32>(Z) } & SIGNIF_MASK;
   const B: f32 = 2.0 / Z;
   const C: f32 = -B / (6.0 * Z);
@@ -51,10 +54,15 @@ fn bin_cost<L: Latent>(
   upper: L,
   count: Weight,
   total_count_log2: f32,
+  soft_max_offset_bits: Bitlen,
 ) -> f32 {
   let count = count as f32;
   let ans_cost = total_count_log2 - log2_approx(count);
-  let offset_cost = bits::bits_to_encode_offset(upper - lower) as f32;
+  let offset_bits = bits::bits_to_encode_offset(upper - lower);
+  let mut offset_cost = offset_bits as f32;
+  if offset_bits > soft_max_offset_bits {
+    offset_cost += WIDE_OFFSET_SLOWDOWN_COST_IN_BITS_PER_NUM;
+  }
   bin_meta_cost + (ans_cost + offset_cost) * count
 }
 
@@ -62,6 +70,7 @@ fn calc_trivial_offset_partitioning<L: Latent>(
   bin_meta_cost: f32,
   total_count_log2: f32,
   bins: &[HistogramBin<L>],
+  soft_max_offset_bits: Bitlen,
 ) -> Option<(Partitioning, f32)> {
   if bins.iter().any(|bin| bin.lower != bin.upper) {
     return None;
@@ -77,6 +86,7 @@ fn calc_trivial_offset_partitioning<L: Latent>(
         bin.upper,
         bin.count as Weight,
         total_count_log2,
+        soft_max_offset_bits,
       )
     })
     .sum();
@@ -106,6 +116,7 @@ fn rewind_best_partitioning(best_js: &[usize], n_bins: usize) -> Partitioning {
 fn choose_optimized_partitioning<L: Latent>(
   bins: &[HistogramBin<L>],
   ans_size_log: Bitlen,
+  soft_max_offset_bits: Bitlen,
 ) -> Partitioning {
   let mut c = 0;
   let mut c_counts_and_best_costs = Vec::with_capacity(bins.len() + 1);
@@ -142,6 +153,7 @@ fn choose_optimized_partitioning<L: Latent>(
           upper,
           c_count_i - c_count_j,
           total_count_log2,
+          soft_max_offset_bits,
         );
       if cost < best_cost {
         best_cost = cost;
@@ -155,20 +167,26 @@ fn choose_optimized_partitioning<L: Latent>(
   let &(_, best_cost) = c_counts_and_best_costs.last().unwrap();
 
   let single_bin_partitioning = vec![(0_usize, bins.len() - 1)];
+  // A single bin needs no ANS decoding, which more than makes up for wide
+  // offsets, so it gets no penalty for them.
   let single_bin_cost = bin_cost(
     bin_meta_cost,
     lowers[0],
     uppers[bins.len() - 1],
     total_count,
     total_count_log2,
+    L::BITS,
   );
   if single_bin_cost < best_cost + SINGLE_BIN_SPEEDUP_WORTH_IN_BITS_PER_NUM * total_count as f32 {
     return single_bin_partitioning;
   }
 
-  if let Some((trivial_offset_partitioning, trivial_offset_cost)) =
-    calc_trivial_offset_partitioning(bin_meta_cost, total_count_log2, bins)
-  {
+  if let Some((trivial_offset_partitioning, trivial_offset_cost)) = calc_trivial_offset_partitioning(
+    bin_meta_cost,
+    total_count_log2,
+    bins,
+    soft_max_offset_bits,
+  ) {
     if trivial_offset_cost
       < best_cost + TRIVIAL_OFFSET_SPEEDUP_WORTH_IN_BITS_PER_NUM * total_count as f32
     {
@@ -179,11 +197,15 @@ fn choose_optimized_partitioning<L: Latent>(
   rewind_best_partitioning(&best_js, bins.len())
 }
 
+// Bins with offsets wider than `soft_max_offset_bits` are penalized, since
+// they're slower to decode.
 pub fn optimize_bins<L: Latent>(
   bins: &[HistogramBin<L>],
   ans_size_log: Bitlen,
+  soft_max_offset_bits: Option<Bitlen>,
 ) -> Vec<BinCompressionInfo<L>> {
-  let partitioning = choose_optimized_partitioning(bins, ans_size_log);
+  let soft_max_offset_bits = soft_max_offset_bits.unwrap_or(L::BITS);
+  let partitioning = choose_optimized_partitioning(bins, ans_size_log, soft_max_offset_bits);
   let mut res = Vec::with_capacity(partitioning.len());
   for (symbol, &(j, i)) in partitioning.iter().enumerate() {
     let count: usize = bins.iter().take(i + 1).skip(j).map(|bin| bin.count).sum();
@@ -222,7 +244,7 @@ mod tests {
       make_bin(100, 65, 74), // same density as next bin (but different from previous ones)
       make_bin(50, 75, 79),
     ];
-    let optimized = optimize_bins(&infos, 10);
+    let optimized = optimize_bins(&infos, 10, None);
     assert_eq!(
       optimized,
       vec![
@@ -257,7 +279,7 @@ mod tests {
     // bits), but it's disadvantageous to combine them because the 2nd bin has
     // so much higher density
     let infos = vec![make_bin(1000, 0, 150), make_bin(1000, 200, 200)];
-    let optimized = optimize_bins(&infos, 10);
+    let optimized = optimize_bins(&infos, 10, None);
     assert_eq!(
       optimized,
       vec![
@@ -279,6 +301,27 @@ mod tests {
     )
   }
 
+  #[test]
+  fn test_bin_optimization_soft_max_offset_bits() {
+    // a dense bin of small numbers, then uniformly dense bins that would
+    // normally get combined into one wide bin
+    let mut infos = vec![make_bin(1600, 0, 0)];
+    for i in 1..16 {
+      infos.push(make_bin(
+        100,
+        i << 28,
+        (i << 28) | ((1 << 28) - 1),
+      ));
+    }
+    let optimized = optimize_bins(&infos, 10, None);
+    assert_eq!(optimized.len(), 2);
+    assert_eq!(optimized[1].offset_bits, 32);
+
+    let optimized = optimize_bins(&infos, 10, Some(30));
+    assert!(optimized.len() >= 5);
+    assert!(optimized.iter().all(|info| info.offset_bits <= 30));
+  }
+
   #[test]
   fn test_log2_approx() {
     // should be exact at powers of 2
@@ -273,8 +273,11 @@ impl<R: BetterBufRead> BitReaderBuilder<R> {
     f: F,
   ) -> PcoResult<Y> {
     let mut reader = self.build()?;
-    let res = f(&mut reader)?;
+    let res = f(&mut reader);
+    // If we read past the end of the data, whatever `f` did was based on
+    // padding, so running out of data is the real error.
     let final_bit_idx = reader.bit_idx_safe()?;
+    let res = res?;
     self.update(final_bit_idx);
     Ok(res)
   }
@@ -1,5 +1,7 @@
 use crate::constants::{Bitlen, DEFAULT_MAX_PAGE_N};
+use crate::data_types::{Latent, Number, NumberType};
 use crate::errors::{PcoError, PcoResult};
+use crate::metadata::LatentVarKey;
 use crate::DEFAULT_COMPRESSION_LEVEL;
 
 /// Specifies how Pco should choose a [`mode`][crate::metadata::Mode] to compress this
@@ -9,6 +11,21 @@ use crate::DEFAULT_COMPRESSION_LEVEL;
 /// `Classic` if the provided mode is especially bad.
 /// It is recommended that you only use the `Try*` variants if you know for
 /// certain that your numbers benefit from that mode.
+///
+/// Every [`Mode`][crate::metadata::Mode] can be forced by some spec, which
+/// makes it possible to compare modes on the same data:
+///
+/// | spec            | mode         | number types | valid arguments                            |
+/// |-----------------|--------------|--------------|--------------------------------------------|
+/// | `Classic`       | `Classic`    | all          | -                                          |
+/// | `TryIntMult`    | `IntMult`    | integers     | `1 <= base <=` the latent type's max       |
+/// | `TryFloatMult`  | `FloatMult`  | floats       | finite, nonzero `base` in the float type   |
+/// | `TryFloatQuant` | `FloatQuant` | floats       | `1 <= k <=` the float type's mantissa bits |
+///
+/// Forcing a mode on the wrong number type or with an invalid argument
+/// returns an invalid argument error.
+/// A valid but poorly suited mode still compresses losslessly, possibly
+/// after falling back to `Classic`.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 #[non_exhaustive]
 pub enum ModeSpec {
@@ -22,6 +39,8 @@ pub enum ModeSpec {
   Classic,
   /// Tries using `FloatMult` mode with a given `base`.
   ///
+  /// This can force a base that automatic detection would not find, e.g.
+  /// 0.001 for timestamps in seconds with millisecond precision.
   /// Only applies to floating-point types.
   TryFloatMult(f64),
   /// Tries using `FloatQuant` mode with `k` bits of quantization.
@@ -62,12 +81,68 @@ pub enum DeltaSpec {
   /// For instance, 1st order is just regular delta encoding, 2nd is
   /// deltas-of-deltas, etc.
   /// It is legal to use 0th order, but it is identical to `None`.
+  ///
+  /// Deltas are taken modulo 2^k for k-bit numbers and reconstructed by
+  /// wrapping addition, so a counter that rolls over, e.g. from near
+  /// `u64::MAX` to near 0, costs one small delta instead of a huge one.
   TryConsecutive(usize),
   /// Tries delta encoding according to an extra latent variable of "lookback".
   ///
   /// This can improve compression ratio when there are nontrivial patterns in
   /// your numbers, but reduces compression speed substantially.
   TryLookback,
+  /// Tries XORing each number's bits with the previous number's, similar to
+  /// Gorilla float compression.
+  ///
+  /// This is experimental and is mainly useful for comparison.
+  /// `Auto` will also consider it for classic mode.
+  TryXor,
+  /// Like `TryConsecutive`, but lets each page use its own order from 0 up to
+  /// the given one.
+  ///
+  /// Each page's order is chosen by a cheap estimate and stored in the page's
+  /// metadata.
+  /// This helps when the data's smoothness changes over the chunk, e.g.
+  /// stretches of a smooth signal followed by noise.
+  TryConsecutivePerPage(usize),
+}
+
+/// Specifies what Pco should prioritize when choosing how to compress a
+/// chunk.
+///
+/// Other than `Ratio`, each objective also compresses the chunk without the
+/// automatically chosen mode and/or delta encoding, then picks whichever
+/// candidate has the lowest
+/// [estimated decode cost][crate::metadata::ChunkMeta::estimated_decode_cost]
+/// among those within a size tolerance of the smallest.
+/// Only the aspects left on `Auto` in the `ModeSpec` and `DeltaSpec` are
+/// varied, and trying the extra candidates takes up to 4 times as long to
+/// compress.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Objective {
+  /// Chooses whatever compresses smallest.
+  #[default]
+  Ratio,
+  /// Gives up at most 3% of compressed size for faster decompression.
+  BalancedSpeed,
+  /// Gives up at most 10% of compressed size for faster decompression.
+  ///
+  /// On typical data this costs nothing or a few percent, since the fast
+  /// candidates are only chosen when they decode meaningfully faster.
+  DecodeSpeed,
+}
+
+impl Objective {
+  // the largest acceptable ratio of a candidate's size to the smallest
+  // candidate's
+  pub(crate) fn max_size_ratio(&self) -> f64 {
+    match self {
+      Objective::Ratio => 1.0,
+      Objective::BalancedSpeed => 1.03,
+      Objective::DecodeSpeed => 1.1,
+    }
+  }
 }
 
 // TODO consider adding a "lossiness" spec that allows dropping secondary latent
@@ -91,6 +166,44 @@ pub struct ChunkConfig {
   /// The meaning of the compression levels is subject to change with
   /// new releases.
   pub compression_level: usize,
+  /// Optional overrides of `compression_level` for each latent variable
+  /// produced by the mode (default: empty).
+  ///
+  /// These only take effect when the chosen mode produces multiple latent
+  /// variables (e.g. `IntMult`, `FloatMult`, or `FloatQuant`); any latent
+  /// variable without an override falls back to `compression_level`.
+  /// The entries are ordered as
+  /// * 0: the primary latent variable (e.g. the multiplier for `IntMult` and
+  ///   `FloatMult`, or the quantums for `FloatQuant`),
+  /// * 1: the secondary latent variable (the adjustment for all of the above).
+  ///
+  /// By default, secondary latents are compressed with a reduced effort for
+  /// speed, so overriding them to a high level can improve compression ratio
+  /// when they are high-entropy.
+  pub latent_compression_levels: Vec<Option<usize>>,
+  /// An optional offset bit width that bins should preferably not exceed
+  /// (default: none).
+  ///
+  /// Very wide offsets decode more slowly than narrow ones; in particular,
+  /// offsets over 57 bits need an extra word read per number.
+  /// When set, the bin optimizer treats each number in a wider bin as costing
+  /// an extra half bit, so it prefers keeping more bins with narrower offsets
+  /// over merging them.
+  /// This trades a little compression ratio for decompression speed.
+  /// Chunks that would use a single bin are unaffected, since they skip
+  /// ANS decoding and are already fast.
+  /// It is only a preference: if the finest bins the compression level
+  /// provides are already wider than this, they stay that way, so it works
+  /// best with higher compression levels.
+  pub soft_max_offset_bits: Option<u32>,
+  /// The seed for the random subsampling pco does while analyzing the data
+  /// (default: 0).
+  ///
+  /// Compression is deterministic: the same version of pco given the same
+  /// numbers, config, and seed always produces the same bytes.
+  /// Different seeds may lead to different choices of mode, and therefore
+  /// different bytes.
+  pub analysis_seed: u64,
   /// Specifies how the mode should be determined.
   ///
   /// See [`Mode`](crate::metadata::Mode) to understand what modes are.
@@ -107,15 +220,37 @@ pub struct ChunkConfig {
   /// Specifies how the chunk should be split into pages (default: equal pages
   /// up to 2^18 numbers each).
   pub paging_spec: PagingSpec,
+  /// An optional range known to contain every number in the chunk, used
+  /// only for validation (default: none).
+  ///
+  /// When set, compression returns an InvalidArgument error if any number
+  /// falls outside the range or the range was built for a different number
+  /// type.
+  /// This is useful for catching upstream bugs when the range is externally
+  /// guaranteed, e.g. by a sensor's clamping.
+  /// It costs an extra pass over the numbers and is never used for binning:
+  /// pco's bins already adapt to the range of the data itself, so this does
+  /// not change the compressed bytes.
+  pub value_range: Option<ValueRange>,
+  /// What to prioritize when choosing among modes and delta encodings
+  /// (default: `Ratio`).
+  ///
+  /// See [`Objective`] for details.
+  pub objective: Objective,
 }
 
 impl Default for ChunkConfig {
   fn default() -> Self {
     Self {
       compression_level: DEFAULT_COMPRESSION_LEVEL,
+      latent_compression_levels: Vec::new(),
+      soft_max_offset_bits: None,
+      analysis_seed: 0,
       mode_spec: ModeSpec::default(),
       delta_spec: DeltaSpec::default(),
       paging_spec: PagingSpec::EqualPagesUpTo(DEFAULT_MAX_PAGE_N),
+      value_range: None,
+      objective: Objective::default(),
     }
   }
 }
@@ -127,6 +262,25 @@ impl ChunkConfig {
     self
   }
 
+  /// Sets
+  /// [`latent_compression_levels`][ChunkConfig::latent_compression_levels].
+  pub fn with_latent_compression_levels(mut self, levels: Vec<Option<usize>>) -> Self {
+    self.latent_compression_levels = levels;
+    self
+  }
+
+  /// Sets [`soft_max_offset_bits`][ChunkConfig::soft_max_offset_bits].
+  pub fn with_soft_max_offset_bits(mut self, bits: Option<u32>) -> Self {
+    self.soft_max_offset_bits = bits;
+    self
+  }
+
+  /// Sets [`analysis_seed`][ChunkConfig::analysis_seed].
+  pub fn with_analysis_seed(mut self, seed: u64) -> Self {
+    self.analysis_seed = seed;
+    self
+  }
+
   /// Sets [`mode_spec`][ChunkConfig::mode_spec].
   pub fn with_mode_spec(mut self, mode_spec: ModeSpec) -> Self {
     self.mode_spec = mode_spec;
@@ -144,9 +298,98 @@ impl ChunkConfig {
     self.paging_spec = paging_spec;
     self
   }
+
+  /// Sets [`value_range`][ChunkConfig::value_range].
+  pub fn with_value_range(mut self, value_range: Option<ValueRange>) -> Self {
+    self.value_range = value_range;
+    self
+  }
+
+  /// Sets [`objective`][ChunkConfig::objective].
+  pub fn with_objective(mut self, objective: Objective) -> Self {
+    self.objective = objective;
+    self
+  }
+
+  pub(crate) fn latent_compression_level_override(&self, key: LatentVarKey) -> Option<usize> {
+    let idx = match key {
+      LatentVarKey::Delta => return None,
+      LatentVarKey::Primary => 0,
+      LatentVarKey::Secondary => 1,
+    };
+    self.latent_compression_levels.get(idx).copied().flatten()
+  }
+}
+
+/// An inclusive range of numbers of a single type, used to validate
+/// numbers via [`ChunkConfig::value_range`].
+///
+/// Numbers are ordered the way pco orders them internally, which for floats
+/// places negative NaNs below negative infinity and positive NaNs above
+/// positive infinity, and -0.0 just below +0.0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValueRange {
+  number_type_byte: u8,
+  lower: u64,
+  upper: u64,
+}
+
+impl ValueRange {
+  /// Creates a range from `min` to `max`, inclusive.
+  ///
+  /// Will return an InvalidArgument error if `min` is greater than `max`.
+  pub fn new<T: Number>(min: T, max: T) -> PcoResult<Self> {
+    let lower = min.to_latent_ordered();
+    let upper = max.to_latent_ordered();
+    if lower > upper {
+      return Err(PcoError::invalid_argument(format!(
+        "value range min {:?} may not exceed max {:?}",
+        min, max,
+      )));
+    }
+    Ok(Self {
+      number_type_byte: T::NUMBER_TYPE_BYTE,
+      lower: lower.to_u64(),
+      upper: upper.to_u64(),
+    })
+  }
+
+  pub(crate) fn validate<T: Number>(&self, nums: &[T]) -> PcoResult<()> {
+    if self.number_type_byte != T::NUMBER_TYPE_BYTE {
+      return Err(PcoError::invalid_argument(format!(
+        "value range was built for {:?} but numbers have type {:?}",
+        NumberType::from_descriminant(self.number_type_byte),
+        NumberType::from_descriminant(T::NUMBER_TYPE_BYTE),
+      )));
+    }
+
+    let lower = T::L::from_u64(self.lower);
+    let upper = T::L::from_u64(self.upper);
+    let outlier = nums.iter().position(|num| {
+      let latent = num.to_latent_ordered();
+      latent < lower || latent > upper
+    });
+    if let Some(i) = outlier {
+      return Err(PcoError::invalid_argument(format!(
+        "number {:?} at index {} is outside the value range of {:?} to {:?}",
+        nums[i],
+        i,
+        T::from_latent_ordered(lower),
+        T::from_latent_ordered(upper),
+      )));
+    }
+    Ok(())
+  }
 }
 
 /// `PagingSpec` specifies how a chunk is split into pages.
+///
+/// Each page stores its own delta encoding state, so pages double as
+/// checkpoints for random access: in the wrapped format, a page can be
+/// decompressed given only its chunk's metadata and the page's bytes, without
+/// replaying any earlier pages.
+/// Smaller pages make random access cheaper at the cost of slightly more
+/// per-page state.
 #[derive(Clone, Debug)]
 #[non_exhaustive]
 pub enum PagingSpec {
@@ -160,6 +403,13 @@ pub enum PagingSpec {
   /// Will return an InvalidArgument error during compression if
   /// any of the counts are 0 or the sum does not equal the chunk count.
   Exact(Vec<usize>),
+  /// Put all the numbers into a single page.
+  ///
+  /// In standalone files, this means the whole file gets a single chunk,
+  /// minimizing metadata overhead for small arrays.
+  /// Will return an InvalidArgument error during compression if the count
+  /// exceeds the maximum chunk size of 2^24 numbers.
+  SinglePage,
 }
 
 impl Default for PagingSpec {
@@ -198,6 +448,12 @@ impl PagingSpec {
         res
       }
       PagingSpec::Exact(n_per_page) => n_per_page.to_vec(),
+      PagingSpec::SinglePage => {
+        if n == 0 {
+          return Ok(Vec::new());
+        }
+        vec![n]
+      }
     };
 
     let summed_n: usize = n_per_page.iter().sum();
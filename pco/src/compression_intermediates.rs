@@ -16,6 +16,8 @@ pub struct PageInfoVar {
 #[derive(Clone, Debug)]
 pub struct PageInfo {
   pub page_n: usize,
+  // only present for ConsecutivePerPage delta encoding
+  pub delta_order: Option<usize>,
   pub per_latent_var: PerLatentVar<PageInfoVar>,
 }
 
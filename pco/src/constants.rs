@@ -9,7 +9,17 @@ pub(crate) type Weight = u32;
 pub(crate) type DeltaLookback = u32;
 
 // compatibility
-pub const CURRENT_FORMAT_VERSION: u8 = 3;
+/// The newest format version this release of pco can read and write.
+pub const CURRENT_FORMAT_VERSION: u8 = 4;
+/// The format version written by default.
+///
+/// This keeps files readable by older releases of pco unless they opt into
+/// newer features; see
+/// [`wrapped::FileCompressor::with_format_version`][crate::wrapped::FileCompressor::with_format_version].
+pub const DEFAULT_FORMAT_VERSION: u8 = 3;
+// Older versions encoded some things differently, and we only write the
+// current encodings.
+pub(crate) const MIN_WRITABLE_FORMAT_VERSION: u8 = 3;
 
 // bit lengths
 pub const BITS_TO_ENCODE_ANS_SIZE_LOG: Bitlen = 4;
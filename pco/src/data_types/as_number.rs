@@ -0,0 +1,187 @@
+use std::fmt::{Debug, Display};
+use std::slice;
+
+use crate::data_types::{ModeAndLatents, Number};
+use crate::describers::LatentDescriber;
+use crate::errors::PcoResult;
+use crate::metadata::dyn_latents::DynLatents;
+use crate::metadata::per_latent_var::PerLatentVar;
+use crate::metadata::{ChunkMeta, Mode};
+use crate::ChunkConfig;
+
+/// **unstable API** Trait for compressing custom data types by delegating to
+/// one of pco's built-in [`Number`]s.
+///
+/// Every `AsNumber` type implements `Number`, so it works with all of pco's
+/// compression and decompression functions.
+/// Pco chooses modes and delta encodings exactly as it would for
+/// `Self::Number`.
+///
+/// # Safety
+///
+/// `Self` must have the same size, alignment, and bit validity as
+/// `Self::Number`, e.g. by being a `#[repr(transparent)]` wrapper around it.
+/// Pco reinterprets slices of `Self` as slices of `Self::Number` and their
+/// latents in place.
+///
+/// # Example
+///
+/// A fixed-point type with 4 decimal places, stored as an `i64`:
+///
+/// ```
+/// use std::fmt;
+/// use pco::data_types::AsNumber;
+/// use pco::standalone::{simple_decompress, simpler_compress};
+/// use pco::DEFAULT_COMPRESSION_LEVEL;
+/// # use pco::errors::PcoResult;
+///
+/// #[derive(Clone, Copy, Debug, Default, PartialEq)]
+/// #[repr(transparent)]
+/// struct Fixed4(i64);
+///
+/// impl fmt::Display for Fixed4 {
+///   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///     write!(f, "{}e-4", self.0)
+///   }
+/// }
+///
+/// unsafe impl AsNumber for Fixed4 {
+///   const NUMBER_TYPE_BYTE: u8 = 200;
+///   type Number = i64;
+///
+///   fn to_number(self) -> i64 {
+///     self.0
+///   }
+///   fn from_number(number: i64) -> Self {
+///     Fixed4(number)
+///   }
+/// }
+///
+/// # fn main() -> PcoResult<()> {
+/// let prices = vec![Fixed4(12_3400), Fixed4(12_3500), Fixed4(12_2900)];
+/// let compressed = simpler_compress(&prices, DEFAULT_COMPRESSION_LEVEL)?;
+/// assert_eq!(simple_decompress::<Fixed4>(&compressed)?, prices);
+/// # Ok(())
+/// # }
+/// ```
+pub unsafe trait AsNumber:
+  Copy + Debug + Display + Default + PartialEq + Send + Sync + 'static
+{
+  /// A number from 1-255 that identifies this type in standalone files.
+  ///
+  /// This must differ from the `NUMBER_TYPE_BYTE` of every built-in
+  /// [`Number`] (listed in [`NumberType`][crate::data_types::NumberType]) and
+  /// of any other custom types you decompress alongside it.
+  /// Decompressing data as the wrong type then returns an error.
+  const NUMBER_TYPE_BYTE: u8;
+
+  /// The built-in number type this delegates to.
+  type Number: Number;
+
+  fn to_number(self) -> Self::Number;
+  fn from_number(number: Self::Number) -> Self;
+}
+
+fn as_numbers<T: AsNumber>(nums: &[T]) -> &[T::Number] {
+  // guaranteed sound by the safety contract of AsNumber
+  unsafe { slice::from_raw_parts(nums.as_ptr() as *const T::Number, nums.len()) }
+}
+
+fn as_numbers_mut<T: AsNumber>(nums: &mut [T]) -> &mut [T::Number] {
+  // guaranteed sound by the safety contract of AsNumber
+  unsafe {
+    slice::from_raw_parts_mut(
+      nums.as_mut_ptr() as *mut T::Number,
+      nums.len(),
+    )
+  }
+}
+
+impl<T: AsNumber> Number for T {
+  const NUMBER_TYPE_BYTE: u8 = <T as AsNumber>::NUMBER_TYPE_BYTE;
+
+  type L = <T::Number as Number>::L;
+
+  fn get_latent_describers(meta: &ChunkMeta) -> PerLatentVar<LatentDescriber> {
+    T::Number::get_latent_describers(meta)
+  }
+
+  fn mode_is_valid(mode: Mode) -> bool {
+    T::Number::mode_is_valid(mode)
+  }
+
+  fn choose_mode_and_split_latents(
+    nums: &[Self],
+    config: &ChunkConfig,
+  ) -> PcoResult<ModeAndLatents> {
+    T::Number::choose_mode_and_split_latents(as_numbers(nums), config)
+  }
+
+  fn from_latent_ordered(l: Self::L) -> Self {
+    T::from_number(T::Number::from_latent_ordered(l))
+  }
+
+  fn to_latent_ordered(self) -> Self::L {
+    self.to_number().to_latent_ordered()
+  }
+
+  fn join_latents(mode: Mode, primary: &mut [Self::L], secondary: Option<&DynLatents>) {
+    T::Number::join_latents(mode, primary, secondary)
+  }
+
+  fn transmute_to_latents(slice: &mut [Self]) -> &mut [Self::L] {
+    T::Number::transmute_to_latents(as_numbers_mut(slice))
+  }
+
+  fn transmute_to_latent(self) -> Self::L {
+    self.to_number().transmute_to_latent()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fmt;
+
+  use super::*;
+  use crate::standalone::{simple_compress, simple_decompress};
+
+  #[derive(Clone, Copy, Debug, Default, PartialEq)]
+  #[repr(transparent)]
+  struct Cents(i64);
+
+  impl Display for Cents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      write!(f, "{}c", self.0)
+    }
+  }
+
+  unsafe impl AsNumber for Cents {
+    const NUMBER_TYPE_BYTE: u8 = 200;
+    type Number = i64;
+
+    fn to_number(self) -> i64 {
+      self.0
+    }
+    fn from_number(number: i64) -> Self {
+      Cents(number)
+    }
+  }
+
+  #[test]
+  fn test_delegates_to_number() -> PcoResult<()> {
+    let nums = (0..2000).map(|i| Cents((i % 37) * 25)).collect::<Vec<_>>();
+    let config = &ChunkConfig::default();
+    let compressed = simple_compress(&nums, config)?;
+    let raw_compressed = simple_compress(as_numbers(&nums), config)?;
+    // only the number type bytes should differ
+    assert_eq!(compressed.len(), raw_compressed.len());
+    assert_eq!(
+      simple_decompress::<Cents>(&compressed)?,
+      nums
+    );
+
+    // decompressing as the underlying type is a mismatch
+    assert!(simple_decompress::<i64>(&compressed).is_err());
+    Ok(())
+  }
+}
@@ -40,7 +40,11 @@ fn choose_mode_and_split_latents<F: Float>(
         split_fn: Box::new(|nums| split_latents_classic(nums)),
       });
 
-      if let Some(sample) = sampling::choose_sample(nums, filter_sample) {
+      if let Some(sample) = sampling::choose_sample(
+        nums,
+        chunk_config.analysis_seed,
+        filter_sample,
+      ) {
         bids.extend(float_mult_utils::compute_bid(&sample));
         bids.extend(float_quant_utils::compute_bid(&sample));
       }
@@ -53,6 +57,12 @@ fn choose_mode_and_split_latents<F: Float>(
     ModeSpec::TryFloatMult(base_f64) => {
       let base = F::from_f64(base_f64);
       let mode = Mode::float_mult(base);
+      if !F::mode_is_valid(mode) {
+        return Err(PcoError::invalid_argument(format!(
+          "float mult base must be finite and nonzero for this type (was {})",
+          base_f64,
+        )));
+      }
       let float_mult_config = FloatMultConfig {
         base,
         inv_base: base.inv(),
@@ -60,10 +70,19 @@ fn choose_mode_and_split_latents<F: Float>(
       let latents = float_mult_utils::split_latents(nums, float_mult_config);
       Ok((mode, latents))
     }
-    ModeSpec::TryFloatQuant(k) => Ok((
-      Mode::FloatQuant(k),
-      float_quant_utils::split_latents(nums, k),
-    )),
+    ModeSpec::TryFloatQuant(k) => {
+      if !F::mode_is_valid(Mode::FloatQuant(k)) {
+        return Err(PcoError::invalid_argument(format!(
+          "float quant bits must be between 1 and {} for this type (was {})",
+          F::PRECISION_BITS,
+          k,
+        )));
+      }
+      Ok((
+        Mode::FloatQuant(k),
+        float_quant_utils::split_latents(nums, k),
+      ))
+    }
     ModeSpec::TryIntMult(_) => Err(PcoError::invalid_argument(
       "unable to use int mult mode on floats",
     )),
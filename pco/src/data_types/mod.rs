@@ -1,10 +1,11 @@
 use std::fmt::{Debug, Display};
 use std::hash::Hash;
 use std::ops::{
-  Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, Div, DivAssign, Mul, MulAssign, Neg,
-  Rem, RemAssign, Shl, Shr, Sub, SubAssign,
+  Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, Div, DivAssign, Mul, MulAssign,
+  Neg, Rem, RemAssign, Shl, Shr, Sub, SubAssign,
 };
 
+pub use as_number::AsNumber;
 pub use dynamic::{LatentType, NumberType};
 pub use split_latents::SplitLatents;
 
@@ -16,6 +17,7 @@ use crate::metadata::per_latent_var::PerLatentVar;
 use crate::metadata::{ChunkMeta, Mode};
 use crate::ChunkConfig;
 
+mod as_number;
 mod dynamic;
 mod floats;
 mod signeds;
@@ -94,6 +96,7 @@ pub trait Latent:
   + BitOr<Output = Self>
   + BitAndAssign
   + BitOrAssign
+  + BitXor<Output = Self>
   + Display
   + Div<Output = Self>
   + DivAssign
@@ -145,7 +148,8 @@ pub trait Latent:
 ///   wouldn't preserve ordering and would cause pco to fail. In this example,
 ///   one needs to flip the sign bit and, if negative, the rest of the bits.
 ///
-/// Custom data types (defined outside of pco) are not currently supported.
+/// Custom data types (defined outside of pco) are best supported by
+/// implementing [`AsNumber`] instead, which delegates to a built-in type.
 pub trait Number: Copy + Debug + Display + Default + PartialEq + Send + Sync + 'static {
   /// A number from 1-255 that corresponds to the number's data type.
   ///
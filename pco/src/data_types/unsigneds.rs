@@ -13,7 +13,7 @@ pub fn choose_mode_and_split_latents<T: Number>(
 ) -> PcoResult<ModeAndLatents> {
   match config.mode_spec {
     ModeSpec::Auto => {
-      if let Some(base) = int_mult_utils::choose_base(nums) {
+      if let Some(base) = int_mult_utils::choose_base(nums, config.analysis_seed) {
         let mode = Mode::int_mult(base);
         let latents = int_mult_utils::split_latents(nums, base);
         Ok((mode, latents))
@@ -27,6 +27,13 @@ pub fn choose_mode_and_split_latents<T: Number>(
       "unable to use float mode for ints",
     )),
     ModeSpec::TryIntMult(base_u64) => {
+      if base_u64 == 0 || base_u64 > T::L::MAX.to_u64() {
+        return Err(PcoError::invalid_argument(format!(
+          "int mult base must be between 1 and {} for this type (was {})",
+          T::L::MAX,
+          base_u64,
+        )));
+      }
       let base = T::L::from_u64(base_u64);
       let mode = Mode::IntMult(DynLatent::new(base).unwrap());
       let latents = int_mult_utils::split_latents(nums, base);
@@ -72,6 +72,30 @@ pub(crate) fn decode_consecutive_in_place<L: Latent>(delta_moments: &mut [L], la
   }
 }
 
+// Used for a single page, so we return the initial state.
+// Like the other encode in place functions, this leaves a junk latent at the
+// front.
+// XORs are already unsigned-ish, so we don't toggle the center.
+#[inline(never)]
+fn encode_xor_in_place<L: Latent>(latents: &mut [L]) -> Vec<L> {
+  let state = vec![latents.first().copied().unwrap_or(L::ZERO)];
+  for i in (1..latents.len()).rev() {
+    latents[i] = latents[i] ^ latents[i - 1];
+  }
+  state
+}
+
+// used for a single batch, so we mutate the state
+#[inline(never)]
+pub(crate) fn decode_xor_in_place<L: Latent>(state: &mut [L], latents: &mut [L]) {
+  let prev = &mut state[0];
+  for xor in latents.iter_mut() {
+    let tmp = *xor;
+    *xor = *prev;
+    *prev = *prev ^ tmp;
+  }
+}
+
 // there are 3 types of proposed lookbacks:
 // * brute force: just try the most recent few latents
 // * repeating: try the most recent lookbacks we actually used
@@ -297,13 +321,66 @@ pub fn decode_with_lookbacks_in_place<L: Latent>(
   has_oob_lookbacks
 }
 
+// Sorts the latents in the process.
+fn estimated_bit_size<L: Latent>(latents: &mut [L]) -> usize {
+  if latents.is_empty() {
+    return 0;
+  }
+
+  let (_, &mut median, _) = latents.select_nth_unstable(latents.len() / 2);
+  latents
+    .iter()
+    .map(|&l| {
+      let dist = if l >= median { l - median } else { median - l };
+      (L::BITS - dist.leading_zeros()) as usize
+    })
+    .sum()
+}
+
+// Cheaply estimates which consecutive delta order from 0 to `max_order`
+// makes the latents in `range` smallest, based on how many bits each order's
+// deltas span around their median, plus the delta state it would store.
+pub fn choose_consecutive_order(
+  max_order: usize,
+  latents: &DynLatents,
+  range: Range<usize>,
+) -> usize {
+  match_latent_enum!(
+    latents,
+    DynLatents<L>(inner) => {
+      let mut deltas = inner[range].to_vec();
+      let mut scratch = deltas.clone();
+      let mut best_order = 0;
+      let mut best_size = estimated_bit_size(&mut scratch);
+      for order in 1..max_order + 1 {
+        if deltas.len() <= 1 {
+          break;
+        }
+
+        deltas = deltas.windows(2).map(|w| w[1].wrapping_sub(w[0])).collect();
+        scratch.clear();
+        scratch.extend(deltas.iter().map(|l| l.toggle_center()));
+        let size = order * L::BITS as usize + estimated_bit_size(&mut scratch);
+        if size < best_size {
+          best_order = order;
+          best_size = size;
+        }
+      }
+      best_order
+    }
+  )
+}
+
 pub fn compute_delta_latent_var(
   delta_encoding: DeltaEncoding,
   primary_latents: &mut DynLatents,
   range: Range<usize>,
 ) -> Option<DynLatents> {
   match delta_encoding {
-    DeltaEncoding::None | DeltaEncoding::Consecutive(_) => None,
+    DeltaEncoding::None
+    | DeltaEncoding::Consecutive(_)
+    | DeltaEncoding::Xor
+    | DeltaEncoding::ConsecutivePerPage(_) => None,
     DeltaEncoding::Lookback(config) => {
       let res = match_latent_enum!(
         primary_latents,
@@ -328,13 +405,14 @@ pub fn encode_in_place(
     DynLatents<L>(inner) => {
       let delta_state = match delta_encoding {
         DeltaEncoding::None => Vec::<L>::new(),
-        DeltaEncoding::Consecutive(config) => {
+        DeltaEncoding::Consecutive(config) | DeltaEncoding::ConsecutivePerPage(config) => {
           encode_consecutive_in_place(config.order, &mut inner[range])
         }
         DeltaEncoding::Lookback(config) => {
           let lookbacks = delta_latents.unwrap().downcast_ref::<DeltaLookback>().unwrap();
           encode_with_lookbacks_in_place(config, lookbacks, &mut inner[range])
         }
+        DeltaEncoding::Xor => encode_xor_in_place(&mut inner[range]),
       };
       DynLatents::new(delta_state).unwrap()
     }
@@ -356,9 +434,7 @@ mod tests {
     // but for decoding we need junk deltas at the end.
     let mut deltas_to_decode = Vec::new();
     deltas_to_decode.extend(&deltas[order..]);
-    for _ in 0..order {
-      deltas_to_decode.push(1337);
-    }
+    deltas_to_decode.extend(vec![1337; order]);
     let mut deltas = deltas_to_decode;
 
     // decode in two parts to show we keep state properly
@@ -369,6 +445,24 @@ mod tests {
     assert_eq!(&deltas[3..5], &orig_latents[3..5]);
   }
 
+  #[test]
+  fn test_xor_encode_decode() {
+    let orig_latents: Vec<u32> = vec![2, 2, 1, u32::MAX, 0];
+    let mut xors = orig_latents.clone();
+    let mut state = encode_xor_in_place(&mut xors);
+    assert_eq!(state, vec![2]);
+    assert_eq!(&xors[1..], &[0, 3, u32::MAX - 1, u32::MAX]);
+
+    // move the junk latent from the front to the end
+    let mut xors_to_decode = xors[1..].to_vec();
+    xors_to_decode.push(1337);
+
+    // decode in two parts to show we keep state properly
+    decode_xor_in_place::<u32>(&mut state, &mut xors_to_decode[..2]);
+    decode_xor_in_place::<u32>(&mut state, &mut xors_to_decode[2..]);
+    assert_eq!(xors_to_decode, orig_latents);
+  }
+
   #[test]
   fn test_lookback_encode_decode() {
     let original_latents = {
@@ -405,9 +499,7 @@ mod tests {
     // but for decoding we need junk deltas at the end.
     let mut deltas_to_decode = Vec::<u32>::new();
     deltas_to_decode.extend(&deltas[state_n..]);
-    for _ in 0..state_n {
-      deltas_to_decode.push(1337);
-    }
+    deltas_to_decode.extend(vec![1337; state_n]);
 
     let (mut window_buffer, mut pos) = new_lookback_window_buffer_and_pos(config, &state);
     assert_eq!(pos, window_n);
@@ -1,7 +1,11 @@
 use crate::constants::{Bitlen, DeltaLookback};
-use crate::data_types::{Float, Latent, Number};
+use crate::data_types::{Float, Latent, LatentType, Number};
+use crate::errors::PcoResult;
+use crate::macros::match_latent_enum;
 use crate::metadata::per_latent_var::PerLatentVar;
-use crate::metadata::{ChunkMeta, DeltaEncoding, DynLatent, LatentVarKey, Mode};
+use crate::metadata::{ChunkMeta, DeltaEncoding, DynBins, DynLatent, LatentVarKey, Mode};
+use crate::wrapped::FileCompressor;
+use crate::ChunkConfig;
 use std::marker::PhantomData;
 
 /// Interprets the meaning of latent variables and values from [`ChunkMeta`].
@@ -22,9 +26,86 @@ pub trait DescribeLatent {
 
 pub type LatentDescriber = Box<dyn DescribeLatent>;
 
+/// The latent variables pco would use to compress some numbers, as returned
+/// by [`describe_latents`].
+#[derive(Clone, Debug)]
+pub struct LatentPlan {
+  /// How each number gets split into primary and possibly secondary latents.
+  pub mode: Mode,
+  /// How those latents get delta encoded, possibly adding a delta latent
+  /// variable.
+  pub delta_encoding: DeltaEncoding,
+  pub latent_vars: PerLatentVar<LatentVarPlan>,
+}
+
+/// A description of one latent variable in a [`LatentPlan`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LatentVarPlan {
+  /// What the latents represent, e.g. `"multiplier delta [x1000]"`.
+  pub description: String,
+  /// See [`DescribeLatent::latent_units`].
+  pub units: String,
+  pub latent_type: LatentType,
+  /// How many bins pco used to encode these latents.
+  pub n_bins: usize,
+  /// The average number of offset bits each latent costs beyond its bin's
+  /// tANS code, weighted by how often each bin occurs.
+  ///
+  /// Pre-transforms that reduce this for the primary latent variable usually
+  /// improve compression.
+  pub avg_offset_bits: f64,
+}
+
+/// Returns the latent variables pco would choose to compress these numbers
+/// with the given config.
+///
+/// Pco first converts each number into unsigned latents in a way that
+/// preserves ordering: unsigned integers stay as they are, signed integers
+/// have their sign bit flipped so that their minimum maps to 0, and floats
+/// have their sign bit flipped (and, if negative, the rest of their bits
+/// too).
+/// The chosen [`Mode`] then splits those into one or two latent variables, and
+/// the [`DeltaEncoding`] may take wrapping differences of them; the docs of
+/// those types describe the formulas.
+/// [`Number::to_latent_ordered`] exposes the first step directly.
+///
+/// This runs the same analysis as compression, so it costs about as much as
+/// compressing `sample`; passing a representative sample rather than all the
+/// data is usually enough.
+/// Will return an error if the config is invalid or the sample is empty.
+pub fn describe_latents<T: Number>(sample: &[T], config: &ChunkConfig) -> PcoResult<LatentPlan> {
+  let meta = FileCompressor::default()
+    .chunk_compressor(sample, config)?
+    .meta()
+    .clone();
+  let describers = T::get_latent_describers(&meta);
+  let latent_vars = meta
+    .per_latent_var
+    .as_ref()
+    .zip_exact(describers.as_ref())
+    .map(|_, (var_meta, describer)| LatentVarPlan {
+      description: describer.latent_var(),
+      units: describer.latent_units(),
+      latent_type: var_meta.latent_type(),
+      n_bins: match_latent_enum!(
+        &var_meta.bins,
+        DynBins<L>(bins) => { bins.len() }
+      ),
+      avg_offset_bits: var_meta.avg_offset_bits(),
+    });
+  Ok(LatentPlan {
+    mode: meta.mode,
+    delta_encoding: meta.delta_encoding,
+    latent_vars,
+  })
+}
+
 fn delta_latent_describer(delta_encoding: DeltaEncoding) -> Option<LatentDescriber> {
   match delta_encoding {
-    DeltaEncoding::None | DeltaEncoding::Consecutive(_) => None,
+    DeltaEncoding::None
+    | DeltaEncoding::Consecutive(_)
+    | DeltaEncoding::Xor
+    | DeltaEncoding::ConsecutivePerPage(_) => None,
     DeltaEncoding::Lookback(_) => {
       let describer = IntDescriber {
         description: "lookback".to_string(),
@@ -43,6 +124,12 @@ pub(crate) fn match_classic_mode<T: Number>(
 ) -> Option<PerLatentVar<LatentDescriber>> {
   let primary: LatentDescriber = match (meta.mode, meta.delta_encoding) {
     (Mode::Classic, DeltaEncoding::None) => Box::new(ClassicDescriber::<T>::default()),
+    (Mode::Classic, DeltaEncoding::Xor) => Box::new(IntDescriber {
+      description: "xor".to_string(),
+      units: "".to_string(),
+      center: T::L::ZERO,
+      is_signed: false,
+    }),
     (Mode::Classic, _) => {
       centered_delta_describer::<T::L>("delta".to_string(), delta_units.to_string())
     }
@@ -285,3 +372,40 @@ impl<F: Float> DescribeLatent for FloatQuantDescriber<F> {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::DeltaSpec;
+
+  #[test]
+  fn test_describe_latents() -> PcoResult<()> {
+    // multiples of 1000 with occasional adjustments
+    let nums = (0..2000_u32)
+      .map(|i| (i * 7919 % 1999) * 1000 + (i % 10 == 0) as u32)
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default().with_delta_spec(DeltaSpec::None);
+    let plan = describe_latents(&nums, &config)?;
+    assert_eq!(
+      plan.mode,
+      Mode::IntMult(DynLatent::U32(1000))
+    );
+    assert_eq!(plan.delta_encoding, DeltaEncoding::None);
+    let latent_vars = plan.latent_vars;
+    assert!(latent_vars.delta.is_none());
+    assert_eq!(
+      latent_vars.primary.description,
+      "multiplier [x1000]"
+    );
+    assert_eq!(
+      latent_vars.primary.latent_type,
+      LatentType::U32
+    );
+    let secondary = latent_vars.secondary.unwrap();
+    assert_eq!(secondary.description, "adjustment");
+    assert!(secondary.avg_offset_bits <= 2.0);
+
+    assert!(describe_latents::<u32>(&[], &config).is_err());
+    Ok(())
+  }
+}
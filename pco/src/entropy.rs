@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::compression_intermediates::DissectedPageVar;
+use crate::data_types::Latent;
+
+/// Empirical entropy estimates of one latent variable in a chunk, alongside
+/// the bits pco actually spent encoding it.
+///
+/// All quantities are in bits per latent, and the latents are the ones pco
+/// entropy codes, i.e. after splitting by mode and delta encoding.
+/// The actual bits include ANS and offset bits, but not chunk or page
+/// metadata.
+/// Only available with the `entropy` feature.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct LatentEntropy {
+  /// The count of latents.
+  pub n: usize,
+  /// The entropy of the latents' distribution, ignoring their order.
+  pub zero_order_bits: f64,
+  /// The entropy of each latent given the one before it.
+  pub first_order_bits: f64,
+  /// The bits pco actually used.
+  pub actual_bits: f64,
+}
+
+fn entropy_of_counts<K: Eq + Hash>(counts: HashMap<K, usize>, n: usize) -> f64 {
+  counts
+    .into_values()
+    .map(|count| {
+      let p = count as f64 / n as f64;
+      -p * p.log2()
+    })
+    .sum()
+}
+
+fn zero_order_bits<L: Latent>(latents: &[L]) -> f64 {
+  let mut counts = HashMap::new();
+  for &latent in latents {
+    *counts.entry(latent).or_insert(0) += 1;
+  }
+  entropy_of_counts(counts, latents.len())
+}
+
+// H(X_i | X_{i-1}) = H(X_{i-1}, X_i) - H(X_{i-1}), over consecutive pairs
+fn first_order_bits<L: Latent>(latents: &[L]) -> f64 {
+  if latents.len() < 2 {
+    return 0.0;
+  }
+  let n_pairs = latents.len() - 1;
+  let mut pair_counts = HashMap::new();
+  let mut prev_counts = HashMap::new();
+  for pair in latents.windows(2) {
+    *pair_counts.entry((pair[0], pair[1])).or_insert(0) += 1;
+    *prev_counts.entry(pair[0]).or_insert(0) += 1;
+  }
+  // clamping avoids tiny negative results from float error
+  (entropy_of_counts(pair_counts, n_pairs) - entropy_of_counts(prev_counts, n_pairs)).max(0.0)
+}
+
+pub(crate) fn latent_entropy<L: Latent>(
+  latents: &[L],
+  dissected_pages: &[DissectedPageVar],
+) -> LatentEntropy {
+  let n = latents.len();
+  let actual_bits = dissected_pages
+    .iter()
+    .map(|page| {
+      page
+        .ans_bits
+        .iter()
+        .map(|&bits| bits as usize)
+        .sum::<usize>()
+        + page
+          .offset_bits
+          .iter()
+          .map(|&bits| bits as usize)
+          .sum::<usize>()
+    })
+    .sum::<usize>();
+  let per_latent = |bits: usize| {
+    if n == 0 {
+      0.0
+    } else {
+      bits as f64 / n as f64
+    }
+  };
+  LatentEntropy {
+    n,
+    zero_order_bits: zero_order_bits(latents),
+    first_order_bits: first_order_bits(latents),
+    actual_bits: per_latent(actual_bits),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  use super::*;
+  use crate::errors::PcoResult;
+  use crate::wrapped::FileCompressor;
+  use crate::ChunkConfig;
+
+  #[test]
+  fn test_entropies() {
+    let alternating = [0_u32, 1].repeat(500);
+    assert!((zero_order_bits(&alternating) - 1.0).abs() < 1E-9);
+    assert_eq!(first_order_bits(&alternating), 0.0);
+
+    let quarters = (0..1000_u32).map(|i| i % 4).collect::<Vec<_>>();
+    assert!((zero_order_bits(&quarters) - 2.0).abs() < 1E-9);
+    assert_eq!(first_order_bits(&quarters), 0.0);
+    assert_eq!(zero_order_bits::<u32>(&[]), 0.0);
+  }
+
+  #[test]
+  fn test_chunk_latent_entropy() -> PcoResult<()> {
+    // uniformly random bytes should cost about 8 bits apiece
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let nums = (0..10_000)
+      .map(|_| rng.gen_range(0..256))
+      .collect::<Vec<u32>>();
+    let cc = FileCompressor::default().chunk_compressor(&nums, &ChunkConfig::default())?;
+    let entropy = cc.latent_entropy()?;
+    let primary = entropy.primary;
+    assert_eq!(primary.n, nums.len());
+    assert!(primary.zero_order_bits > 7.9 && primary.zero_order_bits <= 8.0);
+    assert!(primary.actual_bits >= primary.zero_order_bits * 0.99);
+    assert!(primary.actual_bits < 8.1);
+    assert!(entropy.secondary.is_none());
+    Ok(())
+  }
+}
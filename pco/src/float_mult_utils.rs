@@ -77,6 +77,8 @@ const SNAP_THRESHOLD_DECIMAL_RELATIVE: f64 = 0.01;
 const INTERESTING_TRAILING_ZEROS: u32 = 5;
 const REQUIRED_TRAILING_ZEROS_FREQUENCY: f64 = 0.5;
 const REQUIRED_GCD_PAIR_FREQUENCY: f64 = 0.001;
+const MAX_DECIMAL_CANDIDATE_DIGITS: i32 = 18;
+const DECIMAL_DIGITS_SAMPLE_N: usize = 64;
 
 fn insignificant_float_to<F: Float>(x: F) -> F {
   let spare_precision_bits = F::PRECISION_BITS.saturating_sub(REQUIRED_PRECISION_BITS) as i32;
@@ -335,14 +337,72 @@ fn choose_config<F: Float>(sample: &[F]) -> Option<FloatMultConfig<F>> {
   choose_config_by_trailing_zeros(sample).or_else(|| choose_config_by_euclidean(sample))
 }
 
+// Returns the count of decimal digits after the point that `x` needs, if
+// that's few enough for its mult to be exactly representable.
+fn decimal_digits<F: Float>(x: F, max_mult: F) -> Option<i32> {
+  (1..=MAX_DECIMAL_CANDIDATE_DIGITS)
+    .map(|digits| (digits, F::from_f64(10.0_f64.powi(digits))))
+    .take_while(|&(_, inv_base)| x.abs() * inv_base < max_mult)
+    .find(|&(_, inv_base)| (x * inv_base).round() / inv_base == x)
+    .map(|(digits, _)| digits)
+}
+
+// Large numbers with small fractional steps (e.g. epoch seconds with
+// millisecond precision) defeat the Euclidean algorithm: each number's
+// rounding error is too large relative to `base` for pairs to reveal it.
+// Such data is nearly always decimal, so we count the decimal digits of the
+// first few numbers and try the most common and largest counts as bases.
+// Noisy floats rarely have few enough digits, so they skip this entirely.
+#[inline(never)]
+fn choose_config_by_decimal_candidates<F: Float>(
+  sample: &[F],
+) -> Option<(FloatMultConfig<F>, f64)> {
+  let max_mult = F::exp2(F::PRECISION_BITS as i32);
+  let digits_sample = &sample[..min(sample.len(), DECIMAL_DIGITS_SAMPLE_N)];
+  let mut digit_counts = digits_sample
+    .iter()
+    .filter_map(|&x| decimal_digits(x, max_mult))
+    .collect::<Vec<_>>();
+  if digit_counts.is_empty() || digit_counts.len() * 2 < digits_sample.len() {
+    return None;
+  }
+
+  digit_counts.sort_unstable();
+  let max_digits = *digit_counts.last().unwrap();
+  let mut most_common_digits = max_digits;
+  let mut max_count = 0;
+  for run in digit_counts.chunk_by(|a, b| a == b) {
+    if run.len() > max_count {
+      most_common_digits = run[0];
+      max_count = run.len();
+    }
+  }
+
+  let mut candidates = vec![most_common_digits, max_digits];
+  candidates.dedup();
+  let mut best = None;
+  for digits in candidates {
+    let config = FloatMultConfig::from_inv_base(F::from_f64(10.0_f64.powi(digits)));
+    if let Some(bits_saved) = bits_saved_per_num_over_classic(config, sample) {
+      if !matches!(best, Some((_, best_bits_saved)) if best_bits_saved >= bits_saved) {
+        best = Some((config, bits_saved));
+      }
+    }
+  }
+  best
+}
+
 pub(crate) fn compute_bid<F: Float>(sample: &[F]) -> Option<Bid<F>> {
-  choose_config(sample).and_then(|config| {
+  let found = choose_config(sample).and_then(|config| {
     let bits_saved_per_num = bits_saved_per_num_over_classic(config, sample)?;
-    Some(Bid {
-      mode: Mode::float_mult(config.base),
-      bits_saved_per_num,
-      split_fn: Box::new(move |nums| split_latents(nums, config)),
-    })
+    Some((config, bits_saved_per_num))
+  });
+  let (config, bits_saved_per_num) =
+    found.or_else(|| choose_config_by_decimal_candidates(sample))?;
+  Some(Bid {
+    mode: Mode::float_mult(config.base),
+    bits_saved_per_num,
+    split_fn: Box::new(move |nums| split_latents(nums, config)),
   })
 }
 
@@ -446,7 +506,7 @@ mod test {
 
   #[test]
   fn test_candidate_euclidean() {
-    let mut nums = vec![0.0, 2.0_f32.powi(-100), 0.0037, 1.0001].repeat(5);
+    let mut nums = [0.0, 2.0_f32.powi(-100), 0.0037, 1.0001].repeat(5);
     nums.push(f32::MAX);
     assert_almost_equal(
       choose_config_by_euclidean(&nums).unwrap().base,
@@ -458,7 +518,7 @@ mod test {
 
   #[test]
   fn test_gcd_euclidean() {
-    let nums = vec![0.0, 2.0_f32.powi(-100), 0.0037, 1.0001, f32::MAX].repeat(5);
+    let nums = [0.0, 2.0_f32.powi(-100), 0.0037, 1.0001, f32::MAX].repeat(5);
     assert_almost_equal(
       approx_sample_gcd_euclidean(&nums).unwrap(),
       1.0E-4,
@@ -466,7 +526,7 @@ mod test {
       "10^-4 adverse",
     );
 
-    let nums = vec![0.0, 2.0_f32.powi(-100), 0.0037, 0.0049, 1.0001, f32::MAX].repeat(5);
+    let nums = [0.0, 2.0_f32.powi(-100), 0.0037, 0.0049, 1.0001, f32::MAX].repeat(5);
     assert_almost_equal(
       approx_sample_gcd_euclidean(&nums).unwrap(),
       1.0E-4,
@@ -493,6 +553,30 @@ mod test {
     )
   }
 
+  #[test]
+  fn test_decimal_candidates() {
+    let sample = (0..100)
+      .map(|i| (1_700_000_000_000_u64 + i * 37) as f64 / 1000.0)
+      .collect::<Vec<_>>();
+    let (config, _) = choose_config_by_decimal_candidates(&sample).unwrap();
+    assert_eq!(
+      config,
+      FloatMultConfig::from_inv_base(1000.0)
+    );
+
+    let sample = (0..100).map(|i| (i as f32 + 1.0) * TAU).collect::<Vec<_>>();
+    assert!(choose_config_by_decimal_candidates(&sample).is_none());
+
+    let max_mult = <f64 as Float>::exp2(f64::PRECISION_BITS as i32);
+    assert_eq!(decimal_digits(1.25_f64, max_mult), Some(2));
+    assert_eq!(
+      decimal_digits(1_700_000_000.037_f64, max_mult),
+      Some(3)
+    );
+    // typical noisy floats need too many digits
+    assert_eq!(decimal_digits(0.1_f64 + 0.2, max_mult), None);
+  }
+
   #[test]
   fn test_snap() {
     assert_eq!(
@@ -154,7 +154,7 @@ mod test {
   #[test]
   fn test_estimate_best_k() {
     // all but the last of these have 21 out of 23 mantissa bits zeroed
-    let mut sample = vec![1.0_f32, 1.25, -1.5, 1.75, -0.875, 0.75, 0.625].repeat(3);
+    let mut sample = [1.0_f32, 1.25, -1.5, 1.75, -0.875, 0.75, 0.625].repeat(3);
     sample.push(f32::from_bits(1.0_f32.to_bits() + 1));
     let (k, bits_saved) = estimate_best_k_and_bits_saved(&sample);
     assert_eq!(k, 21);
@@ -112,6 +112,11 @@ impl<L: Latent> LatentChunkCompressor<L> {
     })
   }
 
+  #[cfg(feature = "entropy")]
+  pub(crate) fn latents(&self) -> &[L] {
+    &self.latents
+  }
+
   pub fn dissect_page(&self, page_range: Range<usize>) -> DissectedPageVar {
     let uninit_dissected_page_var = |n, ans_default_state| {
       let ans_final_states = [ans_default_state; ANS_INTERLEAVING];
@@ -1,3 +1,4 @@
+use std::cmp::min;
 use std::fmt::Debug;
 
 use crate::ans::{AnsState, Spec};
@@ -158,6 +159,39 @@ impl<L: Latent> LatentPageDecompressor<L> {
     reader.bits_past_byte = final_bit_idx as Bitlen % 8;
   }
 
+  #[inline(never)]
+  unsafe fn decompress_selected_offsets<const MAX_U64S: usize>(
+    &mut self,
+    reader: &BitReader,
+    selected: &[usize],
+    dst: &mut [L],
+  ) {
+    let base_bit_idx = reader.bit_idx();
+    let src = reader.src;
+    let state = &self.state;
+    for (dst, &i) in dst.iter_mut().zip(selected) {
+      let bit_idx = base_bit_idx + state.offset_bits_csum_scratch[i] as usize;
+      let byte_idx = bit_idx / 8;
+      let bits_past_byte = bit_idx as Bitlen % 8;
+      *dst = bit_reader::read_uint_at::<L, MAX_U64S>(
+        src,
+        byte_idx,
+        bits_past_byte,
+        state.offset_bits_scratch[i],
+      );
+    }
+  }
+
+  // Advances the reader past the offsets of a batch whose ANS symbols have
+  // been decoded.
+  unsafe fn skip_offsets(&self, reader: &mut BitReader, batch_n: usize) {
+    let final_bit_idx = reader.bit_idx()
+      + self.state.offset_bits_csum_scratch[batch_n - 1] as usize
+      + self.state.offset_bits_scratch[batch_n - 1] as usize;
+    reader.stale_byte_idx = final_bit_idx / 8;
+    reader.bits_past_byte = final_bit_idx as Bitlen % 8;
+  }
+
   #[inline(never)]
   fn add_lowers(&self, dst: &mut [L]) {
     for (&lower, dst) in self.state.lowers_scratch[0..dst.len()]
@@ -205,6 +239,81 @@ impl<L: Latent> LatentPageDecompressor<L> {
     self.add_lowers(dst);
   }
 
+  // Advances the reader past a batch as if decompressing it, but only decodes
+  // the ANS symbols needed to know how many offset bits there are.
+  pub unsafe fn skip_batch_pre_delta(&mut self, reader: &mut BitReader, batch_n: usize) {
+    if batch_n == 0 {
+      return;
+    }
+
+    if self.needs_ans {
+      assert!(batch_n <= FULL_BATCH_N);
+
+      if batch_n == FULL_BATCH_N {
+        self.decompress_full_ans_symbols(reader);
+      } else {
+        self.decompress_ans_symbols(reader, batch_n);
+      }
+    }
+
+    self.skip_offsets(reader, batch_n);
+  }
+
+  // Advances the reader past a batch like skip_batch_pre_delta, but also
+  // writes the latents at the selected (increasing) indices of the batch to
+  // dst.
+  // Only makes sense without delta encoding, since otherwise every latent is
+  // needed to reconstruct the rest.
+  pub unsafe fn decompress_selected_pre_delta(
+    &mut self,
+    reader: &mut BitReader,
+    batch_n: usize,
+    selected: &[usize],
+    dst: &mut [L],
+  ) {
+    if batch_n == 0 {
+      return;
+    }
+
+    if self.needs_ans {
+      assert!(batch_n <= FULL_BATCH_N);
+
+      if batch_n == FULL_BATCH_N {
+        self.decompress_full_ans_symbols(reader);
+      } else {
+        self.decompress_ans_symbols(reader, batch_n);
+      }
+    }
+
+    assert!(self.u64s_per_offset <= read_write_uint::calc_max_u64s(L::BITS));
+    match self.u64s_per_offset {
+      0 => dst.fill(L::ZERO),
+      1 => self.decompress_selected_offsets::<1>(reader, selected, dst),
+      2 => self.decompress_selected_offsets::<2>(reader, selected, dst),
+      3 => self.decompress_selected_offsets::<3>(reader, selected, dst),
+      _ => panic!(
+        "[LatentBatchDecompressor] data type too large (extra u64's {} > 2)",
+        self.u64s_per_offset
+      ),
+    }
+    for (dst, &i) in dst.iter_mut().zip(selected) {
+      *dst = dst.wrapping_add(self.state.lowers_scratch[i]);
+    }
+
+    self.skip_offsets(reader, batch_n);
+  }
+
+  pub unsafe fn skip_batch(
+    &mut self,
+    n_remaining_in_page: usize,
+    reader: &mut BitReader,
+    batch_n: usize,
+  ) {
+    let n_remaining_pre_delta =
+      n_remaining_in_page.saturating_sub(self.delta_encoding.n_latents_per_state());
+    self.skip_batch_pre_delta(reader, min(batch_n, n_remaining_pre_delta));
+  }
+
   pub unsafe fn decompress_batch(
     &mut self,
     delta_latents: Option<&DynLatents>,
@@ -227,10 +336,14 @@ impl<L: Latent> LatentPageDecompressor<L> {
 
     match self.delta_encoding {
       DeltaEncoding::None => Ok(()),
-      DeltaEncoding::Consecutive(_) => {
+      DeltaEncoding::Consecutive(_) | DeltaEncoding::ConsecutivePerPage(_) => {
         delta::decode_consecutive_in_place(&mut self.state.delta_state, dst);
         Ok(())
       }
+      DeltaEncoding::Xor => {
+        delta::decode_xor_in_place(&mut self.state.delta_state, dst);
+        Ok(())
+      }
       DeltaEncoding::Lookback(config) => {
         let has_oob_lookbacks = delta::decode_with_lookbacks_in_place(
           config,
@@ -281,7 +394,10 @@ impl DynLatentPageDecompressor {
     let decoder = ans::Decoder::new(&ans_spec, &bin_offset_bits);
 
     let (working_delta_state, delta_state_pos) = match delta_encoding {
-      DeltaEncoding::None | DeltaEncoding::Consecutive(_) => (stored_delta_state, 0),
+      DeltaEncoding::None
+      | DeltaEncoding::Consecutive(_)
+      | DeltaEncoding::Xor
+      | DeltaEncoding::ConsecutivePerPage(_) => (stored_delta_state, 0),
       DeltaEncoding::Lookback(config) => {
         delta::new_lookback_window_buffer_and_pos(config, &stored_delta_state)
       }
@@ -16,8 +16,12 @@
 #[cfg(doctest)]
 struct ReadmeDoctest;
 
-pub use chunk_config::{ChunkConfig, DeltaSpec, ModeSpec, PagingSpec};
-pub use constants::{DEFAULT_COMPRESSION_LEVEL, DEFAULT_MAX_PAGE_N, FULL_BATCH_N};
+pub use bin_layout::{learn_layout, BinLayout};
+pub use chunk_config::{ChunkConfig, DeltaSpec, ModeSpec, Objective, PagingSpec, ValueRange};
+pub use constants::{
+  CURRENT_FORMAT_VERSION, DEFAULT_COMPRESSION_LEVEL, DEFAULT_FORMAT_VERSION, DEFAULT_MAX_PAGE_N,
+  FULL_BATCH_N,
+};
 pub use progress::Progress;
 
 pub mod data_types;
@@ -32,6 +36,7 @@ pub mod standalone;
 pub mod wrapped;
 
 mod ans;
+mod bin_layout;
 mod bin_optimization;
 mod bit_reader;
 mod bit_writer;
@@ -41,6 +46,8 @@ mod compression_intermediates;
 mod compression_table;
 mod constants;
 mod delta;
+#[cfg(feature = "entropy")]
+mod entropy;
 mod float_mult_utils;
 mod float_quant_utils;
 mod histograms;
@@ -53,6 +60,7 @@ mod progress;
 mod read_write_uint;
 mod sampling;
 mod sort_utils;
+mod telemetry;
 
 #[cfg(test)]
 mod tests;
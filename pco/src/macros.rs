@@ -58,6 +58,29 @@ dtype_dispatch::build_dtype_macros!(
   },
 );
 
+// These emit `tracing` events with the `tracing` feature and compile to
+// nothing otherwise. They must never be given actual number values.
+macro_rules! trace_info {
+  ($($arg:tt)*) => {
+    #[cfg(feature = "tracing")]
+    {
+      tracing::info!($($arg)*);
+    }
+  };
+}
+
+macro_rules! trace_debug {
+  ($($arg:tt)*) => {
+    #[cfg(feature = "tracing")]
+    {
+      tracing::debug!($($arg)*);
+    }
+  };
+}
+
 pub(crate) use define_latent_enum;
 pub(crate) use define_number_enum;
 pub(crate) use match_latent_enum;
+pub(crate) use match_number_enum;
+pub(crate) use trace_debug;
+pub(crate) use trace_info;
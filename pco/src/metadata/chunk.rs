@@ -4,15 +4,27 @@ use better_io::BetterBufRead;
 
 use crate::bit_reader::BitReaderBuilder;
 use crate::bit_writer::BitWriter;
-use crate::constants::DeltaLookback;
+use crate::constants::{DeltaLookback, BITS_TO_ENCODE_DELTA_ENCODING_ORDER};
 use crate::data_types::LatentType;
 use crate::errors::{PcoError, PcoResult};
+use crate::macros::match_latent_enum;
 use crate::metadata::chunk_latent_var::ChunkLatentVarMeta;
 use crate::metadata::delta_encoding::DeltaEncoding;
+use crate::metadata::dyn_bins::DynBins;
 use crate::metadata::format_version::FormatVersion;
 use crate::metadata::per_latent_var::PerLatentVar;
 use crate::metadata::Mode;
 
+// Relative per-number costs of each part of decompression, in rough units of
+// reading one offset bit. These are ballpark figures from benchmarks and only
+// meant to be meaningful relative to each other.
+const BASE_DECODE_COST: f64 = 2.0;
+const ANS_DECODE_COST: f64 = 8.0;
+const DELTA_ORDER_DECODE_COST: f64 = 2.0;
+const LOOKBACK_DECODE_COST: f64 = 6.0;
+const XOR_DECODE_COST: f64 = 1.0;
+const SECONDARY_JOIN_DECODE_COST: f64 = 3.0;
+
 /// The metadata of a pco chunk.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ChunkMeta {
@@ -27,6 +39,99 @@ pub struct ChunkMeta {
 }
 
 impl ChunkMeta {
+  /// Returns whether every number in the chunk is known to be identical
+  /// from the metadata alone.
+  ///
+  /// This is the case when there is no delta encoding and each latent
+  /// variable has a single bin with 0 offset bits, so the chunk is fully
+  /// described by one value and its count, and its pages contain
+  /// essentially no data.
+  /// Pco always compresses constant chunks this way.
+  pub fn is_constant(&self) -> bool {
+    self.delta_encoding == DeltaEncoding::None
+      && self
+        .per_latent_var
+        .as_ref()
+        .enumerated()
+        .into_iter()
+        .all(|(_, var_meta)| var_meta.is_trivial())
+  }
+
+  /// Returns whether the numbers in each page are known to form an
+  /// arithmetic sequence (like row numbers) from the metadata alone.
+  ///
+  /// This is the case when there is 1st order consecutive delta encoding
+  /// and each latent variable has a single bin with 0 offset bits, so each
+  /// page is fully described by its starting value and the common step.
+  /// Pco always compresses linear chunks this way.
+  pub fn is_linear(&self) -> bool {
+    matches!(
+      self.delta_encoding,
+      DeltaEncoding::Consecutive(config) if config.order == 1
+    ) && self
+      .per_latent_var
+      .as_ref()
+      .enumerated()
+      .into_iter()
+      .all(|(_, var_meta)| var_meta.is_trivial())
+  }
+
+  /// Returns whether every latent is known to be the same value after delta
+  /// encoding, from the metadata alone.
+  ///
+  /// This is the case when each latent variable has a single bin with 0
+  /// offset bits, so each page contains nothing but its delta state.
+  /// It includes [constant][Self::is_constant] and
+  /// [linear][Self::is_linear] chunks, as well as e.g. polynomial sequences,
+  /// whose consecutive deltas of some order are all zero.
+  /// Pco always compresses chunks like these this way when it is free to
+  /// choose the mode and delta encoding.
+  pub fn is_constant_after_delta(&self) -> bool {
+    self
+      .per_latent_var
+      .as_ref()
+      .enumerated()
+      .into_iter()
+      .all(|(_, var_meta)| var_meta.is_trivial())
+  }
+
+  /// Returns a rough estimate of how expensive decompressing `n` numbers of
+  /// this chunk will be, from the metadata alone.
+  ///
+  /// The estimate is in abstract units and grows with `n`, the number of
+  /// offset bits per number, and the delta encoding order; chunks whose
+  /// latent variables need ANS decoding cost more than trivial ones.
+  /// It is a relative heuristic for balancing work across threads, not a
+  /// prediction of wall-clock time, and its scale may change between
+  /// versions.
+  pub fn estimated_decode_cost(&self, n: usize) -> u64 {
+    let mut cost_per_num = BASE_DECODE_COST;
+    for (key, var_meta) in self.per_latent_var.as_ref().enumerated() {
+      let n_bins = match_latent_enum!(&var_meta.bins, DynBins<L>(bins) => { bins.len() });
+      if n_bins > 1 {
+        cost_per_num += ANS_DECODE_COST;
+      }
+      cost_per_num += var_meta.avg_offset_bits();
+
+      if self.delta_encoding.applies_to_latent_var(key) {
+        cost_per_num += match self.delta_encoding {
+          DeltaEncoding::None => 0.0,
+          // for per-page orders, we assume the worst
+          DeltaEncoding::Consecutive(config) | DeltaEncoding::ConsecutivePerPage(config) => {
+            config.order as f64 * DELTA_ORDER_DECODE_COST
+          }
+          DeltaEncoding::Lookback(_) => LOOKBACK_DECODE_COST,
+          DeltaEncoding::Xor => XOR_DECODE_COST,
+        };
+      }
+    }
+    if self.per_latent_var.secondary.is_some() {
+      cost_per_num += SECONDARY_JOIN_DECODE_COST;
+    }
+
+    (n as f64 * cost_per_num).ceil() as u64
+  }
+
   pub(crate) fn exact_size(&self) -> usize {
     let bits_for_latent_vars = self
       .per_latent_var
@@ -39,15 +144,21 @@ impl ChunkMeta {
     n_bits.div_ceil(8)
   }
 
+  // For ConsecutivePerPage, this is the size of a page using the max order.
   pub(crate) fn exact_page_meta_size(&self) -> usize {
-    let bit_size = self
-      .per_latent_var
-      .as_ref()
-      .map(|key, var_meta| {
-        let delta_encoding = self.delta_encoding.for_latent_var(key);
-        var_meta.exact_page_meta_bit_size(delta_encoding)
-      })
-      .sum();
+    let delta_order_bit_size = match self.delta_encoding {
+      DeltaEncoding::ConsecutivePerPage(_) => BITS_TO_ENCODE_DELTA_ENCODING_ORDER as usize,
+      _ => 0,
+    };
+    let bit_size = delta_order_bit_size
+      + self
+        .per_latent_var
+        .as_ref()
+        .map(|key, var_meta| {
+          let delta_encoding = self.delta_encoding.for_latent_var(key);
+          var_meta.exact_page_meta_bit_size(delta_encoding)
+        })
+        .sum();
     bit_size.div_ceil(8)
   }
 
@@ -69,7 +180,10 @@ impl ChunkMeta {
           Ok(())
         }
       }
-      (DeltaEncoding::None, None) | (DeltaEncoding::Consecutive(_), None) => Ok(()),
+      (DeltaEncoding::None, None)
+      | (DeltaEncoding::Consecutive(_), None)
+      | (DeltaEncoding::Xor, None)
+      | (DeltaEncoding::ConsecutivePerPage(_), None) => Ok(()),
       _ => unreachable!(),
     }
   }
@@ -145,11 +259,9 @@ impl ChunkMeta {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::constants::ANS_INTERLEAVING;
+  use crate::constants::{Bitlen, ANS_INTERLEAVING};
   use crate::data_types::Latent;
-  use crate::macros::match_latent_enum;
   use crate::metadata::delta_encoding::DeltaConsecutiveConfig;
-  use crate::metadata::dyn_bins::DynBins;
   use crate::metadata::dyn_latents::DynLatents;
   use crate::metadata::page::PageMeta;
   use crate::metadata::page_latent_var::PageLatentVarMeta;
@@ -166,7 +278,12 @@ mod tests {
     // page meta size
     let mut dst = Vec::new();
     let mut writer = BitWriter::new(&mut dst, buffer_size);
+    let delta_order = match meta.delta_encoding {
+      DeltaEncoding::ConsecutivePerPage(config) => Some(config.order),
+      _ => None,
+    };
     let page_meta = PageMeta {
+      delta_order,
       per_latent_var: meta.per_latent_var.as_ref().map(|key, latent_var_meta| {
         let delta_encoding = meta.delta_encoding.for_latent_var(key);
         let delta_moments = match_latent_enum!(
@@ -283,4 +400,87 @@ mod tests {
 
     check_exact_sizes(&meta)
   }
+
+  #[test]
+  fn exact_size_per_page_delta() -> PcoResult<()> {
+    let meta = ChunkMeta {
+      mode: Mode::Classic,
+      delta_encoding: DeltaEncoding::ConsecutivePerPage(DeltaConsecutiveConfig {
+        order: 2,
+        secondary_uses_delta: false,
+      }),
+      per_latent_var: PerLatentVar {
+        delta: None,
+        primary: ChunkLatentVarMeta {
+          ans_size_log: 1,
+          bins: DynBins::U16(vec![
+            Bin {
+              weight: 1,
+              lower: 0_u16,
+              offset_bits: 3,
+            },
+            Bin {
+              weight: 1,
+              lower: 9,
+              offset_bits: 0,
+            },
+          ]),
+        },
+        secondary: None,
+      },
+    };
+
+    check_exact_sizes(&meta)
+  }
+
+  #[test]
+  fn test_estimated_decode_cost() {
+    let make_meta = |order: usize, offset_bits: Bitlen| {
+      let bins = vec![
+        Bin {
+          weight: 1,
+          lower: 0_u64,
+          offset_bits,
+        },
+        Bin {
+          weight: 3,
+          lower: 1 << 40,
+          offset_bits,
+        },
+      ];
+      ChunkMeta {
+        mode: Mode::Classic,
+        delta_encoding: DeltaEncoding::Consecutive(DeltaConsecutiveConfig {
+          order,
+          secondary_uses_delta: false,
+        }),
+        per_latent_var: PerLatentVar {
+          delta: None,
+          primary: ChunkLatentVarMeta {
+            ans_size_log: 2,
+            bins: DynBins::U64(bins),
+          },
+          secondary: None,
+        },
+      }
+    };
+
+    let base_cost = make_meta(1, 10).estimated_decode_cost(1000);
+    assert!(base_cost > 0);
+    assert_eq!(make_meta(1, 10).estimated_decode_cost(0), 0);
+    assert_eq!(
+      make_meta(1, 10).estimated_decode_cost(2000),
+      2 * base_cost
+    );
+    assert!(make_meta(3, 10).estimated_decode_cost(1000) > base_cost);
+    assert!(make_meta(1, 30).estimated_decode_cost(1000) > base_cost);
+
+    let mut trivial_meta = make_meta(1, 0);
+    trivial_meta.per_latent_var.primary.bins = DynBins::U64(vec![Bin {
+      weight: 1,
+      lower: 7,
+      offset_bits: 0,
+    }]);
+    assert!(trivial_meta.estimated_decode_cost(1000) < base_cost);
+  }
 }
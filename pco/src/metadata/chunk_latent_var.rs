@@ -94,6 +94,33 @@ impl ChunkLatentVarMeta {
     )
   }
 
+  // true if every latent in this var must be the same value
+  pub(crate) fn is_trivial(&self) -> bool {
+    match_latent_enum!(
+      &self.bins,
+      DynBins<L>(bins) => { bins.len() == 1 && bins[0].offset_bits == 0 }
+    )
+  }
+
+  // the average offset bits per latent, weighted by how often ANS is expected
+  // to pick each bin
+  pub(crate) fn avg_offset_bits(&self) -> f64 {
+    match_latent_enum!(
+      &self.bins,
+      DynBins<L>(bins) => {
+        let total_weight = bins.iter().map(|bin| bin.weight as f64).sum::<f64>();
+        if total_weight == 0.0 {
+          return 0.0;
+        }
+        bins
+          .iter()
+          .map(|bin| bin.weight as f64 * bin.offset_bits as f64)
+          .sum::<f64>()
+          / total_weight
+      }
+    )
+  }
+
   pub(crate) unsafe fn read_from<R: BetterBufRead>(
     reader_builder: &mut BitReaderBuilder<R>,
     latent_type: LatentType,
@@ -61,6 +61,9 @@ pub enum DeltaEncoding {
   ///
   /// This is best if your numbers have high variance overall, but adjacent
   /// numbers are close in value, e.g. an arithmetic sequence.
+  /// It also handles long runs of repeated values (e.g. a step function)
+  /// well: each repeat becomes a 0 delta in a bin with no offset bits, which
+  /// costs only a small fraction of a bit, similar to run-length encoding.
   Consecutive(DeltaConsecutiveConfig),
   /// Encodes an extra "lookback" latent variable and the differences
   /// `x[i] - x[i - lookback[i]]` between values.
@@ -70,6 +73,21 @@ pub enum DeltaEncoding {
   /// It is in spirit similar to LZ77 compression, but only stores lookbacks
   /// (AKA match offsets) and no match lengths.
   Lookback(DeltaLookbackConfig),
+  /// Encodes the bitwise XOR `x[i] ^ x[i - 1]` between consecutive values,
+  /// similar to Gorilla float compression.
+  ///
+  /// This is experimental.
+  /// It can be best for slowly-changing floats that share most of their
+  /// leading bits, such as monitoring timeseries.
+  /// Only the primary latent variable is XOR encoded.
+  Xor,
+  /// Like `Consecutive`, but each page uses its own order between 0 and the
+  /// configured order, which is stored in the page's metadata.
+  ///
+  /// This is best if some stretches of your numbers are smooth (e.g.
+  /// piecewise linear) and others are noisy, so that no single order suits
+  /// the whole chunk.
+  ConsecutivePerPage(DeltaConsecutiveConfig),
 }
 
 impl DeltaEncoding {
@@ -124,6 +142,19 @@ impl DeltaEncoding {
           secondary_uses_delta: reader.read_bool(),
         })
       }
+      3 if version.supports_xor_delta() => Xor,
+      4 if version.supports_per_page_delta() => {
+        let order = reader.read_usize(BITS_TO_ENCODE_DELTA_ENCODING_ORDER);
+        if order == 0 {
+          return Err(PcoError::corruption(
+            "ConsecutivePerPage delta encoding order must not be 0",
+          ));
+        }
+        ConsecutivePerPage(DeltaConsecutiveConfig {
+          order,
+          secondary_uses_delta: reader.read_bool(),
+        })
+      }
       value => {
         return Err(PcoError::corruption(format!(
           "unknown delta encoding value: {}",
@@ -139,6 +170,8 @@ impl DeltaEncoding {
       None => 0,
       Consecutive(_) => 1,
       Lookback(_) => 2,
+      Xor => 3,
+      ConsecutivePerPage(_) => 4,
     };
     writer.write_bitlen(
       variant,
@@ -146,8 +179,8 @@ impl DeltaEncoding {
     );
 
     match self {
-      None => (),
-      Consecutive(config) => {
+      None | Xor => (),
+      Consecutive(config) | ConsecutivePerPage(config) => {
         writer.write_usize(
           config.order,
           BITS_TO_ENCODE_DELTA_ENCODING_ORDER,
@@ -170,7 +203,7 @@ impl DeltaEncoding {
 
   pub(crate) fn latent_type(&self) -> Option<LatentType> {
     match self {
-      None | Consecutive(_) => Option::None,
+      None | Consecutive(_) | Xor | ConsecutivePerPage(_) => Option::None,
       Lookback(_) => Some(LatentType::U32),
     }
   }
@@ -181,8 +214,9 @@ impl DeltaEncoding {
       (_, LatentVarKey::Delta) => false,
       // We always apply the DeltaEncoding to the primary latents.
       (_, LatentVarKey::Primary) => true,
-      (None, LatentVarKey::Secondary) => false,
-      (Consecutive(config), LatentVarKey::Secondary) => config.secondary_uses_delta,
+      (None, LatentVarKey::Secondary) | (Xor, LatentVarKey::Secondary) => false,
+      (Consecutive(config), LatentVarKey::Secondary)
+      | (ConsecutivePerPage(config), LatentVarKey::Secondary) => config.secondary_uses_delta,
       (Lookback(config), LatentVarKey::Secondary) => config.secondary_uses_delta,
     }
   }
@@ -195,24 +229,38 @@ impl DeltaEncoding {
     }
   }
 
+  // For ConsecutivePerPage, this is the most any page can have.
   pub(crate) fn n_latents_per_state(&self) -> usize {
     match self {
       None => 0,
-      Consecutive(config) => config.order,
+      Consecutive(config) | ConsecutivePerPage(config) => config.order,
       Lookback(config) => 1 << config.state_n_log,
+      Xor => 1,
     }
   }
 
   pub(crate) fn exact_bit_size(&self) -> Bitlen {
     let payload_bits = match self {
-      None => 0,
+      None | Xor => 0,
       // For nontrivial encodings, we have a +1 bit for whether the
       // secondary latent is delta-encoded or not.
-      Consecutive(_) => BITS_TO_ENCODE_DELTA_ENCODING_ORDER + 1,
+      Consecutive(_) | ConsecutivePerPage(_) => BITS_TO_ENCODE_DELTA_ENCODING_ORDER + 1,
       Lookback(_) => BITS_TO_ENCODE_LZ_DELTA_WINDOW_N_LOG + BITS_TO_ENCODE_LZ_DELTA_STATE_N_LOG + 1,
     };
     BITS_TO_ENCODE_DELTA_ENCODING_VARIANT + payload_bits
   }
+
+  // Returns the delta encoding a page actually uses, given the order stored
+  // in its metadata (only present for ConsecutivePerPage).
+  pub(crate) fn for_page(self, page_order: Option<usize>) -> DeltaEncoding {
+    match (self, page_order) {
+      (ConsecutivePerPage(_), Some(0)) => None,
+      (ConsecutivePerPage(config), Some(order)) => {
+        Consecutive(DeltaConsecutiveConfig { order, ..config })
+      }
+      _ => self,
+    }
+  }
 }
 
 #[cfg(test)]
@@ -249,5 +297,12 @@ mod tests {
         secondary_uses_delta: true,
       },
     ));
+    check_bit_size(DeltaEncoding::Xor);
+    check_bit_size(DeltaEncoding::ConsecutivePerPage(
+      DeltaConsecutiveConfig {
+        order: 2,
+        secondary_uses_delta: false,
+      },
+    ));
   }
 }
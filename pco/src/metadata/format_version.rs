@@ -2,8 +2,12 @@ use std::io::Write;
 
 use crate::bit_reader::BitReader;
 use crate::bit_writer::BitWriter;
-use crate::constants::CURRENT_FORMAT_VERSION;
+use crate::chunk_config::DeltaSpec;
+use crate::constants::{
+  CURRENT_FORMAT_VERSION, DEFAULT_FORMAT_VERSION, MIN_WRITABLE_FORMAT_VERSION,
+};
 use crate::errors::{PcoError, PcoResult};
+use crate::metadata::DeltaEncoding;
 
 /// The version of pco used to compress a file.
 ///
@@ -15,16 +19,49 @@ use crate::errors::{PcoError, PcoResult};
 /// You will not need to manually instantiate this.
 /// However, in some circumstances you may want to inspect this during
 /// decompression.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct FormatVersion(pub u8);
 
 impl Default for FormatVersion {
   fn default() -> Self {
-    Self(CURRENT_FORMAT_VERSION)
+    Self(DEFAULT_FORMAT_VERSION)
   }
 }
 
 impl FormatVersion {
+  pub(crate) fn for_writing(version: u8) -> PcoResult<Self> {
+    if !(MIN_WRITABLE_FORMAT_VERSION..=CURRENT_FORMAT_VERSION).contains(&version) {
+      return Err(PcoError::invalid_argument(format!(
+        "can only write format versions {} through {} (was {})",
+        MIN_WRITABLE_FORMAT_VERSION, CURRENT_FORMAT_VERSION, version,
+      )));
+    }
+
+    Ok(Self(version))
+  }
+
+  pub(crate) fn newest() -> Self {
+    Self(CURRENT_FORMAT_VERSION)
+  }
+
+  // the oldest version we can write that supports this delta encoding
+  pub(crate) fn oldest_supporting(delta_encoding: &DeltaEncoding) -> Self {
+    match delta_encoding {
+      DeltaEncoding::Xor | DeltaEncoding::ConsecutivePerPage(_) => Self(4),
+      _ => Self(MIN_WRITABLE_FORMAT_VERSION),
+    }
+  }
+
+  // the oldest version we can write that supports every delta encoding this
+  // spec may choose but Auto
+  pub(crate) fn oldest_supporting_spec(delta_spec: DeltaSpec) -> Self {
+    match delta_spec {
+      DeltaSpec::TryXor => Self(4),
+      DeltaSpec::TryConsecutivePerPage(order) if order > 0 => Self(4),
+      _ => Self(MIN_WRITABLE_FORMAT_VERSION),
+    }
+  }
+
   pub(crate) fn read_from(reader: &mut BitReader) -> PcoResult<Self> {
     let version = reader.read_aligned_bytes(1)?[0];
     if version > CURRENT_FORMAT_VERSION {
@@ -38,7 +75,6 @@ impl FormatVersion {
   }
 
   pub(crate) fn write_to<W: Write>(&self, writer: &mut BitWriter<W>) -> PcoResult<usize> {
-    // in the future, we may want to allow the user to encode with their choice of a recent version
     writer.write_aligned_bytes(&[self.0])?;
     Ok(1)
   }
@@ -50,4 +86,20 @@ impl FormatVersion {
   pub(crate) fn supports_delta_variants(&self) -> bool {
     self.0 >= 3
   }
+
+  pub(crate) fn supports_xor_delta(&self) -> bool {
+    self.0 >= 4
+  }
+
+  pub(crate) fn supports_per_page_delta(&self) -> bool {
+    self.0 >= 4
+  }
+
+  pub(crate) fn supports_delta_encoding(&self, delta_encoding: &DeltaEncoding) -> bool {
+    *self >= Self::oldest_supporting(delta_encoding)
+  }
+
+  pub(crate) fn supports_delta_spec(&self, delta_spec: DeltaSpec) -> bool {
+    *self >= Self::oldest_supporting_spec(delta_spec)
+  }
 }
@@ -6,6 +6,7 @@ use crate::errors::{PcoError, PcoResult};
 use crate::macros::match_latent_enum;
 use crate::metadata::dyn_latent::DynLatent;
 use crate::metadata::format_version::FormatVersion;
+use crate::metadata::LatentVarKey;
 use crate::metadata::Mode::*;
 use std::fmt::Debug;
 use std::io::Write;
@@ -51,6 +52,10 @@ pub enum Mode {
   /// Represents each number as a single latent: itself.
   ///
   /// Formula: `num = num`
+  ///
+  /// Bins are placed at absolute latent values, so numbers clustered far
+  /// from zero, like epoch timestamps, take no more bits than the same
+  /// numbers rebased to start at zero.
   #[default]
   Classic,
   /// Given a `base`, represents each number as two latents: a multiplier
@@ -66,6 +71,11 @@ pub enum Mode {
   /// Only applies to floats.
   ///
   /// Formula: `num = mode.base * mult + adjustment ULPs`
+  ///
+  /// Like every mode, this is lossless down to the bit: the adjustment
+  /// corrects for whatever `mode.base * mult` evaluates to, so -0.0, +0.0,
+  /// infinities, subnormals, and NaNs with any sign or payload decompress
+  /// exactly as they were.
   FloatMult(DynLatent),
   /// Given a number of bits `k`, represents each number as two latents:
   /// quantums (effectively the first `TYPE_SIZE - k` bits) and an ULPs
@@ -75,6 +85,9 @@ pub enum Mode {
   ///
   /// Formula: `num = from_bits(quantums << k + adjustment)`
   /// (warning: this formula is especially simplified)
+  ///
+  /// This is also lossless down to the bit, since it only splits up each
+  /// number's bits.
   FloatQuant(Bitlen),
 }
 
@@ -145,6 +158,47 @@ impl Mode {
     };
   }
 
+  /// Returns the number of latent variables this mode splits each number
+  /// into.
+  ///
+  /// * `Classic`: 1
+  /// * `IntMult`: 2 (multiplier and adjustment)
+  /// * `FloatMult`: 2 (multiplier and adjustment)
+  /// * `FloatQuant`: 2 (quantums and adjustment)
+  ///
+  /// This doesn't include the latent variable some delta encodings add, e.g.
+  /// for lookback indices, so a chunk's
+  /// [`per_latent_var`][crate::metadata::ChunkMeta::per_latent_var] may have
+  /// one more.
+  pub fn latent_count(&self) -> usize {
+    match self {
+      Classic => 1,
+      IntMult(_) | FloatMult(_) | FloatQuant(_) => 2,
+    }
+  }
+
+  /// Returns the latent variables this mode splits each number into, in
+  /// order of appearance.
+  ///
+  /// * `Classic`: the number itself as `Primary`
+  /// * `IntMult`: the multiplier as `Primary`, then the adjustment as
+  ///   `Secondary`
+  /// * `FloatMult`: the multiplier as `Primary`, then the ULPs adjustment as
+  ///   `Secondary`
+  /// * `FloatQuant`: the quantums as `Primary`, then the ULPs adjustment as
+  ///   `Secondary`
+  ///
+  /// Like [`LatentVarKey::ORDER`], this is stable.
+  /// A delta encoding's latent variable, if any, comes before these.
+  pub fn latent_var_keys(&self) -> &'static [LatentVarKey] {
+    match self {
+      Classic => &[LatentVarKey::Primary],
+      IntMult(_) | FloatMult(_) | FloatQuant(_) => {
+        &[LatentVarKey::Primary, LatentVarKey::Secondary]
+      }
+    }
+  }
+
   pub(crate) fn primary_latent_type(&self, number_latent_type: LatentType) -> LatentType {
     match self {
       Classic | FloatMult(_) | FloatQuant(_) | IntMult(_) => number_latent_type,
@@ -179,7 +233,8 @@ impl Mode {
 #[cfg(test)]
 mod tests {
   use crate::bit_writer::BitWriter;
-  use crate::metadata::{DynLatent, Mode};
+  use crate::data_types::LatentType;
+  use crate::metadata::{DynLatent, LatentVarKey, Mode, PerLatentVar};
 
   fn check_bit_size(mode: Mode) {
     let mut bytes = Vec::new();
@@ -204,4 +259,60 @@ mod tests {
     ));
     check_bit_size(Mode::FloatQuant(7));
   }
+
+  #[test]
+  fn test_latent_count() {
+    for mode in [
+      Mode::Classic,
+      Mode::IntMult(DynLatent::new(77_u32).unwrap()),
+      Mode::FloatMult(DynLatent::new(77_u32).unwrap()),
+      Mode::FloatQuant(7),
+    ] {
+      let n_secondary = mode.secondary_latent_type(LatentType::U32).is_some() as usize;
+      assert_eq!(
+        mode.latent_count(),
+        1 + n_secondary,
+        "{:?}",
+        mode
+      );
+      assert_eq!(
+        mode.latent_var_keys().len(),
+        mode.latent_count()
+      );
+    }
+  }
+
+  // Downstream code indexes latent variables by these orders, so they must
+  // never change.
+  #[test]
+  fn test_stable_latent_var_order() {
+    use LatentVarKey::*;
+    assert_eq!(
+      LatentVarKey::ORDER,
+      [Delta, Primary, Secondary]
+    );
+    let all = PerLatentVar {
+      delta: Some(0),
+      primary: 1,
+      secondary: Some(2),
+    };
+    assert_eq!(
+      all.enumerated(),
+      vec![(Delta, 0), (Primary, 1), (Secondary, 2)]
+    );
+
+    assert_eq!(Mode::Classic.latent_var_keys(), [Primary]);
+    for mode in [
+      Mode::IntMult(DynLatent::new(77_u32).unwrap()),
+      Mode::FloatMult(DynLatent::new(77_u32).unwrap()),
+      Mode::FloatQuant(7),
+    ] {
+      assert_eq!(
+        mode.latent_var_keys(),
+        [Primary, Secondary],
+        "{:?}",
+        mode
+      );
+    }
+  }
 }
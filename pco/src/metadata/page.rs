@@ -2,11 +2,11 @@ use std::io::Write;
 
 use crate::bit_reader::BitReader;
 use crate::bit_writer::BitWriter;
-use crate::constants::Bitlen;
-use crate::errors::PcoResult;
+use crate::constants::{Bitlen, BITS_TO_ENCODE_DELTA_ENCODING_ORDER};
+use crate::errors::{PcoError, PcoResult};
 use crate::metadata::page_latent_var::PageLatentVarMeta;
 use crate::metadata::per_latent_var::PerLatentVar;
-use crate::metadata::ChunkMeta;
+use crate::metadata::{ChunkMeta, DeltaEncoding};
 
 // Data page metadata is slightly semantically different from chunk metadata,
 // so it gets its own type.
@@ -15,6 +15,8 @@ use crate::metadata::ChunkMeta;
 // (wrapped mode).
 #[derive(Clone, Debug)]
 pub struct PageMeta {
+  // only present when the chunk uses ConsecutivePerPage delta encoding
+  pub delta_order: Option<usize>,
   pub per_latent_var: PerLatentVar<PageLatentVarMeta>,
 }
 
@@ -24,6 +26,9 @@ impl PageMeta {
     ans_size_logs: PerLatentVar<Bitlen>,
     writer: &mut BitWriter<W>,
   ) {
+    if let Some(order) = self.delta_order {
+      writer.write_usize(order, BITS_TO_ENCODE_DELTA_ENCODING_ORDER);
+    }
     for (_, (ans_size_log, latent_var_meta)) in ans_size_logs
       .zip_exact(self.per_latent_var.as_ref())
       .enumerated()
@@ -34,14 +39,26 @@ impl PageMeta {
   }
 
   pub unsafe fn read_from(reader: &mut BitReader, chunk_meta: &ChunkMeta) -> PcoResult<Self> {
+    let delta_order = match chunk_meta.delta_encoding {
+      DeltaEncoding::ConsecutivePerPage(config) => {
+        let order = reader.read_usize(BITS_TO_ENCODE_DELTA_ENCODING_ORDER);
+        if order > config.order {
+          return Err(PcoError::corruption(format!(
+            "page's delta encoding order of {} exceeded chunk's max of {}",
+            order, config.order,
+          )));
+        }
+        Some(order)
+      }
+      _ => None,
+    };
+    let delta_encoding = chunk_meta.delta_encoding.for_page(delta_order);
+
     let per_latent_var = chunk_meta
       .per_latent_var
       .as_ref()
       .map(|key, chunk_latent_var_meta| {
-        let n_latents_per_state = chunk_meta
-          .delta_encoding
-          .for_latent_var(key)
-          .n_latents_per_state();
+        let n_latents_per_state = delta_encoding.for_latent_var(key).n_latents_per_state();
         PageLatentVarMeta::read_from(
           reader,
           chunk_latent_var_meta.latent_type(),
@@ -51,6 +68,13 @@ impl PageMeta {
       });
     reader.drain_empty_byte("non-zero bits at end of data page metadata")?;
 
-    Ok(Self { per_latent_var })
+    Ok(Self {
+      delta_order,
+      per_latent_var,
+    })
+  }
+
+  pub fn delta_encoding(&self, chunk_meta: &ChunkMeta) -> DeltaEncoding {
+    chunk_meta.delta_encoding.for_page(self.delta_order)
   }
 }
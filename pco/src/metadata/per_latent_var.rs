@@ -3,6 +3,11 @@ use std::iter::Sum;
 
 /// The possible kinds of latent variables present in a chunk.
 ///
+/// Latent variables always appear in the order of [`LatentVarKey::ORDER`],
+/// both in files and in [`PerLatentVar::enumerated`], so they can be
+/// indexed reliably.
+/// Which ones are present depends on the chunk's delta encoding and
+/// [mode][crate::metadata::Mode::latent_var_keys].
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum LatentVarKey {
   /// Used by certain types of
@@ -19,6 +24,19 @@ pub enum LatentVarKey {
   Secondary,
 }
 
+impl LatentVarKey {
+  /// The canonical order of latent variables: delta, then primary, then
+  /// secondary.
+  ///
+  /// This is part of the file format and is stable; changing it would be a
+  /// breaking change.
+  pub const ORDER: [LatentVarKey; 3] = [
+    LatentVarKey::Delta,
+    LatentVarKey::Primary,
+    LatentVarKey::Secondary,
+  ];
+}
+
 /// A generic container holding a value for each applicable latent variable.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct PerLatentVar<T> {
@@ -119,7 +137,7 @@ impl<T> PerLatentVar<T> {
   }
 
   /// Returns a vector of the defined `LatentVarKey`s and values, in order
-  /// of appearance in the file.
+  /// of appearance in the file, i.e. of [`LatentVarKey::ORDER`].
   pub fn enumerated(self) -> Vec<(LatentVarKey, T)> {
     let mut res = Vec::with_capacity(3);
     if let Some(value) = self.delta {
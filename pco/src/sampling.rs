@@ -1,3 +1,5 @@
+#[cfg(feature = "debug_sample")]
+use std::cell::RefCell;
 use std::cmp::max;
 use std::collections::HashMap;
 use std::fmt::Debug;
@@ -17,6 +19,20 @@ const CLASSIC_MEMORIZABLE_BINS: f64 = (1 << CLASSIC_MEMORIZABLE_BINS_LOG) as f64
 // giving up
 const SAMPLING_PERSISTENCE: usize = 4;
 
+#[cfg(feature = "debug_sample")]
+thread_local! {
+  static SAMPLE_INDICES: RefCell<Option<Vec<usize>>> = const { RefCell::new(None) };
+}
+
+// Runs f, returning the indices of the last sample chosen during it, if any.
+#[cfg(feature = "debug_sample")]
+pub fn capture_sample_indices<R, F: FnOnce() -> R>(f: F) -> (R, Option<Vec<usize>>) {
+  SAMPLE_INDICES.with_borrow_mut(|indices| *indices = None);
+  let res = f();
+  let indices = SAMPLE_INDICES.with_borrow_mut(Option::take);
+  (res, indices)
+}
+
 fn calc_sample_n(n: usize) -> Option<usize> {
   if n >= MIN_SAMPLE {
     Some(MIN_SAMPLE + (n - MIN_SAMPLE) / SAMPLE_RATIO)
@@ -28,6 +44,7 @@ fn calc_sample_n(n: usize) -> Option<usize> {
 #[inline(never)]
 pub fn choose_sample<T, S: Copy + Debug, Filter: Fn(&T) -> Option<S>>(
   nums: &[T],
+  seed: u64,
   filter: Filter,
 ) -> Option<Vec<S>> {
   // We can't modify the list, and copying it may be expensive, but we want to
@@ -37,9 +54,11 @@ pub fn choose_sample<T, S: Copy + Debug, Filter: Fn(&T) -> Option<S>>(
   // Maybe this is a bad idea, but it works for now.
   let target_sample_size = calc_sample_n(nums.len())?;
 
-  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(seed);
   let mut visited = vec![0_u8; nums.len().div_ceil(8)];
   let mut res = Vec::with_capacity(target_sample_size);
+  #[cfg(feature = "debug_sample")]
+  let mut indices = Vec::with_capacity(target_sample_size);
   let mut n_iters = 0;
   while res.len() < target_sample_size && n_iters < SAMPLING_PERSISTENCE * target_sample_size {
     let rand_idx = rng.next_u64() as usize % nums.len();
@@ -50,6 +69,8 @@ pub fn choose_sample<T, S: Copy + Debug, Filter: Fn(&T) -> Option<S>>(
     if is_visited == 0 {
       if let Some(x) = filter(&nums[rand_idx]) {
         res.push(x);
+        #[cfg(feature = "debug_sample")]
+        indices.push(rand_idx);
       }
       visited[visited_idx] |= mask;
     }
@@ -57,6 +78,8 @@ pub fn choose_sample<T, S: Copy + Debug, Filter: Fn(&T) -> Option<S>>(
   }
 
   if res.len() >= MIN_SAMPLE {
+    #[cfg(feature = "debug_sample")]
+    SAMPLE_INDICES.with_borrow_mut(|recorded| *recorded = Some(indices));
     Some(res)
   } else {
     None
@@ -119,7 +142,7 @@ mod tests {
     for i in 0..150 {
       nums.push(-i as f32);
     }
-    let mut sample = choose_sample(&nums, |&num| {
+    let mut sample = choose_sample(&nums, 0, |&num| {
       if num == 0.0 {
         None
       } else {
@@ -131,4 +154,13 @@ mod tests {
     assert_eq!(sample.len(), 13);
     assert_eq!(&sample[0..3], &[-147.0, -142.0, -119.0]);
   }
+
+  #[test]
+  fn test_choose_sample_seed() {
+    let nums = (0..1000).collect::<Vec<u32>>();
+    let choose = |seed| choose_sample(&nums, seed, |&num| Some(num)).unwrap();
+    assert_eq!(choose(0), choose(0));
+    assert_eq!(choose(1), choose(1));
+    assert_ne!(choose(0), choose(1));
+  }
 }
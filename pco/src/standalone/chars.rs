@@ -0,0 +1,72 @@
+use crate::chunk_config::ChunkConfig;
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::simple::{simple_compress, simple_decompress};
+
+/// Takes in a slice of chars and an exact configuration and returns
+/// compressed bytes of their `u32` code points.
+///
+/// The bytes are an ordinary .pco file of `u32`s, so they can also be
+/// decompressed as code points with
+/// [`simple_decompress`][crate::standalone::simple_decompress].
+/// Will return an error if the compressor config is invalid.
+pub fn simple_compress_chars(chars: &[char], config: &ChunkConfig) -> PcoResult<Vec<u8>> {
+  let code_points = chars.iter().map(|&c| c as u32).collect::<Vec<_>>();
+  simple_compress(&code_points, config)
+}
+
+/// Takes in compressed bytes of `u32` code points (as produced by
+/// [`simple_compress_chars`]) and returns the chars.
+///
+/// Will return a corruption error if any code point is not a Unicode scalar
+/// value, i.e. it is a surrogate or exceeds `char::MAX`.
+/// Will also return an error if there are any other compatibility,
+/// corruption, or insufficient data issues.
+pub fn simple_decompress_chars(src: &[u8]) -> PcoResult<Vec<char>> {
+  simple_decompress::<u32>(src)?
+    .into_iter()
+    .enumerate()
+    .map(|(i, code_point)| {
+      char::from_u32(code_point).ok_or_else(|| {
+        PcoError::corruption(format!(
+          "code point {:#x} at index {} is not a valid char",
+          code_point, i,
+        ))
+      })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::errors::ErrorKind;
+
+  #[test]
+  fn test_code_point_ranges() -> PcoResult<()> {
+    // ASCII, CJK, emoji, and the scalar values around the surrogates
+    let chars = ('\0'..='\u{7f}')
+      .chain('\u{4e00}'..'\u{5000}')
+      .chain('\u{1f600}'..'\u{1f650}')
+      .chain(['\u{d7ff}', '\u{e000}', char::MAX])
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default();
+    let compressed = simple_compress_chars(&chars, &config)?;
+    assert_eq!(simple_decompress_chars(&compressed)?, chars);
+    assert_eq!(
+      simple_decompress::<u32>(&compressed)?,
+      chars.iter().map(|&c| c as u32).collect::<Vec<_>>(),
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_invalid_code_points() -> PcoResult<()> {
+    let config = ChunkConfig::default();
+    for invalid in [0xd800, 0xdfff, char::MAX as u32 + 1, u32::MAX] {
+      let compressed = simple_compress(&[65, invalid, 66], &config)?;
+      let err = simple_decompress_chars(&compressed).unwrap_err();
+      assert!(matches!(err.kind, ErrorKind::Corruption));
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,133 @@
+use crate::chunk_config::ChunkConfig;
+use crate::constants::{DEFAULT_MAX_PAGE_N, MAX_ENTRIES};
+use crate::data_types::{Latent, Number};
+use crate::errors::PcoResult;
+use crate::standalone::simple::simple_compress;
+use crate::{wrapped, PagingSpec};
+
+const MIN_CANDIDATE_LOG: u32 = 12;
+const MAX_CANDIDATE_LOG: u32 = 20;
+// We accept a chunk size whose compressed size is within this factor of the
+// best candidate's.
+const SIZE_TOLERANCE: f64 = 1.01;
+
+/// The count of numbers per chunk that the standalone format uses by
+/// default, via the default [`PagingSpec`].
+///
+/// This is tuned for 64-bit numbers; see [`default_chunk_size`] for a
+/// default that accounts for narrower types.
+pub const DEFAULT_CHUNK_SIZE: usize = DEFAULT_MAX_PAGE_N;
+
+/// Returns a default chunk size for `T`, giving each chunk about the same
+/// uncompressed byte footprint as [`DEFAULT_CHUNK_SIZE`] 64-bit numbers.
+///
+/// Memory use during compression and decompression scales with a chunk's
+/// uncompressed bytes, so narrower types can afford more numbers per chunk,
+/// which amortizes each chunk's metadata over more of them.
+/// For example, this is 4 times larger for 16-bit types than for 64-bit
+/// ones.
+pub fn default_chunk_size<T: Number>() -> usize {
+  (DEFAULT_CHUNK_SIZE * 64 / T::L::BITS as usize).min(MAX_ENTRIES)
+}
+
+/// Suggests a chunk size for compressing data like the sample with this
+/// config, by trial-compressing the sample with a few candidate chunk sizes.
+///
+/// Larger chunks amortize their metadata and give pco more numbers to learn
+/// from, but cost more memory and reduce opportunities for parallelism and
+/// skipping.
+/// The candidates are powers of 2 from 2^12 to 2^20 that fit within the
+/// sample, and the smallest one that compresses within 1% of the best is
+/// chosen.
+/// This means the sample should be representative and ideally several times
+/// larger than the chunk size, and that the suggestion costs up to 9
+/// compressions of it.
+/// If the sample is too small to try any candidates, this returns
+/// [`DEFAULT_MAX_PAGE_N`][crate::DEFAULT_MAX_PAGE_N].
+///
+/// The config's paging spec is ignored.
+/// Will return an error if the config is invalid.
+pub fn suggest_chunk_size<T: Number>(sample: &[T], config: &ChunkConfig) -> PcoResult<usize> {
+  wrapped::validate_config(config)?;
+
+  let mut sizes = Vec::new();
+  for candidate_log in MIN_CANDIDATE_LOG..=MAX_CANDIDATE_LOG {
+    let candidate = 1 << candidate_log;
+    if candidate > sample.len() {
+      break;
+    }
+    let candidate_config = config
+      .clone()
+      .with_paging_spec(PagingSpec::EqualPagesUpTo(candidate));
+    let size = simple_compress(sample, &candidate_config)?.len();
+    sizes.push((candidate, size));
+  }
+
+  let Some(best_size) = sizes.iter().map(|&(_, size)| size).min() else {
+    return Ok(DEFAULT_MAX_PAGE_N);
+  };
+  let chunk_size = sizes
+    .iter()
+    .find(|&&(_, size)| size as f64 <= best_size as f64 * SIZE_TOLERANCE)
+    .map(|&(candidate, _)| candidate)
+    .unwrap();
+  Ok(chunk_size)
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  use super::*;
+
+  #[test]
+  fn test_small_sample_gets_default() -> PcoResult<()> {
+    let sample = (0..1000).collect::<Vec<u32>>();
+    assert_eq!(
+      suggest_chunk_size(&sample, &ChunkConfig::default())?,
+      DEFAULT_MAX_PAGE_N
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_default_chunk_size() {
+    assert_eq!(
+      default_chunk_size::<i64>(),
+      DEFAULT_CHUNK_SIZE
+    );
+    assert_eq!(
+      default_chunk_size::<f32>(),
+      2 * DEFAULT_CHUNK_SIZE
+    );
+    assert_eq!(
+      default_chunk_size::<u16>(),
+      4 * DEFAULT_CHUNK_SIZE
+    );
+  }
+
+  #[test]
+  fn test_suggested_chunk_size() -> PcoResult<()> {
+    // Uniform noise compresses about as well at any size, so it shouldn't
+    // need the largest candidate.
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let noise = (0..100_000)
+      .map(|_| rng.gen_range(0..1_u64 << 44))
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default();
+    let noise_chunk_size = suggest_chunk_size(&noise, &config)?;
+    assert!(noise_chunk_size.is_power_of_two());
+    assert!(noise_chunk_size < 1 << 16);
+
+    // Constant data is dominated by per-chunk overhead, so the largest
+    // candidate that fits should win.
+    let constant = vec![7_u64; 100_000];
+    assert_eq!(
+      suggest_chunk_size(&constant, &config)?,
+      1 << 16
+    );
+    Ok(())
+  }
+}
@@ -0,0 +1,237 @@
+use crate::bin_layout::mode_spec_for;
+use crate::chunk_config::{ChunkConfig, DeltaSpec};
+use crate::data_types::Number;
+use crate::errors::PcoResult;
+use crate::metadata::format_version::FormatVersion;
+use crate::metadata::{ChunkMeta, DeltaEncoding};
+use crate::standalone::simple::simple_compress;
+use crate::wrapped::{chunk_compressor, ChunkCompressor};
+
+// Presets are learned from, and similarity is checked on, a contiguous
+// prefix of each column of this many numbers.
+const SAMPLE_N: usize = 1 << 13;
+// A column shares a preset only if its sample's pages compress to within
+// this ratio of the size of the pages of the sample the preset was learned
+// from.
+const MAX_SHARED_SIZE_RATIO: f64 = 1.05;
+// Each preset costs a trial compression of every later column's sample, so
+// we only keep this many of the most recently used ones.
+const MAX_PRESETS: usize = 4;
+
+// A mode and delta encoding learned from one column's sample, as a config
+// that skips their analysis for similar columns.
+struct Preset {
+  config: ChunkConfig,
+  // the page size of the sample the preset was learned from
+  reference_size: usize,
+  n_nontrivial_vars: usize,
+}
+
+// A forced mode can compress dissimilar data to a similar size, e.g. int
+// mult mode on data that isn't made of multiples of its base just splits
+// each number's entropy across two latent variables, which is slower to
+// decompress.
+// So we also require the same count of latent variables with data.
+fn n_nontrivial_vars(meta: &ChunkMeta) -> usize {
+  meta
+    .per_latent_var
+    .as_ref()
+    .enumerated()
+    .into_iter()
+    .filter(|(_, var_meta)| !var_meta.is_trivial())
+    .count()
+}
+
+// the spec that reproduces this delta encoding, other than details that
+// depend on the count of numbers, like the lookback window
+fn delta_spec_for(delta_encoding: DeltaEncoding) -> DeltaSpec {
+  match delta_encoding {
+    DeltaEncoding::None => DeltaSpec::None,
+    DeltaEncoding::Consecutive(config) => DeltaSpec::TryConsecutive(config.order),
+    DeltaEncoding::ConsecutivePerPage(config) => DeltaSpec::TryConsecutivePerPage(config.order),
+    DeltaEncoding::Lookback(_) => DeltaSpec::TryLookback,
+    DeltaEncoding::Xor => DeltaSpec::TryXor,
+  }
+}
+
+fn page_size(cc: &ChunkCompressor) -> PcoResult<usize> {
+  let mut size = 0;
+  for page_idx in 0..cc.n_per_page().len() {
+    size += cc.write_page(page_idx, Vec::new())?.len();
+  }
+  Ok(size)
+}
+
+fn learn_preset<T: Number>(sample: &[T], config: &ChunkConfig) -> PcoResult<Preset> {
+  let cc = chunk_compressor::new(sample, config, &FormatVersion::newest())?;
+  let meta = cc.meta();
+  Ok(Preset {
+    config: config
+      .clone()
+      .with_mode_spec(mode_spec_for(meta.mode))
+      .with_delta_spec(delta_spec_for(meta.delta_encoding)),
+    reference_size: page_size(&cc)?,
+    n_nontrivial_vars: n_nontrivial_vars(meta),
+  })
+}
+
+// Returns the index of the first preset that compresses the sample about as
+// well as the sample it was learned from.
+// This is cheaper than analyzing the sample from scratch to compare
+// against.
+fn choose_shared_preset<T: Number>(sample: &[T], presets: &[Preset]) -> PcoResult<Option<usize>> {
+  for (idx, preset) in presets.iter().enumerate() {
+    let cc = chunk_compressor::new(
+      sample,
+      &preset.config,
+      &FormatVersion::newest(),
+    )?;
+    let size = page_size(&cc)?;
+    if n_nontrivial_vars(cc.meta()) == preset.n_nontrivial_vars
+      && size as f64 <= preset.reference_size as f64 * MAX_SHARED_SIZE_RATIO
+    {
+      return Ok(Some(idx));
+    }
+  }
+  Ok(None)
+}
+
+/// Takes in columns of numbers, e.g. of a column-major matrix, and an exact
+/// configuration and returns compressed bytes for each column.
+///
+/// Columns that are distributed similarly, like readings from the same type
+/// of sensor, share analysis: each column's start is compressed with the
+/// modes and delta encodings learned from earlier columns, and if one
+/// compresses it nearly as well as the column it was learned from, the
+/// whole column is compressed with them, skipping their analysis.
+/// Otherwise the column is compressed as usual and its mode and delta
+/// encoding are learned from its start for later columns.
+/// Only the 4 most recently used of these are kept.
+/// Bins are still optimized for each column, so this costs little
+/// compression ratio, and it pays off for many long columns.
+///
+/// Each column's bytes are an ordinary, independent .pco file, decompressible
+/// with [`simple_decompress`][crate::standalone::simple_decompress].
+/// Will return an error if the compressor config is invalid.
+pub fn compress_columns<T: Number>(cols: &[&[T]], config: &ChunkConfig) -> PcoResult<Vec<Vec<u8>>> {
+  let mut presets = Vec::new();
+  cols
+    .iter()
+    .map(|col| compress_column(col, config, &mut presets))
+    .collect()
+}
+
+// Presets are ordered from most to least recently used.
+fn compress_column<T: Number>(
+  col: &[T],
+  config: &ChunkConfig,
+  presets: &mut Vec<Preset>,
+) -> PcoResult<Vec<u8>> {
+  // short columns gain nothing from sharing
+  if col.len() <= SAMPLE_N {
+    return simple_compress(col, config);
+  }
+
+  let sample = &col[..SAMPLE_N];
+  match choose_shared_preset(sample, presets)? {
+    Some(idx) => {
+      let preset = presets.remove(idx);
+      let compressed = simple_compress(col, &preset.config)?;
+      presets.insert(0, preset);
+      Ok(compressed)
+    }
+    None => {
+      presets.insert(0, learn_preset(sample, config)?);
+      presets.truncate(MAX_PRESETS);
+      simple_compress(col, config)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::standalone::simple_decompress;
+  use crate::ModeSpec;
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  #[test]
+  fn test_similar_columns_share_presets() -> PcoResult<()> {
+    let n = 40_000;
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    // two kinds of sensors: multiples of 10 around 10^6, and noise around 0
+    let mut cols = (0..6)
+      .map(|col_idx| {
+        (0..n)
+          .map(|_| {
+            let bell = (0..4).map(|_| rng.gen_range(0..200)).sum::<i64>();
+            if col_idx % 2 == 0 {
+              1_000_000 + bell * 10
+            } else {
+              bell - 400
+            }
+          })
+          .collect::<Vec<i64>>()
+      })
+      .collect::<Vec<_>>();
+    // and a dissimilar, smooth column
+    cols.push((0..n as i64).map(|i| i * i / 1000).collect());
+    cols.extend([vec![3, 1, 4], vec![]]);
+    let col_refs = cols.iter().map(|col| col.as_slice()).collect::<Vec<_>>();
+    let config = ChunkConfig::default();
+    let compresseds = compress_columns(&col_refs, &config)?;
+    assert_eq!(compresseds.len(), cols.len());
+
+    let presets = [
+      learn_preset(&cols[0][..SAMPLE_N], &config)?,
+      learn_preset(&cols[1][..SAMPLE_N], &config)?,
+    ];
+    for (col_idx, (col, compressed)) in cols.iter().zip(&compresseds).enumerate() {
+      assert_eq!(&simple_decompress::<i64>(compressed)?, col);
+      let expected = if (2..6).contains(&col_idx) {
+        // later columns of each kind reuse the first one's preset
+        simple_compress(col, &presets[col_idx % 2].config)?
+      } else {
+        simple_compress(col, &config)?
+      };
+      assert_eq!(compressed, &expected, "{}", col_idx);
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_presets_are_capped() -> PcoResult<()> {
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    // each base's multiples only share a preset with each other
+    let mut col = |base: i64| {
+      (0..SAMPLE_N + 1000)
+        .map(|_| rng.gen_range(0..1000) * base)
+        .collect::<Vec<_>>()
+    };
+    let preset_bases = |presets: &[Preset]| {
+      presets
+        .iter()
+        .map(|preset| preset.config.mode_spec)
+        .collect::<Vec<_>>()
+    };
+    let config = ChunkConfig::default();
+    let mut presets = Vec::new();
+    for base in [10, 7, 13, 3, 7, 11] {
+      compress_column(&col(base), &config, &mut presets)?;
+    }
+    // 7 was reused, so 10 was the least recently used and got evicted
+    assert_eq!(
+      preset_bases(&presets),
+      [11, 7, 3, 13].map(ModeSpec::TryIntMult)
+    );
+
+    compress_column(&col(13), &config, &mut presets)?;
+    assert_eq!(
+      preset_bases(&presets),
+      [13, 11, 7, 3].map(ModeSpec::TryIntMult)
+    );
+    Ok(())
+  }
+}
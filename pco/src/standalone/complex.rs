@@ -0,0 +1,84 @@
+use num_complex::Complex;
+
+use crate::chunk_config::ChunkConfig;
+use crate::data_types::Number;
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::simple::{simple_compress, simple_decompress};
+
+/// Takes in a slice of complex numbers and an exact configuration and returns
+/// compressed bytes for the real and imaginary parts, respectively.
+///
+/// The real and imaginary parts are compressed as two separate files, since
+/// each part is usually much smoother on its own than the interleaved
+/// sequence is.
+/// Will return an error if the compressor config is invalid.
+pub fn simple_compress_complex<T: Number>(
+  nums: &[Complex<T>],
+  config: &ChunkConfig,
+) -> PcoResult<(Vec<u8>, Vec<u8>)> {
+  let re = nums.iter().map(|num| num.re).collect::<Vec<_>>();
+  let re_compressed = simple_compress(&re, config)?;
+  let im = nums.iter().map(|num| num.im).collect::<Vec<_>>();
+  let im_compressed = simple_compress(&im, config)?;
+  Ok((re_compressed, im_compressed))
+}
+
+/// Takes in compressed bytes for the real and imaginary parts (as produced by
+/// [`simple_compress_complex`]) and returns a vector of complex numbers.
+///
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues, or if the two parts have different counts.
+pub fn simple_decompress_complex<T: Number>(
+  re_src: &[u8],
+  im_src: &[u8],
+) -> PcoResult<Vec<Complex<T>>> {
+  let re = simple_decompress::<T>(re_src)?;
+  let im = simple_decompress::<T>(im_src)?;
+  if re.len() != im.len() {
+    return Err(PcoError::invalid_argument(format!(
+      "real and imaginary parts had different counts ({} vs {})",
+      re.len(),
+      im.len(),
+    )));
+  }
+  Ok(
+    re.into_iter()
+      .zip(im)
+      .map(|(re, im)| Complex::new(re, im))
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_chirp() -> PcoResult<()> {
+    let n = 100_000;
+    let nums = (0..n)
+      .map(|i| {
+        let t = i as f32 / n as f32;
+        let phase = 2000.0 * t * t;
+        Complex::new(phase.cos(), phase.sin())
+      })
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default();
+
+    let (re_compressed, im_compressed) = simple_compress_complex(&nums, &config)?;
+    let interleaved = nums
+      .iter()
+      .flat_map(|num| [num.re, num.im])
+      .collect::<Vec<_>>();
+    let interleaved_compressed = simple_compress(&interleaved, &config)?;
+    assert!(re_compressed.len() + im_compressed.len() < interleaved_compressed.len());
+
+    let decompressed = simple_decompress_complex::<f32>(&re_compressed, &im_compressed)?;
+    assert_eq!(decompressed, nums);
+
+    // mismatched parts
+    let (short_compressed, _) = simple_compress_complex(&nums[..10], &config)?;
+    assert!(simple_decompress_complex::<f32>(&short_compressed, &im_compressed).is_err());
+    Ok(())
+  }
+}
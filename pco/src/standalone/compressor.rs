@@ -4,7 +4,11 @@ use crate::data_types::{Number, NumberType};
 use crate::errors::{PcoError, PcoResult};
 use crate::metadata::ChunkMeta;
 use crate::standalone::constants::*;
-use crate::{bits, wrapped, ChunkConfig};
+use crate::standalone::fixed_range::{FixedRange, FIXED_RANGE_BYTE_SIZE};
+use crate::standalone::{
+  index_footer, source_info, ChunkByteRange, ChunkConfigSummary, SourceInfo,
+};
+use crate::{bits, wrapped, BinLayout, ChunkConfig};
 use std::io::Write;
 
 unsafe fn write_varint<W: Write>(n: u64, writer: &mut BitWriter<W>) {
@@ -45,6 +49,12 @@ pub struct FileCompressor {
   inner: wrapped::FileCompressor,
   n_hint: usize,
   uniform_type: Option<NumberType>,
+  manifest: Option<ChunkConfigSummary>,
+  source_info: Option<SourceInfo>,
+  annotated_chunks: bool,
+  index_footer: bool,
+  lossy: bool,
+  fixed_range: Option<FixedRange>,
 }
 
 impl FileCompressor {
@@ -68,25 +78,161 @@ impl FileCompressor {
     self
   }
 
+  /// Optionally specify a [`ChunkConfigSummary`] of the settings used, to
+  /// record in the header.
+  ///
+  /// This lets readers audit or reproduce how the file was made via
+  /// [`FileDecompressor::manifest`][crate::standalone::FileDecompressor::manifest].
+  /// It takes a few extra bytes and doesn't affect decompression.
+  /// Since the header comes first, compute a chunk compressor before writing
+  /// the header in order to summarize it.
+  pub fn with_manifest(mut self, manifest: Option<ChunkConfigSummary>) -> Self {
+    self.manifest = manifest;
+    self
+  }
+
+  /// Optionally specify a [`SourceInfo`] describing where the numbers came
+  /// from, e.g. the original format and column name, to record in the
+  /// header.
+  ///
+  /// Read it back with
+  /// [`FileDecompressor::source_info`][crate::standalone::FileDecompressor::source_info].
+  /// It takes 2 bytes plus the length of its strings, each of which may be
+  /// at most [`SourceInfo::MAX_STRING_LEN`] bytes, and doesn't affect
+  /// decompression.
+  pub fn with_source_info(mut self, source_info: Option<SourceInfo>) -> Self {
+    self.source_info = source_info;
+    self
+  }
+
+  /// Optionally require each chunk to carry a `u64` annotation, e.g. the id
+  /// of the sensor that produced it (default: false).
+  ///
+  /// Set each chunk's annotation with
+  /// [`ChunkCompressor::with_annotation`] and read it back with
+  /// [`ChunkDecompressor::annotation`][crate::standalone::ChunkDecompressor::annotation].
+  /// Annotations take 1 to 9 bytes per chunk and don't affect decompression
+  /// of the numbers.
+  pub fn with_annotated_chunks(mut self, annotated_chunks: bool) -> Self {
+    self.annotated_chunks = annotated_chunks;
+    self
+  }
+
+  /// Optionally write an index of every chunk's byte range and count of
+  /// numbers at the end of the file (default: false).
+  ///
+  /// This lets readers open the file with
+  /// [`FileDecompressor::open_with_footer`][crate::standalone::FileDecompressor::open_with_footer]
+  /// and jump to any chunk after one small read from the end, instead of
+  /// parsing every chunk in order.
+  /// The index takes 24 bytes per chunk plus 20 bytes, and must be written
+  /// with [`write_footer_with_index`][Self::write_footer_with_index].
+  pub fn with_index_footer(mut self, index_footer: bool) -> Self {
+    self.index_footer = index_footer;
+    self
+  }
+
+  /// Optionally mark the file as lossy, e.g. because its numbers were
+  /// quantized before compression (default: false).
+  ///
+  /// Pco itself always reproduces the numbers it was given, so this only
+  /// records that they differ from the original data.
+  /// Readers can check it via
+  /// [`FileDecompressor::is_lossless`][crate::standalone::FileDecompressor::is_lossless].
+  pub fn with_lossy(mut self, lossy: bool) -> Self {
+    self.lossy = lossy;
+    self
+  }
+
+  // The chunks must be the range's u64 levels, and the file is marked lossy.
+  pub(crate) fn with_fixed_range(mut self, fixed_range: Option<FixedRange>) -> Self {
+    self.fixed_range = fixed_range;
+    self
+  }
+
+  /// Sets the format version to write (default: 3).
+  ///
+  /// See
+  /// [`wrapped::FileCompressor::with_format_version`][crate::wrapped::FileCompressor::with_format_version]
+  /// for details.
+  pub fn with_format_version(mut self, version: u8) -> Self {
+    self.inner = self.inner.with_format_version(version);
+    self
+  }
+
+  /// Sets the format version to the oldest one that can hold a chunk with
+  /// this metadata, as well as any other chunks compressed with this config.
+  ///
+  /// See
+  /// [`wrapped::FileCompressor::with_oldest_format_version`][crate::wrapped::FileCompressor::with_oldest_format_version]
+  /// for details.
+  pub fn with_oldest_format_version(mut self, config: &ChunkConfig, meta: &ChunkMeta) -> Self {
+    self.inner = self.inner.with_oldest_format_version(config, meta);
+    self
+  }
+
   /// Writes a short header to the destination.
   ///
-  /// Will return an error if the provided `Write` errors.
+  /// Will return an error if the provided `Write` errors or the format
+  /// version is invalid.
   pub fn write_header<W: Write>(&self, dst: W) -> PcoResult<W> {
     let mut writer = BitWriter::new(dst, STANDALONE_HEADER_PADDING);
     writer.write_aligned_bytes(&MAGIC_HEADER)?;
     unsafe {
-      match self.uniform_type {
-        Some(number_type) => {
+      match (
+        self.manifest.is_some()
+          || self.annotated_chunks
+          || self.index_footer
+          || self.source_info.is_some()
+          || self.lossy
+          || self.fixed_range.is_some(),
+        self.uniform_type,
+      ) {
+        (true, uniform_type) => {
+          // Use new standalone v4 to encode this.
+          // This code path is only possible via `with_manifest`,
+          // `with_annotated_chunks`, `with_index_footer`, `with_source_info`,
+          // `with_lossy`, or fixed range compression, which are new
+          // functionality.
+          writer.write_usize(
+            CURRENT_STANDALONE_VERSION,
+            BITS_TO_ENCODE_STANDALONE_VERSION,
+          );
+          let mut flags = 0;
+          if self.manifest.is_some() {
+            flags |= MANIFEST_FLAG;
+          }
+          if self.annotated_chunks {
+            flags |= ANNOTATED_CHUNKS_FLAG;
+          }
+          if self.index_footer {
+            flags |= INDEX_FOOTER_FLAG;
+          }
+          if self.source_info.is_some() {
+            flags |= SOURCE_INFO_FLAG;
+          }
+          if self.lossy || self.fixed_range.is_some() {
+            flags |= LOSSY_FLAG;
+          }
+          if self.fixed_range.is_some() {
+            flags |= FIXED_RANGE_FLAG;
+          }
+          writer.write_aligned_bytes(&[
+            uniform_type.map_or(MAGIC_TERMINATION_BYTE, |t| t as u8),
+            flags,
+          ])?;
+        }
+        (false, Some(number_type)) => {
           // Use new standalone v3 to encode this.
           // This code path is only possible via `with_uniform_type`, which is
           // new functionality.
           writer.write_usize(
-            CURRENT_STANDALONE_VERSION,
+            PRE_FLAGS_STANDALONE_VERSION,
             BITS_TO_ENCODE_STANDALONE_VERSION,
           );
           writer.write_aligned_bytes(&[number_type as u8])?;
         }
-        None => {
+        (false, None) => {
           // no new functionality required, stick to v2 to avoid breaking
           // people's code
           // TODO in 1.0 get rid of this case and write a number type byte of 0
@@ -101,7 +247,39 @@ impl FileCompressor {
     writer.finish_byte();
     writer.flush()?;
     let dst = writer.into_inner();
-    self.inner.write_header(dst)
+    let dst = self.inner.write_header(dst)?;
+
+    // the manifest comes after the wrapped header so it can use the format
+    // version
+    let dst = match self.manifest {
+      Some(manifest) => {
+        let mut writer = BitWriter::new(dst, STANDALONE_HEADER_PADDING);
+        unsafe { manifest.write_to(&mut writer)? };
+        writer.flush()?;
+        writer.into_inner()
+      }
+      None => dst,
+    };
+
+    let dst = match &self.source_info {
+      Some(source_info) => {
+        let mut writer = BitWriter::new(dst, source_info::max_byte_size());
+        source_info.write_to(&mut writer)?;
+        writer.flush()?;
+        writer.into_inner()
+      }
+      None => dst,
+    };
+
+    match &self.fixed_range {
+      Some(fixed_range) => {
+        let mut writer = BitWriter::new(dst, FIXED_RANGE_BYTE_SIZE);
+        fixed_range.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(writer.into_inner())
+      }
+      None => Ok(dst),
+    }
   }
 
   /// Creates a `ChunkCompressor` that can be used to write entire chunks
@@ -116,29 +294,97 @@ impl FileCompressor {
     nums: &[T],
     config: &ChunkConfig,
   ) -> PcoResult<ChunkCompressor> {
-    let number_type = NumberType::from_descriminant(T::NUMBER_TYPE_BYTE).unwrap();
+    let config = self.standalone_config::<T>(nums, config)?;
+    Ok(ChunkCompressor {
+      inner: self.inner.chunk_compressor(nums, &config)?,
+      number_type_byte: T::NUMBER_TYPE_BYTE,
+      annotated: self.annotated_chunks,
+      annotation: None,
+    })
+  }
+
+  /// Creates a `ChunkCompressor` like
+  /// [`chunk_compressor`][Self::chunk_compressor], but using the mode, delta
+  /// encoding, and bins of a previously learned layout instead of analyzing
+  /// the numbers.
+  ///
+  /// See
+  /// [`wrapped::FileCompressor::chunk_compressor_with_layout`][crate::wrapped::FileCompressor::chunk_compressor_with_layout]
+  /// for details.
+  pub fn chunk_compressor_with_layout<T: Number>(
+    &self,
+    nums: &[T],
+    config: &ChunkConfig,
+    layout: &BinLayout,
+  ) -> PcoResult<ChunkCompressor> {
+    let config = self.standalone_config::<T>(nums, config)?;
+    Ok(ChunkCompressor {
+      inner: self
+        .inner
+        .chunk_compressor_with_layout(nums, &config, layout)?,
+      number_type_byte: T::NUMBER_TYPE_BYTE,
+      annotated: self.annotated_chunks,
+      annotation: None,
+    })
+  }
+
+  fn standalone_config<T: Number>(
+    &self,
+    nums: &[T],
+    config: &ChunkConfig,
+  ) -> PcoResult<ChunkConfig> {
     if let Some(uniform_type) = self.uniform_type {
-      if number_type != uniform_type {
+      if T::NUMBER_TYPE_BYTE != uniform_type as u8 {
         return Err(PcoError::corruption(format!(
-          "number type {:?} does not match uniform type {:?}",
-          number_type, uniform_type,
+          "number type byte {} does not match uniform type {:?}",
+          T::NUMBER_TYPE_BYTE,
+          uniform_type,
         )));
       }
     }
 
     let mut config = config.clone();
     config.paging_spec = PagingSpec::Exact(vec![nums.len()]);
-
-    Ok(ChunkCompressor {
-      inner: self.inner.chunk_compressor(nums, &config)?,
-      number_type,
-    })
+    Ok(config)
   }
 
   /// Writes a short footer to the destination.
   ///
-  /// Will return an error if the provided `Write` errors.
+  /// Will return an invalid argument error if the file has an
+  /// [index footer][Self::with_index_footer], or an error if the provided
+  /// `Write` errors.
   pub fn write_footer<W: Write>(&self, dst: W) -> PcoResult<W> {
+    if self.index_footer {
+      return Err(PcoError::invalid_argument(
+        "files with an index footer must be finished with write_footer_with_index",
+      ));
+    }
+    self.write_termination(dst)
+  }
+
+  /// Writes a footer to the destination, including an index of the given
+  /// chunks.
+  ///
+  /// `chunks` should list every chunk written, in order, with byte offsets
+  /// from the start of the file; these are not checked.
+  /// Will return an invalid argument error unless the file has an
+  /// [index footer][Self::with_index_footer], or an error if the provided
+  /// `Write` errors.
+  pub fn write_footer_with_index<W: Write>(
+    &self,
+    chunks: &[ChunkByteRange],
+    dst: W,
+  ) -> PcoResult<W> {
+    if !self.index_footer {
+      return Err(PcoError::invalid_argument(
+        "writing an index footer requires a file compressor with an index footer",
+      ));
+    }
+    let dst = self.write_termination(dst)?;
+    index_footer::write_index_footer(chunks, dst)
+  }
+
+  fn write_termination<W: Write>(&self, dst: W) -> PcoResult<W> {
     let mut writer = BitWriter::new(dst, 1);
     writer.write_aligned_bytes(&[MAGIC_TERMINATION_BYTE])?;
     writer.flush()?;
@@ -150,7 +396,10 @@ impl FileCompressor {
 #[derive(Clone, Debug)]
 pub struct ChunkCompressor {
   inner: wrapped::ChunkCompressor,
-  number_type: NumberType,
+  // a byte rather than a NumberType, since custom types have their own
+  number_type_byte: u8,
+  annotated: bool,
+  annotation: Option<u64>,
 }
 
 impl ChunkCompressor {
@@ -159,12 +408,58 @@ impl ChunkCompressor {
     self.inner.meta()
   }
 
+  /// Sets the annotation to store with this chunk.
+  ///
+  /// Writing the chunk will return an invalid argument error unless an
+  /// annotation is set if and only if the file compressor has
+  /// [annotated chunks][FileCompressor::with_annotated_chunks].
+  pub fn with_annotation(mut self, annotation: Option<u64>) -> Self {
+    self.annotation = annotation;
+    self
+  }
+
+  /// See
+  /// [`wrapped::ChunkCompressor::analysis_sample_indices`][crate::wrapped::ChunkCompressor::analysis_sample_indices].
+  #[cfg(feature = "debug_sample")]
+  pub fn analysis_sample_indices(&self) -> Option<&[usize]> {
+    self.inner.analysis_sample_indices()
+  }
+
+  /// See
+  /// [`wrapped::ChunkCompressor::analysis_sample`][crate::wrapped::ChunkCompressor::analysis_sample].
+  #[cfg(feature = "debug_sample")]
+  pub fn analysis_sample<T: Number>(&self) -> Option<Vec<T>> {
+    self.inner.analysis_sample()
+  }
+
+  /// See
+  /// [`wrapped::ChunkCompressor::telemetry`][crate::wrapped::ChunkCompressor::telemetry].
+  #[cfg(feature = "telemetry")]
+  pub fn telemetry(&self) -> crate::wrapped::ChunkTelemetry {
+    self.inner.telemetry()
+  }
+
+  /// See
+  /// [`wrapped::ChunkCompressor::latent_entropy`][crate::wrapped::ChunkCompressor::latent_entropy].
+  #[cfg(feature = "entropy")]
+  pub fn latent_entropy(
+    &self,
+  ) -> PcoResult<crate::metadata::PerLatentVar<crate::wrapped::LatentEntropy>> {
+    self.inner.latent_entropy()
+  }
+
   /// Returns an estimate of the overall size of the chunk.
   ///
   /// This can be useful when building the file as a `Vec<u8>` in memory;
   /// you can `.reserve(chunk_compressor.chunk_size_hint())` ahead of time.
   pub fn chunk_size_hint(&self) -> usize {
+    let annotation_size = if self.annotated {
+      (BITS_TO_ENCODE_VARINT_POWER + 64).div_ceil(8) as usize
+    } else {
+      0
+    };
     1 + BITS_TO_ENCODE_N_ENTRIES.div_ceil(8) as usize
+      + annotation_size
       + self.inner.chunk_meta_size_hint()
       + self.inner.page_size_hint(0)
   }
@@ -173,11 +468,29 @@ impl ChunkCompressor {
   ///
   /// Will return an error if the provided `Write` errors.
   pub fn write_chunk<W: Write>(&self, dst: W) -> PcoResult<W> {
+    let annotation = match (self.annotated, self.annotation) {
+      (true, None) => {
+        return Err(PcoError::invalid_argument(
+          "every chunk of a file with annotated chunks needs an annotation",
+        ))
+      }
+      (false, Some(_)) => {
+        return Err(PcoError::invalid_argument(
+          "chunk annotations require a file compressor with annotated chunks",
+        ))
+      }
+      (_, annotation) => annotation,
+    };
+
     let mut writer = BitWriter::new(dst, STANDALONE_CHUNK_PREAMBLE_PADDING);
-    writer.write_aligned_bytes(&[self.number_type as u8])?;
+    writer.write_aligned_bytes(&[self.number_type_byte])?;
     let n = self.inner.n_per_page()[0];
     unsafe {
       writer.write_usize(n - 1, BITS_TO_ENCODE_N_ENTRIES);
+      if let Some(annotation) = annotation {
+        write_varint(annotation, &mut writer);
+        writer.finish_byte();
+      }
     }
 
     writer.flush()?;
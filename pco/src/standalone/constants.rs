@@ -3,11 +3,28 @@ use crate::constants::{Bitlen, OVERSHOOT_PADDING};
 // ascii for pco!
 pub const MAGIC_HEADER: [u8; 4] = [112, 99, 111, 33];
 pub const MAGIC_TERMINATION_BYTE: u8 = 0;
+pub const BITS_TO_ENCODE_COMPRESSION_LEVEL: Bitlen = 4;
+pub const BITS_TO_ENCODE_MANIFEST_LATENT_TYPE: Bitlen = 2;
 pub const BITS_TO_ENCODE_N_ENTRIES: Bitlen = 24;
 pub const BITS_TO_ENCODE_STANDALONE_VERSION: Bitlen = 8;
 pub const BITS_TO_ENCODE_VARINT_POWER: Bitlen = 6;
 pub const PRE_UNIFORM_TYPE_STANDALONE_VERSION: usize = 2;
-pub const CURRENT_STANDALONE_VERSION: usize = 3;
+pub const PRE_FLAGS_STANDALONE_VERSION: usize = 3;
+pub const CURRENT_STANDALONE_VERSION: usize = 4;
+
+// header flags, starting in standalone v4
+pub const MANIFEST_FLAG: u8 = 1;
+pub const ANNOTATED_CHUNKS_FLAG: u8 = 2;
+pub const INDEX_FOOTER_FLAG: u8 = 4;
+pub const SOURCE_INFO_FLAG: u8 = 8;
+pub const LOSSY_FLAG: u8 = 16;
+pub const FIXED_RANGE_FLAG: u8 = 32;
+
+// ascii for pcoi
+pub const INDEX_FOOTER_MAGIC: [u8; 4] = [112, 99, 111, 105];
+// ascii for pcot
+pub const TRANSFORM_MAGIC: [u8; 4] = [112, 99, 111, 116];
+pub const CURRENT_TRANSFORM_VERSION: u8 = 0;
 
 // padding
 pub const STANDALONE_CHUNK_PREAMBLE_PADDING: usize =
@@ -16,11 +33,12 @@ pub const STANDALONE_HEADER_PADDING: usize = 31;
 
 #[cfg(test)]
 mod tests {
-  use crate::constants::MAX_ENTRIES;
+  use crate::constants::{MAX_COMPRESSION_LEVEL, MAX_ENTRIES};
   use crate::standalone::constants::*;
 
   #[test]
   fn test_enough_bits() {
-    assert!(1 << BITS_TO_ENCODE_N_ENTRIES >= MAX_ENTRIES);
+    const { assert!(1 << BITS_TO_ENCODE_N_ENTRIES >= MAX_ENTRIES) };
+    const { assert!(1 << BITS_TO_ENCODE_COMPRESSION_LEVEL > MAX_COMPRESSION_LEVEL) };
   }
 }
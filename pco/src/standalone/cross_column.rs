@@ -0,0 +1,212 @@
+use crate::chunk_config::ChunkConfig;
+use crate::data_types::{Latent, Number};
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::simple::{simple_compress, simple_decompress, simple_decompress_at};
+
+fn validate_lens(base_n: usize, dependent_n: usize) -> PcoResult<()> {
+  if base_n != dependent_n {
+    return Err(PcoError::invalid_argument(format!(
+      "base and dependent columns had different counts ({} vs {})",
+      base_n, dependent_n,
+    )));
+  }
+  Ok(())
+}
+
+/// Takes in a base column, a dependent column of the same length, and an
+/// exact configuration, and returns compressed bytes for the dependent column
+/// stored relative to the base.
+///
+/// This is useful when the dependent column is usually close to the base
+/// column, e.g. a column B that is almost always column A plus a small offset.
+/// Only the (wrapping) differences between their latent representations get
+/// compressed, so the result is a standalone file of the corresponding
+/// unsigned integer type, and decompressing it requires the exact same base
+/// column via [`simple_decompress_dependent`].
+/// The base column itself is not included and should be compressed on its own.
+///
+/// Will return an error if the compressor config is invalid or the columns
+/// have different lengths.
+pub fn simple_compress_dependent<T: Number>(
+  base: &[T],
+  dependent: &[T],
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  validate_lens(base.len(), dependent.len())?;
+  simple_compress(&residuals(base, dependent), config)
+}
+
+/// Takes in the base column and compressed bytes for a dependent column (as
+/// produced by [`simple_compress_dependent`]) and returns the dependent
+/// column.
+///
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues, or if the base column has a different length
+/// than the dependent one.
+pub fn simple_decompress_dependent<T: Number>(base: &[T], src: &[u8]) -> PcoResult<Vec<T>> {
+  let residuals = simple_decompress::<T::L>(src)?;
+  validate_lens(base.len(), residuals.len())?;
+  Ok(apply_residuals(base, residuals))
+}
+
+/// Takes in an old and a new version of a column, which may differ in
+/// length, and an exact configuration, and returns compressed bytes for the
+/// new version stored as a diff against the old one.
+///
+/// For the positions both versions share, only the (wrapping) differences
+/// between their latent representations get compressed, so unchanged
+/// numbers cost almost nothing and floats recover bit-for-bit.
+/// If the new version is longer, its extra tail is compressed as is.
+/// Decompressing requires the exact same old version via
+/// [`simple_decompress_diff`].
+///
+/// Will return an error if the compressor config is invalid.
+pub fn simple_compress_diff<T: Number>(
+  old: &[T],
+  new: &[T],
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  let shared_n = old.len().min(new.len());
+  let mut res = simple_compress(
+    &residuals(&old[..shared_n], &new[..shared_n]),
+    config,
+  )?;
+  res.extend(simple_compress(&new[shared_n..], config)?);
+  Ok(res)
+}
+
+/// Takes in the old version of a column and compressed bytes for the new
+/// version (as produced by [`simple_compress_diff`]) and returns the new
+/// version.
+///
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues, or if `old` doesn't have the length the diff
+/// was made against.
+pub fn simple_decompress_diff<T: Number>(old: &[T], src: &[u8]) -> PcoResult<Vec<T>> {
+  let (residuals, n_bytes) = simple_decompress_at::<T::L>(src, 0)?;
+  let tail = simple_decompress::<T>(&src[n_bytes..])?;
+  let shared_n = residuals.len();
+  // Either the new version was no longer than the old one (and there is no
+  // tail), or the old version was entirely shared.
+  let old_n_is_valid = if tail.is_empty() {
+    old.len() >= shared_n
+  } else {
+    old.len() == shared_n
+  };
+  if !old_n_is_valid {
+    return Err(PcoError::invalid_argument(format!(
+      "old column had {} numbers, which doesn't match the diff's {} shared numbers and {} new numbers",
+      old.len(),
+      shared_n,
+      tail.len(),
+    )));
+  }
+
+  let mut res = apply_residuals(&old[..shared_n], residuals);
+  res.extend(tail);
+  Ok(res)
+}
+
+fn residuals<T: Number>(base: &[T], dependent: &[T]) -> Vec<T::L> {
+  base
+    .iter()
+    .zip(dependent)
+    .map(|(&base, &dependent)| {
+      dependent
+        .to_latent_ordered()
+        .wrapping_sub(base.to_latent_ordered())
+        .toggle_center()
+    })
+    .collect()
+}
+
+fn apply_residuals<T: Number>(base: &[T], residuals: Vec<T::L>) -> Vec<T> {
+  base
+    .iter()
+    .zip(residuals)
+    .map(|(&base, residual)| {
+      T::from_latent_ordered(
+        base
+          .to_latent_ordered()
+          .wrapping_add(residual.toggle_center()),
+      )
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+
+  use super::*;
+
+  #[test]
+  fn test_base_plus_noise() -> PcoResult<()> {
+    let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+    let base = (0..10000)
+      .map(|_| rng.gen_range(-1_000_000..1_000_000))
+      .collect::<Vec<i64>>();
+    let dependent = base
+      .iter()
+      .map(|&x| x + rng.gen_range(-3..8))
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default();
+
+    let compressed = simple_compress_dependent(&base, &dependent, &config)?;
+    let independent_compressed = simple_compress(&dependent, &config)?;
+    assert!(compressed.len() * 4 < independent_compressed.len());
+    assert_eq!(
+      simple_decompress_dependent(&base, &compressed)?,
+      dependent
+    );
+
+    // floats and extreme values recover exactly too
+    let base = [f32::MIN, -0.0, 1.0, f32::INFINITY];
+    let dependent = [f32::MAX, 0.0, 1.0 + f32::EPSILON, f32::NEG_INFINITY];
+    let compressed = simple_compress_dependent(&base, &dependent, &config)?;
+    let decompressed = simple_decompress_dependent(&base, &compressed)?;
+    assert_eq!(
+      decompressed.iter().map(|x| x.to_bits()).collect::<Vec<_>>(),
+      dependent.map(|x| x.to_bits())
+    );
+
+    assert!(simple_compress_dependent(&base[..1], &dependent, &config).is_err());
+    assert!(simple_decompress_dependent(&base[..1], &compressed).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_diff_with_edits() -> PcoResult<()> {
+    let old = (0..10000).map(|i| (i as f64).sin()).collect::<Vec<_>>();
+    let mut new = old.clone();
+    new[17] = -0.0;
+    new[5000] += 1.0;
+    new[9999] = f64::NAN;
+    let config = ChunkConfig::default();
+
+    let compressed = simple_compress_diff(&old, &new, &config)?;
+    assert!(compressed.len() * 20 < simple_compress(&new, &config)?.len());
+    let recover = |old: &[f64], compressed: &[u8]| -> PcoResult<Vec<u64>> {
+      let decompressed = simple_decompress_diff(old, compressed)?;
+      Ok(decompressed.iter().map(|x| x.to_bits()).collect())
+    };
+    let to_bits = |nums: &[f64]| nums.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+    assert_eq!(recover(&old, &compressed)?, to_bits(&new));
+
+    // the new version grows
+    let mut longer = new.clone();
+    longer.extend([1.0, 2.0, 3.0]);
+    let compressed = simple_compress_diff(&old, &longer, &config)?;
+    assert_eq!(recover(&old, &compressed)?, to_bits(&longer));
+    assert!(simple_decompress_diff(&old[1..], &compressed).is_err());
+    assert!(simple_decompress_diff(&longer, &compressed).is_err());
+
+    // the new version shrinks
+    let shorter = &new[..7000];
+    let compressed = simple_compress_diff(&old, shorter, &config)?;
+    assert_eq!(recover(&old, &compressed)?, to_bits(shorter));
+    assert!(simple_decompress_diff(&old[..6999], &compressed).is_err());
+    Ok(())
+  }
+}
@@ -1,22 +1,50 @@
 use better_io::BetterBufRead;
 
 use crate::bit_reader::{BitReader, BitReaderBuilder};
-use crate::constants::Bitlen;
+use crate::constants::{Bitlen, FULL_BATCH_N};
 use crate::data_types::{Number, NumberType};
 use crate::errors::{PcoError, PcoResult};
+use crate::macros::match_number_enum;
 use crate::metadata::ChunkMeta;
 use crate::progress::Progress;
 use crate::standalone::constants::*;
-use crate::standalone::NumberTypeOrTermination;
+use crate::standalone::fixed_range::{FixedRange, FIXED_RANGE_BYTE_SIZE};
+use crate::standalone::{
+  index_footer, parallel, source_info, ChunkByteRange, ChunkConfigSummary, NumberTypeOrTermination,
+  ParallelConfig, SourceInfo,
+};
 use crate::{bit_reader, wrapped};
 
-unsafe fn read_varint(reader: &mut BitReader) -> PcoResult<u64> {
+unsafe fn read_varint(reader: &mut BitReader, name: &str) -> PcoResult<u64> {
   let power = 1 + reader.read_uint::<Bitlen>(BITS_TO_ENCODE_VARINT_POWER);
   let res = reader.read_uint(power);
-  reader.drain_empty_byte("standalone size hint")?;
+  reader.drain_empty_byte(name)?;
   Ok(res)
 }
 
+unsafe fn read_flags(reader: &mut BitReader) -> PcoResult<u8> {
+  let flags = reader.read_aligned_bytes(1)?[0];
+  let unknown = flags
+    & !(MANIFEST_FLAG
+      | ANNOTATED_CHUNKS_FLAG
+      | INDEX_FOOTER_FLAG
+      | SOURCE_INFO_FLAG
+      | LOSSY_FLAG
+      | FIXED_RANGE_FLAG);
+  if unknown != 0 {
+    return Err(PcoError::corruption(format!(
+      "unknown standalone header flags: {}",
+      unknown
+    )));
+  }
+  if flags & FIXED_RANGE_FLAG != 0 && flags & LOSSY_FLAG == 0 {
+    return Err(PcoError::corruption(
+      "fixed range files must be marked lossy",
+    ));
+  }
+  Ok(flags)
+}
+
 unsafe fn read_uniform_type(reader: &mut BitReader) -> PcoResult<Option<NumberType>> {
   let byte = reader.read_aligned_bytes(1)?[0];
   if byte == MAGIC_TERMINATION_BYTE {
@@ -60,9 +88,33 @@ unsafe fn read_uniform_type(reader: &mut BitReader) -> PcoResult<Option<NumberTy
 pub struct FileDecompressor {
   uniform_type: Option<NumberType>,
   n_hint: usize,
+  manifest: Option<ChunkConfigSummary>,
+  source_info: Option<SourceInfo>,
+  annotated_chunks: bool,
+  index_footer: bool,
+  lossy: bool,
+  fixed_range: Option<FixedRange>,
   inner: wrapped::FileDecompressor,
 }
 
+/// A `FileDecompressor` along with the rest of the source after its header,
+/// as returned by [`FileDecompressor::from_bytes`].
+#[derive(Clone, Debug)]
+pub struct FileDecompressorAndRest<'a> {
+  pub decompressor: FileDecompressor,
+  /// The compressed bytes following the header, starting at the first chunk.
+  pub rest: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for FileDecompressorAndRest<'a> {
+  type Error = PcoError;
+
+  fn try_from(src: &'a [u8]) -> PcoResult<Self> {
+    let (decompressor, rest) = FileDecompressor::new(src)?;
+    Ok(Self { decompressor, rest })
+  }
+}
+
 /// The outcome of starting a new chunk of a standalone file.
 #[allow(clippy::large_enum_variant)]
 pub enum MaybeChunkDecompressor<T: Number, R: BetterBufRead> {
@@ -94,14 +146,14 @@ impl FileDecompressor {
       )));
     }
 
-    let (standalone_version, uniform_number_type, n_hint) =
+    let (standalone_version, uniform_number_type, flags, n_hint) =
       reader_builder.with_reader(|reader| unsafe {
         let standalone_version = reader.read_usize(BITS_TO_ENCODE_STANDALONE_VERSION);
         if standalone_version < 2 {
           // These versions only had wrapped version; we need to rewind so they can
           // reuse it.
           reader.bits_past_byte -= BITS_TO_ENCODE_STANDALONE_VERSION;
-          return Ok((standalone_version, None, 0));
+          return Ok((standalone_version, None, 0, 0));
         }
 
         let uniform_type = if standalone_version >= 3 {
@@ -110,9 +162,20 @@ impl FileDecompressor {
           None
         };
 
-        let n_hint = read_varint(reader)? as usize;
+        let flags = if standalone_version > PRE_FLAGS_STANDALONE_VERSION {
+          read_flags(reader)?
+        } else {
+          0
+        };
+
+        let n_hint = read_varint(reader, "standalone size hint")? as usize;
 
-        Ok((standalone_version, uniform_type, n_hint))
+        Ok((
+          standalone_version,
+          uniform_type,
+          flags,
+          n_hint,
+        ))
       })?;
 
     if standalone_version > CURRENT_STANDALONE_VERSION {
@@ -122,17 +185,215 @@ impl FileDecompressor {
       )));
     }
 
-    let (inner, rest) = wrapped::FileDecompressor::new(reader_builder.into_inner())?;
+    let (inner, mut rest) = wrapped::FileDecompressor::new(reader_builder.into_inner())?;
+    let manifest = if flags & MANIFEST_FLAG != 0 {
+      bit_reader::ensure_buf_read_capacity(&mut rest, STANDALONE_HEADER_PADDING);
+      let mut reader_builder = BitReaderBuilder::new(rest, STANDALONE_HEADER_PADDING, 0);
+      let manifest = reader_builder.with_reader(|reader| unsafe {
+        ChunkConfigSummary::read_from(reader, &inner.format_version)
+      })?;
+      rest = reader_builder.into_inner();
+      Some(manifest)
+    } else {
+      None
+    };
+    let source_info = if flags & SOURCE_INFO_FLAG != 0 {
+      let padding = source_info::max_byte_size();
+      bit_reader::ensure_buf_read_capacity(&mut rest, padding);
+      let mut reader_builder = BitReaderBuilder::new(rest, padding, 0);
+      let source_info =
+        reader_builder.with_reader(|reader| unsafe { SourceInfo::read_from(reader) })?;
+      rest = reader_builder.into_inner();
+      Some(source_info)
+    } else {
+      None
+    };
+    let fixed_range = if flags & FIXED_RANGE_FLAG != 0 {
+      bit_reader::ensure_buf_read_capacity(&mut rest, FIXED_RANGE_BYTE_SIZE);
+      let mut reader_builder = BitReaderBuilder::new(rest, FIXED_RANGE_BYTE_SIZE, 0);
+      let fixed_range = reader_builder.with_reader(FixedRange::read_from)?;
+      rest = reader_builder.into_inner();
+      Some(fixed_range)
+    } else {
+      None
+    };
+
     Ok((
       Self {
         inner,
         uniform_type: uniform_number_type,
         n_hint,
+        manifest,
+        source_info,
+        annotated_chunks: flags & ANNOTATED_CHUNKS_FLAG != 0,
+        index_footer: flags & INDEX_FOOTER_FLAG != 0,
+        lossy: flags & LOSSY_FLAG != 0,
+        fixed_range,
       },
       rest,
     ))
   }
 
+  /// Reads the header of a whole file's bytes and returns a
+  /// `FileDecompressor` along with the byte range of every chunk.
+  ///
+  /// If the file has an
+  /// [index footer][crate::standalone::FileCompressor::with_index_footer],
+  /// the ranges come from it, so only the header and the end of the file are
+  /// read.
+  /// Otherwise this falls back to finding the chunks in order, which skips
+  /// through every chunk's data.
+  /// Pass each range to [`decompress_chunk`][Self::decompress_chunk] to
+  /// decompress its chunk.
+  ///
+  /// Will return an error if any corruptions, version incompatibilities, or
+  /// insufficient data are found.
+  pub fn open_with_footer<T: Number>(src: &[u8]) -> PcoResult<(Self, Vec<ChunkByteRange>)> {
+    let (file_decompressor, mut rest) = Self::new(src)?;
+    if file_decompressor.index_footer {
+      let chunks = index_footer::find_index_footer(src)?;
+      if let Some(chunk) = chunks.iter().find(|chunk| chunk.end() > src.len()) {
+        return Err(PcoError::corruption(format!(
+          "index footer's chunk byte range {}..{} is out of bounds for {} bytes",
+          chunk.start,
+          chunk.end(),
+          src.len(),
+        )));
+      }
+      return Ok((file_decompressor, chunks));
+    }
+
+    let mut chunks = Vec::new();
+    while let MaybeChunkDecompressor::Some(chunk_decompressor) =
+      file_decompressor.chunk_decompressor::<T, _>(rest)?
+    {
+      let n = chunk_decompressor.n();
+      let start = src.len() - rest.len();
+      rest = chunk_decompressor.skip_remaining()?;
+      chunks.push(ChunkByteRange {
+        start,
+        len: src.len() - rest.len() - start,
+        n,
+      });
+    }
+    Ok((file_decompressor, chunks))
+  }
+
+  /// Takes in the bytes of a whole file and returns its last `n` numbers,
+  /// or all of them if it has fewer.
+  ///
+  /// Only the trailing chunks containing those numbers are decompressed.
+  /// Each chunk is a single page whose delta encoding state is stored with
+  /// it, so delta encodings never require decoding earlier chunks.
+  /// However, a page's numbers (and their deltas) can only be decoded from
+  /// the start, so the whole chunk containing the `n`th to last number gets
+  /// decompressed, even if `n` is small; files with smaller chunks make
+  /// tails cheaper.
+  /// Chunks are found like [`open_with_footer`][Self::open_with_footer]
+  /// does, so with an
+  /// [index footer][crate::standalone::FileCompressor::with_index_footer],
+  /// earlier chunks aren't read at all, and without one they're skipped
+  /// through.
+  ///
+  /// Will return an error if there are any compatibility, corruption, or
+  /// insufficient data issues.
+  pub fn tail<T: Number>(src: &[u8], n: usize) -> PcoResult<Vec<T>> {
+    let (file_decompressor, chunks) = Self::open_with_footer::<T>(src)?;
+    let mut first_chunk_idx = chunks.len();
+    let mut n_remaining = n;
+    while n_remaining > 0 && first_chunk_idx > 0 {
+      first_chunk_idx -= 1;
+      n_remaining = n_remaining.saturating_sub(chunks[first_chunk_idx].n);
+    }
+
+    let mut res = Vec::with_capacity(chunks[first_chunk_idx..].iter().map(|chunk| chunk.n).sum());
+    for chunk in &chunks[first_chunk_idx..] {
+      res.extend(file_decompressor.decompress_chunk::<T>(src, chunk)?);
+    }
+    res.drain(..res.len().saturating_sub(n));
+    Ok(res)
+  }
+
+  /// Returns the average count of compressed bits per number in a whole
+  /// file's bytes.
+  ///
+  /// This counts every byte of the file, including its header, chunk
+  /// metadata, and footer, but not any zero padding after it.
+  /// Numbers aren't decompressed: if the file has an
+  /// [index footer][crate::standalone::FileCompressor::with_index_footer],
+  /// the counts of numbers come from it; otherwise this skips through every
+  /// chunk's data, like [`open_with_footer`][Self::open_with_footer].
+  /// Returns NaN if the file has no numbers.
+  ///
+  /// Will return an error if any corruptions, version incompatibilities, or
+  /// insufficient data are found.
+  pub fn bits_per_value(src: &[u8]) -> PcoResult<f64> {
+    let (file_decompressor, mut rest) = Self::new(src)?;
+    let (n, file_size) = if file_decompressor.index_footer {
+      let n = index_footer::find_index_footer(src)?
+        .iter()
+        .map(|chunk| chunk.n)
+        .sum::<usize>();
+      // the footer ends with its magic, so this excludes only padding
+      let unpadded_size = src
+        .iter()
+        .rposition(|&byte| byte != 0)
+        .map_or(0, |idx| idx + 1);
+      (n, unpadded_size)
+    } else {
+      let mut n = 0;
+      loop {
+        let number_type = match file_decompressor.peek_number_type_or_termination(rest)? {
+          NumberTypeOrTermination::Known(number_type) => number_type,
+          NumberTypeOrTermination::Unknown(byte) => {
+            return Err(PcoError::compatibility(format!(
+              "unrecognized number type byte {}",
+              byte,
+            )))
+          }
+          // any number type reads the termination
+          NumberTypeOrTermination::Termination => NumberType::U32,
+        };
+        let maybe_rest = match_number_enum!(
+          number_type,
+          NumberType<T> => {
+            match file_decompressor.chunk_decompressor::<T, _>(rest)? {
+              MaybeChunkDecompressor::Some(chunk_decompressor) => {
+                n += chunk_decompressor.n();
+                Ok(chunk_decompressor.skip_remaining()?)
+              }
+              MaybeChunkDecompressor::EndOfData(rest) => Err(rest),
+            }
+          }
+        );
+        match maybe_rest {
+          Ok(chunk_rest) => rest = chunk_rest,
+          Err(end_rest) => {
+            rest = end_rest;
+            break;
+          }
+        }
+      }
+      (n, src.len() - rest.len())
+    };
+    if n == 0 {
+      return Ok(f64::NAN);
+    }
+    Ok((file_size * 8) as f64 / n as f64)
+  }
+
+  /// Reads a short header from a byte slice, returning the
+  /// `FileDecompressor` and the remaining bytes under named fields.
+  ///
+  /// This is equivalent to [`FileDecompressor::new`] and
+  /// `FileDecompressorAndRest::try_from`.
+  ///
+  /// Will return an error if any corruptions, version incompatibilities, or
+  /// insufficient data are found.
+  pub fn from_bytes(src: &[u8]) -> PcoResult<FileDecompressorAndRest<'_>> {
+    FileDecompressorAndRest::try_from(src)
+  }
+
   pub fn format_version(&self) -> u8 {
     self.inner.format_version()
   }
@@ -145,6 +406,117 @@ impl FileDecompressor {
     self.n_hint
   }
 
+  /// Returns whether decompression reproduces the original numbers exactly.
+  ///
+  /// This is false only if the file was marked via
+  /// [`FileCompressor::with_lossy`][crate::standalone::FileCompressor::with_lossy]
+  /// or compressed with a [`fixed_range`][Self::fixed_range], so files
+  /// written before the flag existed report true.
+  pub fn is_lossless(&self) -> bool {
+    !self.lossy
+  }
+
+  /// Returns the summary of settings the file was compressed with, if one
+  /// was recorded via
+  /// [`FileCompressor::with_manifest`][crate::standalone::FileCompressor::with_manifest].
+  ///
+  /// This only describes one chunk, usually the first; see
+  /// [`ChunkConfigSummary`] for details.
+  pub fn manifest(&self) -> Option<ChunkConfigSummary> {
+    self.manifest
+  }
+
+  /// Returns the range and bit depth the file's floats were quantized to, if
+  /// it was compressed with
+  /// [`simple_compress_fixed_range`][crate::standalone::simple_compress_fixed_range].
+  ///
+  /// Such files' chunks hold `u64` levels; decompress them with
+  /// [`simple_decompress_fixed_range`][crate::standalone::simple_decompress_fixed_range].
+  pub fn fixed_range(&self) -> Option<FixedRange> {
+    self.fixed_range
+  }
+
+  /// Returns where the file's numbers came from, if recorded via
+  /// [`FileCompressor::with_source_info`][crate::standalone::FileCompressor::with_source_info].
+  pub fn source_info(&self) -> Option<&SourceInfo> {
+    self.source_info.as_ref()
+  }
+
+  /// Returns whether each chunk of the file carries an annotation, as set via
+  /// [`FileCompressor::with_annotated_chunks`][crate::standalone::FileCompressor::with_annotated_chunks].
+  pub fn has_annotated_chunks(&self) -> bool {
+    self.annotated_chunks
+  }
+
+  /// Returns whether the file ends with an index of its chunks, as set via
+  /// [`FileCompressor::with_index_footer`][crate::standalone::FileCompressor::with_index_footer].
+  pub fn has_index_footer(&self) -> bool {
+    self.index_footer
+  }
+
+  /// Checks whether the chunks of the file `other` was read from can be
+  /// decoded under this file's header, so that the two files can be
+  /// concatenated by writing this header, the chunks of both files, and a
+  /// footer.
+  ///
+  /// These must match exactly:
+  /// * the [format version][Self::format_version], since chunks are decoded
+  ///   according to their header's version,
+  /// * the [uniform number type][Self::uniform_type], if either file has
+  ///   one,
+  /// * whether chunks are
+  ///   [annotated][crate::standalone::FileCompressor::with_annotated_chunks],
+  ///   since annotations change the layout of each chunk,
+  /// * whether the file is [lossless][Self::is_lossless], since the header
+  ///   makes that claim for every chunk, and
+  /// * the [fixed range][Self::fixed_range], if either file has one, since
+  ///   chunks hold levels of that range rather than the numbers themselves.
+  ///
+  /// Nothing else needs to match.
+  /// In particular, no `ChunkConfig` setting matters, since each chunk's
+  /// metadata records everything needed to decode it, and files without a
+  /// uniform number type may mix chunks of different number types.
+  /// The `n_hint`, manifest, and source info are informational, and an
+  /// [index footer][crate::standalone::FileCompressor::with_index_footer]
+  /// must be rewritten for the concatenated chunks regardless.
+  ///
+  /// Will return an invalid argument error describing the first mismatch,
+  /// if any.
+  pub fn check_concat_compatible(&self, other: &Self) -> PcoResult<()> {
+    if self.format_version() != other.format_version() {
+      return Err(PcoError::invalid_argument(format!(
+        "format versions differ ({} vs {})",
+        self.format_version(),
+        other.format_version(),
+      )));
+    }
+    if self.uniform_type != other.uniform_type {
+      return Err(PcoError::invalid_argument(format!(
+        "uniform number types differ ({:?} vs {:?})",
+        self.uniform_type, other.uniform_type,
+      )));
+    }
+    if self.annotated_chunks != other.annotated_chunks {
+      return Err(PcoError::invalid_argument(format!(
+        "annotated chunks differ ({} vs {})",
+        self.annotated_chunks, other.annotated_chunks,
+      )));
+    }
+    if self.lossy != other.lossy {
+      return Err(PcoError::invalid_argument(format!(
+        "lossy flags differ ({} vs {})",
+        self.lossy, other.lossy,
+      )));
+    }
+    if self.fixed_range != other.fixed_range {
+      return Err(PcoError::invalid_argument(format!(
+        "fixed ranges differ ({:?} vs {:?})",
+        self.fixed_range, other.fixed_range,
+      )));
+    }
+    Ok(())
+  }
+
   /// Peeks at what's next in the file, returning whether it's a termination
   /// or chunk with some data type.
   ///
@@ -177,9 +549,12 @@ impl FileDecompressor {
     let type_or_termination_byte =
       reader_builder.with_reader(|reader| Ok(reader.read_aligned_bytes(1)?[0]))?;
     if type_or_termination_byte == MAGIC_TERMINATION_BYTE {
-      return Ok(MaybeChunkDecompressor::EndOfData(
-        reader_builder.into_inner(),
-      ));
+      let mut rest = reader_builder.into_inner();
+      if self.index_footer {
+        // we skip over the footer so the rest starts after the whole file
+        index_footer::read_index_footer(&mut rest)?;
+      }
+      return Ok(MaybeChunkDecompressor::EndOfData(rest));
     }
 
     if let Some(uniform_type) = self.uniform_type() {
@@ -200,8 +575,15 @@ impl FileDecompressor {
       )));
     }
 
-    let n = reader_builder
-      .with_reader(|reader| unsafe { Ok(reader.read_usize(BITS_TO_ENCODE_N_ENTRIES) + 1) })?;
+    let (n, annotation) = reader_builder.with_reader(|reader| unsafe {
+      let n = reader.read_usize(BITS_TO_ENCODE_N_ENTRIES) + 1;
+      let annotation = if self.annotated_chunks {
+        Some(read_varint(reader, "chunk annotation")?)
+      } else {
+        None
+      };
+      Ok((n, annotation))
+    })?;
     let src = reader_builder.into_inner();
     let (inner_cd, src) = self.inner.chunk_decompressor::<T, R>(src)?;
     let inner_pd = inner_cd.page_decompressor(src, n)?;
@@ -211,6 +593,7 @@ impl FileDecompressor {
       inner_pd,
       n,
       n_processed: 0,
+      annotation,
     };
     Ok(MaybeChunkDecompressor::Some(res))
   }
@@ -234,6 +617,224 @@ impl FileDecompressor {
     }
     Ok(res)
   }
+
+  /// Takes in the bytes of the whole file and returns the numbers of the
+  /// chunk at `range`, as returned by
+  /// [`Compressor::compress_with_ranges`][crate::standalone::Compressor::compress_with_ranges].
+  ///
+  /// This jumps directly to the chunk without reading any others.
+  /// Will return an error if the range doesn't fit in `src` or doesn't
+  /// exactly span a chunk, or if there are any compatibility, corruption, or
+  /// insufficient data issues.
+  pub fn decompress_chunk<T: Number>(
+    &self,
+    src: &[u8],
+    range: &ChunkByteRange,
+  ) -> PcoResult<Vec<T>> {
+    let mut chunk_decompressor = self.chunk_decompressor_at(src, range)?;
+    let mut res = Vec::with_capacity(range.n);
+    chunk_decompressor.decompress_remaining_extend(&mut res)?;
+    let n_unread = chunk_decompressor.into_src().len();
+    if n_unread > 0 {
+      return Err(PcoError::invalid_argument(format!(
+        "chunk byte range has {} bytes past the end of the chunk",
+        n_unread,
+      )));
+    }
+    Ok(res)
+  }
+
+  fn chunk_decompressor_at<'a, T: Number>(
+    &self,
+    src: &'a [u8],
+    range: &ChunkByteRange,
+  ) -> PcoResult<ChunkDecompressor<T, &'a [u8]>> {
+    let Some(chunk_src) = src.get(range.start..range.end()) else {
+      return Err(PcoError::invalid_argument(format!(
+        "chunk byte range {}..{} is out of bounds for {} bytes",
+        range.start,
+        range.end(),
+        src.len(),
+      )));
+    };
+
+    let MaybeChunkDecompressor::Some(chunk_decompressor) = self.chunk_decompressor(chunk_src)?
+    else {
+      return Err(PcoError::invalid_argument(format!(
+        "chunk byte range {}..{} points at the end of the file",
+        range.start,
+        range.end(),
+      )));
+    };
+    if chunk_decompressor.n() != range.n {
+      return Err(PcoError::invalid_argument(format!(
+        "chunk byte range expected {} numbers, but the chunk has {}",
+        range.n,
+        chunk_decompressor.n(),
+      )));
+    }
+    Ok(chunk_decompressor)
+  }
+
+  /// Takes in compressed bytes (after the header, at the start of the chunks)
+  /// and returns the number at global index `i`.
+  ///
+  /// Only the batches of the containing page up to `i` are decoded and
+  /// nothing is cached, so the cost within that page is O(page size).
+  /// However, standalone chunks don't record their compressed sizes, so
+  /// all preceding chunks must still be skipped through, which makes this
+  /// O(file size) in the worst case; use
+  /// [`get_with_ranges`][Self::get_with_ranges] to jump directly to the
+  /// containing chunk instead.
+  ///
+  /// Will return an error if `i` is out of range or if there are any
+  /// compatibility, corruption, or insufficient data issues.
+  pub fn get<T: Number>(&self, mut src: &[u8], i: usize) -> PcoResult<T> {
+    let mut chunk_start = 0;
+    while let MaybeChunkDecompressor::Some(chunk_decompressor) = self.chunk_decompressor(src)? {
+      let n = chunk_decompressor.n();
+      if i < chunk_start + n {
+        return get_in_chunk(chunk_decompressor, i - chunk_start);
+      }
+      chunk_start += n;
+      src = chunk_decompressor.skip_remaining()?;
+    }
+    Err(PcoError::invalid_argument(format!(
+      "index {} is out of range for file with {} numbers",
+      i, chunk_start,
+    )))
+  }
+
+  /// Takes in the bytes of the whole file and the byte ranges of all its
+  /// chunks, as returned by [`open_with_footer`][Self::open_with_footer],
+  /// and returns the number at global index `i`.
+  ///
+  /// Unlike [`get`][Self::get], this jumps directly to the chunk containing
+  /// `i` without reading any others, so each call only costs O(page size)
+  /// plus a scan over the ranges.
+  ///
+  /// Will return an error if `i` is out of range, the containing chunk's
+  /// range doesn't exactly span a chunk, or if there are any compatibility,
+  /// corruption, or insufficient data issues.
+  pub fn get_with_ranges<T: Number>(
+    &self,
+    src: &[u8],
+    ranges: &[ChunkByteRange],
+    i: usize,
+  ) -> PcoResult<T> {
+    let mut chunk_start = 0;
+    for range in ranges {
+      if i < chunk_start + range.n {
+        return get_in_chunk(
+          self.chunk_decompressor_at(src, range)?,
+          i - chunk_start,
+        );
+      }
+      chunk_start += range.n;
+    }
+    Err(PcoError::invalid_argument(format!(
+      "index {} is out of range for file with {} numbers",
+      i, chunk_start,
+    )))
+  }
+
+  /// Takes in compressed bytes (after the header, at the start of the chunks)
+  /// and decompresses its chunks in parallel according to `parallel_config`
+  /// into `dst`, returning the count of numbers written.
+  ///
+  /// Standalone chunks don't record their compressed sizes, so this first
+  /// finds where each chunk starts by skipping through the file, which only
+  /// decodes the entropy-coded part of each batch.
+  /// It then decompresses the chunks independently, each into its own region
+  /// of `dst`.
+  /// The result is identical to decompressing serially, and files with many
+  /// similarly sized chunks get the most speedup.
+  ///
+  /// Will return an error if `dst` is shorter than the count of numbers in
+  /// the file or if there are any compatibility, corruption, or insufficient
+  /// data issues.
+  pub fn decompress_parallel<T: Number>(
+    &self,
+    src: &[u8],
+    parallel_config: &ParallelConfig,
+    dst: &mut [T],
+  ) -> PcoResult<usize> {
+    let chunks = self.locate_chunks::<T>(src)?;
+    let n_total = chunks.iter().map(|&(_, n)| n).sum::<usize>();
+    if dst.len() < n_total {
+      return Err(PcoError::invalid_argument(format!(
+        "dst's length must be at least the count of numbers in the file ({} < {})",
+        dst.len(),
+        n_total,
+      )));
+    }
+
+    self.decompress_chunks_parallel(chunks, parallel_config, dst)?;
+    Ok(n_total)
+  }
+
+  // Returns the compressed bytes and count of numbers of each chunk.
+  pub(crate) fn locate_chunks<'a, T: Number>(
+    &self,
+    mut src: &'a [u8],
+  ) -> PcoResult<Vec<(&'a [u8], usize)>> {
+    let mut res = Vec::new();
+    while let MaybeChunkDecompressor::Some(chunk_decompressor) =
+      self.chunk_decompressor::<T, _>(src)?
+    {
+      let n = chunk_decompressor.n();
+      let rest = chunk_decompressor.skip_remaining()?;
+      res.push((&src[..src.len() - rest.len()], n));
+      src = rest;
+    }
+    Ok(res)
+  }
+
+  // dst must be at least as long as the chunks' total count of numbers.
+  pub(crate) fn decompress_chunks_parallel<T: Number>(
+    &self,
+    chunks: Vec<(&[u8], usize)>,
+    parallel_config: &ParallelConfig,
+    mut dst: &mut [T],
+  ) -> PcoResult<()> {
+    let decompress_chunk = |chunk_src: &[u8], chunk_dst: &mut [T]| -> PcoResult<()> {
+      let MaybeChunkDecompressor::Some(mut chunk_decompressor) =
+        self.chunk_decompressor::<T, _>(chunk_src)?
+      else {
+        unreachable!("chunk was found while locating chunks");
+      };
+      chunk_decompressor.decompress(chunk_dst)?;
+      Ok(())
+    };
+
+    let mut jobs = Vec::with_capacity(chunks.len());
+    for (chunk_src, n) in chunks {
+      let (chunk_dst, rest) = dst.split_at_mut(n);
+      jobs.push((chunk_src, chunk_dst));
+      dst = rest;
+    }
+    parallel::run_jobs(
+      jobs,
+      parallel_config,
+      |(chunk_src, chunk_dst)| decompress_chunk(chunk_src, chunk_dst),
+    )
+  }
+}
+
+// Decodes batches of the chunk up to its `target`th number.
+fn get_in_chunk<T: Number>(
+  mut chunk_decompressor: ChunkDecompressor<T, &[u8]>,
+  target: usize,
+) -> PcoResult<T> {
+  let mut batch = [T::default(); FULL_BATCH_N];
+  let mut n_processed = 0;
+  loop {
+    let progress = chunk_decompressor.decompress(&mut batch)?;
+    if target < n_processed + progress.n_processed {
+      return Ok(batch[target - n_processed]);
+    }
+    n_processed += progress.n_processed;
+  }
 }
 
 /// Holds metadata about a chunk and supports decompression.
@@ -242,6 +843,7 @@ pub struct ChunkDecompressor<T: Number, R: BetterBufRead> {
   inner_pd: wrapped::PageDecompressor<T, R>,
   n: usize,
   n_processed: usize,
+  annotation: Option<u64>,
 }
 
 impl<T: Number, R: BetterBufRead> ChunkDecompressor<T, R> {
@@ -255,6 +857,12 @@ impl<T: Number, R: BetterBufRead> ChunkDecompressor<T, R> {
     self.n
   }
 
+  /// Returns the chunk's annotation, if the file has
+  /// [annotated chunks][FileDecompressor::has_annotated_chunks].
+  pub fn annotation(&self) -> Option<u64> {
+    self.annotation
+  }
+
   /// Reads the next decompressed numbers into the destination, returning
   /// progress into the chunk and advancing along the compressed data.
   ///
@@ -275,6 +883,55 @@ impl<T: Number, R: BetterBufRead> ChunkDecompressor<T, R> {
     self.inner_pd.into_src()
   }
 
+  /// Returns an iterator over the chunk's remaining numbers, decompressing
+  /// them lazily.
+  ///
+  /// Only one batch of numbers is buffered at a time, and any buffered
+  /// numbers not yet yielded are skipped if the iterator is dropped early.
+  /// If decompression fails, the iterator yields the error and then ends.
+  pub fn iter(&mut self) -> ChunkIter<'_, T, R> {
+    let finished = self.n_processed == self.n;
+    ChunkIter {
+      chunk_decompressor: self,
+      batch: vec![T::default(); FULL_BATCH_N],
+      batch_pos: 0,
+      batch_len: 0,
+      finished,
+    }
+  }
+
+  // decodes the remaining latents before delta decoding or mode inversion,
+  // returning them along with the source after this chunk
+  #[cfg(feature = "debug_latents")]
+  pub(crate) fn decompress_latents(
+    self,
+  ) -> PcoResult<(
+    crate::metadata::PerLatentVar<crate::metadata::DynLatents>,
+    R,
+  )> {
+    self.inner_pd.decompress_latents()
+  }
+
+  // skips the remaining numbers, returning the source after this chunk
+  pub(crate) fn skip_remaining(mut self) -> PcoResult<R> {
+    self.inner_pd.skip_remaining()?;
+    Ok(self.into_src())
+  }
+
+  // Decompresses the rest of a chunk that hasn't been read yet, appending the
+  // number at index `first` and every `stride`th one after it to dst.
+  pub(crate) fn decompress_strided_remaining(
+    mut self,
+    first: usize,
+    stride: usize,
+    dst: &mut Vec<T>,
+  ) -> PcoResult<R> {
+    self
+      .inner_pd
+      .decompress_strided_remaining(first, stride, dst)?;
+    Ok(self.into_src())
+  }
+
   // a helper for some internal things
   pub(crate) fn decompress_remaining_extend(&mut self, dst: &mut Vec<T>) -> PcoResult<()> {
     let initial_len = dst.len();
@@ -288,3 +945,43 @@ impl<T: Number, R: BetterBufRead> ChunkDecompressor<T, R> {
     Ok(())
   }
 }
+
+/// An iterator over a chunk's numbers, as returned by
+/// [`ChunkDecompressor::iter`].
+pub struct ChunkIter<'a, T: Number, R: BetterBufRead> {
+  chunk_decompressor: &'a mut ChunkDecompressor<T, R>,
+  batch: Vec<T>,
+  batch_pos: usize,
+  batch_len: usize,
+  finished: bool,
+}
+
+impl<T: Number, R: BetterBufRead> Iterator for ChunkIter<'_, T, R> {
+  type Item = PcoResult<T>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.batch_pos == self.batch_len {
+      if self.finished {
+        return None;
+      }
+      match self.chunk_decompressor.decompress(&mut self.batch) {
+        Ok(progress) => {
+          self.batch_pos = 0;
+          self.batch_len = progress.n_processed;
+          self.finished = progress.finished;
+          if self.batch_len == 0 {
+            return None;
+          }
+        }
+        Err(e) => {
+          self.finished = true;
+          return Some(Err(e));
+        }
+      }
+    }
+
+    let num = self.batch[self.batch_pos];
+    self.batch_pos += 1;
+    Some(Ok(num))
+  }
+}
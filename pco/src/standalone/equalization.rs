@@ -0,0 +1,264 @@
+use std::cmp::Reverse;
+
+use crate::chunk_config::ChunkConfig;
+use crate::data_types::{Latent, Number};
+use crate::errors::{PcoError, PcoResult};
+use crate::sampling;
+use crate::standalone::simple::{simple_compress, simple_decompress, simple_decompress_at};
+use crate::standalone::transform_header::Transform;
+
+const PLAIN_BYTE: u8 = 0;
+const EQUALIZED_BYTE: u8 = 1;
+const FREQUENCY_SORTED_BYTE: u8 = 2;
+// We only try remapping if each distinct value appears this many times on
+// average, so the mapping stays small relative to the data.
+const MIN_NUMS_PER_DISTINCT: usize = 8;
+
+fn sorted_distinct<T: Number>(mut latents: Vec<T::L>) -> Vec<T::L> {
+  latents.sort_unstable();
+  latents.dedup();
+  latents
+}
+
+// Returns the sorted distinct values of nums if a rank transform looks
+// worthwhile.
+fn choose_mapping<T: Number>(nums: &[T], config: &ChunkConfig) -> Option<Vec<T::L>> {
+  // checking a sample first lets us bail out cheaply on high-cardinality data
+  let sample = sampling::choose_sample(nums, config.analysis_seed, |num| {
+    Some(num.to_latent_ordered())
+  })?;
+  let sample_n = sample.len();
+  if sorted_distinct::<T>(sample).len() * 2 > sample_n {
+    return None;
+  }
+
+  let mapping = sorted_distinct::<T>(nums.iter().map(|num| num.to_latent_ordered()).collect());
+  if mapping.len() * MIN_NUMS_PER_DISTINCT > nums.len() || mapping.len() > u32::MAX as usize {
+    return None;
+  }
+  Some(mapping)
+}
+
+fn ranks<T: Number>(nums: &[T], mapping: &[T::L]) -> Vec<u32> {
+  nums
+    .iter()
+    .map(|num| mapping.binary_search(&num.to_latent_ordered()).unwrap() as u32)
+    .collect()
+}
+
+// Reorders the mapping so that the most common values come first, breaking
+// ties by value, and returns it along with each number's code in it.
+fn frequency_sorted<L: Latent>(mapping: &[L], ranks: &[u32]) -> (Vec<L>, Vec<u32>) {
+  let mut counts = vec![0_usize; mapping.len()];
+  for &rank in ranks {
+    counts[rank as usize] += 1;
+  }
+  let mut ranks_by_frequency = (0..mapping.len()).collect::<Vec<_>>();
+  ranks_by_frequency.sort_by_key(|&rank| Reverse(counts[rank]));
+
+  let mut code_by_rank = vec![0_u32; mapping.len()];
+  for (code, &rank) in ranks_by_frequency.iter().enumerate() {
+    code_by_rank[rank] = code as u32;
+  }
+  let frequency_mapping = ranks_by_frequency
+    .iter()
+    .map(|&rank| mapping[rank])
+    .collect();
+  let codes = ranks
+    .iter()
+    .map(|&rank| code_by_rank[rank as usize])
+    .collect();
+  (frequency_mapping, codes)
+}
+
+fn compress_mapped<T: Number>(
+  transform_byte: u8,
+  mapping: &[T::L],
+  codes: &[u32],
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  let mapping_nums = mapping
+    .iter()
+    .map(|&latent| T::from_latent_ordered(latent))
+    .collect::<Vec<_>>();
+  let mapping_config = ChunkConfig::default().with_compression_level(config.compression_level);
+
+  let mut res = Transform::Equalization.header();
+  res.push(transform_byte);
+  res.extend(simple_compress(
+    &mapping_nums,
+    &mapping_config,
+  )?);
+  res.extend(simple_compress(codes, config)?);
+  Ok(res)
+}
+
+/// Takes in numbers and an exact configuration and returns compressed bytes,
+/// possibly after a monotone rank transform.
+///
+/// This is an experimental preprocessing step for skewed distributions, e.g.
+/// Zipf-distributed integers.
+/// When the numbers have few distinct values, each number can be replaced by
+/// its rank among them, and the sorted distinct values are stored alongside.
+/// This packs sparse values into a dense range, which can compress better.
+/// Pco also tries ordering the distinct values by frequency instead, so the
+/// most common values get the smallest codes, which often helps categorical
+/// data.
+/// A transform is only used when its result is smaller, so expect up to
+/// three times the compression time of [`simple_compress`].
+///
+/// Decompress with [`simple_decompress_equalized`].
+/// Will return an error if the compressor config is invalid.
+pub fn simple_compress_equalized<T: Number>(
+  nums: &[T],
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  let mut res = Transform::Equalization.header();
+  res.push(PLAIN_BYTE);
+  res.extend(simple_compress(nums, config)?);
+
+  if let Some(mapping) = choose_mapping(nums, config) {
+    let ranks = ranks(nums, &mapping);
+    let value_sorted = compress_mapped::<T>(EQUALIZED_BYTE, &mapping, &ranks, config)?;
+    let (frequency_mapping, codes) = frequency_sorted(&mapping, &ranks);
+    let frequency_sorted = compress_mapped::<T>(
+      FREQUENCY_SORTED_BYTE,
+      &frequency_mapping,
+      &codes,
+      config,
+    )?;
+    for candidate in [value_sorted, frequency_sorted] {
+      if candidate.len() < res.len() {
+        res = candidate;
+      }
+    }
+  }
+  Ok(res)
+}
+
+/// Takes in compressed bytes (as produced by [`simple_compress_equalized`])
+/// and returns the numbers.
+///
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+pub fn simple_decompress_equalized<T: Number>(src: &[u8]) -> PcoResult<Vec<T>> {
+  let src = Transform::Equalization.read_header(src)?;
+  let (&transform_byte, rest) = src
+    .split_first()
+    .ok_or_else(|| PcoError::insufficient_data("missing equalization byte"))?;
+  match transform_byte {
+    PLAIN_BYTE => simple_decompress(rest),
+    // both orders decode the same way, by looking up each code's position
+    EQUALIZED_BYTE | FREQUENCY_SORTED_BYTE => {
+      let (mapping, n_bytes) = simple_decompress_at::<T>(rest, 0)?;
+      let codes = simple_decompress::<u32>(&rest[n_bytes..])?;
+      codes
+        .into_iter()
+        .map(|code| {
+          mapping.get(code as usize).copied().ok_or_else(|| {
+            PcoError::corruption(format!(
+              "code {} out of range for mapping of {} values",
+              code,
+              mapping.len(),
+            ))
+          })
+        })
+        .collect()
+    }
+    other => Err(PcoError::corruption(format!(
+      "unknown equalization byte {}",
+      other
+    ))),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  use super::*;
+  use crate::standalone::transform_header::TRANSFORM_HEADER_SIZE;
+
+  // integers following a Zipf-like power law
+  fn zipf(n: usize) -> Vec<u64> {
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    (0..n)
+      .map(|_| {
+        let u = 1.0 - rng.gen::<f64>();
+        (1.0 / u) as u64
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_zipf() -> PcoResult<()> {
+    let nums = zipf(100_000);
+    let config = ChunkConfig::default();
+    let plain = simple_compress(&nums, &config)?;
+    let equalized = simple_compress_equalized(&nums, &config)?;
+    assert_eq!(
+      equalized[TRANSFORM_HEADER_SIZE],
+      EQUALIZED_BYTE
+    );
+    assert!(equalized.len() < plain.len());
+    assert_eq!(
+      simple_decompress_equalized::<u64>(&equalized)?,
+      nums
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_skewed_categories_prefer_frequency_order() -> PcoResult<()> {
+    // Zipf-distributed categories whose values are unrelated to frequency
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(1);
+    let mut values = HashMap::new();
+    let nums = zipf(200_000)
+      .into_iter()
+      .map(|category| {
+        *values
+          .entry(category)
+          .or_insert_with(|| rng.gen_range(0..1 << 24))
+      })
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default();
+    let mapping = choose_mapping(&nums, &config).unwrap();
+    let ranks = ranks(&nums, &mapping);
+    let value_sorted = compress_mapped::<u64>(EQUALIZED_BYTE, &mapping, &ranks, &config)?;
+    let (frequency_mapping, codes) = frequency_sorted(&mapping, &ranks);
+    let frequency_sorted = compress_mapped::<u64>(
+      FREQUENCY_SORTED_BYTE,
+      &frequency_mapping,
+      &codes,
+      &config,
+    )?;
+    assert!(frequency_sorted.len() < value_sorted.len());
+
+    let equalized = simple_compress_equalized(&nums, &config)?;
+    assert_eq!(equalized, frequency_sorted);
+    assert_eq!(
+      simple_decompress_equalized::<u64>(&equalized)?,
+      nums
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_high_cardinality_stays_plain() -> PcoResult<()> {
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let nums = (0..10_000)
+      .map(|_| rng.gen_range(0..1_u64 << 44))
+      .collect::<Vec<_>>();
+    let equalized = simple_compress_equalized(&nums, &ChunkConfig::default())?;
+    assert_eq!(equalized[TRANSFORM_HEADER_SIZE], PLAIN_BYTE);
+    assert_eq!(
+      simple_decompress_equalized::<u64>(&equalized)?,
+      nums
+    );
+    Ok(())
+  }
+}
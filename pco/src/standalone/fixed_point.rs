@@ -0,0 +1,292 @@
+use half::f16;
+
+use crate::chunk_config::ChunkConfig;
+use crate::data_types::{Latent, Number, NumberType};
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::simple::{simple_compress, simple_decompress};
+use crate::standalone::transform_header::{Transform, TRANSFORM_HEADER_SIZE};
+
+const PLAIN_BYTE: u8 = 0;
+const FIXED_POINT_BYTE: u8 = 1;
+// k must fit in a byte, which also keeps 2^k and 2^-k normal f64s.
+const MAX_K: u32 = u8::MAX as u32;
+// exclusive bound on the magnitude of the integers we store as i64
+const I64_BOUND: f64 = 9_223_372_036_854_775_808.0;
+
+pub(crate) fn float_type<T: Number>() -> Option<NumberType> {
+  match NumberType::from_descriminant(T::NUMBER_TYPE_BYTE) {
+    Some(number_type @ (NumberType::F16 | NumberType::F32 | NumberType::F64)) => Some(number_type),
+    _ => None,
+  }
+}
+
+// Every f16 and f32 is exactly representable as an f64, so we can detect and
+// scale in f64 regardless of the float type.
+pub(crate) fn to_f64<T: Number>(num: T, number_type: NumberType) -> f64 {
+  let bits = num.transmute_to_latent().to_u64();
+  match number_type {
+    NumberType::F16 => f16::from_bits(bits as u16).to_f64(),
+    NumberType::F32 => f32::from_bits(bits as u32) as f64,
+    _ => f64::from_bits(bits),
+  }
+}
+
+pub(crate) fn from_f64<T: Number>(x: f64, number_type: NumberType) -> T {
+  let latent = match number_type {
+    NumberType::F16 => f16::from_f64(x).to_latent_ordered().to_u64(),
+    NumberType::F32 => (x as f32).to_latent_ordered().to_u64(),
+    _ => x.to_latent_ordered().to_u64(),
+  };
+  T::from_latent_ordered(T::L::from_u64(latent))
+}
+
+// Returns the smallest k >= 0 such that x is a multiple of 2^-k, if x is
+// finite and such a k is small enough.
+fn min_k(x: f64) -> Option<u32> {
+  if !x.is_finite() {
+    return None;
+  }
+  if x == 0.0 {
+    return Some(0);
+  }
+  let bits = x.to_bits();
+  let exponent_bits = ((bits >> 52) & 0x7ff) as i32;
+  let mantissa = bits & ((1 << 52) - 1);
+  // x = significand * 2^exponent
+  let (significand, exponent) = if exponent_bits == 0 {
+    (mantissa, -1074)
+  } else {
+    (mantissa | (1 << 52), exponent_bits - 1075)
+  };
+  let exponent = exponent + significand.trailing_zeros() as i32;
+  let k = (-exponent).max(0) as u32;
+  (k <= MAX_K).then_some(k)
+}
+
+// Returns k and the multiples of 2^-k if every number is one, each fitting
+// in an i64.
+fn to_multiples<T: Number>(nums: &[T], number_type: NumberType) -> Option<(u32, Vec<i64>)> {
+  let mut k = 0;
+  for &num in nums {
+    let x = to_f64(num, number_type);
+    // the integers can't distinguish -0.0 from 0.0
+    if x == 0.0 && x.is_sign_negative() {
+      return None;
+    }
+    k = k.max(min_k(x)?);
+  }
+
+  let scale = 2.0_f64.powi(k as i32);
+  nums
+    .iter()
+    .map(|&num| {
+      let scaled = to_f64(num, number_type) * scale;
+      (scaled.abs() < I64_BOUND).then_some(scaled as i64)
+    })
+    .collect::<Option<Vec<_>>>()
+    .map(|multiples| (k, multiples))
+}
+
+/// Takes in floats and an exact configuration and returns compressed bytes,
+/// storing them as integers if they are all multiples of 2^-k for some k.
+///
+/// This is an experimental tool for fixed-point data stored as floats.
+/// Pco's float mult mode already compresses such data losslessly, but the
+/// integers it derives from the floats are ordered by magnitude within each
+/// sign, so delta encoding works poorly on values that cross zero.
+/// Here each float is replaced by its exact multiple of 2^-k as an `i64`,
+/// which compresses like any other integer data.
+/// The smallest such k is detected automatically.
+/// Every finite float is a multiple of some tiny power of two, so the integers
+/// are only used when their result is smaller; otherwise, or if the floats
+/// include NaNs, infinities, or -0.0, they are compressed as usual.
+/// Expect up to twice the compression time of [`simple_compress`].
+///
+/// The detected k can be read back with [`fixed_point_exponent`].
+/// Decompress with [`simple_decompress_fixed_point`].
+/// Will return an error if `T` is not a float type or the compressor config
+/// is invalid.
+pub fn simple_compress_fixed_point<T: Number>(
+  nums: &[T],
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  let number_type = float_type::<T>()
+    .ok_or_else(|| PcoError::invalid_argument("fixed point compression only applies to floats"))?;
+
+  let mut res = Transform::FixedPoint.header();
+  res.push(PLAIN_BYTE);
+  res.extend(simple_compress(nums, config)?);
+
+  if let Some((k, multiples)) = to_multiples(nums, number_type) {
+    let mut candidate = Transform::FixedPoint.header();
+    candidate.extend([FIXED_POINT_BYTE, k as u8]);
+    candidate.extend(simple_compress(&multiples, config)?);
+    if candidate.len() < res.len() {
+      res = candidate;
+    }
+  }
+  Ok(res)
+}
+
+/// Returns the k such that compressed bytes (as produced by
+/// [`simple_compress_fixed_point`]) store multiples of 2^-k, or `None` if
+/// they store the floats as usual.
+///
+/// Will return an error if the leading bytes are missing or invalid.
+pub fn fixed_point_exponent(src: &[u8]) -> PcoResult<Option<u32>> {
+  match Transform::FixedPoint.read_header(src)? {
+    [] => Err(PcoError::insufficient_data(
+      "missing fixed point byte",
+    )),
+    [PLAIN_BYTE, ..] => Ok(None),
+    [FIXED_POINT_BYTE, k, ..] => Ok(Some(*k as u32)),
+    [FIXED_POINT_BYTE] => Err(PcoError::insufficient_data(
+      "missing fixed point exponent",
+    )),
+    [other, ..] => Err(PcoError::corruption(format!(
+      "unknown fixed point byte {}",
+      other
+    ))),
+  }
+}
+
+/// Takes in compressed bytes (as produced by [`simple_compress_fixed_point`])
+/// and returns the floats.
+///
+/// Will return an error if `T` is not a float type or there are any
+/// compatibility, corruption, or insufficient data issues.
+pub fn simple_decompress_fixed_point<T: Number>(src: &[u8]) -> PcoResult<Vec<T>> {
+  let number_type = float_type::<T>()
+    .ok_or_else(|| PcoError::invalid_argument("fixed point compression only applies to floats"))?;
+
+  let exponent = fixed_point_exponent(src)?;
+  let src = &src[TRANSFORM_HEADER_SIZE..];
+  match exponent {
+    None => simple_decompress(&src[1..]),
+    Some(k) => {
+      // scaling by a power of two is exact, so this recovers each float
+      let scale = 2.0_f64.powi(-(k as i32));
+      let multiples = simple_decompress::<i64>(&src[2..])?;
+      Ok(
+        multiples
+          .into_iter()
+          .map(|multiple| from_f64(multiple as f64 * scale, number_type))
+          .collect(),
+      )
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  use super::*;
+
+  fn assert_same_bits<T: Number>(decompressed: &[T], nums: &[T]) {
+    assert_eq!(
+      decompressed
+        .iter()
+        .map(|x| x.to_latent_ordered())
+        .collect::<Vec<_>>(),
+      nums
+        .iter()
+        .map(|x| x.to_latent_ordered())
+        .collect::<Vec<_>>(),
+    );
+  }
+
+  #[test]
+  fn test_min_k() {
+    assert_eq!(min_k(0.0), Some(0));
+    assert_eq!(min_k(3.0), Some(0));
+    assert_eq!(min_k(1024.0), Some(0));
+    assert_eq!(min_k(-0.75), Some(2));
+    assert_eq!(min_k(5.0 / 256.0), Some(8));
+    assert_eq!(min_k(f64::from_bits(1)), None);
+    assert_eq!(min_k(0.1), Some(55));
+    assert_eq!(min_k(f64::NAN), None);
+    assert_eq!(min_k(f64::INFINITY), None);
+  }
+
+  #[test]
+  fn test_quantized_to_2_pow_minus_8() -> PcoResult<()> {
+    // a random walk in steps of 1/256 that crosses zero
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let mut x = 0_i64;
+    let nums = (0..100_000)
+      .map(|_| {
+        x += rng.gen_range(-16..16);
+        x as f64 / 256.0
+      })
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default();
+    let compressed = simple_compress_fixed_point(&nums, &config)?;
+    assert_eq!(fixed_point_exponent(&compressed)?, Some(8));
+    assert!(compressed.len() < simple_compress(&nums, &config)?.len());
+    assert_same_bits(
+      &simple_decompress_fixed_point::<f64>(&compressed)?,
+      &nums,
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_float_types() -> PcoResult<()> {
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let ints = (0..2000)
+      .map(|_| rng.gen_range(-512..512_i64))
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default();
+
+    let nums = ints
+      .iter()
+      .map(|&x| f16::from_f64(x as f64 / 64.0))
+      .collect::<Vec<_>>();
+    let compressed = simple_compress_fixed_point(&nums, &config)?;
+    assert_eq!(fixed_point_exponent(&compressed)?, Some(6));
+    assert_same_bits(
+      &simple_decompress_fixed_point::<f16>(&compressed)?,
+      &nums,
+    );
+
+    let mut nums = ints.iter().map(|&x| x as f32 * 0.125).collect::<Vec<_>>();
+    nums.extend([f32::MAX, f32::MIN_POSITIVE]);
+    let compressed = simple_compress_fixed_point(&nums, &config)?;
+    assert_eq!(fixed_point_exponent(&compressed)?, None);
+    assert_same_bits(
+      &simple_decompress_fixed_point::<f32>(&compressed)?,
+      &nums,
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_falls_back_to_plain() -> PcoResult<()> {
+    let config = ChunkConfig::default();
+    for extra in [0.1, -0.0, f64::NAN, f64::NEG_INFINITY, 1e30] {
+      let mut nums = (0..1000).map(|i| i as f64 * 0.5).collect::<Vec<_>>();
+      nums.push(extra);
+      let compressed = simple_compress_fixed_point(&nums, &config)?;
+      assert_eq!(
+        fixed_point_exponent(&compressed)?,
+        None,
+        "{}",
+        extra
+      );
+      assert_same_bits(
+        &simple_decompress_fixed_point::<f64>(&compressed)?,
+        &nums,
+      );
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_non_floats_rejected() {
+    let result = simple_compress_fixed_point(&[1_i32, 2], &ChunkConfig::default());
+    assert!(result.is_err());
+  }
+}
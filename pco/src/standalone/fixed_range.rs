@@ -0,0 +1,279 @@
+use std::io::Write;
+
+use crate::bit_reader::BitReader;
+use crate::bit_writer::BitWriter;
+use crate::chunk_config::ChunkConfig;
+use crate::data_types::Number;
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::decompressor::FileDecompressor;
+use crate::standalone::fixed_point::{float_type, from_f64, to_f64};
+use crate::standalone::simple::{simple_decompress, Compressor};
+
+// the range's bounds as f64s and the bit depth as a byte
+pub(crate) const FIXED_RANGE_BYTE_SIZE: usize = 17;
+// Beyond this, steps are finer than an f64 can resolve.
+const MAX_BIT_DEPTH: u32 = 52;
+
+/// A declared range of floats and a bit depth to quantize them to, e.g.
+/// probabilities in `[0, 1]` at 12 bits.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FixedRange {
+  /// The smallest value numbers may take.
+  pub lower: f64,
+  /// The largest value numbers may take.
+  pub upper: f64,
+  /// How many bits each number is quantized to, from 1 to 52.
+  pub bit_depth: u32,
+}
+
+impl FixedRange {
+  /// Creates a range quantizing numbers in `[lower, upper]` to `bit_depth`
+  /// bits, i.e. to `2^bit_depth` evenly spaced levels including both bounds.
+  pub fn new(lower: f64, upper: f64, bit_depth: u32) -> Self {
+    Self {
+      lower,
+      upper,
+      bit_depth,
+    }
+  }
+
+  /// Creates a range quantizing numbers in `[0, 1]`, such as probabilities,
+  /// to `bit_depth` bits.
+  pub fn unit(bit_depth: u32) -> Self {
+    Self::new(0.0, 1.0, bit_depth)
+  }
+
+  /// Returns the largest error quantization can introduce, which is half
+  /// the spacing between levels.
+  ///
+  /// Decompressed numbers are within this of the originals, plus the
+  /// rounding error of converting back to the float type, which is much
+  /// smaller unless the bit depth approaches the type's precision.
+  pub fn max_error(&self) -> f64 {
+    self.step() / 2.0
+  }
+
+  fn max_level(&self) -> u64 {
+    (1 << self.bit_depth) - 1
+  }
+
+  fn step(&self) -> f64 {
+    (self.upper - self.lower) / self.max_level() as f64
+  }
+
+  fn validate(&self) -> PcoResult<()> {
+    if !(self.lower.is_finite() && self.upper.is_finite() && self.lower < self.upper) {
+      return Err(PcoError::invalid_argument(format!(
+        "fixed range must have finite bounds with lower < upper (was {} and {})",
+        self.lower, self.upper,
+      )));
+    }
+    if self.bit_depth == 0 || self.bit_depth > MAX_BIT_DEPTH {
+      return Err(PcoError::invalid_argument(format!(
+        "fixed range bit depth must be between 1 and {} (was {})",
+        MAX_BIT_DEPTH, self.bit_depth,
+      )));
+    }
+    Ok(())
+  }
+
+  pub(crate) fn read_from(reader: &mut BitReader) -> PcoResult<Self> {
+    let bytes = reader.read_aligned_bytes(FIXED_RANGE_BYTE_SIZE)?;
+    let read_f64 = |i: usize| f64::from_le_bytes(bytes[i * 8..(i + 1) * 8].try_into().unwrap());
+    let range = Self::new(read_f64(0), read_f64(1), bytes[16] as u32);
+    range
+      .validate()
+      .map_err(|e| PcoError::corruption(e.message))?;
+    Ok(range)
+  }
+
+  pub(crate) fn write_to<W: Write>(&self, writer: &mut BitWriter<W>) -> PcoResult<()> {
+    let mut bytes = Vec::with_capacity(FIXED_RANGE_BYTE_SIZE);
+    bytes.extend(self.lower.to_le_bytes());
+    bytes.extend(self.upper.to_le_bytes());
+    bytes.push(self.bit_depth as u8);
+    writer.write_aligned_bytes(&bytes)
+  }
+}
+
+/// Takes in floats within a declared range, the range and bit depth to
+/// quantize them to, and an exact configuration and returns compressed
+/// bytes.
+///
+/// This is lossy: each float is mapped to the nearest of `2^bit_depth`
+/// evenly spaced levels spanning the range, so decompressed floats differ
+/// from the originals by at most [`FixedRange::max_error`] (plus float
+/// rounding), and the levels take at most `bit_depth` bits each before
+/// compression.
+/// Pco's float mult and float quant modes compress such data losslessly,
+/// but can't discard precision beyond the declared resolution.
+/// The levels are stored as a standalone file of `u64`s whose header
+/// records the range and marks the file lossy, so decompression doesn't need
+/// them; see
+/// [`FileDecompressor::fixed_range`][crate::standalone::FileDecompressor::fixed_range].
+///
+/// Decompress with [`simple_decompress_fixed_range`].
+/// Will return an error if `T` is not a float type, the range or compressor
+/// config is invalid, or any float is NaN or outside the range.
+pub fn simple_compress_fixed_range<T: Number>(
+  nums: &[T],
+  range: &FixedRange,
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  let number_type = float_type::<T>()
+    .ok_or_else(|| PcoError::invalid_argument("fixed range compression only applies to floats"))?;
+  range.validate()?;
+
+  let max_level = range.max_level() as f64;
+  let width = range.upper - range.lower;
+  let mut levels = Vec::with_capacity(nums.len());
+  for (i, &num) in nums.iter().enumerate() {
+    let x = to_f64(num, number_type);
+    if !(range.lower..=range.upper).contains(&x) {
+      return Err(PcoError::invalid_argument(format!(
+        "number at index {} was {}, outside the fixed range [{}, {}]",
+        i, x, range.lower, range.upper,
+      )));
+    }
+    let level = ((x - range.lower) / width * max_level).round();
+    levels.push(level.min(max_level) as u64);
+  }
+
+  Compressor::new(config)?
+    .with_fixed_range(Some(*range))
+    .compress(&levels)
+}
+
+/// Takes in compressed bytes (as produced by
+/// [`simple_compress_fixed_range`]) and returns the quantized floats.
+///
+/// Will return an error if `T` is not a float type, the file has no fixed
+/// range, or there are any compatibility, corruption, or insufficient data
+/// issues.
+pub fn simple_decompress_fixed_range<T: Number>(src: &[u8]) -> PcoResult<Vec<T>> {
+  let number_type = float_type::<T>()
+    .ok_or_else(|| PcoError::invalid_argument("fixed range compression only applies to floats"))?;
+  let (file_decompressor, _) = FileDecompressor::new(src)?;
+  let range = file_decompressor
+    .fixed_range()
+    .ok_or_else(|| PcoError::invalid_argument("file was not compressed with a fixed range"))?;
+
+  let max_level = range.max_level();
+  let step = range.step();
+  simple_decompress::<u64>(src)?
+    .into_iter()
+    .map(|level| {
+      if level > max_level {
+        return Err(PcoError::corruption(format!(
+          "fixed range level {} exceeds the max of {}",
+          level, max_level,
+        )));
+      }
+      let x = (range.lower + level as f64 * step).clamp(range.lower, range.upper);
+      Ok(from_f64(x, number_type))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  use super::*;
+  use crate::standalone::simple_compress;
+
+  // Beta(2, 5) samples, as the 2nd smallest of 6 uniforms
+  fn beta_2_5(n: usize) -> Vec<f32> {
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    (0..n)
+      .map(|_| {
+        let mut uniforms = [0.0_f32; 6];
+        uniforms.fill_with(|| rng.gen_range(0.0..=1.0));
+        uniforms.sort_by(f32::total_cmp);
+        uniforms[1]
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_beta_error_bounded() -> PcoResult<()> {
+    let nums = beta_2_5(100_000);
+    let config = ChunkConfig::default();
+    let lossless = simple_compress(&nums, &config)?;
+    for bit_depth in [4, 8, 12, 16] {
+      let range = FixedRange::unit(bit_depth);
+      let compressed = simple_compress_fixed_range(&nums, &range, &config)?;
+      assert!(compressed.len() < lossless.len());
+      // at most bit_depth bits per number, plus metadata
+      assert!(compressed.len() * 8 < nums.len() * bit_depth as usize + 1000);
+
+      let decompressed = simple_decompress_fixed_range::<f32>(&compressed)?;
+      assert_eq!(decompressed.len(), nums.len());
+      // allowing for rounding to f32
+      let bound = range.max_error() + f32::EPSILON as f64;
+      for (&x, &y) in nums.iter().zip(&decompressed) {
+        assert!(
+          (x as f64 - y as f64).abs() <= bound,
+          "{} vs {} at depth {}",
+          x,
+          y,
+          bit_depth
+        );
+        assert!((0.0..=1.0).contains(&y));
+      }
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_arbitrary_range() -> PcoResult<()> {
+    let nums = [-40.0, -39.99, 0.0, 12.5, 85.0];
+    let range = FixedRange::new(-40.0, 85.0, 20);
+    let compressed = simple_compress_fixed_range(&nums, &range, &ChunkConfig::default())?;
+    let decompressed = simple_decompress_fixed_range::<f64>(&compressed)?;
+    assert_eq!(decompressed[0], -40.0);
+    assert_eq!(decompressed[4], 85.0);
+    for (&x, &y) in nums.iter().zip(&decompressed) {
+      assert!((x - y).abs() <= range.max_error());
+    }
+
+    let (file_decompressor, _) = FileDecompressor::new(compressed.as_slice())?;
+    assert_eq!(file_decompressor.fixed_range(), Some(range));
+    assert!(!file_decompressor.is_lossless());
+    // the levels are ordinary u64s
+    assert_eq!(
+      simple_decompress::<u64>(&compressed)?[4],
+      (1 << 20) - 1
+    );
+
+    let compressed = simple_compress_fixed_range::<f64>(&[], &range, &ChunkConfig::default())?;
+    assert_eq!(
+      simple_decompress_fixed_range::<f64>(&compressed)?,
+      vec![]
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_fixed_range_errors() {
+    let config = ChunkConfig::default();
+    let unit = FixedRange::unit(8);
+    assert!(simple_compress_fixed_range(&[0.5_f32, 1.5], &unit, &config).is_err());
+    assert!(simple_compress_fixed_range(&[f32::NAN], &unit, &config).is_err());
+    assert!(simple_compress_fixed_range(&[1_u32], &unit, &config).is_err());
+    for range in [
+      FixedRange::unit(0),
+      FixedRange::unit(53),
+      FixedRange::new(1.0, 1.0, 8),
+      FixedRange::new(0.0, f64::INFINITY, 8),
+    ] {
+      assert!(simple_compress_fixed_range(&[1.0_f64], &range, &config).is_err());
+    }
+    assert!(simple_decompress_fixed_range::<f32>(&[0; 16]).is_err());
+    let plain = simple_compress(&[0.5_f32], &config).unwrap();
+    assert!(simple_decompress_fixed_range::<f32>(&plain).is_err());
+  }
+}
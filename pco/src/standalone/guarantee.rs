@@ -4,21 +4,36 @@ use crate::standalone::constants::{
   BITS_TO_ENCODE_N_ENTRIES, BITS_TO_ENCODE_STANDALONE_VERSION, BITS_TO_ENCODE_VARINT_POWER,
   MAGIC_HEADER,
 };
+use crate::standalone::manifest;
 use crate::wrapped::guarantee as wrapped_guarantee;
 use crate::PagingSpec;
 
-/// Returns the maximum possible byte size of a standalone header.
+/// Returns the maximum possible byte size of a standalone header, including
+/// any manifest.
+///
+/// Headers with
+/// [source info][crate::standalone::FileCompressor::with_source_info]
+/// take 2 more bytes plus the length of its strings, and those of files
+/// compressed with
+/// [`simple_compress_fixed_range`][crate::standalone::simple_compress_fixed_range]
+/// take 17 more bytes.
 pub fn header_size() -> usize {
   let max_varint_bits = BITS_TO_ENCODE_VARINT_POWER + 64;
   MAGIC_HEADER.len()
     + 1 // uniform dtype
+    + 1 // flags
     + (max_varint_bits + BITS_TO_ENCODE_STANDALONE_VERSION).div_ceil(8) as usize
     + wrapped_guarantee::header_size()
+    + manifest::max_byte_size()
 }
 
 // TODO in 1.0 make these take NumberType enums?
 /// Returns the maximum possible byte size of a standalone chunk for a given
 /// latent type (e.g. u32 or u64) and count of numbers.
+///
+/// Chunks of files with
+/// [annotated chunks][crate::standalone::FileCompressor::with_annotated_chunks]
+/// may take up to 9 more bytes for their annotations.
 pub fn chunk_size<L: Latent>(n: usize) -> usize {
   1 + BITS_TO_ENCODE_N_ENTRIES.div_ceil(8) as usize + wrapped_guarantee::chunk_size::<L>(n)
 }
@@ -46,16 +61,28 @@ mod tests {
 
   use super::*;
   use crate::chunk_config::DeltaSpec;
+  use crate::constants::MAX_COMPRESSION_LEVEL;
   use crate::data_types::{Number, NumberType};
   use crate::errors::PcoResult;
-  use crate::standalone::{simple_compress, FileCompressor};
+  use crate::metadata::{DeltaEncoding, DeltaLookbackConfig, DynLatent, Mode};
+  use crate::standalone::{simple_compress, ChunkConfigSummary, FileCompressor};
   use crate::{ChunkConfig, ModeSpec, PagingSpec};
 
   #[test]
   fn test_header_guarantee() -> PcoResult<()> {
     let fc = FileCompressor::default()
       .with_n_hint(usize::MAX)
-      .with_uniform_type(Some(NumberType::F64));
+      .with_uniform_type(Some(NumberType::F64))
+      .with_annotated_chunks(true)
+      .with_manifest(Some(ChunkConfigSummary {
+        compression_level: MAX_COMPRESSION_LEVEL,
+        mode: Mode::IntMult(DynLatent::U64(u64::MAX)),
+        delta_encoding: DeltaEncoding::Lookback(DeltaLookbackConfig {
+          window_n_log: 15,
+          state_n_log: 4,
+          secondary_uses_delta: true,
+        }),
+      }));
     let mut dst = Vec::new();
     fc.write_header(&mut dst)?;
     assert_eq!(header_size(), dst.len());
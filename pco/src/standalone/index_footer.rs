@@ -0,0 +1,184 @@
+use std::io::Write;
+
+use better_io::BetterBufRead;
+
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::constants::INDEX_FOOTER_MAGIC;
+use crate::standalone::ChunkByteRange;
+
+// The index footer follows the termination byte and is made of
+// little-endian u64s, so readers can parse it from the end of the file:
+// * the count of chunks
+// * per chunk, its start, byte length, and count of numbers
+// * the footer's total byte length, including this and the magic
+// * the magic
+const WORD_SIZE: usize = 8;
+const WORDS_PER_CHUNK: usize = 3;
+const TRAILER_SIZE: usize = WORD_SIZE + INDEX_FOOTER_MAGIC.len();
+
+fn footer_size(n_chunks: usize) -> usize {
+  WORD_SIZE * (1 + WORDS_PER_CHUNK * n_chunks) + TRAILER_SIZE
+}
+
+pub(crate) fn write_index_footer<W: Write>(chunks: &[ChunkByteRange], mut dst: W) -> PcoResult<W> {
+  let mut bytes = Vec::with_capacity(footer_size(chunks.len()));
+  bytes.extend((chunks.len() as u64).to_le_bytes());
+  for chunk in chunks {
+    for word in [chunk.start, chunk.len, chunk.n] {
+      bytes.extend((word as u64).to_le_bytes());
+    }
+  }
+  bytes.extend((footer_size(chunks.len()) as u64).to_le_bytes());
+  bytes.extend(INDEX_FOOTER_MAGIC);
+  dst.write_all(&bytes)?;
+  Ok(dst)
+}
+
+fn read_word(bytes: &[u8], word_idx: usize) -> u64 {
+  let start = word_idx * WORD_SIZE;
+  u64::from_le_bytes(bytes[start..start + WORD_SIZE].try_into().unwrap())
+}
+
+// Parses a whole footer, checking that its own records of its size agree.
+fn parse_index_footer(footer: &[u8]) -> PcoResult<Vec<ChunkByteRange>> {
+  if footer.len() < footer_size(0) {
+    return Err(PcoError::insufficient_data(
+      "index footer is too short",
+    ));
+  }
+  let n_chunks = read_word(footer, 0) as usize;
+  let trailer = &footer[footer.len() - TRAILER_SIZE..];
+  if trailer[WORD_SIZE..] != INDEX_FOOTER_MAGIC {
+    return Err(PcoError::corruption(format!(
+      "index footer magic does not match {:?}",
+      INDEX_FOOTER_MAGIC,
+    )));
+  }
+  let recorded_size = read_word(trailer, 0);
+  if recorded_size != footer.len() as u64
+    || n_chunks.checked_mul(WORDS_PER_CHUNK * WORD_SIZE) != Some(footer.len() - footer_size(0))
+  {
+    return Err(PcoError::corruption(format!(
+      "index footer of {} bytes is inconsistent with its recorded size of {} and {} chunks",
+      footer.len(),
+      recorded_size,
+      n_chunks,
+    )));
+  }
+
+  Ok(
+    (0..n_chunks)
+      .map(|chunk_idx| {
+        let word_idx = 1 + chunk_idx * WORDS_PER_CHUNK;
+        ChunkByteRange {
+          start: read_word(footer, word_idx) as usize,
+          len: read_word(footer, word_idx + 1) as usize,
+          n: read_word(footer, word_idx + 2) as usize,
+        }
+      })
+      .collect(),
+  )
+}
+
+fn read_bytes<R: BetterBufRead>(src: &mut R, n_bytes: usize) -> PcoResult<Vec<u8>> {
+  let mut res = Vec::with_capacity(n_bytes);
+  while res.len() < n_bytes {
+    let remaining = n_bytes - res.len();
+    src.fill_or_eof(
+      src
+        .capacity()
+        .map_or(remaining, |capacity| remaining.min(capacity)),
+    )?;
+    let n_read = remaining.min(src.buffer().len());
+    if n_read == 0 {
+      return Err(PcoError::insufficient_data(
+        "file ended partway through index footer",
+      ));
+    }
+    res.extend_from_slice(&src.buffer()[..n_read]);
+    src.consume(n_read);
+  }
+  Ok(res)
+}
+
+// Reads the footer that starts at the source, e.g. right after the
+// termination byte during sequential decompression.
+pub(crate) fn read_index_footer<R: BetterBufRead>(src: &mut R) -> PcoResult<Vec<ChunkByteRange>> {
+  let mut footer = read_bytes(src, WORD_SIZE)?;
+  let n_chunks = read_word(&footer, 0) as usize;
+  let Some(n_rest) = n_chunks
+    .checked_mul(WORDS_PER_CHUNK * WORD_SIZE)
+    .and_then(|chunk_bytes| chunk_bytes.checked_add(TRAILER_SIZE))
+  else {
+    return Err(PcoError::corruption(format!(
+      "index footer has an impossible count of {} chunks",
+      n_chunks
+    )));
+  };
+  footer.extend(read_bytes(src, n_rest)?);
+  parse_index_footer(&footer)
+}
+
+// Finds the footer at the end of a whole file's bytes, ignoring any zero
+// padding after it.
+pub(crate) fn find_index_footer(src: &[u8]) -> PcoResult<Vec<ChunkByteRange>> {
+  let unpadded_len = src
+    .iter()
+    .rposition(|&byte| byte != 0)
+    .map_or(0, |idx| idx + 1);
+  let src = &src[..unpadded_len];
+  if src.len() < TRAILER_SIZE {
+    return Err(PcoError::insufficient_data(
+      "file is too short for an index footer",
+    ));
+  }
+  let trailer = &src[src.len() - TRAILER_SIZE..];
+  let footer_size = read_word(trailer, 0);
+  if footer_size > src.len() as u64 {
+    return Err(PcoError::corruption(format!(
+      "index footer size of {} exceeds file size of {}",
+      footer_size,
+      src.len(),
+    )));
+  }
+  parse_index_footer(&src[src.len() - footer_size as usize..])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_footer_roundtrip() -> PcoResult<()> {
+    let chunks = vec![
+      ChunkByteRange {
+        start: 10,
+        len: 300,
+        n: 256,
+      },
+      ChunkByteRange {
+        start: 310,
+        len: 5,
+        n: 1,
+      },
+    ];
+    let footer = write_index_footer(&chunks, Vec::new())?;
+    assert_eq!(footer.len(), footer_size(2));
+    assert_eq!(
+      read_index_footer(&mut footer.as_slice())?,
+      chunks
+    );
+
+    let mut file = vec![7; 311];
+    file.extend(&footer);
+    file.extend([0; 5]);
+    assert_eq!(find_index_footer(&file)?, chunks);
+
+    // truncated footers
+    for len in [0, 8, footer.len() - 1] {
+      assert!(read_index_footer(&mut &footer[..len]).is_err());
+      assert!(find_index_footer(&footer[..len]).is_err());
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,216 @@
+use crate::data_types::NumberType;
+use crate::errors::{PcoError, PcoResult};
+use crate::macros::match_number_enum;
+use crate::metadata::{DeltaEncoding, DynLatents, LatentVarKey, Mode};
+use crate::standalone::decompressor::{FileDecompressor, MaybeChunkDecompressor};
+use crate::standalone::NumberTypeOrTermination;
+
+/// The raw values of one latent variable in one chunk, as returned by
+/// [`decompress_latents`].
+///
+/// The values are exactly what the entropy stage decoded: delta decoding
+/// and the mode's join have not been applied.
+/// What they mean depends on the chunk's mode and the latent variable:
+/// * Classic: the primary latents are the numbers themselves, converted to
+///   unsigned integers that preserve their order.
+/// * IntMult: the primary latents are the multipliers on the base, and the
+///   secondary latents are the adjustments.
+/// * FloatMult: the primary latents are the multipliers on the base, and the
+///   secondary latents are the ULPs adjustments.
+/// * FloatQuant: the primary latents are the quantums, effectively the
+///   ordered representations shifted right by k, and the secondary latents
+///   are the ULPs adjustments, effectively the low k bits.
+///
+/// If the chunk uses delta encoding, the latent variables it applies to hold
+/// centered deltas rather than the values described above, and the delta
+/// latent variable, if any, holds each number's lookback.
+/// Each page's delta state is not included, so each page contributes fewer
+/// latents to those variables (and to the delta latent variable) than it has
+/// numbers: as many fewer as the consecutive delta order, the lookback state
+/// size, or one for xor.
+///
+/// Only available with the `debug_latents` feature.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct LatentVec {
+  /// The index of the chunk within the file.
+  pub chunk_idx: usize,
+  /// The chunk's mode.
+  pub mode: Mode,
+  /// The chunk's delta encoding.
+  pub delta_encoding: DeltaEncoding,
+  /// Which latent variable these values belong to.
+  pub key: LatentVarKey,
+  /// The latents, concatenated over the chunk's pages.
+  pub latents: DynLatents,
+}
+
+/// Takes in compressed bytes and returns the raw latents of each latent
+/// variable in each chunk, in order of appearance in the file.
+///
+/// This is a debugging tool for studying how pco represents data; see
+/// [`LatentVec`] for what the values mean.
+/// Only available with the `debug_latents` feature.
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+pub fn decompress_latents(src: &[u8]) -> PcoResult<Vec<LatentVec>> {
+  let (file_decompressor, mut src) = FileDecompressor::new(src)?;
+  let mut res = Vec::new();
+  let mut chunk_idx = 0;
+  loop {
+    let number_type = match file_decompressor.peek_number_type_or_termination(src)? {
+      NumberTypeOrTermination::Termination => break,
+      NumberTypeOrTermination::Known(number_type) => number_type,
+      NumberTypeOrTermination::Unknown(byte) => {
+        return Err(PcoError::compatibility(format!(
+          "unrecognized number type byte {}",
+          byte,
+        )))
+      }
+    };
+
+    let maybe_cd = match_number_enum!(
+      number_type,
+      NumberType<T> => {
+        match file_decompressor.chunk_decompressor::<T, _>(src)? {
+          MaybeChunkDecompressor::Some(cd) => {
+            let meta = cd.meta();
+            let (mode, delta_encoding) = (meta.mode, meta.delta_encoding);
+            let (latents, rest) = cd.decompress_latents()?;
+            Some((mode, delta_encoding, latents, rest))
+          }
+          // a file with a uniform type reports it even at termination
+          MaybeChunkDecompressor::EndOfData(_) => None,
+        }
+      }
+    );
+    let Some((mode, delta_encoding, latents, rest)) = maybe_cd else {
+      break;
+    };
+
+    for (key, latents) in latents.enumerated() {
+      res.push(LatentVec {
+        chunk_idx,
+        mode,
+        delta_encoding,
+        key,
+        latents,
+      });
+    }
+    src = rest;
+    chunk_idx += 1;
+  }
+  Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::chunk_config::{ChunkConfig, DeltaSpec, ModeSpec, PagingSpec};
+  use crate::data_types::Latent;
+  use crate::metadata::DynLatent;
+  use crate::standalone::{simple_compress, FileCompressor};
+
+  fn latents_u32(latent_vec: &LatentVec) -> &[u32] {
+    latent_vec.latents.downcast_ref::<u32>().unwrap()
+  }
+
+  #[test]
+  fn test_classic_and_int_mult() -> PcoResult<()> {
+    let nums = (0..3000_u32)
+      .map(|i| 7 * ((i * 31) % 1000) + i % 3)
+      .collect::<Vec<_>>();
+    let classic = ChunkConfig::default()
+      .with_mode_spec(ModeSpec::Classic)
+      .with_delta_spec(DeltaSpec::None);
+    let fc = FileCompressor::default();
+    let mut compressed = Vec::new();
+    fc.write_header(&mut compressed)?;
+    fc.chunk_compressor(&nums, &classic)?
+      .write_chunk(&mut compressed)?;
+    fc.chunk_compressor(
+      &nums,
+      &classic.with_mode_spec(ModeSpec::TryIntMult(7)),
+    )?
+    .write_chunk(&mut compressed)?;
+    fc.write_footer(&mut compressed)?;
+
+    let latent_vecs = decompress_latents(&compressed)?;
+    assert_eq!(latent_vecs.len(), 3);
+    let [classic_primary, mult, adj] = latent_vecs.as_slice() else {
+      unreachable!();
+    };
+
+    assert_eq!(classic_primary.chunk_idx, 0);
+    assert_eq!(classic_primary.mode, Mode::Classic);
+    assert_eq!(classic_primary.key, LatentVarKey::Primary);
+    assert_eq!(latents_u32(classic_primary), nums);
+
+    assert_eq!(mult.chunk_idx, 1);
+    assert_eq!(
+      mult.mode,
+      Mode::IntMult(DynLatent::new(7_u32).unwrap())
+    );
+    assert_eq!(
+      (mult.key, adj.key),
+      (
+        LatentVarKey::Primary,
+        LatentVarKey::Secondary
+      )
+    );
+    let joined = latents_u32(mult)
+      .iter()
+      .zip(latents_u32(adj))
+      .map(|(&mult, &adj)| 7 * mult + adj)
+      .collect::<Vec<_>>();
+    assert_eq!(joined, nums);
+    Ok(())
+  }
+
+  #[test]
+  fn test_pre_delta_per_page() -> PcoResult<()> {
+    let nums = (0..1000_u32).map(|i| i * i).collect::<Vec<_>>();
+    let config = ChunkConfig::default()
+      .with_mode_spec(ModeSpec::Classic)
+      .with_delta_spec(DeltaSpec::TryConsecutive(1))
+      .with_paging_spec(PagingSpec::Exact(vec![300, 700]));
+    // each page of a standalone file is its own chunk
+    let latent_vecs = decompress_latents(&simple_compress(&nums, &config)?)?;
+    assert_eq!(latent_vecs.len(), 2);
+    for (chunk_idx, (latent_vec, page)) in latent_vecs.iter().zip([0..300, 300..1000]).enumerate() {
+      assert_eq!(latent_vec.chunk_idx, chunk_idx);
+      assert!(matches!(
+        latent_vec.delta_encoding,
+        DeltaEncoding::Consecutive(_)
+      ));
+
+      // the page's first number is its delta state
+      let expected = nums[page]
+        .windows(2)
+        .map(|w| w[1].wrapping_sub(w[0]).toggle_center())
+        .collect::<Vec<_>>();
+      assert_eq!(latents_u32(latent_vec), expected);
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_empty_and_uniform_type() -> PcoResult<()> {
+    let config = ChunkConfig::default().with_delta_spec(DeltaSpec::None);
+    assert!(decompress_latents(&simple_compress::<i64>(&[], &config)?)?.is_empty());
+
+    let fc = FileCompressor::default().with_uniform_type(Some(NumberType::F32));
+    let mut compressed = Vec::new();
+    fc.write_header(&mut compressed)?;
+    fc.chunk_compressor(&[1.5_f32, -2.0, 8.0], &config)?
+      .write_chunk(&mut compressed)?;
+    fc.write_footer(&mut compressed)?;
+    let latent_vecs = decompress_latents(&compressed)?;
+    assert_eq!(latent_vecs.len(), 1);
+    assert_eq!(
+      latent_vecs[0].latents.downcast_ref::<u32>().unwrap().len(),
+      3
+    );
+    Ok(())
+  }
+}
@@ -0,0 +1,134 @@
+use std::io::Write;
+
+use crate::bit_reader::BitReader;
+use crate::bit_writer::BitWriter;
+use crate::constants::{
+  Bitlen, BITS_TO_ENCODE_DELTA_ENCODING_VARIANT, BITS_TO_ENCODE_LZ_DELTA_STATE_N_LOG,
+  BITS_TO_ENCODE_LZ_DELTA_WINDOW_N_LOG, BITS_TO_ENCODE_MODE_VARIANT, MAX_COMPRESSION_LEVEL,
+};
+use crate::data_types::LatentType;
+use crate::errors::{PcoError, PcoResult};
+use crate::macros::match_latent_enum;
+use crate::metadata::format_version::FormatVersion;
+use crate::metadata::{ChunkMeta, DeltaEncoding, DynLatent, Mode};
+use crate::standalone::constants::{
+  BITS_TO_ENCODE_COMPRESSION_LEVEL, BITS_TO_ENCODE_MANIFEST_LATENT_TYPE,
+};
+use crate::ChunkConfig;
+
+/// A record of the settings a file was compressed with, which can be stored
+/// in its header via
+/// [`FileCompressor::with_manifest`][crate::standalone::FileCompressor::with_manifest].
+///
+/// Unlike a `ChunkConfig`, this holds what pco actually chose, e.g. the mode
+/// and delta encoding that `Auto` resolved to.
+/// It is purely informational and doesn't affect decompression.
+///
+/// A file has at most one manifest, which describes a single chunk (the
+/// first, when written by
+/// [`Compressor`][crate::standalone::Compressor]).
+/// Later chunks are compressed independently, so `Auto` specs may have
+/// resolved differently for them; use
+/// [`ChunkMeta`][crate::metadata::ChunkMeta] to see what each chunk used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChunkConfigSummary {
+  /// The compression level requested.
+  pub compression_level: usize,
+  /// The mode that was used.
+  pub mode: Mode,
+  /// The delta encoding that was used.
+  pub delta_encoding: DeltaEncoding,
+}
+
+fn mode_latent_type(mode: &Mode) -> LatentType {
+  match mode {
+    Mode::IntMult(base) | Mode::FloatMult(base) => match_latent_enum!(
+      base,
+      DynLatent<L>(_base) => {
+        LatentType::new::<L>().unwrap()
+      }
+    ),
+    // the other modes don't depend on the latent type
+    _ => LatentType::U64,
+  }
+}
+
+// The largest manifest has a 64-bit mult mode and lookback delta encoding.
+pub(crate) fn max_byte_size() -> usize {
+  let max_mode_bits = BITS_TO_ENCODE_MODE_VARIANT + 64;
+  let max_delta_bits = BITS_TO_ENCODE_DELTA_ENCODING_VARIANT
+    + BITS_TO_ENCODE_LZ_DELTA_WINDOW_N_LOG
+    + BITS_TO_ENCODE_LZ_DELTA_STATE_N_LOG
+    + 1;
+  let bits: Bitlen = BITS_TO_ENCODE_COMPRESSION_LEVEL
+    + BITS_TO_ENCODE_MANIFEST_LATENT_TYPE
+    + max_mode_bits
+    + max_delta_bits;
+  bits.div_ceil(8) as usize
+}
+
+impl ChunkConfigSummary {
+  /// Summarizes the settings used for a chunk, given the config it was
+  /// compressed with and its resulting metadata.
+  pub fn new(config: &ChunkConfig, meta: &ChunkMeta) -> Self {
+    Self {
+      compression_level: config.compression_level,
+      mode: meta.mode,
+      delta_encoding: meta.delta_encoding,
+    }
+  }
+
+  pub(crate) unsafe fn read_from(
+    reader: &mut BitReader,
+    version: &FormatVersion,
+  ) -> PcoResult<Self> {
+    let compression_level = reader.read_usize(BITS_TO_ENCODE_COMPRESSION_LEVEL);
+    let latent_type = match reader.read_usize(BITS_TO_ENCODE_MANIFEST_LATENT_TYPE) {
+      0 => LatentType::U16,
+      1 => LatentType::U32,
+      2 => LatentType::U64,
+      value => {
+        return Err(PcoError::corruption(format!(
+          "unknown manifest latent type value {}",
+          value
+        )))
+      }
+    };
+    let mode = Mode::read_from(reader, version, latent_type)?;
+    let delta_encoding = DeltaEncoding::read_from(version, reader)?;
+    reader.drain_empty_byte("nonzero bits in end of final byte of manifest")?;
+    Ok(Self {
+      compression_level,
+      mode,
+      delta_encoding,
+    })
+  }
+
+  pub(crate) unsafe fn write_to<W: Write>(&self, writer: &mut BitWriter<W>) -> PcoResult<()> {
+    if self.compression_level > MAX_COMPRESSION_LEVEL {
+      return Err(PcoError::invalid_argument(format!(
+        "manifest compression level may not exceed {} (was {})",
+        MAX_COMPRESSION_LEVEL, self.compression_level,
+      )));
+    }
+
+    writer.write_usize(
+      self.compression_level,
+      BITS_TO_ENCODE_COMPRESSION_LEVEL,
+    );
+    let latent_type_value = match mode_latent_type(&self.mode) {
+      LatentType::U16 => 0,
+      LatentType::U32 => 1,
+      LatentType::U64 => 2,
+    };
+    writer.write_usize(
+      latent_type_value,
+      BITS_TO_ENCODE_MANIFEST_LATENT_TYPE,
+    );
+    self.mode.write_to(writer);
+    self.delta_encoding.write_to(writer);
+    writer.finish_byte();
+    Ok(())
+  }
+}
@@ -1,11 +1,80 @@
+pub use bit_transpose::{simple_compress_bit_transposed, simple_decompress_bit_transposed};
+pub use chars::{simple_compress_chars, simple_decompress_chars};
+pub use chunk_size::{default_chunk_size, suggest_chunk_size, DEFAULT_CHUNK_SIZE};
+pub use columns::compress_columns;
+#[cfg(feature = "complex")]
+pub use complex::{simple_compress_complex, simple_decompress_complex};
 pub use compressor::{ChunkCompressor, FileCompressor};
-pub use decompressor::{ChunkDecompressor, FileDecompressor, MaybeChunkDecompressor};
+pub use cross_column::{
+  simple_compress_dependent, simple_compress_diff, simple_decompress_dependent,
+  simple_decompress_diff,
+};
+pub use decompressor::{
+  ChunkDecompressor, ChunkIter, FileDecompressor, FileDecompressorAndRest, MaybeChunkDecompressor,
+};
+pub use equalization::{simple_compress_equalized, simple_decompress_equalized};
+pub use fixed_point::{
+  fixed_point_exponent, simple_compress_fixed_point, simple_decompress_fixed_point,
+};
+pub use fixed_range::{simple_compress_fixed_range, simple_decompress_fixed_range, FixedRange};
+#[cfg(feature = "debug_latents")]
+pub use latents::{decompress_latents, LatentVec};
+pub use manifest::ChunkConfigSummary;
+pub use nullable::{
+  compress_optionals, decompress_optionals, simple_compress_nullable, simple_decompress_nullable,
+  ValidityCodec,
+};
 pub use number_type_or_termination::NumberTypeOrTermination;
+pub use parallel::{simple_compress_parallel, ParallelConfig};
+pub use periodic_mask::{
+  simple_compress_periodic_masked, simple_decompress_periodic_masked, PeriodicMask,
+};
+pub use permuted::{simple_compress_permuted, simple_decompress_permuted};
+pub use rechunk::{rechunk, RechunkIter};
+pub use rle::{compress_rle, decompress_rle, decompress_rle_runs};
+pub use scratch::{compress_with_scratch, decompress_with_scratch, Scratch};
+pub use sequential::{simple_compress_sequential, simple_decompress_sequential};
+pub use signed_order::{
+  signed_latent_order, simple_compress_with_signed_order, simple_decompress_with_signed_order,
+  SignedLatentOrder,
+};
 pub use simple::*;
+pub use sorted_unique::{compress_sorted_unique, decompress_sorted_unique};
+pub use source_info::SourceInfo;
+pub use time_budget::compress_within_time;
+pub use widen::decompress_widened;
 
+mod bit_transpose;
+mod chars;
+mod chunk_size;
+mod columns;
+#[cfg(feature = "complex")]
+mod complex;
 mod compressor;
 mod constants;
+mod cross_column;
 mod decompressor;
+mod equalization;
+mod fixed_point;
+mod fixed_range;
 pub mod guarantee;
+mod index_footer;
+#[cfg(feature = "debug_latents")]
+mod latents;
+mod manifest;
+mod nullable;
 mod number_type_or_termination;
+mod parallel;
+mod periodic_mask;
+mod permuted;
+mod rechunk;
+mod rle;
+mod scratch;
+mod sequential;
+mod signed_order;
 mod simple;
+mod sorted_unique;
+mod source_info;
+mod time_budget;
+mod transform_header;
+mod widen;
@@ -0,0 +1,394 @@
+use crate::chunk_config::ChunkConfig;
+use crate::constants::MAX_ENTRIES;
+use crate::data_types::Number;
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::simple::{simple_compress, simple_decompress, simple_decompress_at};
+
+const BITMAP_BYTE: u8 = 0;
+const RUN_LENGTHS_BYTE: u8 = 1;
+// Null runs cost almost nothing to store, so corrupt run lengths could claim
+// an arbitrarily large count. We cap the nulls so that decompressing
+// allocates at most this many entries beyond the valid numbers.
+const MAX_RUN_LENGTH_NULLS: usize = MAX_ENTRIES;
+
+/// How [`simple_compress_nullable`] stores which numbers are valid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidityCodec {
+  /// One uncompressed bit per number.
+  Bitmap,
+  /// The lengths of the alternating runs of valid and null numbers,
+  /// compressed as a pco file.
+  ///
+  /// This is much smaller than a bitmap when nulls are clustered.
+  /// It supports up to 2^24 nulls.
+  RunLengths,
+}
+
+fn run_lengths(validity: &[bool]) -> Vec<u64> {
+  // runs alternate starting with a (possibly empty) valid run
+  let mut res = Vec::new();
+  let mut current = true;
+  let mut run_length = 0;
+  for &is_valid in validity {
+    if is_valid != current {
+      res.push(run_length);
+      current = is_valid;
+      run_length = 0;
+    }
+    run_length += 1;
+  }
+  res.push(run_length);
+  res
+}
+
+// Checks the counts of valid numbers and nulls before allocating, since
+// corrupt run lengths could otherwise claim an arbitrarily large count.
+fn validity_from_run_lengths(run_lengths: &[u64], n_valid: usize) -> PcoResult<Vec<bool>> {
+  let overflowed = || PcoError::corruption("validity run lengths overflowed");
+  let sum_runs = |parity: usize| {
+    run_lengths
+      .iter()
+      .skip(parity)
+      .step_by(2)
+      .try_fold(0_usize, |n, &run_length| {
+        n.checked_add(usize::try_from(run_length).ok()?)
+      })
+      .ok_or_else(overflowed)
+  };
+  let runs_n_valid = sum_runs(0)?;
+  if runs_n_valid != n_valid {
+    return Err(PcoError::corruption(format!(
+      "validity had {} valid numbers, but {} numbers were decompressed",
+      runs_n_valid, n_valid,
+    )));
+  }
+  let n_null = sum_runs(1)?;
+  if n_null > MAX_RUN_LENGTH_NULLS {
+    return Err(PcoError::corruption(format!(
+      "validity run lengths had {} nulls, exceeding the max of {}",
+      n_null, MAX_RUN_LENGTH_NULLS,
+    )));
+  }
+  let n = n_valid + n_null;
+  let mut res = Vec::with_capacity(n);
+  for (i, &run_length) in run_lengths.iter().enumerate() {
+    res.resize(res.len() + run_length as usize, i % 2 == 0);
+  }
+  Ok(res)
+}
+
+fn write_bitmap(validity: &[bool], dst: &mut Vec<u8>) {
+  dst.extend((validity.len() as u64).to_le_bytes());
+  for bits in validity.chunks(8) {
+    let byte = bits.iter().enumerate().fold(0_u8, |byte, (i, &is_valid)| {
+      byte | ((is_valid as u8) << i)
+    });
+    dst.push(byte);
+  }
+}
+
+fn read_bitmap(src: &[u8]) -> PcoResult<(Vec<bool>, &[u8])> {
+  let insufficient = || PcoError::insufficient_data("validity bitmap was truncated");
+  let (n_bytes, rest) = src.split_first_chunk::<8>().ok_or_else(insufficient)?;
+  let n = usize::try_from(u64::from_le_bytes(*n_bytes))
+    .map_err(|_| PcoError::corruption("validity bitmap was too long"))?;
+  let bitmap_size = n.div_ceil(8);
+  if rest.len() < bitmap_size {
+    return Err(insufficient());
+  }
+  let (bitmap, rest) = rest.split_at(bitmap_size);
+  let validity = (0..n)
+    .map(|i| bitmap[i / 8] & (1 << (i % 8)) != 0)
+    .collect();
+  Ok((validity, rest))
+}
+
+fn write_validity(
+  validity: &[bool],
+  config: &ChunkConfig,
+  validity_codec: ValidityCodec,
+  dst: &mut Vec<u8>,
+) -> PcoResult<()> {
+  match validity_codec {
+    ValidityCodec::Bitmap => {
+      dst.push(BITMAP_BYTE);
+      write_bitmap(validity, dst);
+    }
+    ValidityCodec::RunLengths => {
+      let n_null = validity.iter().filter(|&&is_valid| !is_valid).count();
+      if n_null > MAX_RUN_LENGTH_NULLS {
+        return Err(PcoError::invalid_argument(format!(
+          "run length validity supports at most {} nulls, but there were {}",
+          MAX_RUN_LENGTH_NULLS, n_null,
+        )));
+      }
+      dst.push(RUN_LENGTHS_BYTE);
+      let run_lengths_config =
+        ChunkConfig::default().with_compression_level(config.compression_level);
+      dst.extend(simple_compress(
+        &run_lengths(validity),
+        &run_lengths_config,
+      )?);
+    }
+  }
+  Ok(())
+}
+
+/// Takes in numbers, whether each one is valid (non-null), an exact
+/// configuration, and a codec for the validity, and returns compressed bytes.
+///
+/// Only the valid numbers get compressed, so whatever values sit behind the
+/// nulls don't matter.
+/// The validity is stored first, followed by a standalone file of the valid
+/// numbers.
+/// Run lengths are compressed at the config's compression level.
+///
+/// Will return an error if the compressor config is invalid, `nums` and
+/// `validity` have different lengths, or the validity codec can't store this
+/// many nulls.
+pub fn simple_compress_nullable<T: Number>(
+  nums: &[T],
+  validity: &[bool],
+  config: &ChunkConfig,
+  validity_codec: ValidityCodec,
+) -> PcoResult<Vec<u8>> {
+  if nums.len() != validity.len() {
+    return Err(PcoError::invalid_argument(format!(
+      "nums and validity had different lengths ({} vs {})",
+      nums.len(),
+      validity.len(),
+    )));
+  }
+
+  let mut res = Vec::new();
+  write_validity(validity, config, validity_codec, &mut res)?;
+
+  let valid_nums = nums
+    .iter()
+    .zip(validity)
+    .filter_map(|(&num, &is_valid)| is_valid.then_some(num))
+    .collect::<Vec<_>>();
+  res.extend(simple_compress(&valid_nums, config)?);
+  Ok(res)
+}
+
+/// Takes in optional numbers and an exact configuration and returns
+/// compressed bytes.
+///
+/// This is like [`simple_compress_nullable`], but chooses whichever
+/// [`ValidityCodec`] is smaller.
+/// Decompress with [`decompress_optionals`] or
+/// [`simple_decompress_nullable`].
+///
+/// Will return an error if the compressor config is invalid.
+pub fn compress_optionals<T: Number>(
+  nums: &[Option<T>],
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  let validity = nums.iter().map(Option::is_some).collect::<Vec<_>>();
+  let n_null = validity.iter().filter(|&&is_valid| !is_valid).count();
+  let mut res = Vec::new();
+  if n_null <= MAX_RUN_LENGTH_NULLS {
+    write_validity(
+      &validity,
+      config,
+      ValidityCodec::RunLengths,
+      &mut res,
+    )?;
+  }
+  // the codec byte, the count, and the bitmap itself
+  let bitmap_size = 1 + 8 + validity.len().div_ceil(8);
+  if res.is_empty() || bitmap_size < res.len() {
+    res.clear();
+    write_validity(
+      &validity,
+      config,
+      ValidityCodec::Bitmap,
+      &mut res,
+    )?;
+  }
+
+  let valid_nums = nums.iter().flatten().copied().collect::<Vec<_>>();
+  res.extend(simple_compress(&valid_nums, config)?);
+  Ok(res)
+}
+
+/// Takes in compressed bytes (as produced by [`simple_compress_nullable`])
+/// and returns the numbers and whether each one is valid.
+///
+/// Null numbers are filled in with the default value (e.g. 0).
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+pub fn simple_decompress_nullable<T: Number>(src: &[u8]) -> PcoResult<(Vec<T>, Vec<bool>)> {
+  let (&codec_byte, rest) = src
+    .split_first()
+    .ok_or_else(|| PcoError::insufficient_data("missing validity codec byte"))?;
+  let (validity, valid_nums) = match codec_byte {
+    BITMAP_BYTE => {
+      let (validity, rest) = read_bitmap(rest)?;
+      let valid_nums = simple_decompress::<T>(rest)?;
+      let n_valid = validity.iter().filter(|&&is_valid| is_valid).count();
+      if valid_nums.len() != n_valid {
+        return Err(PcoError::corruption(format!(
+          "validity had {} valid numbers, but {} numbers were decompressed",
+          n_valid,
+          valid_nums.len(),
+        )));
+      }
+      (validity, valid_nums)
+    }
+    RUN_LENGTHS_BYTE => {
+      let (run_lengths, n_bytes) = simple_decompress_at::<u64>(rest, 0)?;
+      let valid_nums = simple_decompress::<T>(&rest[n_bytes..])?;
+      (
+        validity_from_run_lengths(&run_lengths, valid_nums.len())?,
+        valid_nums,
+      )
+    }
+    other => {
+      return Err(PcoError::corruption(format!(
+        "unknown validity codec byte {}",
+        other
+      )))
+    }
+  };
+
+  let mut valid_nums = valid_nums.into_iter();
+  let nums = validity
+    .iter()
+    .map(|&is_valid| {
+      if is_valid {
+        valid_nums.next().unwrap()
+      } else {
+        T::default()
+      }
+    })
+    .collect();
+  Ok((nums, validity))
+}
+
+/// Takes in compressed bytes (as produced by [`compress_optionals`] or
+/// [`simple_compress_nullable`]) and returns the optional numbers.
+///
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+pub fn decompress_optionals<T: Number>(src: &[u8]) -> PcoResult<Vec<Option<T>>> {
+  let (nums, validity) = simple_decompress_nullable::<T>(src)?;
+  Ok(
+    nums
+      .into_iter()
+      .zip(validity)
+      .map(|(num, is_valid)| is_valid.then_some(num))
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  use super::*;
+  use crate::errors::ErrorKind;
+
+  #[test]
+  fn test_run_lengths() -> PcoResult<()> {
+    for (validity, expected) in [
+      (vec![], vec![0]),
+      (vec![false], vec![0, 1]),
+      (
+        vec![true, true, false, true, false, false],
+        vec![2, 1, 1, 2],
+      ),
+    ] {
+      let run_lengths = run_lengths(&validity);
+      assert_eq!(run_lengths, expected);
+      let n_valid = validity.iter().filter(|&&is_valid| is_valid).count();
+      assert_eq!(
+        validity_from_run_lengths(&run_lengths, n_valid)?,
+        validity
+      );
+    }
+
+    // corrupt run lengths get caught before allocating
+    assert!(validity_from_run_lengths(&[1 << 40, 0], 5).is_err());
+    assert!(validity_from_run_lengths(&[5, u64::MAX, 0, u64::MAX], 5).is_err());
+    // a single huge null run
+    let err = validity_from_run_lengths(&[5, 1 << 40], 5).unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::Corruption));
+    Ok(())
+  }
+
+  #[test]
+  fn test_long_null_runs() -> PcoResult<()> {
+    // a sensor that drops out for long stretches
+    let n = 100_000;
+    let validity = (0..n).map(|i| (i / 5000) % 3 != 1).collect::<Vec<_>>();
+    let nums = (0..n).map(|i| (i % 1000) as i32).collect::<Vec<_>>();
+    let config = ChunkConfig::default();
+
+    let bitmap_compressed = simple_compress_nullable(
+      &nums,
+      &validity,
+      &config,
+      ValidityCodec::Bitmap,
+    )?;
+    let runs_compressed = simple_compress_nullable(
+      &nums,
+      &validity,
+      &config,
+      ValidityCodec::RunLengths,
+    )?;
+    assert!(runs_compressed.len() + n / 8 - 100 < bitmap_compressed.len());
+
+    for compressed in [bitmap_compressed, runs_compressed] {
+      let (decompressed, decompressed_validity) = simple_decompress_nullable::<i32>(&compressed)?;
+      assert_eq!(decompressed_validity, validity);
+      for i in 0..n {
+        let expected = if validity[i] { nums[i] } else { 0 };
+        assert_eq!(decompressed[i], expected, "at {}", i);
+      }
+    }
+
+    assert!(simple_compress_nullable(
+      &nums[1..],
+      &validity,
+      &config,
+      ValidityCodec::Bitmap
+    )
+    .is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_optionals() -> PcoResult<()> {
+    let config = ChunkConfig::default();
+    // randomly interleaved nulls favor a bitmap, and clustered ones favor run
+    // lengths
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let interleaved = (0..3000)
+      .map(|i| rng.gen_bool(0.5).then_some(i as f64 * 0.25))
+      .collect::<Vec<_>>();
+    let clustered = (0..3000)
+      .map(|i| (!(1000..2500).contains(&i)).then_some(i as f64 * 0.25))
+      .collect::<Vec<_>>();
+    for (nums, codec_byte) in [
+      (interleaved, Some(BITMAP_BYTE)),
+      (clustered, Some(RUN_LENGTHS_BYTE)),
+      (vec![None, Some(1.0), None], None),
+      (vec![], None),
+    ] {
+      let compressed = compress_optionals(&nums, &config)?;
+      if let Some(codec_byte) = codec_byte {
+        assert_eq!(compressed[0], codec_byte);
+      }
+      assert_eq!(
+        decompress_optionals::<f64>(&compressed)?,
+        nums
+      );
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,183 @@
+use std::sync::Mutex;
+use std::thread;
+
+use crate::chunk_config::ChunkConfig;
+use crate::data_types::Number;
+use crate::errors::PcoResult;
+use crate::standalone::compressor::FileCompressor;
+use crate::{wrapped, PagingSpec, CURRENT_FORMAT_VERSION, DEFAULT_FORMAT_VERSION};
+
+/// Controls how the parallel standalone APIs, like
+/// [`simple_compress_parallel`] and
+/// [`simple_decompress_parallel`][crate::standalone::simple_decompress_parallel],
+/// spread chunks across threads.
+///
+/// Each call spawns up to `n_threads` scoped threads of its own and joins
+/// them before returning; pco doesn't use a global thread pool.
+/// So if you already run an external pool, such as rayon's, either call
+/// these APIs from outside it with `n_threads` sized for the spare cores,
+/// or call them from within its tasks with `n_threads` set to 1 and let the
+/// pool parallelize across files instead.
+/// The results never depend on the parallelism.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParallelConfig {
+  /// The maximum count of threads to use (default: the available
+  /// parallelism, or 1 if unknown).
+  ///
+  /// 0 is treated as 1, and no more threads than chunks are used.
+  pub n_threads: usize,
+  /// The minimum count of chunks for which to use multiple threads; fewer
+  /// chunks are processed serially on the calling thread (default: 4).
+  ///
+  /// Small inputs with only a few chunks usually finish faster than the
+  /// threads can be spawned.
+  pub min_chunks_for_parallel: usize,
+}
+
+impl Default for ParallelConfig {
+  fn default() -> Self {
+    Self {
+      n_threads: thread::available_parallelism().map_or(1, |n| n.get()),
+      min_chunks_for_parallel: 4,
+    }
+  }
+}
+
+impl ParallelConfig {
+  /// Sets [`n_threads`][ParallelConfig::n_threads].
+  pub fn with_n_threads(mut self, n_threads: usize) -> Self {
+    self.n_threads = n_threads;
+    self
+  }
+
+  /// Sets [`min_chunks_for_parallel`][ParallelConfig::min_chunks_for_parallel].
+  pub fn with_min_chunks_for_parallel(mut self, min_chunks: usize) -> Self {
+    self.min_chunks_for_parallel = min_chunks;
+    self
+  }
+
+  fn n_threads_for(&self, n_jobs: usize) -> usize {
+    if n_jobs < self.min_chunks_for_parallel {
+      1
+    } else {
+      self.n_threads.clamp(1, n_jobs.max(1))
+    }
+  }
+}
+
+// Runs each job, using multiple threads if the config calls for it.
+pub(crate) fn run_jobs<J: Send, F: Fn(J) -> PcoResult<()> + Sync>(
+  jobs: Vec<J>,
+  config: &ParallelConfig,
+  run_job: F,
+) -> PcoResult<()> {
+  let n_threads = config.n_threads_for(jobs.len());
+  if n_threads == 1 {
+    return jobs.into_iter().try_for_each(run_job);
+  }
+
+  let jobs = Mutex::new(jobs.into_iter());
+  thread::scope(|scope| {
+    let handles = (0..n_threads)
+      .map(|_| {
+        scope.spawn(|| -> PcoResult<()> {
+          loop {
+            let job = jobs.lock().unwrap().next();
+            let Some(job) = job else {
+              return Ok(());
+            };
+            run_job(job)?;
+          }
+        })
+      })
+      .collect::<Vec<_>>();
+    handles
+      .into_iter()
+      .try_for_each(|handle| handle.join().unwrap())
+  })
+}
+
+/// Takes in a slice of numbers and an exact configuration and returns
+/// compressed bytes, compressing chunks in parallel.
+///
+/// Chunks are determined by the config's paging spec, as in
+/// [`simple_compress`][crate::standalone::simple_compress], and the output
+/// is identical to it.
+/// Each chunk is compressed independently, so inputs with many similarly
+/// sized chunks get the most speedup.
+///
+/// Will return an error if the compressor config is invalid.
+pub fn simple_compress_parallel<T: Number>(
+  nums: &[T],
+  config: &ChunkConfig,
+  parallel_config: &ParallelConfig,
+) -> PcoResult<Vec<u8>> {
+  wrapped::validate_config(config)?;
+  let mut file_compressor = FileCompressor::default()
+    .with_format_version(CURRENT_FORMAT_VERSION)
+    .with_n_hint(nums.len());
+  let chunk_config = |n: usize| config.clone().with_paging_spec(PagingSpec::Exact(vec![n]));
+
+  // here we use the paging spec to determine chunks; each chunk has 1 page
+  let n_per_page = config.paging_spec.n_per_page(nums.len())?;
+  let mut chunks = vec![Vec::new(); n_per_page.len()];
+  // As in `simple_compress`, the first chunk determines the format version,
+  // so we compress it before the others.
+  match n_per_page.first() {
+    Some(&page_n) => {
+      let chunk_config = chunk_config(page_n);
+      let chunk_compressor = file_compressor.chunk_compressor(&nums[..page_n], &chunk_config)?;
+      file_compressor =
+        file_compressor.with_oldest_format_version(&chunk_config, chunk_compressor.meta());
+      chunk_compressor.write_chunk(&mut chunks[0])?;
+    }
+    None => file_compressor = file_compressor.with_format_version(DEFAULT_FORMAT_VERSION),
+  }
+
+  let mut jobs = Vec::with_capacity(n_per_page.len());
+  let mut start = n_per_page.first().copied().unwrap_or(0);
+  for (&page_n, chunk_dst) in n_per_page.iter().zip(chunks.iter_mut()).skip(1) {
+    jobs.push((&nums[start..start + page_n], chunk_dst));
+    start += page_n;
+  }
+  run_jobs(
+    jobs,
+    parallel_config,
+    |(chunk_nums, chunk_dst): (&[T], &mut Vec<u8>)| {
+      let chunk_compressor =
+        file_compressor.chunk_compressor(chunk_nums, &chunk_config(chunk_nums.len()))?;
+      chunk_compressor.write_chunk(chunk_dst)?;
+      Ok(())
+    },
+  )?;
+
+  let mut res = file_compressor.write_header(Vec::new())?;
+  for chunk in chunks {
+    res.extend(chunk);
+  }
+  file_compressor.write_footer(res)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_n_threads_for() {
+    let config = ParallelConfig::default()
+      .with_n_threads(8)
+      .with_min_chunks_for_parallel(4);
+    assert_eq!(config.n_threads_for(0), 1);
+    assert_eq!(config.n_threads_for(3), 1);
+    assert_eq!(config.n_threads_for(4), 4);
+    assert_eq!(config.n_threads_for(100), 8);
+    assert_eq!(
+      config.clone().with_n_threads(0).n_threads_for(100),
+      1
+    );
+    assert_eq!(
+      config.with_min_chunks_for_parallel(0).n_threads_for(1),
+      1
+    );
+  }
+}
@@ -0,0 +1,255 @@
+use crate::chunk_config::ChunkConfig;
+use crate::constants::MAX_ENTRIES;
+use crate::data_types::{Latent, Number};
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::simple::{simple_compress, simple_decompress};
+use crate::standalone::transform_header::Transform;
+
+// the header: the count, period, phase, and sentinel
+const HEADER_SIZE: usize = 32;
+// Masked numbers take no space, so a corrupt header could claim an
+// arbitrarily large count. We cap how far the masked numbers can outnumber
+// the unmasked ones, which only matters for a period of 1.
+const MAX_EXCESS_MASKED: usize = MAX_ENTRIES;
+
+fn n_masked(n: usize, period: usize, phase: usize) -> usize {
+  n / period + (n % period > phase) as usize
+}
+
+fn check_n_masked(n: usize, n_masked: usize) -> bool {
+  n_masked <= (n - n_masked).saturating_add(MAX_EXCESS_MASKED)
+}
+
+/// A deterministic pattern of missing numbers, e.g. a sensor that drops
+/// every `period`th reading, where missing numbers hold a sentinel value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct PeriodicMask<T: Number> {
+  /// How often a number is missing.
+  pub period: usize,
+  /// The index of the first missing number, less than `period`.
+  pub phase: usize,
+  /// The value held by every missing number.
+  pub sentinel: T,
+}
+
+impl<T: Number> PeriodicMask<T> {
+  /// Creates a mask marking the numbers at `phase`, `phase + period`,
+  /// `phase + 2 * period`, and so forth as missing.
+  pub fn new(period: usize, phase: usize, sentinel: T) -> Self {
+    Self {
+      period,
+      phase,
+      sentinel,
+    }
+  }
+
+  fn validate(&self) -> PcoResult<()> {
+    if self.period == 0 || self.phase >= self.period {
+      return Err(PcoError::invalid_argument(format!(
+        "periodic mask phase must be less than its nonzero period (was {} and {})",
+        self.phase, self.period,
+      )));
+    }
+    Ok(())
+  }
+
+  fn is_masked(&self, i: usize) -> bool {
+    i % self.period == self.phase
+  }
+}
+
+/// Takes in numbers, a periodic mask of missing numbers, and an exact
+/// configuration and returns compressed bytes.
+///
+/// Only the numbers outside the mask get compressed, so the sentinel
+/// doesn't pollute their distribution, and the mask itself takes a few
+/// bytes regardless of the count of numbers.
+/// Decompress with [`simple_decompress_periodic_masked`], which reinserts
+/// the sentinel.
+///
+/// Will return an error if the mask or compressor config is invalid, if
+/// any masked number isn't bitwise equal to the sentinel, or if the masked
+/// numbers outnumber the unmasked ones by more than 2^24.
+pub fn simple_compress_periodic_masked<T: Number>(
+  nums: &[T],
+  mask: &PeriodicMask<T>,
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  mask.validate()?;
+  if !check_n_masked(
+    nums.len(),
+    n_masked(nums.len(), mask.period, mask.phase),
+  ) {
+    return Err(PcoError::invalid_argument(format!(
+      "masked numbers may outnumber unmasked ones by at most {}",
+      MAX_EXCESS_MASKED,
+    )));
+  }
+  let sentinel = mask.sentinel.to_latent_ordered();
+  let mut dense = Vec::with_capacity(nums.len() - nums.len() / mask.period);
+  for (i, &num) in nums.iter().enumerate() {
+    if !mask.is_masked(i) {
+      dense.push(num);
+    } else if num.to_latent_ordered() != sentinel {
+      return Err(PcoError::invalid_argument(format!(
+        "masked number at index {} was {} instead of the sentinel {}",
+        i, num, mask.sentinel,
+      )));
+    }
+  }
+
+  let mut res = Transform::PeriodicMask.header();
+  res.extend((nums.len() as u64).to_le_bytes());
+  res.extend((mask.period as u64).to_le_bytes());
+  res.extend((mask.phase as u64).to_le_bytes());
+  res.extend(sentinel.to_u64().to_le_bytes());
+  res.extend(simple_compress(&dense, config)?);
+  Ok(res)
+}
+
+/// Takes in compressed bytes (as produced by
+/// [`simple_compress_periodic_masked`]) and returns the numbers, with the
+/// sentinel at every masked position.
+///
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+pub fn simple_decompress_periodic_masked<T: Number>(src: &[u8]) -> PcoResult<Vec<T>> {
+  let src = Transform::PeriodicMask.read_header(src)?;
+  if src.len() < HEADER_SIZE {
+    return Err(PcoError::insufficient_data(
+      "periodic mask header was truncated",
+    ));
+  }
+  let read_usize = |i: usize| {
+    usize::try_from(u64::from_le_bytes(
+      src[i * 8..(i + 1) * 8].try_into().unwrap(),
+    ))
+    .map_err(|_| PcoError::corruption("periodic mask header value was too large"))
+  };
+  let n = read_usize(0)?;
+  let mask = PeriodicMask {
+    period: read_usize(1)?,
+    phase: read_usize(2)?,
+    sentinel: T::from_latent_ordered(T::L::from_u64(u64::from_le_bytes(
+      src[24..32].try_into().unwrap(),
+    ))),
+  };
+  mask
+    .validate()
+    .map_err(|_| PcoError::corruption("invalid periodic mask"))?;
+
+  let n_masked = n_masked(n, mask.period, mask.phase);
+  if !check_n_masked(n, n_masked) {
+    return Err(PcoError::corruption(format!(
+      "periodic mask claimed {} masked numbers out of {}",
+      n_masked, n,
+    )));
+  }
+  let dense = simple_decompress::<T>(&src[HEADER_SIZE..])?;
+  if dense.len() + n_masked != n {
+    return Err(PcoError::corruption(format!(
+      "expected {} unmasked numbers but found {}",
+      n - n_masked,
+      dense.len(),
+    )));
+  }
+
+  let mut dense = dense.into_iter();
+  Ok(
+    (0..n)
+      .map(|i| {
+        if mask.is_masked(i) {
+          mask.sentinel
+        } else {
+          dense.next().unwrap()
+        }
+      })
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::errors::ErrorKind;
+  use crate::standalone::transform_header::TRANSFORM_HEADER_SIZE;
+
+  #[test]
+  fn test_every_10th_missing() -> PcoResult<()> {
+    let sentinel = -9999.0;
+    let nums = (0..100_000)
+      .map(|i| {
+        if i % 10 == 3 {
+          sentinel
+        } else {
+          (i as f64 * 0.001).sin() * 50.0
+        }
+      })
+      .collect::<Vec<_>>();
+    let dense = nums
+      .iter()
+      .copied()
+      .filter(|&x| x != sentinel)
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default();
+
+    let mask = PeriodicMask::new(10, 3, sentinel);
+    let masked = simple_compress_periodic_masked(&nums, &mask, &config)?;
+    let dense_size = simple_compress(&dense, &config)?.len();
+    assert_eq!(
+      masked.len(),
+      dense_size + TRANSFORM_HEADER_SIZE + HEADER_SIZE
+    );
+    assert!(masked.len() < simple_compress(&nums, &config)?.len());
+    assert_eq!(
+      simple_decompress_periodic_masked::<f64>(&masked)?,
+      nums
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_periodic_mask_edge_cases() -> PcoResult<()> {
+    let config = ChunkConfig::default();
+    for n in [0, 1, 4, 5, 6, 17] {
+      let nums = (0..n)
+        .map(|i| if i % 5 == 4 { u16::MAX } else { i as u16 })
+        .collect::<Vec<_>>();
+      let mask = PeriodicMask::new(5, 4, u16::MAX);
+      let compressed = simple_compress_periodic_masked(&nums, &mask, &config)?;
+      assert_eq!(
+        simple_decompress_periodic_masked::<u16>(&compressed)?,
+        nums,
+        "n={}",
+        n
+      );
+    }
+
+    let nums = [1_u16, 2, 3];
+    for mask in [
+      PeriodicMask::new(0, 0, 0),
+      PeriodicMask::new(2, 2, 0),
+      PeriodicMask::new(2, 1, 7),
+    ] {
+      assert!(simple_compress_periodic_masked(&nums, &mask, &config).is_err());
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_corrupt_count() -> PcoResult<()> {
+    // a period of 1 masks every number, so nothing else bounds the count
+    let mut src = Transform::PeriodicMask.header();
+    for header_value in [1_u64 << 40, 1, 0, 0] {
+      src.extend(header_value.to_le_bytes());
+    }
+    src.extend(simple_compress::<u32>(
+      &[],
+      &ChunkConfig::default(),
+    )?);
+    let err = simple_decompress_periodic_masked::<u32>(&src).unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::Corruption));
+    Ok(())
+  }
+}
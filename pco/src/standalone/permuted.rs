@@ -0,0 +1,283 @@
+use crate::chunk_config::ChunkConfig;
+use crate::data_types::{Latent, Number};
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::simple::{simple_compress, simple_decompress, simple_decompress_at};
+use crate::standalone::transform_header::Transform;
+use crate::PagingSpec;
+
+const PLAIN_BYTE: u8 = 0;
+const PERMUTED_BYTE: u8 = 1;
+// We only try the permuted encoding if at most this fraction of the sorted
+// numbers don't follow their predecessor by the common step.
+const MAX_EXCEPTION_FRACTION: f64 = 0.05;
+// The permutation's Lehmer code digits shrink in range over the file, so we
+// split them into this many chunks to let each chunk's bins fit its range.
+const N_PERMUTATION_CHUNKS: usize = 16;
+
+// A Fenwick tree counting which ranks have been used so far.
+struct RankCounts {
+  tree: Vec<u32>,
+}
+
+impl RankCounts {
+  fn new(n: usize) -> Self {
+    Self {
+      tree: vec![0; n + 1],
+    }
+  }
+
+  fn mark(&mut self, rank: usize) {
+    let mut i = rank + 1;
+    while i < self.tree.len() {
+      self.tree[i] += 1;
+      i += i & i.wrapping_neg();
+    }
+  }
+
+  // the count of marked ranks less than `rank`
+  fn count_below(&self, rank: usize) -> usize {
+    let mut i = rank;
+    let mut res = 0;
+    while i > 0 {
+      res += self.tree[i] as usize;
+      i -= i & i.wrapping_neg();
+    }
+    res
+  }
+
+  // the smallest unmarked rank with `k` unmarked ranks below it
+  fn kth_unmarked(&self, mut k: usize) -> usize {
+    let n = self.tree.len() - 1;
+    let mut pos = 0;
+    let mut step = if n == 0 { 0 } else { 1 << n.ilog2() };
+    while step > 0 {
+      let next = pos + step;
+      if next <= n {
+        let unmarked = step - self.tree[next] as usize;
+        if unmarked <= k {
+          pos = next;
+          k -= unmarked;
+        }
+      }
+      step >>= 1;
+    }
+    pos
+  }
+}
+
+// Whether the sorted latents are nearly an arithmetic sequence with a
+// nonzero step.
+fn is_nearly_arithmetic<L: Latent>(sorted: &[L]) -> bool {
+  let n = sorted.len();
+  if n < 2 {
+    return false;
+  }
+  let step = (sorted[n - 1] - sorted[0]).to_u64() / (n as u64 - 1);
+  if step == 0 {
+    return false;
+  }
+  let n_exceptions = sorted
+    .windows(2)
+    .filter(|pair| (pair[1] - pair[0]).to_u64() != step)
+    .count();
+  n_exceptions as f64 <= MAX_EXCEPTION_FRACTION * (n - 1) as f64
+}
+
+fn compress_permuted<T: Number>(
+  sorted: &[T],
+  order: &[usize],
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  let n = order.len();
+  let mut ranks = vec![0; n];
+  for (rank, &idx) in order.iter().enumerate() {
+    ranks[idx] = rank;
+  }
+  // each digit is how many still-unused ranks are below the number's rank
+  let mut used = RankCounts::new(n);
+  let digits = ranks
+    .iter()
+    .map(|&rank| {
+      let digit = rank - used.count_below(rank);
+      used.mark(rank);
+      digit as u64
+    })
+    .collect::<Vec<_>>();
+  let digits_config = config.clone().with_paging_spec(PagingSpec::EqualPagesUpTo(
+    n.div_ceil(N_PERMUTATION_CHUNKS).max(1),
+  ));
+
+  let mut res = Transform::Permuted.header();
+  res.push(PERMUTED_BYTE);
+  res.extend(simple_compress(sorted, config)?);
+  res.extend(simple_compress(&digits, &digits_config)?);
+  Ok(res)
+}
+
+/// Takes in numbers and an exact configuration and returns compressed bytes,
+/// possibly encoding them as sorted numbers and a permutation.
+///
+/// This is an experimental preprocessing step for numbers that are a
+/// shuffled, nearly arithmetic sequence, e.g. a dense range of ids in random
+/// order.
+/// The sorted numbers compress to almost nothing, and the permutation is
+/// stored as its Lehmer code, whose digits need fewer bits than the numbers
+/// themselves.
+/// It is only tried when at most 5% of the sorted numbers don't follow their
+/// predecessor by a common nonzero step and the numbers aren't already
+/// sorted, and only used when its result is smaller, so expect up to twice
+/// the compression time of [`simple_compress`].
+///
+/// Decompress with [`simple_decompress_permuted`].
+/// Will return an error if the compressor config is invalid.
+pub fn simple_compress_permuted<T: Number>(nums: &[T], config: &ChunkConfig) -> PcoResult<Vec<u8>> {
+  let mut res = Transform::Permuted.header();
+  res.push(PLAIN_BYTE);
+  res.extend(simple_compress(nums, config)?);
+
+  let latents = nums
+    .iter()
+    .map(|num| num.to_latent_ordered())
+    .collect::<Vec<_>>();
+  if latents.windows(2).all(|pair| pair[0] <= pair[1]) {
+    return Ok(res);
+  }
+  let mut order = (0..nums.len()).collect::<Vec<_>>();
+  order.sort_unstable_by_key(|&i| (latents[i], i));
+  let sorted_latents = order.iter().map(|&i| latents[i]).collect::<Vec<_>>();
+  if is_nearly_arithmetic(&sorted_latents) {
+    let sorted = order.iter().map(|&i| nums[i]).collect::<Vec<_>>();
+    let permuted = compress_permuted(&sorted, &order, config)?;
+    if permuted.len() < res.len() {
+      return Ok(permuted);
+    }
+  }
+  Ok(res)
+}
+
+fn decompress_permuted<T: Number>(src: &[u8]) -> PcoResult<Vec<T>> {
+  let (sorted, n_bytes) = simple_decompress_at::<T>(src, 0)?;
+  let digits = simple_decompress::<u64>(&src[n_bytes..])?;
+  let n = sorted.len();
+  if digits.len() != n {
+    return Err(PcoError::corruption(format!(
+      "found {} sorted numbers but {} permutation digits",
+      n,
+      digits.len(),
+    )));
+  }
+
+  let mut used = RankCounts::new(n);
+  let mut res = Vec::with_capacity(n);
+  for (i, &digit) in digits.iter().enumerate() {
+    if digit >= (n - i) as u64 {
+      return Err(PcoError::corruption(format!(
+        "permutation digit {} is out of range for position {} of {}",
+        digit, i, n,
+      )));
+    }
+    let rank = used.kth_unmarked(digit as usize);
+    used.mark(rank);
+    res.push(sorted[rank]);
+  }
+  Ok(res)
+}
+
+/// Takes in compressed bytes (as produced by [`simple_compress_permuted`])
+/// and returns the numbers.
+///
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+pub fn simple_decompress_permuted<T: Number>(src: &[u8]) -> PcoResult<Vec<T>> {
+  let src = Transform::Permuted.read_header(src)?;
+  let (&transform_byte, rest) = src
+    .split_first()
+    .ok_or_else(|| PcoError::insufficient_data("missing permuted byte"))?;
+  match transform_byte {
+    PLAIN_BYTE => simple_decompress(rest),
+    PERMUTED_BYTE => decompress_permuted(rest),
+    other => Err(PcoError::corruption(format!(
+      "unknown permuted byte {}",
+      other
+    ))),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::seq::SliceRandom;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  use super::*;
+  use crate::standalone::transform_header::TRANSFORM_HEADER_SIZE;
+
+  fn shuffled<T: Clone>(nums: &[T]) -> Vec<T> {
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let mut res = nums.to_vec();
+    res.shuffle(&mut rng);
+    res
+  }
+
+  #[test]
+  fn test_shuffled_dense_range() -> PcoResult<()> {
+    let nums = shuffled(&(0..1_000_000_u32).collect::<Vec<_>>());
+    let config = ChunkConfig::default();
+
+    let plain = simple_compress(&nums, &config)?;
+    let permuted = simple_compress_permuted(&nums, &config)?;
+    assert_eq!(
+      permuted[TRANSFORM_HEADER_SIZE],
+      PERMUTED_BYTE
+    );
+    // a random permutation needs about log2(n) - 1.44 bits per number
+    assert!((permuted.len() as f64) < 0.97 * plain.len() as f64);
+    assert_eq!(
+      simple_decompress_permuted::<u32>(&permuted)?,
+      nums
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_permuted_roundtrips() -> PcoResult<()> {
+    let config = ChunkConfig::default();
+    for nums in [
+      vec![],
+      vec![7],
+      vec![3, 1, 2],
+      vec![5, 5, -1, 5, i64::MIN, i64::MAX],
+      shuffled(&(0..5000).map(|i| 10 * i - 7).collect::<Vec<_>>()),
+    ] {
+      let mut order = (0..nums.len()).collect::<Vec<_>>();
+      order.sort_unstable_by_key(|&i| (nums[i], i));
+      let sorted = order.iter().map(|&i| nums[i]).collect::<Vec<_>>();
+      let compressed = compress_permuted(&sorted, &order, &config)?;
+      assert_eq!(
+        simple_decompress_permuted::<i64>(&compressed)?,
+        nums
+      );
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_conservative_detection() -> PcoResult<()> {
+    let config = ChunkConfig::default();
+    // already sorted
+    let sorted = (0..10_000).collect::<Vec<u32>>();
+    // constant
+    let constant = vec![3_u32; 10_000];
+    // shuffled, but far from arithmetic
+    let squares = shuffled(&(0..10_000_u32).map(|i| i * i).collect::<Vec<_>>());
+    for nums in [sorted, constant, squares] {
+      let compressed = simple_compress_permuted(&nums, &config)?;
+      assert_eq!(compressed[TRANSFORM_HEADER_SIZE], PLAIN_BYTE);
+      assert_eq!(
+        simple_decompress_permuted::<u32>(&compressed)?,
+        nums
+      );
+    }
+    Ok(())
+  }
+}
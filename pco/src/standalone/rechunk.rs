@@ -0,0 +1,223 @@
+use crate::chunk_config::ChunkConfig;
+use crate::constants::MAX_ENTRIES;
+use crate::data_types::Number;
+use crate::errors::{PcoError, PcoResult};
+use crate::metadata::format_version::FormatVersion;
+use crate::standalone::compressor::FileCompressor;
+use crate::standalone::decompressor::{FileDecompressor, MaybeChunkDecompressor};
+use crate::{wrapped, PagingSpec, DEFAULT_FORMAT_VERSION};
+use std::cmp::max;
+
+/// An iterator over the bytes of a rechunked file, as returned by
+/// [`rechunk`].
+pub struct RechunkIter<'a, T: Number> {
+  fd: FileDecompressor,
+  fc: FileCompressor,
+  src: &'a [u8],
+  config: ChunkConfig,
+  new_chunk_size: usize,
+  // decompressed numbers not yet written to a new chunk
+  buffer: Vec<T>,
+  wrote_header: bool,
+  input_finished: bool,
+  finished: bool,
+}
+
+impl<T: Number> RechunkIter<'_, T> {
+  // decompresses input chunks until the buffer can fill a new chunk or the
+  // input runs out
+  fn fill_buffer(&mut self) -> PcoResult<()> {
+    while self.buffer.len() < self.new_chunk_size && !self.input_finished {
+      match self.fd.chunk_decompressor::<T, _>(self.src)? {
+        MaybeChunkDecompressor::Some(mut cd) => {
+          cd.decompress_remaining_extend(&mut self.buffer)?;
+          self.src = cd.into_src();
+        }
+        MaybeChunkDecompressor::EndOfData(_) => self.input_finished = true,
+      }
+    }
+    Ok(())
+  }
+
+  fn next_bytes(&mut self) -> PcoResult<Option<Vec<u8>>> {
+    if !self.wrote_header {
+      self.wrote_header = true;
+      return Ok(Some(self.fc.write_header(Vec::new())?));
+    }
+
+    self.fill_buffer()?;
+    if !self.buffer.is_empty() {
+      let n = self.buffer.len().min(self.new_chunk_size);
+      let config = self
+        .config
+        .clone()
+        .with_paging_spec(PagingSpec::Exact(vec![n]));
+      let cc = self.fc.chunk_compressor(&self.buffer[..n], &config)?;
+      self.buffer.drain(..n);
+      return Ok(Some(cc.write_chunk(Vec::new())?));
+    }
+
+    self.finished = true;
+    Ok(Some(self.fc.write_footer(Vec::new())?))
+  }
+}
+
+impl<T: Number> Iterator for RechunkIter<'_, T> {
+  type Item = PcoResult<Vec<u8>>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.finished {
+      return None;
+    }
+
+    let res = self.next_bytes();
+    if res.is_err() {
+      self.finished = true;
+    }
+    res.transpose()
+  }
+}
+
+/// Takes in compressed bytes of `T`s and returns an iterator over the bytes
+/// of an equivalent file, recompressed into chunks of `new_chunk_size`
+/// numbers with the given config.
+///
+/// This is useful for files chunked for a different workload, e.g. with
+/// small chunks for random access when large ones would compress better.
+/// The input is decompressed lazily, one chunk at a time, so at most
+/// about one input chunk and one new chunk of numbers are held in memory.
+/// The first item is the new file's header and the last is its footer;
+/// concatenated, the items form an ordinary .pco file. Every chunk but the
+/// last has exactly `new_chunk_size` numbers.
+///
+/// The output keeps the input's
+/// [uniform number type][FileDecompressor::uniform_type], if any, but not
+/// its manifest, chunk annotations, or index footer.
+/// The config's paging spec is ignored.
+/// Will return an error if the config is invalid or `new_chunk_size` is 0
+/// or too large, and the iterator will yield an error and then end if
+/// there are any compatibility, corruption, or insufficient data issues,
+/// e.g. if the input's number type isn't `T`.
+pub fn rechunk<'a, T: Number>(
+  src: &'a [u8],
+  new_chunk_size: usize,
+  config: &ChunkConfig,
+) -> PcoResult<RechunkIter<'a, T>> {
+  wrapped::validate_config(config)?;
+  if new_chunk_size == 0 || new_chunk_size > MAX_ENTRIES {
+    return Err(PcoError::invalid_argument(format!(
+      "new chunk size must be between 1 and {}; got {}",
+      MAX_ENTRIES, new_chunk_size,
+    )));
+  }
+
+  let (fd, src) = FileDecompressor::new(src)?;
+  // We write the header before compressing any chunks, so we keep the
+  // input's format version, or a newer one if the config requires it.
+  let format_version = max(
+    fd.format_version().max(DEFAULT_FORMAT_VERSION),
+    FormatVersion::oldest_supporting_spec(config.delta_spec).0,
+  );
+  let fc = FileCompressor::default()
+    .with_format_version(format_version)
+    .with_n_hint(fd.n_hint())
+    .with_uniform_type(fd.uniform_type());
+  Ok(RechunkIter {
+    fd,
+    fc,
+    src,
+    config: config.clone(),
+    new_chunk_size,
+    buffer: Vec::new(),
+    wrote_header: false,
+    input_finished: false,
+    finished: false,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::data_types::NumberType;
+  use crate::standalone::{simple_compress, simple_decompress};
+
+  fn concat(iter: RechunkIter<'_, impl Number>) -> PcoResult<Vec<u8>> {
+    let mut res = Vec::new();
+    for bytes in iter {
+      res.extend(bytes?);
+    }
+    Ok(res)
+  }
+
+  fn chunk_ns(src: &[u8]) -> PcoResult<Vec<usize>> {
+    let (fd, mut src) = FileDecompressor::new(src)?;
+    let mut res = Vec::new();
+    while let MaybeChunkDecompressor::Some(mut cd) = fd.chunk_decompressor::<i32, _>(src)? {
+      res.push(cd.n());
+      let mut nums = Vec::new();
+      cd.decompress_remaining_extend(&mut nums)?;
+      src = cd.into_src();
+    }
+    Ok(res)
+  }
+
+  #[test]
+  fn test_rechunk_roundtrip() -> PcoResult<()> {
+    let nums = (0..10_000)
+      .map(|i| (i * 7) % 1013 - 500)
+      .collect::<Vec<i32>>();
+    let config = ChunkConfig::default().with_paging_spec(PagingSpec::Exact(vec![
+      100, 2500, 7, 3000, 4393,
+    ]));
+    let src = simple_compress(&nums, &config)?;
+
+    for (new_chunk_size, expected_ns) in [
+      (4096, vec![4096, 4096, 1808]),
+      (10_000, vec![10_000]),
+      (1 << 20, vec![10_000]),
+      (3000, vec![3000, 3000, 3000, 1000]),
+    ] {
+      let new_config = ChunkConfig::default().with_compression_level(4);
+      let rechunked = concat(rechunk::<i32>(
+        &src,
+        new_chunk_size,
+        &new_config,
+      )?)?;
+      assert_eq!(simple_decompress::<i32>(&rechunked)?, nums);
+      assert_eq!(chunk_ns(&rechunked)?, expected_ns);
+    }
+
+    // an empty file stays empty
+    let src = simple_compress::<i32>(&[], &ChunkConfig::default())?;
+    let iter = rechunk::<i32>(&src, 100, &ChunkConfig::default())?;
+    assert_eq!(iter.count(), 2);
+    Ok(())
+  }
+
+  #[test]
+  fn test_rechunk_keeps_uniform_type() -> PcoResult<()> {
+    let fc = FileCompressor::default().with_uniform_type(Some(NumberType::U64));
+    let src = fc.write_footer(fc.write_header(Vec::new())?)?;
+    let rechunked = concat(rechunk::<u64>(
+      &src,
+      100,
+      &ChunkConfig::default(),
+    )?)?;
+    let (fd, _) = FileDecompressor::new(rechunked.as_slice())?;
+    assert_eq!(fd.uniform_type(), Some(NumberType::U64));
+    Ok(())
+  }
+
+  #[test]
+  fn test_rechunk_errors() -> PcoResult<()> {
+    let src = simple_compress(&[1_u32, 2, 3], &ChunkConfig::default())?;
+    assert!(rechunk::<u32>(&src, 0, &ChunkConfig::default()).is_err());
+
+    // the wrong number type shows up as soon as a chunk is read
+    let mut iter = rechunk::<f32>(&src, 100, &ChunkConfig::default())?;
+    assert!(iter.next().unwrap().is_ok());
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+    Ok(())
+  }
+}
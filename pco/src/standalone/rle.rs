@@ -0,0 +1,135 @@
+use crate::chunk_config::ChunkConfig;
+use crate::data_types::Number;
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::simple::{simple_compress, simple_decompress_at};
+use crate::standalone::transform_header::Transform;
+
+/// Takes in runs of (value, run length) pairs and an exact configuration and
+/// returns compressed bytes.
+///
+/// This compresses highly repetitive data without expanding it: the values
+/// and the run lengths are stored as two standalone files, one after the
+/// other, so the cost scales with the count of runs rather than the count of
+/// numbers they represent.
+/// Run lengths are compressed at the config's compression level.
+/// Adjacent runs may have the same value, and runs of length 0 are allowed;
+/// both are kept as is.
+///
+/// Decompress with [`decompress_rle_runs`] or [`decompress_rle`].
+/// Will return an error if the compressor config is invalid.
+pub fn compress_rle<T: Number>(runs: &[(T, u64)], config: &ChunkConfig) -> PcoResult<Vec<u8>> {
+  let values = runs.iter().map(|&(value, _)| value).collect::<Vec<_>>();
+  let run_lengths = runs
+    .iter()
+    .map(|&(_, run_length)| run_length)
+    .collect::<Vec<_>>();
+  let run_lengths_config = ChunkConfig::default().with_compression_level(config.compression_level);
+
+  let mut res = Transform::Rle.header();
+  res.extend(simple_compress(&values, config)?);
+  res.extend(simple_compress(
+    &run_lengths,
+    &run_lengths_config,
+  )?);
+  Ok(res)
+}
+
+/// Takes in compressed bytes (as produced by [`compress_rle`]) and returns
+/// the (value, run length) pairs.
+///
+/// Will return an error if there are any compatibility, corruption, or
+/// insufficient data issues.
+pub fn decompress_rle_runs<T: Number>(src: &[u8]) -> PcoResult<Vec<(T, u64)>> {
+  let src = Transform::Rle.read_header(src)?;
+  let (values, values_size) = simple_decompress_at::<T>(src, 0)?;
+  let (run_lengths, _) = simple_decompress_at::<u64>(src, values_size)?;
+  if values.len() != run_lengths.len() {
+    return Err(PcoError::corruption(format!(
+      "found {} run values but {} run lengths",
+      values.len(),
+      run_lengths.len(),
+    )));
+  }
+  Ok(values.into_iter().zip(run_lengths).collect())
+}
+
+/// Takes in compressed bytes (as produced by [`compress_rle`]) and returns
+/// the numbers with every run expanded.
+///
+/// This materializes every number; use [`decompress_rle_runs`] to avoid
+/// that.
+/// Will return an error if there are any compatibility, corruption, or
+/// insufficient data issues.
+pub fn decompress_rle<T: Number>(src: &[u8]) -> PcoResult<Vec<T>> {
+  let runs = decompress_rle_runs::<T>(src)?;
+  let n = runs
+    .iter()
+    .try_fold(0_usize, |n, &(_, run_length)| {
+      n.checked_add(usize::try_from(run_length).ok()?)
+    })
+    .ok_or_else(|| PcoError::corruption("run lengths overflowed"))?;
+  let mut res = Vec::with_capacity(n);
+  for (value, run_length) in runs {
+    res.resize(res.len() + run_length as usize, value);
+  }
+  Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_rle_roundtrip() -> PcoResult<()> {
+    let runs = vec![(1.5_f32, 3), (-2.0, 1), (-2.0, 2), (7.0, 0), (f32::MAX, 5)];
+    let config = ChunkConfig::default();
+    let compressed = compress_rle(&runs, &config)?;
+    assert_eq!(
+      decompress_rle_runs::<f32>(&compressed)?,
+      runs
+    );
+    assert_eq!(
+      decompress_rle::<f32>(&compressed)?,
+      [vec![1.5; 3], vec![-2.0; 3], vec![f32::MAX; 5]].concat(),
+    );
+
+    let compressed = compress_rle::<i64>(&[], &config)?;
+    assert_eq!(
+      decompress_rle_runs::<i64>(&compressed)?,
+      vec![]
+    );
+    assert_eq!(decompress_rle::<i64>(&compressed)?, vec![]);
+    Ok(())
+  }
+
+  #[test]
+  fn test_rle_long_runs() -> PcoResult<()> {
+    // 10 values spanning 10^8 numbers
+    let runs = (0..10_u64)
+      .map(|i| ((i * 37 % 10) as i32 - 5, 10_000_000 + i * 3))
+      .collect::<Vec<_>>();
+    assert!(runs.iter().map(|&(_, run_length)| run_length).sum::<u64>() > 100_000_000);
+    let compressed = compress_rle(&runs, &ChunkConfig::default())?;
+    assert!(compressed.len() < 200);
+    assert_eq!(
+      decompress_rle_runs::<i32>(&compressed)?,
+      runs
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_rle_corruption() -> PcoResult<()> {
+    // two values but one run length
+    let config = ChunkConfig::default();
+    let mut src = Transform::Rle.header();
+    src.extend(simple_compress(&[1_u32, 2], &config)?);
+    src.extend(simple_compress(&[4_u64], &config)?);
+    assert!(decompress_rle_runs::<u32>(&src).is_err());
+
+    // truncated run lengths
+    let compressed = compress_rle(&[(1_u32, 4)], &config)?;
+    assert!(decompress_rle_runs::<u32>(&compressed[..compressed.len() - 1]).is_err());
+    Ok(())
+  }
+}
@@ -0,0 +1,135 @@
+use crate::chunk_config::ChunkConfig;
+use crate::data_types::Number;
+use crate::errors::PcoResult;
+use crate::standalone::decompressor::{FileDecompressor, MaybeChunkDecompressor};
+use crate::standalone::simple::compress_to_writer;
+
+/// Buffers for the output of compression and decompression that can be
+/// reused across calls to [`compress_with_scratch`] and
+/// [`decompress_with_scratch`].
+///
+/// Each call clears the relevant buffer and only grows it when the output no
+/// longer fits, so in steady state the output costs no allocations.
+/// The working memory pco needs while compressing or decompressing each
+/// chunk, e.g. for bins and entropy coding tables, is still allocated
+/// internally; it is proportional to a chunk's metadata rather than its
+/// count of numbers.
+///
+/// A scratch holds no references to shared state, so it is `Send`, but
+/// calls take it mutably: use one scratch per thread.
+#[derive(Clone, Debug, Default)]
+pub struct Scratch<T: Number> {
+  compressed: Vec<u8>,
+  nums: Vec<T>,
+}
+
+impl<T: Number> Scratch<T> {
+  /// Creates a scratch with empty buffers.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Creates a scratch with room for `n_bytes` of compressed output and
+  /// `n_nums` decompressed numbers without allocating.
+  pub fn with_capacity(n_bytes: usize, n_nums: usize) -> Self {
+    Self {
+      compressed: Vec::with_capacity(n_bytes),
+      nums: Vec::with_capacity(n_nums),
+    }
+  }
+}
+
+/// Takes in a slice of numbers and an exact configuration and returns
+/// compressed bytes stored in the scratch.
+///
+/// The bytes are exactly those of
+/// [`simple_compress`][crate::standalone::simple_compress], and remain valid
+/// until the scratch is next used for compression.
+/// Will return an error if the compressor config is invalid.
+pub fn compress_with_scratch<'a, T: Number>(
+  nums: &[T],
+  config: &ChunkConfig,
+  scratch: &'a mut Scratch<T>,
+) -> PcoResult<&'a [u8]> {
+  scratch.compressed.clear();
+  compress_to_writer(nums, config, &mut scratch.compressed)?;
+  Ok(&scratch.compressed)
+}
+
+/// Takes in compressed bytes and returns the numbers stored in the scratch.
+///
+/// The numbers are exactly those of
+/// [`simple_decompress`][crate::standalone::simple_decompress], and remain
+/// valid until the scratch is next used for decompression.
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+pub fn decompress_with_scratch<'a, T: Number>(
+  src: &[u8],
+  scratch: &'a mut Scratch<T>,
+) -> PcoResult<&'a [T]> {
+  scratch.nums.clear();
+  let (file_decompressor, mut src) = FileDecompressor::new(src)?;
+  scratch.nums.reserve(file_decompressor.n_hint());
+  while let MaybeChunkDecompressor::Some(mut chunk_decompressor) =
+    file_decompressor.chunk_decompressor(src)?
+  {
+    chunk_decompressor.decompress_remaining_extend(&mut scratch.nums)?;
+    src = chunk_decompressor.into_src();
+  }
+  Ok(&scratch.nums)
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  use super::*;
+  use crate::standalone::{simple_compress, simple_decompress};
+  use crate::PagingSpec;
+
+  #[test]
+  fn test_matches_simple_api() -> PcoResult<()> {
+    let mut scratch = Scratch::new();
+    let mut decompress_scratch = Scratch::<i32>::new();
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    for (n, max_page_n) in [(10_000, 3000), (500, 100), (0, 100), (20_000, 20_000)] {
+      let nums = (0..n)
+        .map(|_| rng.gen_range(0..1 << 16))
+        .collect::<Vec<_>>();
+      let config = ChunkConfig::default().with_paging_spec(PagingSpec::EqualPagesUpTo(max_page_n));
+      let expected = simple_compress(&nums, &config)?;
+      let compressed = compress_with_scratch(&nums, &config, &mut scratch)?.to_vec();
+      assert_eq!(compressed, expected);
+      assert_eq!(simple_decompress::<i32>(&compressed)?, nums);
+      assert_eq!(
+        decompress_with_scratch(&compressed, &mut decompress_scratch)?,
+        nums
+      );
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_reuses_buffers() -> PcoResult<()> {
+    let nums = (0..5000).collect::<Vec<u32>>();
+    let config = ChunkConfig::default();
+    let mut scratch = Scratch::with_capacity(1 << 16, nums.len());
+    let compressed = compress_with_scratch(&nums, &config, &mut scratch)?.to_vec();
+    let compressed_ptr = scratch.compressed.as_ptr();
+    decompress_with_scratch(&compressed, &mut scratch)?;
+    let nums_ptr = scratch.nums.as_ptr();
+
+    for _ in 0..3 {
+      compress_with_scratch(&nums, &config, &mut scratch)?;
+      assert_eq!(scratch.compressed.as_ptr(), compressed_ptr);
+      assert_eq!(
+        decompress_with_scratch(&compressed, &mut scratch)?,
+        nums
+      );
+      assert_eq!(scratch.nums.as_ptr(), nums_ptr);
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,251 @@
+use crate::chunk_config::ChunkConfig;
+use crate::constants::MAX_ENTRIES;
+use crate::data_types::{Latent, Number};
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::simple::{simple_compress, simple_decompress, simple_decompress_at};
+use crate::standalone::transform_header::Transform;
+
+const PLAIN_BYTE: u8 = 0;
+const SEQUENTIAL_BYTE: u8 = 1;
+// the sequential header: the count and the first number's latent
+const SEQUENTIAL_HEADER_SIZE: usize = 16;
+// We fall back to plain compression if more than this fraction of numbers
+// don't follow their predecessor by exactly 1.
+const MAX_EXCEPTION_FRACTION: f64 = 0.05;
+// A sequence without exceptions takes no space no matter how long it is, so
+// we only try sequences up to this count, letting decompression reject
+// corrupt counts before allocating.
+const MAX_SEQUENTIAL_N: usize = MAX_ENTRIES;
+
+// Returns the indices at which the numbers don't increase by exactly 1, and
+// how far each one is from that prediction.
+fn find_exceptions<T: Number>(nums: &[T]) -> (Vec<u64>, Vec<u64>) {
+  let mut indices = Vec::new();
+  let mut corrections = Vec::new();
+  for (i, pair) in nums.windows(2).enumerate() {
+    let predicted = pair[0].to_latent_ordered().wrapping_add(T::L::ONE);
+    let latent = pair[1].to_latent_ordered();
+    if latent != predicted {
+      indices.push(i as u64 + 1);
+      corrections.push(latent.wrapping_sub(predicted).to_u64());
+    }
+  }
+  (indices, corrections)
+}
+
+fn compress_sequential<T: Number>(
+  nums: &[T],
+  indices: &[u64],
+  corrections: &[u64],
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  let base = nums
+    .first()
+    .map_or(0, |num| num.to_latent_ordered().to_u64());
+  let mut res = Transform::Sequential.header();
+  res.push(SEQUENTIAL_BYTE);
+  res.extend((nums.len() as u64).to_le_bytes());
+  res.extend(base.to_le_bytes());
+  res.extend(simple_compress(indices, config)?);
+  res.extend(simple_compress(corrections, config)?);
+  Ok(res)
+}
+
+/// Takes in numbers and an exact configuration and returns compressed bytes,
+/// possibly encoding them as a sequence with exceptions.
+///
+/// This is an experimental preprocessing step for mostly sequential
+/// integers, e.g. primary keys with occasional gaps.
+/// The sequence is stored as its first number, assuming each subsequent
+/// number is 1 more than the last, along with only the indices and values
+/// where that assumption fails.
+/// It is only tried for up to 2^24 numbers when at most 5% of them are
+/// exceptions, and only used
+/// when its result is smaller, so expect up to twice the compression time of
+/// [`simple_compress`].
+///
+/// Decompress with [`simple_decompress_sequential`].
+/// Will return an error if the compressor config is invalid.
+pub fn simple_compress_sequential<T: Number>(
+  nums: &[T],
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  let mut res = Transform::Sequential.header();
+  res.push(PLAIN_BYTE);
+  res.extend(simple_compress(nums, config)?);
+
+  let (indices, corrections) = find_exceptions(nums);
+  if nums.len() <= MAX_SEQUENTIAL_N
+    && indices.len() as f64 <= MAX_EXCEPTION_FRACTION * nums.len() as f64
+  {
+    let sequential = compress_sequential(nums, &indices, &corrections, config)?;
+    if sequential.len() < res.len() {
+      return Ok(sequential);
+    }
+  }
+  Ok(res)
+}
+
+fn decompress_sequential<T: Number>(src: &[u8]) -> PcoResult<Vec<T>> {
+  if src.len() < SEQUENTIAL_HEADER_SIZE {
+    return Err(PcoError::insufficient_data(
+      "sequential header was truncated",
+    ));
+  }
+  let read_u64 = |i: usize| u64::from_le_bytes(src[i * 8..(i + 1) * 8].try_into().unwrap());
+  let n = usize::try_from(read_u64(0))
+    .ok()
+    .filter(|&n| n <= MAX_SEQUENTIAL_N)
+    .ok_or_else(|| PcoError::corruption("sequential count was too large"))?;
+  let base = T::L::from_u64(read_u64(1));
+
+  let (indices, n_bytes) = simple_decompress_at::<u64>(src, SEQUENTIAL_HEADER_SIZE)?;
+  let corrections = simple_decompress::<u64>(&src[SEQUENTIAL_HEADER_SIZE + n_bytes..])?;
+  if indices.len() != corrections.len() {
+    return Err(PcoError::corruption(format!(
+      "found {} sequential exception indices but {} corrections",
+      indices.len(),
+      corrections.len(),
+    )));
+  }
+  if indices.last().is_some_and(|&idx| idx >= n as u64) {
+    return Err(PcoError::corruption(
+      "sequential exception indices were out of range",
+    ));
+  }
+  if n == 0 {
+    return Ok(Vec::new());
+  }
+
+  let mut res = Vec::with_capacity(n);
+  let mut latent = base;
+  let mut exceptions = indices.iter().zip(&corrections).peekable();
+  res.push(T::from_latent_ordered(latent));
+  for i in 1..n {
+    latent = latent.wrapping_add(T::L::ONE);
+    if let Some((_, &correction)) = exceptions.next_if(|(&idx, _)| idx == i as u64) {
+      latent = latent.wrapping_add(T::L::from_u64(correction));
+    }
+    res.push(T::from_latent_ordered(latent));
+  }
+  if exceptions.next().is_some() {
+    return Err(PcoError::corruption(
+      "sequential exception indices were out of order",
+    ));
+  }
+  Ok(res)
+}
+
+/// Takes in compressed bytes (as produced by [`simple_compress_sequential`])
+/// and returns the numbers.
+///
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+pub fn simple_decompress_sequential<T: Number>(src: &[u8]) -> PcoResult<Vec<T>> {
+  let src = Transform::Sequential.read_header(src)?;
+  let (&transform_byte, rest) = src
+    .split_first()
+    .ok_or_else(|| PcoError::insufficient_data("missing sequential byte"))?;
+  match transform_byte {
+    PLAIN_BYTE => simple_decompress(rest),
+    SEQUENTIAL_BYTE => decompress_sequential(rest),
+    other => Err(PcoError::corruption(format!(
+      "unknown sequential byte {}",
+      other
+    ))),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  use super::*;
+  use crate::errors::ErrorKind;
+  use crate::standalone::transform_header::TRANSFORM_HEADER_SIZE;
+
+  #[test]
+  fn test_ids_with_gaps() -> PcoResult<()> {
+    // IDs from 1 with a gap before 0.1% of them
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let mut id = 0_u64;
+    let nums = (0..1_000_000)
+      .map(|_| {
+        id += if rng.gen_ratio(1, 1000) {
+          rng.gen_range(2..18)
+        } else {
+          1
+        };
+        id
+      })
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default();
+
+    let plain = simple_compress(&nums, &config)?;
+    let sequential = simple_compress_sequential(&nums, &config)?;
+    assert_eq!(
+      sequential[TRANSFORM_HEADER_SIZE],
+      SEQUENTIAL_BYTE
+    );
+    assert!(sequential.len() < plain.len());
+    // a little over 2 bytes per gap
+    assert!(sequential.len() < 2500);
+    assert_eq!(
+      simple_decompress_sequential::<u64>(&sequential)?,
+      nums
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_sequential_roundtrips() -> PcoResult<()> {
+    let config = ChunkConfig::default();
+    for nums in [
+      vec![],
+      vec![7],
+      vec![i32::MAX - 1, i32::MAX, i32::MIN, i32::MIN + 1],
+      vec![5, 6, 4, 5, 6, 7, -100],
+    ] {
+      let (indices, corrections) = find_exceptions(&nums);
+      let compressed = compress_sequential(&nums, &indices, &corrections, &config)?;
+      assert_eq!(
+        simple_decompress_sequential::<i32>(&compressed)?,
+        nums
+      );
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_many_exceptions_stay_plain() -> PcoResult<()> {
+    let nums = (0..10_000).map(|i| i * 2).collect::<Vec<u32>>();
+    let res = simple_compress_sequential(&nums, &ChunkConfig::default())?;
+    assert_eq!(res[TRANSFORM_HEADER_SIZE], PLAIN_BYTE);
+    assert_eq!(
+      simple_decompress_sequential::<u32>(&res)?,
+      nums
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_corrupt_count() -> PcoResult<()> {
+    let config = ChunkConfig::default();
+    for (n, indices) in [(1_u64 << 40, vec![]), (3, vec![5])] {
+      let mut src = Transform::Sequential.header();
+      src.push(SEQUENTIAL_BYTE);
+      src.extend(n.to_le_bytes());
+      src.extend(0_u64.to_le_bytes());
+      src.extend(simple_compress(&indices, &config)?);
+      src.extend(simple_compress(
+        &vec![1_u64; indices.len()],
+        &config,
+      )?);
+      let err = simple_decompress_sequential::<u64>(&src).unwrap_err();
+      assert!(matches!(err.kind, ErrorKind::Corruption));
+    }
+    Ok(())
+  }
+}
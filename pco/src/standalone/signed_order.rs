@@ -0,0 +1,232 @@
+use crate::chunk_config::{ChunkConfig, PagingSpec};
+use crate::data_types::{Latent, Number, NumberType};
+use crate::errors::{PcoError, PcoResult};
+use crate::sampling;
+use crate::standalone::simple::{simple_compress, simple_decompress};
+use crate::standalone::transform_header::{Transform, TRANSFORM_HEADER_SIZE};
+
+const OFFSET_BYTE: u8 = 0;
+const ZIGZAG_BYTE: u8 = 1;
+
+/// Specifies how signed integers are mapped to the unsigned latents pco
+/// compresses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SignedLatentOrder {
+  /// Compresses a sample with each order and uses whichever is smaller.
+  #[default]
+  Auto,
+  /// Offsets each number by the type's minimum, preserving numerical order.
+  ///
+  /// This is what pco does for signed integers by default.
+  Offset,
+  /// Interleaves negative and nonnegative numbers: 0, -1, 1, -2, 2, and so
+  /// forth.
+  ZigZag,
+}
+
+fn to_zigzag<L: Latent>(latent: L) -> L {
+  // the two's complement bits of the signed number
+  let signed = latent ^ L::MID;
+  let sign_fill = if signed & L::MID == L::ZERO {
+    L::ZERO
+  } else {
+    L::MAX
+  };
+  (signed << 1) ^ sign_fill
+}
+
+fn from_zigzag<L: Latent>(zigzag: L) -> L {
+  let sign_fill = if zigzag & L::ONE == L::ZERO {
+    L::ZERO
+  } else {
+    L::MAX
+  };
+  ((zigzag >> 1) ^ sign_fill) ^ L::MID
+}
+
+// We reuse the signed type for the zigzagged numbers, choosing the ones whose
+// ordered latents are the zigzag codes, so pco compresses exactly those codes.
+fn zigzag_nums<T: Number>(nums: &[T]) -> Vec<T> {
+  nums
+    .iter()
+    .map(|num| T::from_latent_ordered(to_zigzag(num.to_latent_ordered())))
+    .collect()
+}
+
+fn choose_order<T: Number>(nums: &[T], config: &ChunkConfig) -> PcoResult<SignedLatentOrder> {
+  let sample = sampling::choose_sample(nums, config.analysis_seed, |&num| Some(num))
+    .unwrap_or_else(|| nums.to_vec());
+  // the caller's paging spec generally won't fit the sample
+  let sample_config = config.clone().with_paging_spec(PagingSpec::default());
+  let offset_size = simple_compress(&sample, &sample_config)?.len();
+  let zigzag_size = simple_compress(&zigzag_nums(&sample), &sample_config)?.len();
+  Ok(if zigzag_size < offset_size {
+    SignedLatentOrder::ZigZag
+  } else {
+    SignedLatentOrder::Offset
+  })
+}
+
+/// Takes in signed integers and an exact configuration and returns compressed
+/// bytes, using the given mapping from signed integers to latents.
+///
+/// This is an experimental tool for comparing latent orders.
+/// It applies one order to the whole file rather than choosing per chunk,
+/// since chunk metadata has no field for it.
+/// The chosen order is recorded in the file's header, so that `Auto` can be
+/// decompressed without knowing which order it picked.
+///
+/// Decompress with [`simple_decompress_with_signed_order`].
+/// Will return an error if `T` is not a signed integer type or the compressor
+/// config is invalid.
+pub fn simple_compress_with_signed_order<T: Number>(
+  nums: &[T],
+  order: SignedLatentOrder,
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  if !matches!(
+    NumberType::from_descriminant(T::NUMBER_TYPE_BYTE),
+    Some(NumberType::I16 | NumberType::I32 | NumberType::I64)
+  ) {
+    return Err(PcoError::invalid_argument(
+      "signed latent orders only apply to signed integers",
+    ));
+  }
+
+  let order = match order {
+    SignedLatentOrder::Auto => choose_order(nums, config)?,
+    order => order,
+  };
+  let mut res = Transform::SignedOrder.header();
+  match order {
+    SignedLatentOrder::ZigZag => {
+      res.push(ZIGZAG_BYTE);
+      res.extend(simple_compress(&zigzag_nums(nums), config)?);
+    }
+    _ => {
+      res.push(OFFSET_BYTE);
+      res.extend(simple_compress(nums, config)?);
+    }
+  }
+  Ok(res)
+}
+
+/// Returns the latent order that compressed bytes (as produced by
+/// [`simple_compress_with_signed_order`]) were written with.
+///
+/// This is never `Auto`.
+/// Will return an error if the header is missing or invalid.
+pub fn signed_latent_order(src: &[u8]) -> PcoResult<SignedLatentOrder> {
+  match Transform::SignedOrder.read_header(src)?.first() {
+    None => Err(PcoError::insufficient_data(
+      "missing signed latent order byte",
+    )),
+    Some(&OFFSET_BYTE) => Ok(SignedLatentOrder::Offset),
+    Some(&ZIGZAG_BYTE) => Ok(SignedLatentOrder::ZigZag),
+    Some(other) => Err(PcoError::corruption(format!(
+      "unknown signed latent order byte {}",
+      other
+    ))),
+  }
+}
+
+/// Takes in compressed bytes (as produced by
+/// [`simple_compress_with_signed_order`]) and returns the numbers.
+///
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+pub fn simple_decompress_with_signed_order<T: Number>(src: &[u8]) -> PcoResult<Vec<T>> {
+  let order = signed_latent_order(src)?;
+  let mut nums = simple_decompress::<T>(&src[TRANSFORM_HEADER_SIZE + 1..])?;
+  if order == SignedLatentOrder::ZigZag {
+    for num in &mut nums {
+      *num = T::from_latent_ordered(from_zigzag(num.to_latent_ordered()));
+    }
+  }
+  Ok(nums)
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  use super::*;
+
+  #[test]
+  fn test_zigzag_codes() {
+    let codes = [0_i16, -1, 1, -2, 2, i16::MIN, i16::MAX]
+      .iter()
+      .map(|num| to_zigzag(num.to_latent_ordered()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      codes,
+      vec![0, 1, 2, 3, 4, u16::MAX, u16::MAX - 1]
+    );
+    for code in codes {
+      assert_eq!(to_zigzag(from_zigzag(code)), code);
+    }
+  }
+
+  fn check_straddling_zero<T: Number>(
+    to_num: impl Fn(i64) -> T,
+    extremes: [T; 2],
+  ) -> PcoResult<()> {
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let mut nums = (0..2000)
+      .map(|_| to_num(rng.gen_range(-512..512)))
+      .collect::<Vec<_>>();
+    nums.extend(extremes);
+    let config = ChunkConfig::default();
+    for order in [
+      SignedLatentOrder::Auto,
+      SignedLatentOrder::Offset,
+      SignedLatentOrder::ZigZag,
+    ] {
+      let compressed = simple_compress_with_signed_order(&nums, order, &config)?;
+      if order != SignedLatentOrder::Auto {
+        assert_eq!(signed_latent_order(&compressed)?, order);
+      }
+      assert_eq!(
+        simple_decompress_with_signed_order::<T>(&compressed)?,
+        nums,
+        "{:?}",
+        order
+      );
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_straddling_zero() -> PcoResult<()> {
+    check_straddling_zero(|x| x as i16, [i16::MIN, i16::MAX])?;
+    check_straddling_zero(|x| x as i32, [i32::MIN, i32::MAX])?;
+    check_straddling_zero(|x| x, [i64::MIN, i64::MAX])
+  }
+
+  #[test]
+  fn test_auto_matches_chosen_order() -> PcoResult<()> {
+    // small magnitudes of both signs, which zigzag maps to small codes
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let nums = (0..10_000)
+      .map(|_| rng.gen_range(-7..8))
+      .collect::<Vec<i32>>();
+    let config = ChunkConfig::default();
+    let auto = simple_compress_with_signed_order(&nums, SignedLatentOrder::Auto, &config)?;
+    let chosen = simple_compress_with_signed_order(&nums, signed_latent_order(&auto)?, &config)?;
+    assert_eq!(auto, chosen);
+    Ok(())
+  }
+
+  #[test]
+  fn test_unsigned_rejected() {
+    let result = simple_compress_with_signed_order(
+      &[1_u32, 2],
+      SignedLatentOrder::ZigZag,
+      &ChunkConfig::default(),
+    );
+    assert!(result.is_err());
+  }
+}
@@ -1,12 +1,23 @@
 use std::cmp::min;
+use std::io::{self, BufRead, Write};
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+
+use better_io::BetterBufReader;
 
 use crate::chunk_config::ChunkConfig;
-use crate::data_types::Number;
-use crate::errors::PcoResult;
+use crate::data_types::{Number, NumberType};
+use crate::errors::{ErrorKind, PcoError, PcoResult};
+use crate::macros::match_number_enum;
 use crate::progress::Progress;
 use crate::standalone::compressor::FileCompressor;
+use crate::standalone::constants::MAGIC_TERMINATION_BYTE;
 use crate::standalone::decompressor::{FileDecompressor, MaybeChunkDecompressor};
-use crate::{PagingSpec, FULL_BATCH_N};
+use crate::standalone::{ChunkConfigSummary, FixedRange, NumberTypeOrTermination, ParallelConfig};
+use crate::{
+  wrapped, BinLayout, DeltaSpec, ModeSpec, PagingSpec, CURRENT_FORMAT_VERSION,
+  DEFAULT_FORMAT_VERSION, FULL_BATCH_N,
+};
 
 // TODO in 1.0 make this generic to Write and make all compress methods
 // accepting a Write return the number of bytes written?
@@ -23,27 +34,10 @@ use crate::{PagingSpec, FULL_BATCH_N};
 pub fn simple_compress_into<T: Number>(
   nums: &[T],
   config: &ChunkConfig,
-  mut dst: &mut [u8],
+  dst: &mut [u8],
 ) -> PcoResult<usize> {
   let original_length = dst.len();
-  let file_compressor = FileCompressor::default().with_n_hint(nums.len());
-  dst = file_compressor.write_header(dst)?;
-
-  // here we use the paging spec to determine chunks; each chunk has 1 page
-  let n_per_page = config.paging_spec.n_per_page(nums.len())?;
-  let mut start = 0;
-  let mut this_chunk_config = config.clone();
-  for &page_n in &n_per_page {
-    let end = start + page_n;
-    this_chunk_config.paging_spec = PagingSpec::Exact(vec![page_n]);
-    let chunk_compressor =
-      file_compressor.chunk_compressor(&nums[start..end], &this_chunk_config)?;
-
-    dst = chunk_compressor.write_chunk(dst)?;
-    start = end;
-  }
-
-  dst = file_compressor.write_footer(dst)?;
+  let dst = Compressor::new(config)?.compress_to_writer(nums, dst)?;
   Ok(original_length - dst.len())
 }
 
@@ -56,34 +50,436 @@ pub fn simple_compress_into<T: Number>(
 /// For standalone, the concepts of chunk and page are conflated since each
 /// chunk has exactly one page.
 pub fn simple_compress<T: Number>(nums: &[T], config: &ChunkConfig) -> PcoResult<Vec<u8>> {
-  let mut dst = Vec::new();
-  let file_compressor = FileCompressor::default().with_n_hint(nums.len());
-  file_compressor.write_header(&mut dst)?;
+  Compressor::new(config)?.compress(nums)
+}
+
+/// Takes in a slice of numbers, a previously learned layout, and a
+/// configuration and returns compressed bytes, skipping analysis of the
+/// numbers.
+///
+/// See
+/// [`wrapped::FileCompressor::chunk_compressor_with_layout`][crate::wrapped::FileCompressor::chunk_compressor_with_layout]
+/// for details.
+/// Will return an error if the compressor config is invalid or the layout was
+/// learned from a different number type.
+pub fn compress_with_layout<T: Number>(
+  nums: &[T],
+  layout: &BinLayout,
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  Compressor::new(config)?
+    .with_layout(Some(layout.clone()))
+    .compress(nums)
+}
+
+/// Takes in a slice of numbers sorted in non-decreasing order and an exact
+/// configuration and returns compressed bytes.
+///
+/// This skips delta encoding analysis and uses first order consecutive
+/// deltas, which suit sorted data like time series index columns; if the
+/// config already has a delta spec other than `Auto`, that is used instead.
+/// The output is an ordinary pco file, decompressible with
+/// [`simple_decompress`].
+///
+/// In debug builds, this checks that the numbers are sorted (ordering floats
+/// as pco does, e.g. -0.0 before 0.0) and returns an error if not.
+/// Release builds trust the caller for speed, but unsorted numbers are never
+/// undefined behavior: they still compress losslessly, just less well.
+/// Will also return an error if the compressor config is invalid.
+pub fn compress_sorted<T: Number>(nums: &[T], config: &ChunkConfig) -> PcoResult<Vec<u8>> {
+  #[cfg(debug_assertions)]
+  if let Some(i) = nums
+    .windows(2)
+    .position(|pair| pair[0].to_latent_ordered() > pair[1].to_latent_ordered())
+  {
+    return Err(PcoError::invalid_argument(format!(
+      "numbers passed to compress_sorted were unsorted at index {} ({} > {})",
+      i + 1,
+      nums[i],
+      nums[i + 1],
+    )));
+  }
+
+  let mut config = config.clone();
+  if matches!(config.delta_spec, DeltaSpec::Auto) {
+    config.delta_spec = DeltaSpec::TryConsecutive(1);
+  }
+  simple_compress(nums, &config)
+}
+
+/// Where a chunk sits within a standalone file, as returned by
+/// [`Compressor::compress_with_ranges`].
+///
+/// This can be stored in an external index and passed to
+/// [`FileDecompressor::decompress_chunk`] to decompress just that chunk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ChunkByteRange {
+  /// The byte offset of the chunk from the start of the file.
+  pub start: usize,
+  /// The chunk's byte length.
+  pub len: usize,
+  /// The count of numbers in the chunk.
+  pub n: usize,
+}
+
+impl ChunkByteRange {
+  /// Returns the byte offset just past the end of the chunk.
+  pub fn end(&self) -> usize {
+    self.start + self.len
+  }
+}
+
+/// Compresses many slices of numbers with the same configuration.
+///
+/// Each call to [`compress`][Compressor::compress] produces exactly the
+/// same bytes as [`simple_compress`] would (plus any padding from
+/// [`with_alignment`][Compressor::with_alignment]), but the config is
+/// validated and copied only once, which helps when compressing many small
+/// slices in a loop.
+///
+/// Cloning is cheap: a compressor holds only its config, not any buffers,
+/// so a configured compressor can serve as a template for per-thread copies.
+///
+/// ```
+/// use pco::ChunkConfig;
+/// use pco::standalone::Compressor;
+/// # use pco::errors::PcoResult;
+///
+/// # fn main() -> PcoResult<()> {
+/// let nums = (0..1000).collect::<Vec<i64>>();
+/// let mut compressor = Compressor::new(&ChunkConfig::default())?;
+/// for window in nums.chunks(100) {
+///   let compressed = compressor.compress(window)?;
+///   // do something with the compressed window...
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Compressor<T: Number> {
+  config: ChunkConfig,
+  this_chunk_config: ChunkConfig,
+  alignment: usize,
+  embeds_manifest: bool,
+  index_footer: bool,
+  layout: Option<BinLayout>,
+  fixed_range: Option<FixedRange>,
+  phantom: PhantomData<T>,
+}
+
+impl<T: Number> Compressor<T> {
+  /// Creates a compressor for the given configuration.
+  ///
+  /// Will return an error if the compressor config is invalid.
+  pub fn new(config: &ChunkConfig) -> PcoResult<Self> {
+    wrapped::validate_config(config)?;
+    Ok(Self {
+      config: config.clone(),
+      this_chunk_config: config.clone(),
+      alignment: 1,
+      embeds_manifest: false,
+      index_footer: false,
+      layout: None,
+      fixed_range: None,
+      phantom: PhantomData,
+    })
+  }
+
+  /// Makes each compressed file's length a multiple of `alignment` bytes
+  /// (default: 1, i.e. no padding).
+  ///
+  /// The padding consists of zero bytes after the file's footer.
+  /// Decompressors stop at the footer's termination byte and never read
+  /// past it, so the padding can't be mistaken for chunk data; functions
+  /// like [`simple_decompress_at`] report the unpadded length.
+  /// An alignment of 0 is treated like 1.
+  pub fn with_alignment(mut self, alignment: usize) -> Self {
+    self.alignment = alignment.max(1);
+    self
+  }
+
+  /// Records a [`ChunkConfigSummary`][crate::standalone::ChunkConfigSummary]
+  /// of the settings used in each compressed file's header (default: false).
+  ///
+  /// The summary describes the first chunk, since chunks are compressed
+  /// independently and `Auto` specs may resolve differently for later ones.
+  /// Empty files get no manifest.
+  pub fn with_manifest(mut self, embeds_manifest: bool) -> Self {
+    self.embeds_manifest = embeds_manifest;
+    self
+  }
+
+  /// Writes an index of every chunk at the end of each compressed file
+  /// (default: false).
+  ///
+  /// See [`FileCompressor::with_index_footer`] for details.
+  /// Any padding from [`with_alignment`][Compressor::with_alignment] goes
+  /// after the index, and
+  /// [`FileDecompressor::open_with_footer`] skips it.
+  pub fn with_index_footer(mut self, index_footer: bool) -> Self {
+    self.index_footer = index_footer;
+    self
+  }
+
+  /// Compresses every chunk with the given [`BinLayout`] instead of
+  /// analyzing the numbers (default: `None`).
+  ///
+  /// See [`compress_with_layout`] for details.
+  pub fn with_layout(mut self, layout: Option<BinLayout>) -> Self {
+    self.layout = layout;
+    self
+  }
+
+  pub(crate) fn with_fixed_range(mut self, fixed_range: Option<FixedRange>) -> Self {
+    self.fixed_range = fixed_range;
+    self
+  }
+
+  /// Returns the configuration this compressor uses.
+  pub fn config(&self) -> &ChunkConfig {
+    &self.config
+  }
+
+  fn reconfigure(&mut self, update: impl Fn(&mut ChunkConfig)) -> PcoResult<()> {
+    let mut config = self.config.clone();
+    update(&mut config);
+    wrapped::validate_config(&config)?;
+    update(&mut self.this_chunk_config);
+    self.config = config;
+    Ok(())
+  }
+
+  /// Sets [`compression_level`][ChunkConfig::compression_level] for
+  /// subsequent calls to [`compress`][Compressor::compress].
+  ///
+  /// Will return an error and leave the compressor unchanged if the level is
+  /// invalid.
+  pub fn set_level(&mut self, level: usize) -> PcoResult<()> {
+    self.reconfigure(|config| config.compression_level = level)
+  }
+
+  /// Sets [`mode_spec`][ChunkConfig::mode_spec] for subsequent calls to
+  /// [`compress`][Compressor::compress].
+  ///
+  /// Will return an error and leave the compressor unchanged if the mode spec
+  /// is invalid.
+  pub fn set_mode_spec(&mut self, mode_spec: ModeSpec) -> PcoResult<()> {
+    self.reconfigure(|config| config.mode_spec = mode_spec)
+  }
+
+  /// Takes in a slice of numbers and returns compressed bytes.
+  ///
+  /// See [`simple_compress`] for details.
+  pub fn compress(&mut self, nums: &[T]) -> PcoResult<Vec<u8>> {
+    Ok(self.compress_with_ranges(nums)?.0)
+  }
+
+  /// Takes in a slice of numbers and returns compressed bytes, along with
+  /// the byte range of each chunk within them.
+  ///
+  /// The ranges are contiguous and in order: the first starts right after
+  /// the header, and the last ends right before the footer.
+  pub fn compress_with_ranges(&mut self, nums: &[T]) -> PcoResult<(Vec<u8>, Vec<ChunkByteRange>)> {
+    self.compress_to_vec(nums, None)
+  }
+
+  /// Takes in a slice of numbers and one annotation per chunk and returns
+  /// compressed bytes.
+  ///
+  /// Chunks are determined by the config's paging spec, and the annotations
+  /// are stored with them in order; see
+  /// [`FileCompressor::with_annotated_chunks`] for details.
+  /// Will return an error if the count of annotations doesn't match the
+  /// count of chunks.
+  pub fn compress_annotated(&mut self, nums: &[T], annotations: &[u64]) -> PcoResult<Vec<u8>> {
+    Ok(self.compress_to_vec(nums, Some(annotations))?.0)
+  }
+
+  /// Takes in a slice of numbers and writes compressed bytes to the writer
+  /// as each chunk is produced, returning the writer.
+  ///
+  /// See [`compress_to_writer`] for details.
+  pub fn compress_to_writer<W: Write>(&mut self, nums: &[T], dst: W) -> PcoResult<W> {
+    Ok(self.compress_inner(nums, None, dst, |_, _| ())?.0)
+  }
+
+  fn compress_to_vec(
+    &mut self,
+    nums: &[T],
+    annotations: Option<&[u64]>,
+  ) -> PcoResult<(Vec<u8>, Vec<ChunkByteRange>)> {
+    self.compress_inner(
+      nums,
+      annotations,
+      Vec::new(),
+      |dst, file_size_hint| dst.reserve_exact(file_size_hint + 10),
+    )
+  }
 
-  // here we use the paging spec to determine chunks; each chunk has 1 page
-  let n_per_page = config.paging_spec.n_per_page(nums.len())?;
-  let mut start = 0;
-  let mut this_chunk_config = config.clone();
-  let mut hinted_size = false;
-  for &page_n in &n_per_page {
-    let end = start + page_n;
-    this_chunk_config.paging_spec = PagingSpec::Exact(vec![page_n]);
-    let chunk_compressor =
-      file_compressor.chunk_compressor(&nums[start..end], &this_chunk_config)?;
+  // `reserve` receives an estimate of the file size once the first chunk is
+  // compressed.
+  fn compress_inner<W: Write>(
+    &mut self,
+    nums: &[T],
+    annotations: Option<&[u64]>,
+    dst: W,
+    reserve: impl FnOnce(&mut W, usize),
+  ) -> PcoResult<(W, Vec<ChunkByteRange>)> {
+    let mut dst = CountingWriter {
+      inner: dst,
+      n_bytes: 0,
+    };
+    let mut reserve = Some(reserve);
+    let mut ranges = Vec::new();
+    // the first chunk may use any delta encoding, and the header records the
+    // oldest format version that supports it
+    let mut file_compressor = FileCompressor::default()
+      .with_format_version(CURRENT_FORMAT_VERSION)
+      .with_n_hint(nums.len())
+      .with_annotated_chunks(annotations.is_some())
+      .with_index_footer(self.index_footer)
+      .with_fixed_range(self.fixed_range);
+
+    // here we use the paging spec to determine chunks; each chunk has 1 page
+    let n_per_page = self.config.paging_spec.n_per_page(nums.len())?;
+    if let Some(annotations) = annotations {
+      if annotations.len() != n_per_page.len() {
+        return Err(PcoError::invalid_argument(format!(
+          "expected one annotation per chunk ({}) but received {}",
+          n_per_page.len(),
+          annotations.len(),
+        )));
+      }
+    }
+    let mut start = 0;
+    let mut wrote_header = false;
+    for (chunk_idx, &page_n) in n_per_page.iter().enumerate() {
+      let end = start + page_n;
+      match &mut self.this_chunk_config.paging_spec {
+        PagingSpec::Exact(ns) => {
+          ns.clear();
+          ns.push(page_n);
+        }
+        other => *other = PagingSpec::Exact(vec![page_n]),
+      }
+      let chunk_nums = &nums[start..end];
+      let chunk_compressor = match &self.layout {
+        Some(layout) => file_compressor.chunk_compressor_with_layout(
+          chunk_nums,
+          &self.this_chunk_config,
+          layout,
+        )?,
+        None => file_compressor.chunk_compressor(chunk_nums, &self.this_chunk_config)?,
+      }
+      .with_annotation(annotations.map(|annotations| annotations[chunk_idx]));
+
+      // we write the header once we have the first chunk, so it can be
+      // summarized in the manifest
+      if !wrote_header {
+        let file_size_hint =
+          chunk_compressor.chunk_size_hint() as f64 * nums.len() as f64 / page_n as f64;
+        if let Some(reserve) = reserve.take() {
+          reserve(&mut dst.inner, file_size_hint as usize);
+        }
+        file_compressor = file_compressor.with_oldest_format_version(
+          &self.this_chunk_config,
+          chunk_compressor.meta(),
+        );
+        if self.embeds_manifest {
+          file_compressor = file_compressor.with_manifest(Some(ChunkConfigSummary::new(
+            &self.config,
+            chunk_compressor.meta(),
+          )));
+        }
+        file_compressor.write_header(&mut dst)?;
+        wrote_header = true;
+      }
 
-    if !hinted_size {
-      let file_size_hint =
-        chunk_compressor.chunk_size_hint() as f64 * nums.len() as f64 / page_n as f64;
-      dst.reserve_exact(file_size_hint as usize + 10);
-      hinted_size = true;
+      let chunk_start = dst.n_bytes;
+      chunk_compressor.write_chunk(&mut dst)?;
+      ranges.push(ChunkByteRange {
+        start: chunk_start,
+        len: dst.n_bytes - chunk_start,
+        n: page_n,
+      });
+      start = end;
     }
 
-    chunk_compressor.write_chunk(&mut dst)?;
-    start = end;
+    if !wrote_header {
+      file_compressor = file_compressor.with_format_version(DEFAULT_FORMAT_VERSION);
+      file_compressor.write_header(&mut dst)?;
+    }
+    if self.index_footer {
+      file_compressor.write_footer_with_index(&ranges, &mut dst)?;
+    } else {
+      file_compressor.write_footer(&mut dst)?;
+    }
+    let padding = dst.n_bytes.next_multiple_of(self.alignment) - dst.n_bytes;
+    dst.write_all(&vec![0; padding])?;
+    Ok((dst.inner, ranges))
+  }
+}
+
+// Tracks how many bytes have been written, so we can report chunk byte
+// ranges for any destination.
+struct CountingWriter<W: Write> {
+  inner: W,
+  n_bytes: usize,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    let n = self.inner.write(buf)?;
+    self.n_bytes += n;
+    Ok(n)
   }
 
-  file_compressor.write_footer(&mut dst)?;
-  Ok(dst)
+  fn flush(&mut self) -> io::Result<()> {
+    self.inner.flush()
+  }
+}
+
+/// Takes in a slice of numbers and an exact configuration and writes
+/// compressed bytes to the writer as each chunk is produced.
+///
+/// Unlike [`simple_compress`], this never holds the whole compressed file in
+/// memory, so peak memory usage is roughly that of a single chunk.
+/// Will return an error if the compressor config is invalid or there is an IO
+/// error.
+/// This will use the `PagingSpec` in `ChunkConfig` to decide where to split
+/// chunks.
+pub fn compress_to_writer<T: Number, W: Write>(
+  nums: &[T],
+  config: &ChunkConfig,
+  dst: &mut W,
+) -> PcoResult<()> {
+  Compressor::new(config)?.compress_to_writer(nums, dst)?;
+  Ok(())
+}
+
+/// Takes in a slice of numbers, an exact configuration, and a maximum byte
+/// size, and returns compressed bytes no longer than `max_bytes`.
+///
+/// Pco falls back to a trivial encoding for any chunk that would otherwise
+/// compress poorly, so the output is never larger than
+/// [`guarantee::file_size`][crate::standalone::guarantee::file_size].
+/// Will return an invalid argument error if the compressor config is invalid
+/// or the compressed file doesn't fit within `max_bytes` even so.
+pub fn compress_within<T: Number>(
+  nums: &[T],
+  config: &ChunkConfig,
+  max_bytes: usize,
+) -> PcoResult<Vec<u8>> {
+  let res = simple_compress(nums, config)?;
+  if res.len() > max_bytes {
+    return Err(PcoError::invalid_argument(format!(
+      "compressed file of {} bytes exceeded the maximum of {} bytes",
+      res.len(),
+      max_bytes,
+    )));
+  }
+  Ok(res)
 }
 
 /// Takes in compressed bytes and writes numbers to the destination, returning
@@ -138,6 +534,228 @@ pub fn simple_decompress_into<T: Number>(src: &[u8], mut dst: &mut [T]) -> PcoRe
   Ok(progress)
 }
 
+/// Takes in compressed bytes and writes all the file's numbers to the
+/// destination, returning the count of numbers written.
+///
+/// Unlike [`simple_decompress_into`], this requires `dst` to fit the whole
+/// file.
+/// Since `dst` can be any slice, such as one backed by a memory-mapped
+/// output file, this can decompress files larger than RAM without an
+/// intermediate `Vec`.
+///
+/// Will return an invalid argument error if `dst` is too short, in which case
+/// its contents are unspecified.
+/// Will also return an error if there are any compatibility, corruption, or
+/// insufficient data issues.
+pub fn decompress_into_slice<T: Number>(src: &[u8], dst: &mut [T]) -> PcoResult<usize> {
+  let dst_len = dst.len();
+  let progress = simple_decompress_into(src, dst)?;
+  if !progress.finished {
+    return Err(PcoError::invalid_argument(format!(
+      "dst's length of {} is too short for the file",
+      dst_len,
+    )));
+  }
+  Ok(progress.n_processed)
+}
+
+/// Takes in compressed bytes and writes each page's numbers to the
+/// destination at a fixed stride, returning the count of pages written.
+///
+/// Each standalone chunk has exactly one page, and page `i` is written
+/// starting at `dst[i * stride]`.
+/// Elements after the end of each page and before the next stride are left
+/// untouched, so pages can land directly in a padded buffer, e.g. for upload
+/// to a GPU, without re-strided copying.
+///
+/// Will return an invalid argument error if `stride` is 0, a page has more
+/// than `stride` numbers, or `dst` is too short, in which case its contents
+/// are unspecified.
+/// Will also return an error if there are any compatibility, corruption, or
+/// insufficient data issues.
+pub fn decompress_strided<T: Number>(src: &[u8], stride: usize, dst: &mut [T]) -> PcoResult<usize> {
+  if stride == 0 {
+    return Err(PcoError::invalid_argument(
+      "stride must be positive",
+    ));
+  }
+
+  let (file_decompressor, mut src) = FileDecompressor::new(src)?;
+  let mut page_idx = 0;
+  while let MaybeChunkDecompressor::Some(mut chunk_decompressor) =
+    file_decompressor.chunk_decompressor(src)?
+  {
+    let n = chunk_decompressor.n();
+    if n > stride {
+      return Err(PcoError::invalid_argument(format!(
+        "page {} has {} numbers, exceeding the stride of {}",
+        page_idx, n, stride,
+      )));
+    }
+    let start = page_idx * stride;
+    let Some(page_dst) = dst.get_mut(start..start + n) else {
+      return Err(PcoError::invalid_argument(format!(
+        "dst's length of {} is too short for page {}, which ends at {}",
+        dst.len(),
+        page_idx,
+        start + n,
+      )));
+    };
+
+    chunk_decompressor.decompress(page_dst)?;
+    page_idx += 1;
+    src = chunk_decompressor.into_src();
+  }
+  Ok(page_idx)
+}
+
+/// Takes in compressed bytes and returns the numbers at index `start` and
+/// every `stride`th index after it, e.g. the even elements with a start of 0
+/// and stride of 2.
+///
+/// This is meant for cheap previews of large columns.
+/// The cost depends on each chunk's delta encoding:
+/// * Without delta encoding, only the entropy-coded part of each batch is
+///   decoded for the numbers skipped over, and batches containing none of
+///   the requested numbers are skipped that way entirely. This gets cheaper
+///   as the stride grows.
+/// * With delta encoding, every number depends on the ones before it, so the
+///   whole chunk is decompressed and only the requested numbers are kept.
+///   This costs about as much as [`simple_decompress`].
+///
+/// Will return an invalid argument error if `stride` is 0.
+/// Will also return an error if there are any compatibility, corruption, or
+/// insufficient data issues.
+pub fn decompress_every_nth<T: Number>(
+  src: &[u8],
+  start: usize,
+  stride: usize,
+) -> PcoResult<Vec<T>> {
+  if stride == 0 {
+    return Err(PcoError::invalid_argument(
+      "stride must be positive",
+    ));
+  }
+
+  let (file_decompressor, mut src) = FileDecompressor::new(src)?;
+  let mut res = Vec::with_capacity(file_decompressor.n_hint().saturating_sub(start) / stride + 1);
+  let mut chunk_start = 0;
+  let mut next_idx = start;
+  while let MaybeChunkDecompressor::Some(chunk_decompressor) =
+    file_decompressor.chunk_decompressor::<T, _>(src)?
+  {
+    let chunk_end = chunk_start + chunk_decompressor.n();
+    src = if next_idx < chunk_end {
+      let rest = chunk_decompressor.decompress_strided_remaining(
+        next_idx - chunk_start,
+        stride,
+        &mut res,
+      )?;
+      next_idx = next_idx.saturating_add(
+        (chunk_end - next_idx)
+          .div_ceil(stride)
+          .saturating_mul(stride),
+      );
+      rest
+    } else {
+      chunk_decompressor.skip_remaining()?
+    };
+    chunk_start = chunk_end;
+  }
+  Ok(res)
+}
+
+/// Takes in compressed bytes and passes the numbers to `sink` in order, at
+/// most `window` at a time, returning the count of numbers decompressed.
+///
+/// This decompresses files of any size in bounded memory: the only buffer
+/// for numbers holds `window` of them (or one batch of 256, if that is more),
+/// and is reused for every call to `sink`, alongside the decoding state of a
+/// single chunk.
+/// Each slice passed to `sink` comes from a single chunk, so slices may be
+/// shorter than `window` near chunk boundaries.
+///
+/// Will return an invalid argument error if `window` is 0.
+/// Will also return an error if there are any compatibility, corruption, or
+/// insufficient data issues, possibly after passing some numbers to `sink`.
+pub fn decompress_windowed<T: Number>(
+  src: &[u8],
+  window: usize,
+  mut sink: impl FnMut(&[T]),
+) -> PcoResult<usize> {
+  if window == 0 {
+    return Err(PcoError::invalid_argument(
+      "window must be positive",
+    ));
+  }
+
+  // chunk decompressors only stop partway through a chunk at batch
+  // boundaries
+  let buffer_len = (window / FULL_BATCH_N).max(1) * FULL_BATCH_N;
+  let mut buffer = vec![T::default(); buffer_len];
+  let (file_decompressor, mut src) = FileDecompressor::new(src)?;
+  let mut n_processed = 0;
+  while let MaybeChunkDecompressor::Some(mut chunk_decompressor) =
+    file_decompressor.chunk_decompressor(src)?
+  {
+    loop {
+      let progress = chunk_decompressor.decompress(&mut buffer)?;
+      for window_nums in buffer[..progress.n_processed].chunks(window) {
+        sink(window_nums);
+      }
+      n_processed += progress.n_processed;
+      if progress.finished {
+        break;
+      }
+    }
+    src = chunk_decompressor.into_src();
+  }
+  Ok(n_processed)
+}
+
+/// Takes in compressed bytes and writes numbers to the possibly uninitialized
+/// destination, returning the count of numbers written.
+///
+/// This behaves like [`simple_decompress_into`], but saves the cost of
+/// initializing a large `dst` beforehand.
+/// Numbers are decoded a batch at a time into a small reused buffer and
+/// moved into `dst` from there.
+/// The first `n` elements of `dst` are guaranteed to be initialized, where
+/// `n` is the returned count, so it is sound to `assume_init` on that prefix.
+///
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues, in which case no elements of `dst` should be
+/// assumed initialized.
+pub fn simple_decompress_into_uninit<T: Number>(
+  src: &[u8],
+  mut dst: &mut [MaybeUninit<T>],
+) -> PcoResult<usize> {
+  let (file_decompressor, mut src) = FileDecompressor::new(src)?;
+  let mut batch = vec![T::default(); FULL_BATCH_N];
+  let mut n_processed = 0;
+  while !dst.is_empty() {
+    let MaybeChunkDecompressor::Some(mut chunk_decompressor) =
+      file_decompressor.chunk_decompressor(src)?
+    else {
+      break;
+    };
+    loop {
+      let progress = chunk_decompressor.decompress(&mut batch)?;
+      let n = min(dst.len(), progress.n_processed);
+      for (dst_num, &num) in dst[..n].iter_mut().zip(&batch[..n]) {
+        dst_num.write(num);
+      }
+      dst = &mut dst[n..];
+      n_processed += n;
+      if progress.finished || dst.is_empty() {
+        break;
+      }
+    }
+    src = chunk_decompressor.into_src();
+  }
+  Ok(n_processed)
+}
+
 /// Compresses the numbers using the given compression level and an otherwise
 /// default configuration.
 ///
@@ -162,14 +780,402 @@ pub fn simple_decompress<T: Number>(src: &[u8]) -> PcoResult<Vec<T>> {
   file_decompressor.simple_decompress(src)
 }
 
+/// Takes in a slice of numbers, an exact configuration, and one annotation
+/// per chunk, and returns compressed bytes.
+///
+/// See [`Compressor::compress_annotated`] for details.
+/// Will return an error if the compressor config is invalid or the count of
+/// annotations doesn't match the count of chunks.
+pub fn simple_compress_annotated<T: Number>(
+  nums: &[T],
+  config: &ChunkConfig,
+  annotations: &[u64],
+) -> PcoResult<Vec<u8>> {
+  Compressor::new(config)?.compress_annotated(nums, annotations)
+}
+
+/// Takes in compressed bytes and returns a vector of numbers along with each
+/// chunk's annotation.
+///
+/// Files without annotated chunks have no annotations.
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+pub fn simple_decompress_annotated<T: Number>(src: &[u8]) -> PcoResult<(Vec<T>, Vec<u64>)> {
+  let (file_decompressor, mut src) = FileDecompressor::new(src)?;
+  let mut nums = Vec::with_capacity(file_decompressor.n_hint());
+  let mut annotations = Vec::new();
+  while let MaybeChunkDecompressor::Some(mut chunk_decompressor) =
+    file_decompressor.chunk_decompressor(src)?
+  {
+    annotations.extend(chunk_decompressor.annotation());
+    chunk_decompressor.decompress_remaining_extend(&mut nums)?;
+    src = chunk_decompressor.into_src();
+  }
+  Ok((nums, annotations))
+}
+
+/// Takes in compressed bytes and returns a vector of numbers, decompressing
+/// chunks in parallel according to `parallel_config`.
+///
+/// See [`FileDecompressor::decompress_parallel`] for details.
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+pub fn simple_decompress_parallel<T: Number>(
+  src: &[u8],
+  parallel_config: &ParallelConfig,
+) -> PcoResult<Vec<T>> {
+  let (file_decompressor, src) = FileDecompressor::new(src)?;
+  let chunks = file_decompressor.locate_chunks::<T>(src)?;
+  let n = chunks.iter().map(|&(_, chunk_n)| chunk_n).sum::<usize>();
+  let mut res = vec![T::default(); n];
+  file_decompressor.decompress_chunks_parallel(chunks, parallel_config, &mut res)?;
+  Ok(res)
+}
+
+/// Reads compressed bytes incrementally from the reader and returns a vector
+/// of numbers.
+///
+/// Unlike [`simple_decompress`], this doesn't need the whole compressed file
+/// in memory at once, so it suits pipes and sockets; reads that return fewer
+/// bytes than requested are fine.
+/// See [`decompress_pages_from_reader`] to also avoid holding all the
+/// decompressed numbers at once.
+/// Bytes after the file's termination byte may be consumed from the reader.
+/// Will return an error if there are any IO, compatibility, corruption, or
+/// insufficient data issues.
+pub fn decompress_from_reader<T: Number, R: BufRead>(src: &mut R) -> PcoResult<Vec<T>> {
+  let pages = decompress_pages_from_reader::<T, R>(src)?;
+  let mut res = Vec::with_capacity(pages.file_decompressor.n_hint());
+  for page in pages {
+    res.extend(page?);
+  }
+  Ok(res)
+}
+
+/// Reads the header of a compressed file from the reader and returns an
+/// iterator that decompresses one page at a time.
+///
+/// Each standalone chunk has exactly one page, so this yields the numbers
+/// of each chunk in order, reading only as many bytes as that chunk needs.
+/// See [`decompress_from_reader`] for details.
+/// Will return an error if there are any IO, compatibility, corruption, or
+/// insufficient data issues in the header.
+pub fn decompress_pages_from_reader<T: Number, R: BufRead>(
+  src: &mut R,
+) -> PcoResult<PageReader<'_, T, R>> {
+  let (file_decompressor, src) = FileDecompressor::new(BetterBufReader::from_read_simple(src))?;
+  Ok(PageReader {
+    file_decompressor,
+    src: Some(src),
+    phantom: PhantomData,
+  })
+}
+
+/// An iterator over the decompressed pages of a file being read incrementally,
+/// as returned by [`decompress_pages_from_reader`].
+///
+/// Once it yields an error, it yields nothing further.
+pub struct PageReader<'a, T: Number, R: BufRead> {
+  file_decompressor: FileDecompressor,
+  // None once we've reached the end of the file or an error
+  src: Option<BetterBufReader<&'a mut R>>,
+  phantom: PhantomData<T>,
+}
+
+impl<'a, T: Number, R: BufRead> PageReader<'a, T, R> {
+  /// Returns the file-level information read from the header.
+  pub fn file_decompressor(&self) -> &FileDecompressor {
+    &self.file_decompressor
+  }
+
+  fn next_page(&mut self, src: BetterBufReader<&'a mut R>) -> PcoResult<Option<Vec<T>>> {
+    match self.file_decompressor.chunk_decompressor(src)? {
+      MaybeChunkDecompressor::Some(mut chunk_decompressor) => {
+        let mut nums = Vec::with_capacity(chunk_decompressor.n());
+        chunk_decompressor.decompress_remaining_extend(&mut nums)?;
+        self.src = Some(chunk_decompressor.into_src());
+        Ok(Some(nums))
+      }
+      MaybeChunkDecompressor::EndOfData(_) => Ok(None),
+    }
+  }
+}
+
+impl<T: Number, R: BufRead> Iterator for PageReader<'_, T, R> {
+  type Item = PcoResult<Vec<T>>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let src = self.src.take()?;
+    self.next_page(src).transpose()
+  }
+}
+
+/// Takes in bytes containing a pco file starting at byte offset `start` and
+/// returns the decompressed numbers along with the number of bytes the pco
+/// file occupied.
+///
+/// Decompression stops at the pco file's termination byte, so any bytes
+/// after it are ignored; `start` plus the returned byte count is where they
+/// begin.
+/// Will return an error if `start` is out of bounds or if there are any
+/// compatibility, corruption, or insufficient data issues.
+pub fn simple_decompress_at<T: Number>(src: &[u8], start: usize) -> PcoResult<(Vec<T>, usize)> {
+  let Some(file_src) = src.get(start..) else {
+    return Err(PcoError::invalid_argument(format!(
+      "start {} is out of bounds for {} bytes",
+      start,
+      src.len(),
+    )));
+  };
+
+  let (file_decompressor, mut rest) = FileDecompressor::new(file_src)?;
+  let mut nums = Vec::with_capacity(file_decompressor.n_hint());
+  loop {
+    match file_decompressor.chunk_decompressor(rest)? {
+      MaybeChunkDecompressor::Some(mut chunk_decompressor) => {
+        chunk_decompressor.decompress_remaining_extend(&mut nums)?;
+        rest = chunk_decompressor.into_src();
+      }
+      MaybeChunkDecompressor::EndOfData(remaining) => {
+        rest = remaining;
+        break;
+      }
+    }
+  }
+  Ok((nums, file_src.len() - rest.len()))
+}
+
+/// Takes in compressed bytes of a possibly truncated file and returns the
+/// numbers of every whole chunk before the truncation, along with whether
+/// the file was truncated.
+///
+/// This is meant for salvaging data from writers that crashed partway
+/// through.
+/// Only whole, valid chunks are returned; numbers from a chunk cut off
+/// partway are discarded, even if some of them could be decoded.
+/// Will return an error if there are any compatibility or corruption issues
+/// other than running out of data.
+pub fn simple_decompress_recover<T: Number>(src: &[u8]) -> PcoResult<(Vec<T>, bool)> {
+  let is_truncation = |e: &PcoError| matches!(e.kind, ErrorKind::InsufficientData);
+
+  let (file_decompressor, mut src) = match FileDecompressor::new(src) {
+    Ok(res) => res,
+    Err(e) if is_truncation(&e) => return Ok((Vec::new(), true)),
+    Err(e) => return Err(e),
+  };
+  let mut nums = Vec::with_capacity(file_decompressor.n_hint());
+  loop {
+    let mut chunk_decompressor = match file_decompressor.chunk_decompressor(src) {
+      Ok(MaybeChunkDecompressor::Some(cd)) => cd,
+      Ok(MaybeChunkDecompressor::EndOfData(_)) => return Ok((nums, false)),
+      Err(e) if is_truncation(&e) => return Ok((nums, true)),
+      Err(e) => return Err(e),
+    };
+
+    let chunk_start = nums.len();
+    match chunk_decompressor.decompress_remaining_extend(&mut nums) {
+      Ok(()) => (),
+      Err(e) if is_truncation(&e) => {
+        nums.truncate(chunk_start);
+        return Ok((nums, true));
+      }
+      Err(e) => return Err(e),
+    }
+    src = chunk_decompressor.into_src();
+  }
+}
+
+fn skip_chunk<'a, T: Number>(
+  file_decompressor: &FileDecompressor,
+  src: &'a [u8],
+) -> PcoResult<&'a [u8]> {
+  match file_decompressor.chunk_decompressor::<T, _>(src)? {
+    MaybeChunkDecompressor::Some(chunk_decompressor) => chunk_decompressor.skip_remaining(),
+    MaybeChunkDecompressor::EndOfData(_) => unreachable!("termination byte was already checked"),
+  }
+}
+
+/// Takes in compressed bytes and returns whether they form a complete file,
+/// with a termination byte after the last chunk.
+///
+/// This is a cheap integrity check for incoming files: it skips through each
+/// chunk, only decoding the entropy-coded part of each batch, and never
+/// produces numbers.
+/// Since standalone chunks don't record their compressed sizes, this still
+/// reads the whole file.
+/// Any bytes after the termination byte are ignored.
+///
+/// Returns false if the file is truncated anywhere, including in its header.
+/// Will return an error if there are any compatibility or corruption issues
+/// other than running out of data.
+pub fn is_complete(src: &[u8]) -> PcoResult<bool> {
+  let is_truncation = |e: &PcoError| matches!(e.kind, ErrorKind::InsufficientData);
+
+  let (file_decompressor, mut src) = match FileDecompressor::new(src) {
+    Ok(res) => res,
+    Err(e) if is_truncation(&e) => return Ok(false),
+    Err(e) => return Err(e),
+  };
+  loop {
+    // we can't use peek_number_type_or_termination, since it doesn't
+    // distinguish termination for files with a uniform type
+    let number_type = match src.first() {
+      None => return Ok(false),
+      Some(&MAGIC_TERMINATION_BYTE) => return Ok(true),
+      Some(&byte) => NumberType::from_descriminant(byte).ok_or_else(|| {
+        PcoError::corruption(format!(
+          "unrecognized number type byte {}",
+          byte,
+        ))
+      })?,
+    };
+
+    let skipped = match_number_enum!(
+      number_type,
+      NumberType<T> => {
+        skip_chunk::<T>(&file_decompressor, src)
+      }
+    );
+    match skipped {
+      Ok(rest) => src = rest,
+      Err(e) if is_truncation(&e) => return Ok(false),
+      Err(e) => return Err(e),
+    }
+  }
+}
+
+fn widen_64_bit_ints<T: Number>(
+  nums: Vec<T>,
+  to_f64: fn(T) -> f64,
+  is_exact: fn(T, f64) -> bool,
+  allow_lossy: bool,
+) -> PcoResult<Vec<f64>> {
+  let mut res = Vec::with_capacity(nums.len());
+  for num in nums {
+    let x = to_f64(num);
+    if !allow_lossy && !is_exact(num, x) {
+      return Err(PcoError::invalid_argument(format!(
+        "{} cannot be represented exactly as f64; consider allowing lossy conversion",
+        num,
+      )));
+    }
+    res.push(x);
+  }
+  Ok(res)
+}
+
+/// Takes in compressed bytes of any number type and returns a vector of
+/// `f64`s.
+///
+/// Floats and integers of up to 32 bits are always converted exactly.
+/// 64-bit integers outside the range of `f64`'s precision are rounded if
+/// `allow_lossy` is true, and otherwise cause an error.
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+pub fn decompress_as_f64(src: &[u8], allow_lossy: bool) -> PcoResult<Vec<f64>> {
+  let (file_decompressor, src) = FileDecompressor::new(src)?;
+  let number_type = match file_decompressor.peek_number_type_or_termination(src)? {
+    NumberTypeOrTermination::Known(number_type) => number_type,
+    NumberTypeOrTermination::Termination => return Ok(Vec::new()),
+    NumberTypeOrTermination::Unknown(byte) => {
+      return Err(PcoError::corruption(format!(
+        "unrecognized number type byte {}",
+        byte,
+      )))
+    }
+  };
+
+  use NumberType::*;
+  match number_type {
+    F16 => Ok(
+      file_decompressor
+        .simple_decompress::<half::f16>(src)?
+        .into_iter()
+        .map(half::f16::to_f64)
+        .collect(),
+    ),
+    F32 => Ok(
+      file_decompressor
+        .simple_decompress::<f32>(src)?
+        .into_iter()
+        .map(f64::from)
+        .collect(),
+    ),
+    F64 => file_decompressor.simple_decompress::<f64>(src),
+    I16 => Ok(
+      file_decompressor
+        .simple_decompress::<i16>(src)?
+        .into_iter()
+        .map(f64::from)
+        .collect(),
+    ),
+    I32 => Ok(
+      file_decompressor
+        .simple_decompress::<i32>(src)?
+        .into_iter()
+        .map(f64::from)
+        .collect(),
+    ),
+    U16 => Ok(
+      file_decompressor
+        .simple_decompress::<u16>(src)?
+        .into_iter()
+        .map(f64::from)
+        .collect(),
+    ),
+    U32 => Ok(
+      file_decompressor
+        .simple_decompress::<u32>(src)?
+        .into_iter()
+        .map(f64::from)
+        .collect(),
+    ),
+    // 2^63 and 2^64 are exactly representable, but casting them back
+    // saturates, so we need to exclude them explicitly
+    I64 => widen_64_bit_ints(
+      file_decompressor.simple_decompress::<i64>(src)?,
+      |x| x as f64,
+      |x, f| f != 9223372036854775808.0 && f as i64 == x,
+      allow_lossy,
+    ),
+    U64 => widen_64_bit_ints(
+      file_decompressor.simple_decompress::<u64>(src)?,
+      |x| x as f64,
+      |x, f| f != 18446744073709551616.0 && f as u64 == x,
+      allow_lossy,
+    ),
+  }
+}
+
 #[cfg(test)]
 mod tests {
+  use std::collections::{BTreeSet, HashSet};
+  use std::mem::size_of;
+
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
   use super::*;
   use crate::chunk_config::DeltaSpec;
+  use crate::metadata::Mode;
+  use crate::standalone::{
+    guarantee, simple_compress_fixed_range, FileDecompressorAndRest, SourceInfo,
+  };
+  use crate::DEFAULT_COMPRESSION_LEVEL;
+
+  // 1000 numbers that compress to 4 chunks of 250 with `paged_config`
+  fn squares() -> Vec<i64> {
+    (0..1000).map(|i| i * i).collect()
+  }
+
+  fn paged_config() -> ChunkConfig {
+    ChunkConfig::default().with_paging_spec(PagingSpec::EqualPagesUpTo(300))
+  }
 
   #[test]
   fn test_simple_compress_into() -> PcoResult<()> {
-    let nums = (0..100).map(|x| x as i32).collect::<Vec<_>>();
+    let nums = (0..100).collect::<Vec<i32>>();
     let config = &ChunkConfig {
       delta_spec: DeltaSpec::None,
       ..Default::default()
@@ -181,8 +1187,8 @@ mod tests {
     let mut buffer = vec![0; 1000];
     let bytes_written = simple_compress_into(&nums, config, &mut buffer)?;
     assert!(bytes_written >= 10);
-    for i in bytes_written..buffer.len() {
-      assert_eq!(buffer[i], 0);
+    for &byte in &buffer[bytes_written..] {
+      assert_eq!(byte, 0);
     }
     let decompressed = simple_decompress::<i32>(&buffer[..bytes_written])?;
     assert_eq!(decompressed, nums);
@@ -190,6 +1196,954 @@ mod tests {
     Ok(())
   }
 
+  #[test]
+  fn test_compress_within() -> PcoResult<()> {
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let nums = (0..1000).map(|_| rng.gen::<u64>()).collect::<Vec<_>>();
+    let config = &ChunkConfig::default();
+    let max_bytes = guarantee::file_size::<u64>(nums.len(), &config.paging_spec)?;
+    let compressed = compress_within(&nums, config, max_bytes)?;
+    assert!(compressed.len() <= max_bytes);
+    assert_eq!(simple_decompress::<u64>(&compressed)?, nums);
+
+    let err = compress_within(&nums, config, 4000).unwrap_err();
+    assert!(matches!(
+      err.kind,
+      ErrorKind::InvalidArgument
+    ));
+    Ok(())
+  }
+
+  #[test]
+  fn test_decompress_into_slice() -> PcoResult<()> {
+    let nums = squares();
+    let config = paged_config();
+    let compressed = simple_compress(&nums, &config)?;
+
+    let mut exact = vec![0; nums.len()];
+    assert_eq!(
+      decompress_into_slice(&compressed, &mut exact)?,
+      nums.len()
+    );
+    assert_eq!(exact, nums);
+
+    let mut long = vec![-1; nums.len() + 5];
+    assert_eq!(
+      decompress_into_slice(&compressed, &mut long)?,
+      nums.len()
+    );
+    assert_eq!(&long[..nums.len()], nums);
+    assert_eq!(&long[nums.len()..], &[-1; 5]);
+
+    let mut short = vec![0_i64; nums.len() - 1];
+    let err = decompress_into_slice(&compressed, &mut short).unwrap_err();
+    assert!(matches!(
+      err.kind,
+      ErrorKind::InvalidArgument
+    ));
+    Ok(())
+  }
+
+  #[test]
+  fn test_decompress_strided() -> PcoResult<()> {
+    // 4 pages of 250 numbers each
+    let nums = squares();
+    let config = paged_config();
+    let compressed = simple_compress(&nums, &config)?;
+
+    let stride = 256;
+    let mut dst = vec![-1; 3 * stride + 250];
+    assert_eq!(
+      decompress_strided(&compressed, stride, &mut dst)?,
+      4
+    );
+    for (page_idx, page) in nums.chunks(250).enumerate() {
+      let start = page_idx * stride;
+      assert_eq!(&dst[start..start + 250], page);
+      if page_idx < 3 {
+        assert_eq!(&dst[start + 250..start + stride], &[-1; 6]);
+      }
+    }
+
+    for (stride, dst_len) in [(0, 1000), (249, 1000), (256, 3 * 256 + 249)] {
+      let mut dst = vec![0_i64; dst_len];
+      let err = decompress_strided(&compressed, stride, &mut dst).unwrap_err();
+      assert!(matches!(
+        err.kind,
+        ErrorKind::InvalidArgument
+      ));
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_decompress_every_nth() -> PcoResult<()> {
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let nums = (0..3000)
+      .map(|_| rng.gen_range(0..1 << 14) as f32 * 0.25)
+      .collect::<Vec<_>>();
+    for delta_spec in [DeltaSpec::None, DeltaSpec::TryConsecutive(2)] {
+      let config = ChunkConfig::default()
+        .with_delta_spec(delta_spec)
+        .with_paging_spec(PagingSpec::Exact(vec![700, 1000, 1300]));
+      let compressed = simple_compress(&nums, &config)?;
+      for (start, stride) in [
+        (0, 1),
+        (0, 2),
+        (1, 2),
+        (5, 3),
+        (100, 257),
+        (699, 1000),
+        (0, 5000),
+        (2, usize::MAX),
+      ] {
+        let expected = nums
+          .iter()
+          .copied()
+          .skip(start)
+          .step_by(stride)
+          .collect::<Vec<_>>();
+        assert_eq!(
+          decompress_every_nth::<f32>(&compressed, start, stride)?,
+          expected,
+          "{:?} start={} stride={}",
+          delta_spec,
+          start,
+          stride,
+        );
+      }
+      assert!(decompress_every_nth::<f32>(&compressed, 3000, 2)?.is_empty());
+    }
+    assert!(decompress_every_nth::<f32>(
+      &simple_compress(&nums, &ChunkConfig::default())?,
+      0,
+      0
+    )
+    .is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_chunk_byte_ranges() -> PcoResult<()> {
+    let nums = squares();
+    let config = paged_config();
+    let (compressed, ranges) = Compressor::new(&config)?.compress_with_ranges(&nums)?;
+    assert_eq!(compressed, simple_compress(&nums, &config)?);
+    assert_eq!(ranges.len(), 4);
+
+    // the header, chunks, and footer tile the whole file
+    let (fd, rest) = FileDecompressor::new(compressed.as_slice())?;
+    let header_size = compressed.len() - rest.len();
+    assert_eq!(ranges[0].start, header_size);
+    for (prev, next) in ranges.iter().zip(&ranges[1..]) {
+      assert_eq!(prev.end(), next.start);
+    }
+    assert_eq!(
+      ranges.last().unwrap().end() + 1,
+      compressed.len()
+    );
+    assert_eq!(
+      header_size + ranges.iter().map(|range| range.len).sum::<usize>() + 1,
+      compressed.len()
+    );
+
+    let mut chunk_start = 0;
+    for range in &ranges {
+      let chunk = fd.decompress_chunk::<i64>(&compressed, range)?;
+      assert_eq!(
+        chunk,
+        &nums[chunk_start..chunk_start + range.n]
+      );
+      chunk_start += range.n;
+    }
+    assert_eq!(chunk_start, nums.len());
+
+    // ranges that don't exactly span a chunk
+    let mut bad_ranges = vec![ranges[1], ranges[1], ranges[1]];
+    bad_ranges[0].len += 1;
+    bad_ranges[1].start = compressed.len();
+    bad_ranges[2].n += 1;
+    for range in &bad_ranges {
+      let err = fd.decompress_chunk::<i64>(&compressed, range).unwrap_err();
+      assert!(matches!(
+        err.kind,
+        ErrorKind::InvalidArgument
+      ));
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_simple_decompress_recover() -> PcoResult<()> {
+    let nums = squares();
+    let config = ChunkConfig::default().with_paging_spec(PagingSpec::Exact(vec![250; 4]));
+    let compressed = simple_compress(&nums, &config)?;
+    assert_eq!(
+      simple_decompress_recover::<i64>(&compressed)?,
+      (nums.clone(), false)
+    );
+
+    // Every truncation should yield some whole number of 250-number chunks,
+    // and each count of chunks should be reachable.
+    let mut recovered_lens = BTreeSet::new();
+    for cut in 0..compressed.len() {
+      let (recovered, is_truncated) = simple_decompress_recover::<i64>(&compressed[..cut])?;
+      assert!(is_truncated, "cut={}", cut);
+      assert_eq!(recovered.len() % 250, 0, "cut={}", cut);
+      assert_eq!(
+        recovered,
+        nums[..recovered.len()],
+        "cut={}",
+        cut
+      );
+      recovered_lens.insert(recovered.len());
+    }
+    assert_eq!(
+      recovered_lens.into_iter().collect::<Vec<_>>(),
+      vec![0, 250, 500, 750, 1000]
+    );
+    // the last chunk is complete just before the footer
+    let (recovered, _) = simple_decompress_recover::<i64>(&compressed[..compressed.len() - 1])?;
+    assert_eq!(recovered, nums);
+    Ok(())
+  }
+
+  #[test]
+  fn test_is_complete() -> PcoResult<()> {
+    let nums = squares();
+    let config = paged_config();
+    let compressed = simple_compress(&nums, &config)?;
+    assert!(is_complete(&compressed)?);
+    for cut in 0..compressed.len() {
+      assert!(
+        !is_complete(&compressed[..cut])?,
+        "cut={}",
+        cut
+      );
+    }
+
+    // bytes after the termination byte don't matter
+    let mut padded = compressed.clone();
+    padded.extend([1, 2, 3]);
+    assert!(is_complete(&padded)?);
+
+    let empty = simple_compress::<f32>(&[], &config)?;
+    assert!(is_complete(&empty)?);
+
+    // an unknown number type byte where a chunk should start is a corruption
+    let mut corrupt = compressed[..compressed.len() - 1].to_vec();
+    corrupt.push(77);
+    assert!(matches!(
+      is_complete(&corrupt).unwrap_err().kind,
+      ErrorKind::Corruption
+    ));
+    Ok(())
+  }
+
+  #[test]
+  fn test_simple_decompress_at() -> PcoResult<()> {
+    let nums = (0..1000).map(|i| i as f32 / 7.0).collect::<Vec<_>>();
+    let config = paged_config();
+    let compressed = simple_compress(&nums, &config)?;
+
+    let mut container = vec![1, 2, 3];
+    container.extend(&compressed);
+    container.extend([4, 5, 6, 7]);
+    let (decompressed, n_bytes) = simple_decompress_at::<f32>(&container, 3)?;
+    assert_eq!(decompressed, nums);
+    assert_eq!(n_bytes, compressed.len());
+    assert_eq!(&container[3 + n_bytes..], &[4, 5, 6, 7]);
+
+    assert!(simple_decompress_at::<f32>(&container, container.len() + 1).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_manifest() -> PcoResult<()> {
+    let nums = (0..1000).map(|i| (i % 77) as f32 * 0.1).collect::<Vec<_>>();
+    let config = paged_config().with_compression_level(5);
+    let plain = simple_compress(&nums, &config)?;
+    let compressed = Compressor::new(&config)?
+      .with_manifest(true)
+      .compress(&nums)?;
+
+    let (fd, src) = FileDecompressor::new(plain.as_slice())?;
+    assert_eq!(fd.manifest(), None);
+    assert!(fd.is_lossless());
+    let chunk_meta = match fd.chunk_decompressor::<f32, _>(src)? {
+      MaybeChunkDecompressor::Some(cd) => cd.meta().clone(),
+      MaybeChunkDecompressor::EndOfData(_) => unreachable!(),
+    };
+
+    let (fd, _) = FileDecompressor::new(compressed.as_slice())?;
+    let manifest = fd.manifest().unwrap();
+    assert_eq!(manifest.compression_level, 5);
+    assert!(matches!(manifest.mode, Mode::FloatMult(_)));
+    assert_eq!(manifest.mode, chunk_meta.mode);
+    assert_eq!(
+      manifest.delta_encoding,
+      chunk_meta.delta_encoding
+    );
+
+    // the manifest doesn't change the numbers
+    assert_eq!(simple_decompress::<f32>(&compressed)?, nums);
+    assert!(compressed.len() > plain.len());
+
+    let empty = Compressor::<f32>::new(&config)?
+      .with_manifest(true)
+      .compress(&[])?;
+    let (fd, _) = FileDecompressor::new(empty.as_slice())?;
+    assert_eq!(fd.manifest(), None);
+    Ok(())
+  }
+
+  #[test]
+  fn test_lossy_flag() -> PcoResult<()> {
+    let nums = (0..100).collect::<Vec<u32>>();
+    for lossy in [false, true] {
+      let fc = FileCompressor::default().with_lossy(lossy);
+      let cc = fc.chunk_compressor(&nums, &ChunkConfig::default())?;
+      let compressed = fc.write_footer(cc.write_chunk(fc.write_header(Vec::new())?)?)?;
+      let (fd, _) = FileDecompressor::new(compressed.as_slice())?;
+      assert_eq!(fd.is_lossless(), !lossy);
+      assert_eq!(simple_decompress::<u32>(&compressed)?, nums);
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_source_info() -> PcoResult<()> {
+    let nums = (0..1000).map(|i| i % 13 - 6).collect::<Vec<i32>>();
+    let config = ChunkConfig::default();
+    let source_info = SourceInfo::new("parquet", "température");
+    for manifest in [false, true] {
+      let mut fc = FileCompressor::default().with_source_info(Some(source_info.clone()));
+      let cc = fc.chunk_compressor(&nums, &config)?;
+      if manifest {
+        fc = fc.with_manifest(Some(ChunkConfigSummary::new(
+          &config,
+          cc.meta(),
+        )));
+      }
+      let compressed = fc.write_footer(cc.write_chunk(fc.write_header(Vec::new())?)?)?;
+
+      let (fd, _) = FileDecompressor::new(compressed.as_slice())?;
+      assert_eq!(fd.source_info(), Some(&source_info));
+      assert_eq!(fd.manifest().is_some(), manifest);
+      // the source info doesn't change the numbers
+      assert_eq!(simple_decompress::<i32>(&compressed)?, nums);
+
+      let header_len = fc.write_header(Vec::new())?.len();
+      assert!(FileDecompressor::new(&compressed[..header_len - 1]).is_err());
+    }
+
+    let (fd, _) = FileDecompressor::new(simple_compress(&nums, &config)?.as_slice())?;
+    assert_eq!(fd.source_info(), None);
+
+    let too_long = SourceInfo::new("csv", "x".repeat(256));
+    let fc = FileCompressor::default().with_source_info(Some(too_long));
+    assert!(fc.write_header(Vec::new()).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_concat_compatible() -> PcoResult<()> {
+    fn header_len(src: &[u8]) -> PcoResult<usize> {
+      let (_, rest) = FileDecompressor::new(src)?;
+      Ok(src.len() - rest.len())
+    }
+
+    // different configs and number types are still compatible
+    let a_nums = (0..500).map(|i| i * 3).collect::<Vec<u32>>();
+    let b_nums = (0..300).map(|i| i as f64 * 0.5).collect::<Vec<_>>();
+    let a = simple_compress(
+      &a_nums,
+      &ChunkConfig::default().with_compression_level(2),
+    )?;
+    let b = simple_compress(
+      &b_nums,
+      &ChunkConfig::default().with_delta_spec(DeltaSpec::None),
+    )?;
+    let (a_fd, _) = FileDecompressor::new(a.as_slice())?;
+    let (b_fd, _) = FileDecompressor::new(b.as_slice())?;
+    a_fd.check_concat_compatible(&b_fd)?;
+
+    // so their chunks concatenate into a valid file
+    let mut concatenated = a[..a.len() - 1].to_vec();
+    concatenated.extend(&b[header_len(&b)?..]);
+    let (fd, rest) = FileDecompressor::new(concatenated.as_slice())?;
+    let mut decompressed_a = Vec::new();
+    let mut decompressed_b = Vec::new();
+    let rest = match fd.chunk_decompressor::<u32, _>(rest)? {
+      MaybeChunkDecompressor::Some(mut cd) => {
+        cd.decompress_remaining_extend(&mut decompressed_a)?;
+        cd.into_src()
+      }
+      MaybeChunkDecompressor::EndOfData(_) => unreachable!(),
+    };
+    let rest = match fd.chunk_decompressor::<f64, _>(rest)? {
+      MaybeChunkDecompressor::Some(mut cd) => {
+        cd.decompress_remaining_extend(&mut decompressed_b)?;
+        cd.into_src()
+      }
+      MaybeChunkDecompressor::EndOfData(_) => unreachable!(),
+    };
+    assert_eq!(decompressed_a, a_nums);
+    assert_eq!(decompressed_b, b_nums);
+    assert_eq!(rest, &[MAGIC_TERMINATION_BYTE]);
+
+    let check = |fc: FileCompressor| -> PcoResult<PcoResult<()>> {
+      let header = fc.write_header(Vec::new())?;
+      let (fd, _) = FileDecompressor::new(header.as_slice())?;
+      Ok(a_fd.check_concat_compatible(&fd))
+    };
+    assert!(check(
+      FileCompressor::default()
+        .with_n_hint(77)
+        .with_index_footer(true)
+    )?
+    .is_ok());
+    let err = check(FileCompressor::default().with_uniform_type(Some(NumberType::U32)))?;
+    assert!(err.unwrap_err().message.contains("uniform number types"));
+    let err = check(FileCompressor::default().with_annotated_chunks(true))?;
+    assert!(err.unwrap_err().message.contains("annotated chunks"));
+    let err = check(FileCompressor::default().with_lossy(true))?;
+    assert!(err.unwrap_err().message.contains("lossy flags"));
+
+    // fixed range files hold levels, which mean nothing without their range
+    let floats = (0..100).map(|i| i as f64 * 0.01).collect::<Vec<_>>();
+    let config = ChunkConfig::default();
+    let unit = simple_compress_fixed_range(&floats, &FixedRange::unit(8), &config)?;
+    let (unit_fd, _) = FileDecompressor::new(unit.as_slice())?;
+    let levels = simple_compress(&[0_u64, 255], &config)?;
+    let (levels_fd, _) = FileDecompressor::new(levels.as_slice())?;
+    assert!(unit_fd.check_concat_compatible(&levels_fd).is_err());
+    let wider = simple_compress_fixed_range(
+      &floats,
+      &FixedRange::new(0.0, 2.0, 8),
+      &config,
+    )?;
+    let (wider_fd, _) = FileDecompressor::new(wider.as_slice())?;
+    let err = unit_fd.check_concat_compatible(&wider_fd).unwrap_err();
+    assert!(err.message.contains("fixed ranges"));
+    unit_fd.check_concat_compatible(&unit_fd)?;
+
+    let mut older = b.clone();
+    older[header_len(&b)? - 1] -= 1;
+    let (older_fd, _) = FileDecompressor::new(older.as_slice())?;
+    let err = a_fd.check_concat_compatible(&older_fd).unwrap_err();
+    assert!(matches!(
+      err.kind,
+      ErrorKind::InvalidArgument
+    ));
+    assert!(err.message.contains("format versions"));
+    Ok(())
+  }
+
+  #[test]
+  fn test_tail() -> PcoResult<()> {
+    let nums = (0..1000).map(|i| i * i % 997).collect::<Vec<i64>>();
+    let config = paged_config().with_delta_spec(DeltaSpec::TryConsecutive(2));
+    for index_footer in [false, true] {
+      let compressed = Compressor::new(&config)?
+        .with_index_footer(index_footer)
+        .compress(&nums)?;
+      for n in [0, 1, 250, 251, 252, 999, 1000, 5000] {
+        assert_eq!(
+          FileDecompressor::tail::<i64>(&compressed, n)?,
+          &nums[nums.len().saturating_sub(n)..],
+          "n={} index_footer={}",
+          n,
+          index_footer,
+        );
+      }
+    }
+
+    let empty = simple_compress::<i64>(&[], &config)?;
+    assert_eq!(
+      FileDecompressor::tail::<i64>(&empty, 10)?,
+      vec![]
+    );
+    assert!(FileDecompressor::tail::<i32>(&simple_compress(&nums, &config)?, 10).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_index_footer() -> PcoResult<()> {
+    let nums = (0..1000).map(|i| i * 3).collect::<Vec<u32>>();
+    let config = paged_config();
+    let (plain, ranges) = Compressor::new(&config)?.compress_with_ranges(&nums)?;
+    let unpadded_len = Compressor::new(&config)?
+      .with_index_footer(true)
+      .compress(&nums)?
+      .len();
+    for alignment in [1, 64] {
+      let (indexed, compressed_ranges) = Compressor::new(&config)?
+        .with_index_footer(true)
+        .with_alignment(alignment)
+        .compress_with_ranges(&nums)?;
+
+      let (fd, chunks) = FileDecompressor::open_with_footer::<u32>(&indexed)?;
+      assert!(fd.has_index_footer());
+      assert_eq!(chunks, compressed_ranges);
+      let mut decompressed = Vec::new();
+      for chunk in chunks.iter().rev() {
+        decompressed.splice(
+          0..0,
+          fd.decompress_chunk::<u32>(&indexed, chunk)?,
+        );
+      }
+      assert_eq!(decompressed, nums);
+
+      // sequential decompression skips the footer
+      assert_eq!(simple_decompress::<u32>(&indexed)?, nums);
+      assert_eq!(
+        decompress_from_reader::<u32, _>(&mut indexed.as_slice())?,
+        nums
+      );
+      assert_eq!(
+        simple_decompress_at::<u32>(&indexed, 0)?.1,
+        unpadded_len
+      );
+    }
+
+    // files without the footer fall back to finding chunks in order
+    let (fd, chunks) = FileDecompressor::open_with_footer::<u32>(&plain)?;
+    assert!(!fd.has_index_footer());
+    assert_eq!(chunks, ranges);
+
+    // footers must be written with the index exactly when configured
+    let fc = FileCompressor::default().with_index_footer(true);
+    assert!(fc.write_footer(Vec::new()).is_err());
+    assert!(FileCompressor::default()
+      .write_footer_with_index(&[], Vec::new())
+      .is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_bits_per_value() -> PcoResult<()> {
+    let nums = (0..1000).map(|i| (i * 37) % 256).collect::<Vec<u32>>();
+    let config = paged_config();
+    let plain = simple_compress(&nums, &config)?;
+    let expected = (plain.len() * 8) as f64 / 1000.0;
+    assert_eq!(
+      FileDecompressor::bits_per_value(&plain)?,
+      expected
+    );
+
+    // the index footer and padding
+    let unpadded_indexed = Compressor::new(&config)?
+      .with_index_footer(true)
+      .compress(&nums)?;
+    let indexed = Compressor::new(&config)?
+      .with_index_footer(true)
+      .with_alignment(64)
+      .compress(&nums)?;
+    assert!(indexed.len() > unpadded_indexed.len());
+    assert_eq!(
+      FileDecompressor::bits_per_value(&indexed)?,
+      (unpadded_indexed.len() * 8) as f64 / 1000.0,
+    );
+
+    // chunks of mixed types, followed by unrelated bytes
+    let fc = FileCompressor::default();
+    let mut mixed = fc.write_header(Vec::new())?;
+    mixed = fc
+      .chunk_compressor(&[1_u64, 2, 3], &ChunkConfig::default())?
+      .write_chunk(mixed)?;
+    mixed = fc
+      .chunk_compressor(&[1.5_f32], &ChunkConfig::default())?
+      .write_chunk(mixed)?;
+    mixed = fc.write_footer(mixed)?;
+    let expected = (mixed.len() * 8) as f64 / 4.0;
+    mixed.extend([7, 7, 7]);
+    assert_eq!(
+      FileDecompressor::bits_per_value(&mixed)?,
+      expected
+    );
+
+    let empty = simple_compress::<u32>(&[], &ChunkConfig::default())?;
+    assert!(FileDecompressor::bits_per_value(&empty)?.is_nan());
+    Ok(())
+  }
+
+  #[test]
+  fn test_annotated_chunks() -> PcoResult<()> {
+    let nums = (0..1000).map(|i| i * 3).collect::<Vec<u32>>();
+    let config = paged_config();
+    let annotations = [0, 1, u64::MAX, 1 << 40];
+    let compressed = simple_compress_annotated(&nums, &config, &annotations)?;
+
+    let (fd, _) = FileDecompressor::new(compressed.as_slice())?;
+    assert!(fd.has_annotated_chunks());
+    assert_eq!(
+      simple_decompress_annotated::<u32>(&compressed)?,
+      (nums.clone(), annotations.to_vec())
+    );
+    // annotations don't affect decompression of the numbers
+    assert_eq!(simple_decompress::<u32>(&compressed)?, nums);
+
+    let plain = simple_compress(&nums, &config)?;
+    let (fd, _) = FileDecompressor::new(plain.as_slice())?;
+    assert!(!fd.has_annotated_chunks());
+    assert_eq!(
+      simple_decompress_annotated::<u32>(&plain)?,
+      (nums.clone(), vec![])
+    );
+
+    // annotations and a manifest can coexist
+    let compressed = Compressor::new(&config)?
+      .with_manifest(true)
+      .compress_annotated(&nums, &annotations)?;
+    let (fd, _) = FileDecompressor::new(compressed.as_slice())?;
+    assert!(fd.manifest().is_some());
+    assert_eq!(
+      simple_decompress_annotated::<u32>(&compressed)?.1,
+      annotations
+    );
+
+    let result = simple_compress_annotated(&nums, &config, &[1, 2, 3]);
+    assert!(matches!(
+      result.unwrap_err().kind,
+      ErrorKind::InvalidArgument
+    ));
+
+    // annotating chunks must agree with the file compressor
+    let chunk_config = ChunkConfig::default();
+    for (annotated_chunks, annotation) in [(true, None), (false, Some(7))] {
+      let fc = FileCompressor::default().with_annotated_chunks(annotated_chunks);
+      let result = fc
+        .chunk_compressor(&nums, &chunk_config)?
+        .with_annotation(annotation)
+        .write_chunk(Vec::new());
+      assert!(matches!(
+        result.unwrap_err().kind,
+        ErrorKind::InvalidArgument
+      ));
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_reused_compressor() -> PcoResult<()> {
+    let nums = (0..1000).collect::<Vec<i64>>();
+    let config = ChunkConfig::default().with_paging_spec(PagingSpec::EqualPagesUpTo(70));
+    let mut compressor = Compressor::new(&config)?;
+    for window in nums.chunks(300) {
+      assert_eq!(
+        compressor.compress(window)?,
+        simple_compress(window, &config)?
+      );
+    }
+
+    let invalid_config = ChunkConfig::default().with_compression_level(100);
+    assert!(Compressor::<i64>::new(&invalid_config).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_aligned_compressor() -> PcoResult<()> {
+    let config = ChunkConfig::default().with_paging_spec(PagingSpec::EqualPagesUpTo(100));
+    let mut compressor = Compressor::new(&config)?.with_alignment(512);
+    for n in [0, 1, 300, 5000] {
+      let nums = (0..n).map(|i| i as f32 * 1.5).collect::<Vec<_>>();
+      let unpadded = simple_compress(&nums, &config)?;
+      let padded = compressor.compress(&nums)?;
+      assert_eq!(padded.len() % 512, 0);
+      assert_eq!(&padded[..unpadded.len()], &unpadded);
+      assert_eq!(simple_decompress::<f32>(&padded)?, nums);
+      assert_eq!(
+        simple_decompress_at::<f32>(&padded, 0)?,
+        (nums, unpadded.len())
+      );
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_reconfigured_compressor() -> PcoResult<()> {
+    let nums = (0..1000).map(|i| i * 3).collect::<Vec<i64>>();
+    let template = Compressor::new(&ChunkConfig::default())?;
+    let mut compressor = template.clone();
+    compressor.set_level(2)?;
+    compressor.set_mode_spec(ModeSpec::Classic)?;
+    let config = ChunkConfig::default()
+      .with_compression_level(2)
+      .with_mode_spec(ModeSpec::Classic);
+    assert_eq!(
+      compressor.compress(&nums)?,
+      simple_compress(&nums, &config)?
+    );
+    assert_eq!(
+      template.config().compression_level,
+      DEFAULT_COMPRESSION_LEVEL
+    );
+
+    assert!(compressor.set_level(100).is_err());
+    assert_eq!(compressor.config().compression_level, 2);
+    Ok(())
+  }
+
+  #[test]
+  fn test_decompress_as_f64() -> PcoResult<()> {
+    let config = ChunkConfig::default();
+    let compressed = simple_compress(&[1.5_f32, -2.0, 3.25], &config)?;
+    assert_eq!(
+      decompress_as_f64(&compressed, false)?,
+      vec![1.5, -2.0, 3.25]
+    );
+
+    let compressed = simple_compress(&[u32::MAX, 0, 7], &config)?;
+    assert_eq!(
+      decompress_as_f64(&compressed, false)?,
+      vec![u32::MAX as f64, 0.0, 7.0]
+    );
+
+    let nums = vec![i64::MIN, -(1 << 53), 1 << 53];
+    let compressed = simple_compress(&nums, &config)?;
+    assert_eq!(
+      decompress_as_f64(&compressed, false)?,
+      vec![
+        -9223372036854775808.0,
+        -9007199254740992.0,
+        9007199254740992.0
+      ]
+    );
+
+    for lossy in [i64::MAX, (1 << 53) + 1] {
+      let compressed = simple_compress(&[0, lossy], &config)?;
+      assert!(decompress_as_f64(&compressed, false).is_err());
+      assert_eq!(
+        decompress_as_f64(&compressed, true)?,
+        vec![0.0, lossy as f64]
+      );
+    }
+    let compressed = simple_compress(&[u64::MAX], &config)?;
+    assert!(decompress_as_f64(&compressed, false).is_err());
+
+    let compressed = simple_compress::<u16>(&[], &config)?;
+    assert!(decompress_as_f64(&compressed, false)?.is_empty());
+    Ok(())
+  }
+
+  #[test]
+  fn test_file_decompressor_from_bytes() -> PcoResult<()> {
+    let nums = vec![1_u32, 2, 3];
+    let compressed = simple_compress(&nums, &ChunkConfig::default())?;
+    let (_, expected_rest) = FileDecompressor::new(compressed.as_slice())?;
+
+    let FileDecompressorAndRest { decompressor, rest } = FileDecompressor::from_bytes(&compressed)?;
+    assert_eq!(rest, expected_rest);
+    assert_eq!(
+      decompressor.simple_decompress::<u32>(rest)?,
+      nums
+    );
+
+    let from_try = FileDecompressorAndRest::try_from(compressed.as_slice())?;
+    assert_eq!(from_try.rest, expected_rest);
+    assert!(FileDecompressor::from_bytes(&compressed[..2]).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_single_page() -> PcoResult<()> {
+    let nums = (0..1000).collect::<Vec<u32>>();
+    let single_config = ChunkConfig::default().with_paging_spec(PagingSpec::SinglePage);
+    let single_compressed = simple_compress(&nums, &single_config)?;
+    let multi_config = paged_config();
+    let multi_compressed = simple_compress(&nums, &multi_config)?;
+    assert!(single_compressed.len() < multi_compressed.len());
+
+    let (file_decompressor, src) = FileDecompressor::new(single_compressed.as_slice())?;
+    let MaybeChunkDecompressor::Some(mut chunk_decompressor) =
+      file_decompressor.chunk_decompressor::<u32, _>(src)?
+    else {
+      panic!("expected a chunk");
+    };
+    assert_eq!(chunk_decompressor.n(), nums.len());
+    let mut decompressed = Vec::new();
+    chunk_decompressor.decompress_remaining_extend(&mut decompressed)?;
+    assert_eq!(decompressed, nums);
+    assert!(matches!(
+      file_decompressor.chunk_decompressor::<u32, _>(chunk_decompressor.into_src())?,
+      MaybeChunkDecompressor::EndOfData(_),
+    ));
+
+    assert!(simple_compress::<u32>(&[], &single_config)?.len() < 20);
+    let too_many = vec![0_u16; (1 << 24) + 1];
+    assert!(simple_compress(&too_many, &single_config).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_get() -> PcoResult<()> {
+    let nums = (0..1000).map(|x| x * 3 - 7).collect::<Vec<i64>>();
+    let config = paged_config();
+    let compressed = simple_compress(&nums, &config)?;
+    let (file_decompressor, src) = FileDecompressor::new(compressed.as_slice())?;
+    for i in [0, 1, 255, 256, 299, 300, 511, 750, 999] {
+      assert_eq!(
+        file_decompressor.get::<i64>(src, i)?,
+        nums[i],
+        "i={}",
+        i
+      );
+    }
+    assert!(file_decompressor.get::<i64>(src, 1000).is_err());
+
+    let (file_decompressor, ranges) = FileDecompressor::open_with_footer::<i64>(&compressed)?;
+    for i in [0, 299, 300, 999] {
+      assert_eq!(
+        file_decompressor.get_with_ranges::<i64>(&compressed, &ranges, i)?,
+        nums[i],
+      );
+    }
+    assert!(file_decompressor
+      .get_with_ranges::<i64>(&compressed, &ranges, 1000)
+      .is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_compress_sorted() -> PcoResult<()> {
+    let nums = (0..10_000_i64)
+      .map(|i| 1_700_000_000 + i * 60 + i % 7)
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default();
+    let compressed = compress_sorted(&nums, &config)?;
+    let consecutive_config = config.clone().with_delta_spec(DeltaSpec::TryConsecutive(1));
+    assert_eq!(
+      compressed,
+      simple_compress(&nums, &consecutive_config)?
+    );
+    assert_eq!(simple_decompress::<i64>(&compressed)?, nums);
+
+    let floats = [-1.0, -0.0, 0.0, 0.0, 2.5];
+    let compressed = compress_sorted(&floats, &config)?;
+    assert_eq!(
+      simple_decompress::<f64>(&compressed)?,
+      floats
+    );
+    Ok(())
+  }
+
+  #[cfg(debug_assertions)]
+  #[test]
+  fn test_compress_sorted_rejects_unsorted() {
+    let err = compress_sorted(&[1_u32, 3, 2], &ChunkConfig::default()).unwrap_err();
+    assert!(matches!(
+      err.kind,
+      ErrorKind::InvalidArgument
+    ));
+    assert!(err.message.contains("index 2"));
+    assert!(compress_sorted(&[0.0, -0.0], &ChunkConfig::default()).is_err());
+  }
+
+  #[test]
+  fn test_chunk_iter() -> PcoResult<()> {
+    let nums = (0..1000)
+      .map(|x| (x * 7919 % 1000) as f32 * 0.5)
+      .collect::<Vec<_>>();
+    let compressed = simple_compress(&nums, &ChunkConfig::default())?;
+    let (fd, src) = FileDecompressor::new(compressed.as_slice())?;
+    let MaybeChunkDecompressor::Some(mut cd) = fd.chunk_decompressor::<f32, _>(src)? else {
+      panic!("expected a chunk");
+    };
+    let sum = cd
+      .iter()
+      .map(|num| num.map(|num| num * 2.0))
+      .try_fold(0.0, |sum, num| num.map(|num| sum + num))?;
+    assert_eq!(sum, nums.iter().sum::<f32>() * 2.0);
+    assert!(cd.iter().next().is_none());
+
+    // errors end iteration
+    let truncated = &compressed[..compressed.len() / 2];
+    let (fd, src) = FileDecompressor::new(truncated)?;
+    let MaybeChunkDecompressor::Some(mut cd) = fd.chunk_decompressor::<f32, _>(src)? else {
+      panic!("expected a chunk");
+    };
+    let mut iter = cd.iter();
+    assert!(iter.by_ref().any(|num| num.is_err()));
+    assert!(iter.next().is_none());
+    Ok(())
+  }
+
+  #[test]
+  fn test_compress_to_writer() -> PcoResult<()> {
+    let nums = (0..1000).collect::<Vec<u32>>();
+    let config = paged_config();
+    let mut dst = Vec::new();
+    compress_to_writer(&nums, &config, &mut dst)?;
+    assert_eq!(dst, simple_compress(&nums, &config)?);
+    assert_eq!(simple_decompress::<u32>(&dst)?, nums);
+
+    let mut compressor = Compressor::new(&config)?.with_alignment(64);
+    let written = compressor.compress_to_writer(&nums, Vec::new())?;
+    assert_eq!(written, compressor.compress(&nums)?);
+    Ok(())
+  }
+
+  // returns at most 3 bytes per read
+  struct TrickleReader<'a>(&'a [u8]);
+
+  impl std::io::Read for TrickleReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+      let n = min(min(buf.len(), 3), self.0.len());
+      buf[..n].copy_from_slice(&self.0[..n]);
+      self.0 = &self.0[n..];
+      Ok(n)
+    }
+  }
+
+  #[test]
+  fn test_decompress_from_reader() -> PcoResult<()> {
+    let nums = (0..1000).map(|x| x * 7 % 300).collect::<Vec<u32>>();
+    let config = paged_config();
+    let compressed = simple_compress(&nums, &config)?;
+
+    let mut src = std::io::BufReader::with_capacity(5, TrickleReader(&compressed));
+    assert_eq!(
+      decompress_from_reader::<u32, _>(&mut src)?,
+      nums
+    );
+
+    let mut src = std::io::BufReader::with_capacity(5, TrickleReader(&compressed));
+    let pages = decompress_pages_from_reader::<u32, _>(&mut src)?.collect::<PcoResult<Vec<_>>>()?;
+    assert_eq!(
+      pages.iter().map(Vec::len).collect::<Vec<_>>(),
+      vec![250; 4]
+    );
+    assert_eq!(pages.concat(), nums);
+
+    // truncated files yield their whole pages, then an error
+    let mut truncated = &compressed[..compressed.len() - 20];
+    let mut pages = decompress_pages_from_reader::<u32, _>(&mut truncated)?;
+    for _ in 0..3 {
+      assert_eq!(pages.next().unwrap()?.len(), 250);
+    }
+    assert!(pages.next().unwrap().is_err());
+    assert!(pages.next().is_none());
+    Ok(())
+  }
+
+  #[test]
+  fn test_simple_decompress_into_uninit() -> PcoResult<()> {
+    let nums = (0..1000).map(|x| x as f32 * 0.5).collect::<Vec<f32>>();
+    let config = paged_config();
+    let src = simple_compress(&nums, &config)?;
+
+    for dst_n in [0, 299, 512, 1000, 1001] {
+      let mut dst = Vec::<f32>::with_capacity(dst_n);
+      let n = simple_decompress_into_uninit(&src, dst.spare_capacity_mut())?;
+      assert_eq!(n, min(dst_n, nums.len()));
+      unsafe { dst.set_len(n) };
+      assert_eq!(dst, &nums[..n], "dst_n={}", dst_n);
+    }
+
+    Ok(())
+  }
+
   #[test]
   fn test_simple_decompress_into() -> PcoResult<()> {
     let max_n = 600;
@@ -220,4 +2174,36 @@ mod tests {
 
     Ok(())
   }
+
+  #[test]
+  fn test_decompress_windowed() -> PcoResult<()> {
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let nums = (0..100_000)
+      .map(|_| rng.gen_range(0..1 << 24))
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default().with_paging_spec(PagingSpec::EqualPagesUpTo(30_000));
+    let src = simple_compress(&nums, &config)?;
+
+    for window in [1, 100, 256, 1000, 4096, 200_000] {
+      let mut decompressed = Vec::new();
+      let mut buffer_ptrs = HashSet::new();
+      let n = decompress_windowed(&src, window, |window_nums: &[u32]| {
+        assert!(!window_nums.is_empty() && window_nums.len() <= window);
+        decompressed.extend_from_slice(window_nums);
+        buffer_ptrs.insert(window_nums.as_ptr() as usize);
+      })?;
+      assert_eq!(n, nums.len());
+      assert_eq!(decompressed, nums, "window={}", window);
+
+      // every window is a view into the same buffer of at most
+      // max(window, 256) numbers
+      let buffer_len = window.max(FULL_BATCH_N);
+      let min_ptr = *buffer_ptrs.iter().min().unwrap();
+      let max_ptr = *buffer_ptrs.iter().max().unwrap();
+      assert!(max_ptr - min_ptr < buffer_len * size_of::<u32>());
+    }
+
+    assert!(decompress_windowed::<u32>(&src, 0, |_| {}).is_err());
+    Ok(())
+  }
 }
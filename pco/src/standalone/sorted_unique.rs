@@ -0,0 +1,128 @@
+use crate::chunk_config::ChunkConfig;
+use crate::data_types::{Latent, Number};
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::simple::{simple_compress, simple_decompress};
+use crate::standalone::transform_header::Transform;
+
+/// Takes in strictly increasing numbers and an exact configuration and
+/// returns compressed bytes.
+///
+/// This is meant for sets of unique integers, e.g. the row ids of a bitmap
+/// index.
+/// Since every gap between consecutive numbers is at least 1, we store the
+/// first number followed by each gap minus 1, so dense ranges become runs
+/// of 0s that compress to almost nothing.
+/// Floats are ordered by their latent representation, in which -0.0 comes
+/// before 0.0.
+///
+/// Decompress with [`decompress_sorted_unique`].
+/// In debug builds, will return an invalid argument error if the numbers
+/// aren't strictly increasing; in release builds, such numbers still
+/// round-trip, but compress worse.
+/// Will return an error if the compressor config is invalid.
+pub fn compress_sorted_unique<T: Number>(nums: &[T], config: &ChunkConfig) -> PcoResult<Vec<u8>> {
+  let mut gaps = Vec::with_capacity(nums.len());
+  let mut prev: Option<T::L> = None;
+  for (i, num) in nums.iter().enumerate() {
+    let latent = num.to_latent_ordered();
+    let gap = match prev {
+      Some(prev) => {
+        if cfg!(debug_assertions) && latent <= prev {
+          return Err(PcoError::invalid_argument(format!(
+            "numbers must be strictly increasing, but number {} did not increase",
+            i,
+          )));
+        }
+        latent.wrapping_sub(prev).wrapping_sub(T::L::ONE)
+      }
+      None => latent,
+    };
+    gaps.push(gap.to_u64());
+    prev = Some(latent);
+  }
+  let mut res = Transform::SortedUnique.header();
+  res.extend(simple_compress(&gaps, config)?);
+  Ok(res)
+}
+
+/// Takes in compressed bytes (as produced by [`compress_sorted_unique`])
+/// and returns the strictly increasing numbers.
+///
+/// Will return an error if there are any compatibility, corruption, or
+/// insufficient data issues.
+pub fn decompress_sorted_unique<T: Number>(src: &[u8]) -> PcoResult<Vec<T>> {
+  let src = Transform::SortedUnique.read_header(src)?;
+  let gaps = simple_decompress::<u64>(src)?;
+  let mut res = Vec::with_capacity(gaps.len());
+  let mut prev: Option<T::L> = None;
+  for gap in gaps {
+    let gap = T::L::from_u64(gap);
+    let latent = match prev {
+      Some(prev) => prev.wrapping_add(gap).wrapping_add(T::L::ONE),
+      None => gap,
+    };
+    res.push(T::from_latent_ordered(latent));
+    prev = Some(latent);
+  }
+  Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  use super::*;
+
+  #[test]
+  fn test_dense_range() -> PcoResult<()> {
+    let nums = (0..1_000_000_u32).collect::<Vec<_>>();
+    let compressed = compress_sorted_unique(&nums, &ChunkConfig::default())?;
+    assert!(compressed.len() < 100);
+    assert_eq!(
+      decompress_sorted_unique::<u32>(&compressed)?,
+      nums
+    );
+
+    let compressed = compress_sorted_unique::<i64>(&[], &ChunkConfig::default())?;
+    assert_eq!(
+      decompress_sorted_unique::<i64>(&compressed)?,
+      vec![]
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_sparse_set() -> PcoResult<()> {
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let mut num = i64::MIN;
+    let mut nums = Vec::new();
+    for _ in 0..10_000 {
+      nums.push(num);
+      num += rng.gen_range(1..1_000_000);
+    }
+    nums.push(i64::MAX);
+    let compressed = compress_sorted_unique(&nums, &ChunkConfig::default())?;
+    assert_eq!(
+      decompress_sorted_unique::<i64>(&compressed)?,
+      nums
+    );
+
+    let nums = [-1.5_f32, -0.0, 0.0, f32::MIN_POSITIVE, 7.0];
+    let compressed = compress_sorted_unique(&nums, &ChunkConfig::default())?;
+    assert_eq!(
+      decompress_sorted_unique::<f32>(&compressed)?,
+      nums
+    );
+    Ok(())
+  }
+
+  #[test]
+  #[cfg(debug_assertions)]
+  fn test_not_increasing() {
+    let config = ChunkConfig::default();
+    assert!(compress_sorted_unique(&[1_u32, 2, 2, 3], &config).is_err());
+    assert!(compress_sorted_unique(&[5_i32, -1], &config).is_err());
+  }
+}
@@ -0,0 +1,73 @@
+use std::io::Write;
+
+use crate::bit_reader::BitReader;
+use crate::bit_writer::BitWriter;
+use crate::errors::{PcoError, PcoResult};
+
+/// A record of where a file's numbers came from, which can be stored in its
+/// header via
+/// [`FileCompressor::with_source_info`][crate::standalone::FileCompressor::with_source_info].
+///
+/// This lets tools recreate the original file sensibly, e.g. by writing a
+/// Parquet column with the original name.
+/// It is purely informational and doesn't affect decompression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SourceInfo {
+  /// The format the numbers were read from, e.g. "parquet" or "csv".
+  pub format: String,
+  /// The name of the column the numbers were read from.
+  pub name: String,
+}
+
+// Each string is stored as a 1-byte length followed by its UTF-8 bytes.
+pub(crate) fn max_byte_size() -> usize {
+  2 * (1 + SourceInfo::MAX_STRING_LEN)
+}
+
+unsafe fn read_string(reader: &mut BitReader, field: &str) -> PcoResult<String> {
+  let len = reader.read_aligned_bytes(1)?[0] as usize;
+  let bytes = reader.read_aligned_bytes(len)?;
+  String::from_utf8(bytes.to_vec()).map_err(|_| {
+    PcoError::corruption(format!(
+      "source info {} is not valid UTF-8",
+      field
+    ))
+  })
+}
+
+fn write_string<W: Write>(s: &str, field: &str, writer: &mut BitWriter<W>) -> PcoResult<()> {
+  if s.len() > SourceInfo::MAX_STRING_LEN {
+    return Err(PcoError::invalid_argument(format!(
+      "source info {} may not exceed {} bytes (was {})",
+      field,
+      SourceInfo::MAX_STRING_LEN,
+      s.len(),
+    )));
+  }
+  writer.write_aligned_bytes(&[s.len() as u8])?;
+  writer.write_aligned_bytes(s.as_bytes())
+}
+
+impl SourceInfo {
+  /// The maximum byte length of each string.
+  pub const MAX_STRING_LEN: usize = u8::MAX as usize;
+
+  pub fn new(format: impl Into<String>, name: impl Into<String>) -> Self {
+    Self {
+      format: format.into(),
+      name: name.into(),
+    }
+  }
+
+  pub(crate) unsafe fn read_from(reader: &mut BitReader) -> PcoResult<Self> {
+    let format = read_string(reader, "format")?;
+    let name = read_string(reader, "name")?;
+    Ok(Self { format, name })
+  }
+
+  pub(crate) fn write_to<W: Write>(&self, writer: &mut BitWriter<W>) -> PcoResult<()> {
+    write_string(&self.format, "format", writer)?;
+    write_string(&self.name, "name", writer)
+  }
+}
@@ -0,0 +1,155 @@
+use std::time::{Duration, Instant};
+
+use crate::chunk_config::{ChunkConfig, DeltaSpec, ModeSpec};
+use crate::data_types::Number;
+use crate::errors::PcoResult;
+use crate::standalone::simple::simple_compress;
+use crate::wrapped;
+
+// Compression levels to escalate through, up to the config's level.
+const ESCALATION_LEVELS: [usize; 3] = [0, 3, 6];
+// We assume each attempt takes at most this many times as long as the
+// previous one, and only start it if that would fit in the budget.
+const ESCALATION_COST_FACTOR: u32 = 3;
+
+// the configs to try, from least to most analysis effort
+fn escalation(config: &ChunkConfig) -> Vec<ChunkConfig> {
+  // skip mode and delta analysis, leaving explicit specs as they are
+  let mut minimal = config.clone().with_compression_level(0);
+  if minimal.mode_spec == ModeSpec::Auto {
+    minimal.mode_spec = ModeSpec::Classic;
+  }
+  if minimal.delta_spec == DeltaSpec::Auto {
+    minimal.delta_spec = DeltaSpec::None;
+  }
+
+  let mut res = vec![minimal];
+  for level in ESCALATION_LEVELS {
+    if level < config.compression_level {
+      res.push(config.clone().with_compression_level(level));
+    }
+  }
+  res.push(config.clone());
+  res
+}
+
+/// Takes in a slice of numbers, an exact configuration, and a wall-clock
+/// time budget and returns the smallest compressed bytes found within the
+/// budget.
+///
+/// This starts with a fast compression that skips mode and delta encoding
+/// analysis (unless the config specifies them) at compression level 0, then
+/// escalates through lower-effort variants of the config up to the config
+/// itself, starting each attempt only if it looks likely to finish within
+/// the budget.
+/// The first attempt always runs, so the result is always a valid .pco file,
+/// and the budget can be exceeded when even it is too slow.
+/// Attempts aren't interrupted, so the budget is estimated rather than
+/// enforced, but it is typically respected to within one attempt's
+/// misprediction.
+///
+/// Because it depends on timing, the output is nondeterministic: it can
+/// differ across machines, or across runs on a busy one.
+/// Will return an error if the compressor config is invalid.
+pub fn compress_within_time<T: Number>(
+  nums: &[T],
+  config: &ChunkConfig,
+  budget: Duration,
+) -> PcoResult<Vec<u8>> {
+  wrapped::validate_config(config)?;
+  let start = Instant::now();
+
+  let mut best: Option<Vec<u8>> = None;
+  let mut last_duration = Duration::ZERO;
+  for attempt_config in escalation(config) {
+    if best.is_some() && start.elapsed() + last_duration * ESCALATION_COST_FACTOR > budget {
+      break;
+    }
+
+    let attempt_start = Instant::now();
+    let compressed = simple_compress(nums, &attempt_config)?;
+    last_duration = attempt_start.elapsed();
+    if best
+      .as_ref()
+      .map_or(true, |best| compressed.len() < best.len())
+    {
+      best = Some(compressed);
+    }
+  }
+  Ok(best.unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::standalone::simple_decompress;
+
+  fn nums() -> Vec<i64> {
+    // a noisy sawtooth of multiples of 3, which benefits from analysis
+    (0..200_000_i64)
+      .map(|i| 3 * ((i % 1000) * 17 + ((i * 0x9E3779B9) >> 20) % 64))
+      .collect()
+  }
+
+  #[test]
+  fn test_zero_and_unlimited_budgets() -> PcoResult<()> {
+    let nums = nums();
+    let config = ChunkConfig::default();
+
+    // only the minimal attempt runs
+    let compressed = compress_within_time(&nums, &config, Duration::ZERO)?;
+    assert_eq!(
+      compressed,
+      simple_compress(&nums, &escalation(&config)[0])?
+    );
+    assert_eq!(simple_decompress::<i64>(&compressed)?, nums);
+
+    // every attempt runs, including the config itself
+    let compressed = compress_within_time(&nums, &config, Duration::MAX)?;
+    assert!(compressed.len() <= simple_compress(&nums, &config)?.len());
+    assert!(compressed.len() < simple_compress(&nums, &escalation(&config)[0])?.len());
+    assert_eq!(simple_decompress::<i64>(&compressed)?, nums);
+    Ok(())
+  }
+
+  #[test]
+  fn test_moderate_budget() -> PcoResult<()> {
+    let nums = nums();
+    let config = ChunkConfig::default().with_compression_level(12);
+    let minimal_start = Instant::now();
+    simple_compress(&nums, &escalation(&config)[0])?;
+    let minimal_duration = minimal_start.elapsed();
+
+    let budget = minimal_duration * 4;
+    // elapsed time depends on the machine's load, so we don't assert on it
+    let compressed = compress_within_time(&nums, &config, budget)?;
+    assert_eq!(simple_decompress::<i64>(&compressed)?, nums);
+    Ok(())
+  }
+
+  #[test]
+  fn test_escalation() {
+    let levels = |config: &ChunkConfig| {
+      escalation(config)
+        .iter()
+        .map(|config| config.compression_level)
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(
+      levels(&ChunkConfig::default()),
+      vec![0, 0, 3, 6, 8]
+    );
+    assert_eq!(
+      levels(&ChunkConfig::default().with_compression_level(1)),
+      vec![0, 0, 1]
+    );
+
+    let config = ChunkConfig::default().with_delta_spec(DeltaSpec::TryConsecutive(2));
+    let minimal = &escalation(&config)[0];
+    assert_eq!(
+      minimal.delta_spec,
+      DeltaSpec::TryConsecutive(2)
+    );
+    assert_eq!(minimal.mode_spec, ModeSpec::Classic);
+  }
+}
@@ -0,0 +1,149 @@
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::constants::{CURRENT_TRANSFORM_VERSION, TRANSFORM_MAGIC};
+
+// Every transformed file starts with this header:
+// * the magic
+// * the transform version
+// * the transform that wrote it
+// followed by whatever the transform stores, which usually includes one or
+// more standalone files.
+pub(crate) const TRANSFORM_HEADER_SIZE: usize = TRANSFORM_MAGIC.len() + 2;
+
+// The preprocessing steps that wrap standalone files in their own format.
+// Their bytes are part of the format, so never reuse or reorder them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Transform {
+  Equalization = 1,
+  BitTranspose = 2,
+  Sequential = 3,
+  PeriodicMask = 4,
+  FixedPoint = 5,
+  Rle = 6,
+  SortedUnique = 7,
+  Permuted = 8,
+  SignedOrder = 9,
+}
+
+impl Transform {
+  fn from_byte(byte: u8) -> Option<Self> {
+    use Transform::*;
+    [
+      Equalization,
+      BitTranspose,
+      Sequential,
+      PeriodicMask,
+      FixedPoint,
+      Rle,
+      SortedUnique,
+      Permuted,
+      SignedOrder,
+    ]
+    .into_iter()
+    .find(|&transform| transform as u8 == byte)
+  }
+
+  // the function that compresses with this transform, for error messages
+  fn compress_fn_name(self) -> &'static str {
+    match self {
+      Transform::Equalization => "simple_compress_equalized",
+      Transform::BitTranspose => "simple_compress_bit_transposed",
+      Transform::Sequential => "simple_compress_sequential",
+      Transform::PeriodicMask => "simple_compress_periodic_masked",
+      Transform::FixedPoint => "simple_compress_fixed_point",
+      Transform::Rle => "compress_rle",
+      Transform::SortedUnique => "compress_sorted_unique",
+      Transform::Permuted => "simple_compress_permuted",
+      Transform::SignedOrder => "simple_compress_with_signed_order",
+    }
+  }
+
+  // Returns the start of a file written by this transform, to which the
+  // transform appends its data.
+  pub(crate) fn header(self) -> Vec<u8> {
+    let mut res = TRANSFORM_MAGIC.to_vec();
+    res.push(CURRENT_TRANSFORM_VERSION);
+    res.push(self as u8);
+    res
+  }
+
+  // Checks that the file was written by this transform and returns the
+  // transform's data after the header.
+  pub(crate) fn read_header(self, src: &[u8]) -> PcoResult<&[u8]> {
+    if src.len() < TRANSFORM_HEADER_SIZE {
+      return Err(PcoError::insufficient_data(
+        "transform header was truncated",
+      ));
+    }
+    let (header, rest) = src.split_at(TRANSFORM_HEADER_SIZE);
+    let magic = &header[..TRANSFORM_MAGIC.len()];
+    if magic != TRANSFORM_MAGIC {
+      return Err(PcoError::corruption(format!(
+        "transform magic does not match {:?}; instead found {:?}",
+        TRANSFORM_MAGIC, magic,
+      )));
+    }
+    let version = header[TRANSFORM_MAGIC.len()];
+    if version > CURRENT_TRANSFORM_VERSION {
+      return Err(PcoError::compatibility(format!(
+        "file's transform version ({}) exceeds max supported ({}); consider upgrading pco",
+        version, CURRENT_TRANSFORM_VERSION,
+      )));
+    }
+    let byte = header[TRANSFORM_MAGIC.len() + 1];
+    match Self::from_byte(byte) {
+      Some(transform) if transform == self => Ok(rest),
+      Some(transform) => Err(PcoError::corruption(format!(
+        "file was written by {}, not {}",
+        transform.compress_fn_name(),
+        self.compress_fn_name(),
+      ))),
+      None => Err(PcoError::corruption(format!(
+        "unknown transform byte {}",
+        byte
+      ))),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::errors::ErrorKind;
+
+  #[test]
+  fn test_transform_header() -> PcoResult<()> {
+    let mut src = Transform::Rle.header();
+    src.push(77);
+    assert_eq!(src.len(), TRANSFORM_HEADER_SIZE + 1);
+    assert_eq!(Transform::Rle.read_header(&src)?, &[77]);
+
+    let kind = |src: &[u8]| Transform::Rle.read_header(src).unwrap_err().kind;
+    assert!(matches!(
+      kind(&src[..TRANSFORM_HEADER_SIZE - 1]),
+      ErrorKind::InsufficientData
+    ));
+    assert!(matches!(
+      kind(&Transform::Permuted.header()),
+      ErrorKind::Corruption
+    ));
+    let mut wrong_magic = src.clone();
+    wrong_magic[0] = b'x';
+    assert!(matches!(
+      kind(&wrong_magic),
+      ErrorKind::Corruption
+    ));
+    let mut newer = src.clone();
+    newer[TRANSFORM_MAGIC.len()] += 1;
+    assert!(matches!(
+      kind(&newer),
+      ErrorKind::Compatibility
+    ));
+    let mut unknown = src;
+    unknown[TRANSFORM_MAGIC.len() + 1] = 0;
+    assert!(matches!(
+      kind(&unknown),
+      ErrorKind::Corruption
+    ));
+    Ok(())
+  }
+}
@@ -0,0 +1,195 @@
+use crate::data_types::{Latent, Number, NumberType};
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::decompressor::{FileDecompressor, MaybeChunkDecompressor};
+use crate::standalone::fixed_point::{from_f64, to_f64};
+use crate::FULL_BATCH_N;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Widening {
+  Unsigned,
+  Signed,
+  Float {
+    src_type: NumberType,
+    dst_type: NumberType,
+  },
+}
+
+fn widening(src: NumberType, dst: NumberType) -> Option<Widening> {
+  use NumberType::*;
+  match (src, dst) {
+    (U16, U32 | U64) | (U32, U64) => Some(Widening::Unsigned),
+    (I16, I32 | I64) | (I32, I64) => Some(Widening::Signed),
+    (F16, F32 | F64) | (F32, F64) => Some(Widening::Float {
+      src_type: src,
+      dst_type: dst,
+    }),
+    _ => None,
+  }
+}
+
+fn widen<Src: Number, Dst: Number>(num: Src, widening: Widening) -> Dst {
+  let latent = num.to_latent_ordered().to_u64();
+  match widening {
+    Widening::Unsigned => Dst::from_latent_ordered(Dst::L::from_u64(latent)),
+    // Signed latents are offset by half their range, so we swap the offset.
+    Widening::Signed => Dst::from_latent_ordered(Dst::L::from_u64(
+      latent
+        .wrapping_sub(Src::L::MID.to_u64())
+        .wrapping_add(Dst::L::MID.to_u64()),
+    )),
+    // Every narrower float is exactly representable as an f64.
+    Widening::Float { src_type, dst_type } => from_f64(to_f64(num, src_type), dst_type),
+  }
+}
+
+/// Takes in compressed bytes of `Src`s and returns the numbers widened
+/// to `Dst`.
+///
+/// `Dst` must be a strictly wider type of the same kind, e.g. `i16` to
+/// `i32` or `i64`, `u32` to `u64`, or `f16` to `f32` or `f64`, so the
+/// widening is always lossless.
+/// Numbers are widened as each batch is decompressed, so no intermediate
+/// vector of `Src`s is allocated.
+///
+/// Will return an invalid argument error if `Dst` isn't a lossless widening
+/// of `Src`, a corruption error if the file's chunks aren't of `Src`s, or
+/// an error if there are any other compatibility, corruption, or
+/// insufficient data issues.
+pub fn decompress_widened<Src: Number, Dst: Number>(src: &[u8]) -> PcoResult<Vec<Dst>> {
+  let number_type = |byte| {
+    NumberType::from_descriminant(byte)
+      .ok_or_else(|| PcoError::invalid_argument(format!("unknown number type byte {}", byte)))
+  };
+  let (src_type, dst_type) = (
+    number_type(Src::NUMBER_TYPE_BYTE)?,
+    number_type(Dst::NUMBER_TYPE_BYTE)?,
+  );
+  let Some(widening) = widening(src_type, dst_type) else {
+    return Err(PcoError::invalid_argument(format!(
+      "{:?} is not a lossless widening of {:?}",
+      dst_type, src_type,
+    )));
+  };
+
+  let (file_decompressor, mut src) = FileDecompressor::new(src)?;
+  let mut res = Vec::with_capacity(file_decompressor.n_hint());
+  let mut batch = vec![Src::default(); FULL_BATCH_N];
+  while let MaybeChunkDecompressor::Some(mut chunk_decompressor) =
+    file_decompressor.chunk_decompressor::<Src, _>(src)?
+  {
+    res.reserve(chunk_decompressor.n());
+    loop {
+      let progress = chunk_decompressor.decompress(&mut batch)?;
+      res.extend(
+        batch[..progress.n_processed]
+          .iter()
+          .map(|&num| widen::<Src, Dst>(num, widening)),
+      );
+      if progress.finished {
+        break;
+      }
+    }
+    src = chunk_decompressor.into_src();
+  }
+  Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+  use half::f16;
+
+  use super::*;
+  use crate::chunk_config::ChunkConfig;
+  use crate::errors::ErrorKind;
+  use crate::standalone::simple_compress;
+  use crate::PagingSpec;
+
+  #[test]
+  fn test_widen_ints() -> PcoResult<()> {
+    let nums = (0..3000_i32)
+      .map(|i| ((i * 97) % 65536 - 32768) as i16)
+      .chain([i16::MIN, -1, 0, 1, i16::MAX])
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default().with_paging_spec(PagingSpec::EqualPagesUpTo(1000));
+    let compressed = simple_compress(&nums, &config)?;
+    assert_eq!(
+      decompress_widened::<i16, i64>(&compressed)?,
+      nums.iter().map(|&x| x as i64).collect::<Vec<_>>(),
+    );
+    assert_eq!(
+      decompress_widened::<i16, i32>(&compressed)?,
+      nums.iter().map(|&x| x as i32).collect::<Vec<_>>(),
+    );
+
+    let nums = [0, 1, u32::MAX / 2, u32::MAX / 2 + 1, u32::MAX];
+    let compressed = simple_compress(&nums, &config)?;
+    assert_eq!(
+      decompress_widened::<u32, u64>(&compressed)?,
+      nums.iter().map(|&x| x as u64).collect::<Vec<_>>(),
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_widen_floats() -> PcoResult<()> {
+    let nums = [
+      -0.0,
+      0.0,
+      1.5,
+      -2.25e-5,
+      6e4,
+      f32::INFINITY,
+      f32::MIN_POSITIVE,
+    ];
+    let config = ChunkConfig::default();
+    let compressed = simple_compress(&nums, &config)?;
+    let widened = decompress_widened::<f32, f64>(&compressed)?;
+    assert_eq!(
+      widened.iter().map(|x| x.to_bits()).collect::<Vec<_>>(),
+      nums
+        .iter()
+        .map(|&x| (x as f64).to_bits())
+        .collect::<Vec<_>>(),
+    );
+
+    let nums = nums.map(f16::from_f32);
+    let compressed = simple_compress(&nums, &config)?;
+    assert_eq!(
+      decompress_widened::<f16, f32>(&compressed)?,
+      nums.iter().map(|x| x.to_f32()).collect::<Vec<_>>(),
+    );
+    Ok(())
+  }
+
+  fn kind<T>(result: PcoResult<T>) -> ErrorKind {
+    result.err().unwrap().kind
+  }
+
+  #[test]
+  fn test_widen_errors() -> PcoResult<()> {
+    let compressed = simple_compress(&[1_i32, -2], &ChunkConfig::default())?;
+    // not widenings
+    assert!(matches!(
+      kind(decompress_widened::<i32, i32>(&compressed)),
+      ErrorKind::InvalidArgument
+    ));
+    assert!(matches!(
+      kind(decompress_widened::<i32, u64>(&compressed)),
+      ErrorKind::InvalidArgument
+    ));
+    assert!(matches!(
+      kind(decompress_widened::<i32, f64>(&compressed)),
+      ErrorKind::InvalidArgument
+    ));
+    // not the stored dtype
+    assert!(matches!(
+      kind(decompress_widened::<i16, i64>(&compressed)),
+      ErrorKind::Corruption
+    ));
+    assert!(matches!(
+      kind(decompress_widened::<u32, u64>(&compressed)),
+      ErrorKind::Corruption
+    ));
+    Ok(())
+  }
+}
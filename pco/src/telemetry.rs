@@ -0,0 +1,119 @@
+// Stage timings are recorded into a thread local, similar to how
+// `sampling` records sample indices, so that we don't need to thread extra
+// state through every compression function. When the `telemetry` feature is
+// off, `time` just calls the function.
+
+#[cfg(feature = "telemetry")]
+use std::cell::RefCell;
+#[cfg(feature = "telemetry")]
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Stage {
+  Mode,
+  Delta,
+  BinOptimization,
+}
+
+/// Nanosecond timings of each stage of compressing a chunk.
+///
+/// Only available with the `telemetry` feature.
+#[cfg(feature = "telemetry")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChunkTelemetry {
+  /// Time spent choosing the mode and splitting numbers into latents.
+  pub mode_nanos: u64,
+  /// Time spent choosing a delta encoding and delta encoding the latents.
+  pub delta_nanos: u64,
+  /// Time spent building histograms, optimizing bins, and quantizing ANS
+  /// weights.
+  pub bin_optimization_nanos: u64,
+  /// Time spent ANS encoding and bit packing pages, summed over all pages
+  /// written so far.
+  pub entropy_coding_nanos: u64,
+}
+
+#[cfg(feature = "telemetry")]
+impl ChunkTelemetry {
+  fn add(&mut self, stage: Stage, nanos: u64) {
+    let total = match stage {
+      Stage::Mode => &mut self.mode_nanos,
+      Stage::Delta => &mut self.delta_nanos,
+      Stage::BinOptimization => &mut self.bin_optimization_nanos,
+    };
+    *total += nanos;
+  }
+}
+
+#[cfg(feature = "telemetry")]
+#[derive(Default)]
+struct Recorder {
+  telemetry: ChunkTelemetry,
+  // Stages can nest (e.g. choosing a delta encoding involves optimizing bins
+  // for a sample), in which case we attribute all the time to the outermost.
+  depth: usize,
+}
+
+#[cfg(feature = "telemetry")]
+thread_local! {
+  static RECORDER: RefCell<Recorder> = RefCell::new(Recorder::default());
+}
+
+#[cfg(feature = "telemetry")]
+#[inline]
+pub fn time<R, F: FnOnce() -> R>(stage: Stage, f: F) -> R {
+  RECORDER.with_borrow_mut(|recorder| recorder.depth += 1);
+  let start = Instant::now();
+  let res = f();
+  let nanos = start.elapsed().as_nanos() as u64;
+  RECORDER.with_borrow_mut(|recorder| {
+    recorder.depth -= 1;
+    if recorder.depth == 0 {
+      recorder.telemetry.add(stage, nanos);
+    }
+  });
+  res
+}
+
+#[cfg(not(feature = "telemetry"))]
+#[inline(always)]
+pub fn time<R, F: FnOnce() -> R>(_stage: Stage, f: F) -> R {
+  f()
+}
+
+// Runs f, returning the stage timings recorded during it.
+#[cfg(feature = "telemetry")]
+pub fn capture<R, F: FnOnce() -> R>(f: F) -> (R, ChunkTelemetry) {
+  let outer = RECORDER.with_borrow_mut(std::mem::take);
+  let res = f();
+  let telemetry = RECORDER.with_borrow_mut(|recorder| std::mem::replace(recorder, outer).telemetry);
+  (res, telemetry)
+}
+
+// Pages can be written concurrently from a shared `ChunkCompressor`, so we
+// accumulate their timings atomically.
+#[cfg(feature = "telemetry")]
+#[derive(Debug, Default)]
+pub struct AtomicNanos(std::sync::atomic::AtomicU64);
+
+#[cfg(feature = "telemetry")]
+impl AtomicNanos {
+  pub fn add_since(&self, start: Instant) {
+    let nanos = start.elapsed().as_nanos() as u64;
+    self
+      .0
+      .fetch_add(nanos, std::sync::atomic::Ordering::Relaxed);
+  }
+
+  pub fn get(&self) -> u64 {
+    self.0.load(std::sync::atomic::Ordering::Relaxed)
+  }
+}
+
+#[cfg(feature = "telemetry")]
+impl Clone for AtomicNanos {
+  fn clone(&self) -> Self {
+    Self(std::sync::atomic::AtomicU64::new(self.get()))
+  }
+}
@@ -185,7 +185,7 @@ fn v0_4_0_lookback_delta() -> PcoResult<()> {
   let name = "lookback_delta";
 
   // randomly generated ahead of time
-  let nums: Vec<u32> = vec![
+  let nums: Vec<u32> = [
     1121827092, 729032807, 3968137854, 2875434067, 3775328080, 431649926, 1048116090, 1906978350,
     14752788, 1180462487,
   ]
@@ -0,0 +1,165 @@
+use half::f16;
+use rand::Rng;
+use rand_xoshiro::rand_core::SeedableRng;
+use rand_xoshiro::Xoroshiro128PlusPlus;
+
+use crate::chunk_config::{ChunkConfig, DeltaSpec};
+use crate::data_types::Number;
+use crate::errors::{ErrorKind, PcoResult};
+use crate::metadata::Mode;
+use crate::standalone::{simple_decompress, FileCompressor};
+use crate::ModeSpec;
+
+const SPECS: [ModeSpec; 11] = [
+  ModeSpec::Classic,
+  ModeSpec::TryIntMult(0),
+  ModeSpec::TryIntMult(1),
+  ModeSpec::TryIntMult(7),
+  ModeSpec::TryIntMult(1 << 40),
+  ModeSpec::TryFloatMult(0.0),
+  ModeSpec::TryFloatMult(0.1),
+  ModeSpec::TryFloatMult(f64::INFINITY),
+  ModeSpec::TryFloatQuant(0),
+  ModeSpec::TryFloatQuant(3),
+  ModeSpec::TryFloatQuant(60),
+];
+
+// Returns the mode the chunk used, or None if the spec was rejected.
+fn check_forced<T: Number>(nums: &[T], mode_spec: ModeSpec) -> PcoResult<Option<Mode>> {
+  let config = ChunkConfig::default()
+    .with_mode_spec(mode_spec)
+    .with_delta_spec(DeltaSpec::None);
+  let fc = FileCompressor::default();
+  let cc = match fc.chunk_compressor(nums, &config) {
+    Ok(cc) => cc,
+    Err(e) => {
+      assert!(
+        matches!(e.kind, ErrorKind::InvalidArgument),
+        "{:?}: {}",
+        mode_spec,
+        e
+      );
+      return Ok(None);
+    }
+  };
+  let mode = cc.meta().mode;
+  let keys = cc
+    .meta()
+    .per_latent_var
+    .as_ref()
+    .enumerated()
+    .into_iter()
+    .map(|(key, _)| key)
+    .collect::<Vec<_>>();
+  assert_eq!(
+    keys,
+    mode.latent_var_keys(),
+    "{:?}",
+    mode_spec
+  );
+  let mut compressed = Vec::new();
+  fc.write_header(&mut compressed)?;
+  cc.write_chunk(&mut compressed)?;
+  fc.write_footer(&mut compressed)?;
+
+  let decompressed = simple_decompress::<T>(&compressed)?;
+  assert_eq!(
+    decompressed
+      .iter()
+      .map(|x| x.to_latent_ordered())
+      .collect::<Vec<_>>(),
+    nums
+      .iter()
+      .map(|x| x.to_latent_ordered())
+      .collect::<Vec<_>>(),
+    "{:?}",
+    mode_spec,
+  );
+  Ok(Some(mode))
+}
+
+fn check_all_specs<T: Number>(nums: &[T], is_float: bool, max_base: u64) -> PcoResult<()> {
+  for mode_spec in SPECS {
+    let mode = check_forced(nums, mode_spec)?;
+    let expected_valid = match mode_spec {
+      ModeSpec::Classic => true,
+      ModeSpec::TryIntMult(base) => !is_float && (1..=max_base).contains(&base),
+      ModeSpec::TryFloatMult(base) => is_float && base.is_finite() && base != 0.0,
+      ModeSpec::TryFloatQuant(k) => is_float && (1..=10).contains(&k),
+      _ => unreachable!(),
+    };
+    assert_eq!(
+      mode.is_some(),
+      expected_valid,
+      "{:?}",
+      mode_spec
+    );
+
+    // forced modes may only fall back to classic
+    if let Some(mode) = mode {
+      let matches_spec = match (mode_spec, mode) {
+        (ModeSpec::TryIntMult(_), Mode::IntMult(_)) => true,
+        (ModeSpec::TryFloatMult(_), Mode::FloatMult(_)) => true,
+        (ModeSpec::TryFloatQuant(k), Mode::FloatQuant(mode_k)) => k == mode_k,
+        _ => false,
+      };
+      assert!(
+        mode == Mode::Classic || matches_spec,
+        "{:?} gave {:?}",
+        mode_spec,
+        mode
+      );
+    }
+  }
+  Ok(())
+}
+
+#[test]
+fn test_forced_modes_int() -> PcoResult<()> {
+  let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+  let ints = (0..2000)
+    .map(|_| rng.gen_range(0..1024_u64) * 7)
+    .collect::<Vec<_>>();
+  check_all_specs(
+    &ints.iter().map(|&x| x as u16).collect::<Vec<_>>(),
+    false,
+    u16::MAX as u64,
+  )?;
+  check_all_specs(
+    &ints.iter().map(|&x| x as u32).collect::<Vec<_>>(),
+    false,
+    u32::MAX as u64,
+  )?;
+  check_all_specs(&ints, false, u64::MAX)?;
+  check_all_specs(
+    &ints.iter().map(|&x| x as i32 - 3000).collect::<Vec<_>>(),
+    false,
+    u32::MAX as u64,
+  )?;
+  check_all_specs(
+    &ints.iter().map(|&x| x as i64 - 3000).collect::<Vec<_>>(),
+    false,
+    u64::MAX,
+  )
+}
+
+#[test]
+fn test_forced_modes_float() -> PcoResult<()> {
+  // f16 has 10 mantissa bits, so float quant is valid up to k=10 for all
+  // float types tested here
+  let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+  let floats = (0..2000)
+    .map(|_| rng.gen_range(0..1024) as f64 * 0.1 - 50.0)
+    .collect::<Vec<_>>();
+  check_all_specs(
+    &floats.iter().map(|&x| f16::from_f64(x)).collect::<Vec<_>>(),
+    true,
+    0,
+  )?;
+  check_all_specs(
+    &floats.iter().map(|&x| x as f32).collect::<Vec<_>>(),
+    true,
+    0,
+  )?;
+  check_all_specs(&floats, true, 0)
+}
@@ -3,11 +3,13 @@ use std::fs::File;
 use std::io::Write;
 
 use better_io::{BetterBufRead, BetterBufReader};
+use rand::Rng;
+use rand_xoshiro::rand_core::SeedableRng;
 
 use crate::chunk_config::{ChunkConfig, DeltaSpec};
 use crate::errors::PcoResult;
 use crate::wrapped::{FileCompressor, FileDecompressor, PageDecompressor};
-use crate::{PagingSpec, FULL_BATCH_N};
+use crate::{PagingSpec, CURRENT_FORMAT_VERSION, FULL_BATCH_N};
 
 struct Chunk {
   nums: Vec<u32>,
@@ -37,7 +39,7 @@ fn decompress_by_batch<R: BetterBufRead>(
 }
 
 fn test_wrapped_compress<W: Write>(chunks: &[Chunk], dst: W) -> PcoResult<W> {
-  let fc = FileCompressor::default();
+  let fc = FileCompressor::default().with_format_version(CURRENT_FORMAT_VERSION);
   let mut dst = fc.write_header(dst)?;
 
   for chunk in chunks {
@@ -78,6 +80,24 @@ fn test_wrapped_decompress<R: BetterBufRead>(chunks: &[Chunk], src: R) -> PcoRes
   Ok(())
 }
 
+// a piecewise linear signal that later turns into noise
+fn piecewise_linear_then_noisy() -> Vec<u32> {
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut nums = Vec::new();
+  let mut x = 1_u32 << 31;
+  for _ in 0..20 {
+    let slope = rng.gen_range(-1000_i32..1000);
+    for _ in 0..500 {
+      x = x.wrapping_add_signed(slope);
+      nums.push(x);
+    }
+  }
+  for _ in 0..10000 {
+    nums.push(rng.gen_range(0..1 << 21));
+  }
+  nums
+}
+
 fn test_wrapped(chunks: &[Chunk]) -> PcoResult<()> {
   // IN MEMORY
   let mut compressed = Vec::new();
@@ -125,5 +145,184 @@ fn test_low_level_wrapped() -> PcoResult<()> {
         ..Default::default()
       },
     },
+    Chunk {
+      nums: piecewise_linear_then_noisy(),
+      config: ChunkConfig {
+        delta_spec: DeltaSpec::TryConsecutivePerPage(2),
+        paging_spec: PagingSpec::EqualPagesUpTo(2000),
+        ..Default::default()
+      },
+    },
   ])
 }
+
+#[test]
+fn test_low_level_per_page_delta_size() -> PcoResult<()> {
+  let nums = piecewise_linear_then_noisy();
+  let compressed_size = |delta_spec| -> PcoResult<usize> {
+    let chunk = Chunk {
+      nums: nums.clone(),
+      config: ChunkConfig {
+        delta_spec,
+        paging_spec: PagingSpec::EqualPagesUpTo(2000),
+        ..Default::default()
+      },
+    };
+    Ok(test_wrapped_compress(&[chunk], Vec::new())?.len())
+  };
+
+  // neither a single order for the whole chunk nor auto should do as well as
+  // choosing the order per page
+  let per_page_size = compressed_size(DeltaSpec::TryConsecutivePerPage(2))?;
+  for delta_spec in [
+    DeltaSpec::None,
+    DeltaSpec::TryConsecutive(1),
+    DeltaSpec::TryConsecutive(2),
+    DeltaSpec::Auto,
+  ] {
+    let size = compressed_size(delta_spec)?;
+    assert!(
+      per_page_size < size,
+      "{:?}: {} vs {}",
+      delta_spec,
+      per_page_size,
+      size,
+    );
+  }
+  Ok(())
+}
+
+#[test]
+fn test_low_level_page_random_access() -> PcoResult<()> {
+  let nums = (0..3000_u32).map(|i| i * i).collect::<Vec<_>>();
+  let config = ChunkConfig {
+    delta_spec: DeltaSpec::TryConsecutive(2),
+    paging_spec: PagingSpec::EqualPagesUpTo(500),
+    ..Default::default()
+  };
+  let fc = FileCompressor::default();
+  let header = fc.write_header(Vec::new())?;
+  let cc = fc.chunk_compressor(&nums, &config)?;
+  let chunk_meta = cc.write_chunk_meta(Vec::new())?;
+  let n_per_page = cc.n_per_page();
+  let pages = (0..n_per_page.len())
+    .map(|page_idx| cc.write_page(page_idx, Vec::new()))
+    .collect::<PcoResult<Vec<_>>>()?;
+
+  // decompress a page in the middle from only its own bytes
+  let (fd, _) = FileDecompressor::new(header.as_slice())?;
+  let (cd, _) = fd.chunk_decompressor::<u32, _>(chunk_meta.as_slice())?;
+  let page_idx = 4;
+  let page_n = n_per_page[page_idx];
+  let page_start = n_per_page[..page_idx].iter().sum::<usize>();
+  let mut pd = cd.page_decompressor(pages[page_idx].as_slice(), page_n)?;
+  let page_nums = decompress_by_batch(&mut pd, page_n)?;
+  assert_eq!(
+    page_nums,
+    &nums[page_start..page_start + page_n]
+  );
+  Ok(())
+}
+
+#[test]
+fn test_low_level_page_first_values() -> PcoResult<()> {
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let multiples = (0..3000)
+    .map(|_| rng.gen_range(0..1000_u32) * 7 + 3)
+    .collect::<Vec<_>>();
+  let datasets = [
+    (
+      piecewise_linear_then_noisy(),
+      DeltaSpec::Auto,
+    ),
+    (
+      piecewise_linear_then_noisy(),
+      DeltaSpec::TryConsecutive(2),
+    ),
+    (
+      piecewise_linear_then_noisy(),
+      DeltaSpec::TryConsecutivePerPage(1),
+    ),
+    (
+      piecewise_linear_then_noisy(),
+      DeltaSpec::TryLookback,
+    ),
+    (
+      piecewise_linear_then_noisy(),
+      DeltaSpec::TryXor,
+    ),
+    (multiples, DeltaSpec::None),
+  ];
+  for (nums, delta_spec) in datasets {
+    let config = ChunkConfig {
+      delta_spec,
+      paging_spec: PagingSpec::EqualPagesUpTo(700),
+      ..Default::default()
+    };
+    let fc = FileCompressor::default().with_format_version(CURRENT_FORMAT_VERSION);
+    let header = fc.write_header(Vec::new())?;
+    let cc = fc.chunk_compressor(&nums, &config)?;
+    let chunk_meta = cc.write_chunk_meta(Vec::new())?;
+    let n_per_page = cc.n_per_page();
+    let pages = (0..n_per_page.len())
+      .map(|page_idx| cc.write_page(page_idx, Vec::new()))
+      .collect::<PcoResult<Vec<_>>>()?;
+
+    let (fd, _) = FileDecompressor::new(header.as_slice())?;
+    let (cd, _) = fd.chunk_decompressor::<u32, _>(chunk_meta.as_slice())?;
+    let page_srcs = pages
+      .iter()
+      .zip(&n_per_page)
+      .map(|(page, &page_n)| (page.as_slice(), page_n))
+      .collect::<Vec<_>>();
+    let mut page_start = 0;
+    let expected = n_per_page
+      .iter()
+      .map(|&page_n| {
+        let first = nums[page_start];
+        page_start += page_n;
+        first
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      cd.page_first_values(&page_srcs)?,
+      expected,
+      "{:?}",
+      delta_spec
+    );
+  }
+  Ok(())
+}
+
+#[test]
+fn test_low_level_format_version() -> PcoResult<()> {
+  let nums = (0..1000_u32).collect::<Vec<_>>();
+  let header = FileCompressor::default().write_header(Vec::new())?;
+  assert_eq!(
+    FileDecompressor::new(header.as_slice())?.0.format_version(),
+    3
+  );
+
+  for version in [2, CURRENT_FORMAT_VERSION + 1] {
+    let fc = FileCompressor::default().with_format_version(version);
+    assert!(fc.write_header(Vec::new()).is_err());
+    assert!(fc.chunk_compressor(&nums, &ChunkConfig::default()).is_err());
+  }
+
+  // newer delta encodings need a newer format version
+  for delta_spec in [DeltaSpec::TryXor, DeltaSpec::TryConsecutivePerPage(1)] {
+    let config = ChunkConfig::default().with_delta_spec(delta_spec);
+    assert!(FileCompressor::default()
+      .chunk_compressor(&nums, &config)
+      .is_err());
+    let fc = FileCompressor::default().with_format_version(CURRENT_FORMAT_VERSION);
+    let cc = fc.chunk_compressor(&nums, &config)?;
+    let fc = fc.with_oldest_format_version(&config, cc.meta());
+    let header = fc.write_header(Vec::new())?;
+    assert_eq!(
+      FileDecompressor::new(header.as_slice())?.0.format_version(),
+      4
+    );
+  }
+  Ok(())
+}
@@ -1,5 +1,7 @@
 mod compatibility;
+mod forced_modes;
 mod low_level;
+mod parallel;
 mod recovery;
 mod stability;
 mod stack_sizes;
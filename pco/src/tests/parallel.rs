@@ -0,0 +1,160 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use rand_xoshiro::rand_core::SeedableRng;
+use rand_xoshiro::Xoroshiro128PlusPlus;
+
+use crate::chunk_config::{ChunkConfig, DeltaSpec};
+use crate::data_types::Number;
+use crate::errors::PcoResult;
+use crate::standalone::{
+  simple_compress, simple_compress_parallel, simple_decompress, simple_decompress_parallel,
+  FileDecompressor, ParallelConfig,
+};
+use crate::{ModeSpec, PagingSpec};
+
+// always parallel, given at least 2 chunks
+fn parallel_config(n_threads: usize) -> ParallelConfig {
+  ParallelConfig::default()
+    .with_n_threads(n_threads)
+    .with_min_chunks_for_parallel(2)
+}
+
+fn check_parallel<T: Number>(nums: &[T], config: &ChunkConfig) -> PcoResult<()> {
+  let compressed = simple_compress(nums, config)?;
+  for n_threads in [1, 3] {
+    assert_eq!(
+      simple_compress_parallel(nums, config, &parallel_config(n_threads))?,
+      compressed,
+      "n_threads={}",
+      n_threads
+    );
+  }
+  let serial = simple_decompress::<T>(&compressed)?;
+  let (fd, src) = FileDecompressor::new(compressed.as_slice())?;
+  for n_threads in [0, 1, 2, 5] {
+    let mut dst = vec![T::default(); nums.len() + 3];
+    let n = fd.decompress_parallel(src, &parallel_config(n_threads), &mut dst)?;
+    assert_eq!(n, nums.len());
+    assert_eq!(
+      &dst[..n],
+      serial.as_slice(),
+      "n_threads={}",
+      n_threads
+    );
+  }
+  assert_eq!(
+    simple_decompress_parallel::<T>(&compressed, &parallel_config(3))?,
+    serial
+  );
+
+  let mut too_short = vec![T::default(); nums.len() - 1];
+  assert!(fd
+    .decompress_parallel(src, &parallel_config(2), &mut too_short)
+    .is_err());
+  Ok(())
+}
+
+fn paging() -> PagingSpec {
+  PagingSpec::Exact(vec![1000, 2001, 1, 257, 3000])
+}
+
+#[test]
+fn test_parallel_matches_serial() -> PcoResult<()> {
+  let n = 6259;
+  let ints = (0..n)
+    .map(|i| ((i * 7919) % 1013) as i64 - 500)
+    .collect::<Vec<_>>();
+  check_parallel(
+    &ints,
+    &ChunkConfig::default().with_paging_spec(paging()),
+  )?;
+
+  let mults = ints.iter().map(|&x| x * 1000 + x % 3).collect::<Vec<_>>();
+  check_parallel(
+    &mults,
+    &ChunkConfig::default()
+      .with_mode_spec(ModeSpec::TryIntMult(1000))
+      .with_paging_spec(paging()),
+  )?;
+
+  let floats = ints
+    .iter()
+    .map(|&x| x as f64 * 0.1 + 3.0)
+    .collect::<Vec<_>>();
+  check_parallel(
+    &floats,
+    &ChunkConfig::default().with_paging_spec(paging()),
+  )?;
+
+  let repeats = (0..n).map(|i| (i % 50) as u32).collect::<Vec<_>>();
+  for delta_spec in [
+    DeltaSpec::None,
+    DeltaSpec::TryConsecutive(2),
+    DeltaSpec::TryLookback,
+  ] {
+    check_parallel(
+      &repeats,
+      &ChunkConfig::default()
+        .with_delta_spec(delta_spec)
+        .with_paging_spec(paging()),
+    )?;
+  }
+
+  let constant = vec![7_u16; n];
+  check_parallel(
+    &constant,
+    &ChunkConfig::default().with_paging_spec(paging()),
+  )?;
+  Ok(())
+}
+
+fn fastest_of_3(mut f: impl FnMut() -> PcoResult<()>) -> PcoResult<Duration> {
+  let mut best = Duration::MAX;
+  for _ in 0..3 {
+    let start = Instant::now();
+    f()?;
+    best = best.min(start.elapsed());
+  }
+  Ok(best)
+}
+
+// This measures wall-clock time, so it's too noisy to run by default.
+#[test]
+#[ignore = "timing-dependent; run with --ignored on an idle machine"]
+fn test_parallel_throughput() -> PcoResult<()> {
+  let n_threads = thread::available_parallelism().map_or(1, |n| n.get());
+  let n = 1 << 20;
+  let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+  let nums = (0..n)
+    .map(|_| rng.gen_range(0..1_i64 << 24))
+    .collect::<Vec<_>>();
+  let config = ChunkConfig::default().with_paging_spec(PagingSpec::EqualPagesUpTo(1 << 16));
+  let compressed = simple_compress(&nums, &config)?;
+  let (fd, src) = FileDecompressor::new(compressed.as_slice())?;
+
+  let mut dst = vec![0; n];
+  let serial = fastest_of_3(|| {
+    fd.decompress_parallel(src, &parallel_config(1), &mut dst)?;
+    Ok(())
+  })?;
+  let parallel = fastest_of_3(|| {
+    fd.decompress_parallel(src, &parallel_config(n_threads), &mut dst)?;
+    Ok(())
+  })?;
+  assert_eq!(dst, nums);
+
+  // Skipping through the file to find chunks stays serial, so we only expect
+  // a clear win given several cores.
+  if n_threads >= 4 {
+    assert!(
+      parallel < serial,
+      "{:?} with {} threads vs {:?} serial",
+      parallel,
+      n_threads,
+      serial,
+    );
+  }
+  Ok(())
+}
@@ -4,24 +4,29 @@ use rand_xoshiro::rand_core::SeedableRng;
 
 use crate::chunk_config::{ChunkConfig, DeltaSpec};
 use crate::constants::Bitlen;
-use crate::data_types::Number;
+use crate::data_types::{Float, Number};
 use crate::errors::PcoResult;
 use crate::metadata::{ChunkMeta, DeltaEncoding, DynLatent, Mode};
-use crate::standalone::{simple_compress, simple_decompress, FileCompressor};
-use crate::ModeSpec;
+use crate::standalone::{simple_compress, simple_decompress, FileCompressor, FileDecompressor};
+use crate::{ModeSpec, CURRENT_FORMAT_VERSION};
 
 fn compress_w_meta<T: Number>(nums: &[T], config: &ChunkConfig) -> PcoResult<(Vec<u8>, ChunkMeta)> {
   let mut compressed = Vec::new();
-  let fc = FileCompressor::default();
-  fc.write_header(&mut compressed)?;
+  let fc = FileCompressor::default().with_format_version(CURRENT_FORMAT_VERSION);
   let cd = fc.chunk_compressor(nums, config)?;
   let meta = cd.meta().clone();
+  let fc = fc.with_oldest_format_version(config, &meta);
+  fc.write_header(&mut compressed)?;
   cd.write_chunk(&mut compressed)?;
   fc.write_footer(&mut compressed)?;
 
   Ok((compressed, meta))
 }
 
+fn format_version(compressed: &[u8]) -> PcoResult<u8> {
+  Ok(FileDecompressor::new(compressed)?.0.format_version())
+}
+
 fn assert_nums_eq<T: Number>(decompressed: &[T], expected: &[T], name: &str) -> PcoResult<()> {
   let debug_info = format!("name={}", name,);
   // We can't do assert_eq on the whole vector because even bitwise identical
@@ -108,10 +113,7 @@ fn test_moderate_data() -> PcoResult<()> {
 
 #[test]
 fn test_sparse() -> PcoResult<()> {
-  let mut v = Vec::new();
-  for _ in 0..10000 {
-    v.push(1);
-  }
+  let mut v = vec![1; 10000];
   v.push(0);
   v.push(0);
   v.push(1);
@@ -266,6 +268,46 @@ fn test_64_bit_offsets() -> PcoResult<()> {
   recover_with_alternating_nums(64, "64 bit offsets")
 }
 
+#[test]
+fn test_soft_max_offset_bits() -> PcoResult<()> {
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  // needs to be large enough that the extra bin metadata is cheap
+  let nums = (0..1 << 17)
+    .map(|_| {
+      if rng.gen_bool(0.5) {
+        rng.gen::<u64>()
+      } else {
+        rng.gen_range(0..16)
+      }
+    })
+    .collect::<Vec<_>>();
+
+  // high compression level so there are enough fine bins to choose from
+  let config = ChunkConfig::default()
+    .with_compression_level(12)
+    .with_delta_spec(DeltaSpec::None);
+  let (_, meta) = compress_w_meta(&nums, &config)?;
+  let bins = meta
+    .per_latent_var
+    .primary
+    .bins
+    .downcast_ref::<u64>()
+    .unwrap();
+  assert!(bins.iter().any(|bin| bin.offset_bits > 57));
+
+  let config = config.with_soft_max_offset_bits(Some(57));
+  let (compressed, meta) = compress_w_meta(&nums, &config)?;
+  let bins = meta
+    .per_latent_var
+    .primary
+    .bins
+    .downcast_ref::<u64>()
+    .unwrap();
+  assert!(bins.iter().all(|bin| bin.offset_bits <= 57));
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "soft max offset bits")
+}
+
 #[test]
 fn test_with_int_mult() -> PcoResult<()> {
   let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
@@ -289,6 +331,42 @@ fn test_with_int_mult() -> PcoResult<()> {
   Ok(())
 }
 
+#[test]
+fn test_latent_compression_levels() -> PcoResult<()> {
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut nums = Vec::new();
+  for _ in 0..1000 {
+    nums.push(rng.gen_range(0..1000_u32) * 1000 + rng.gen_range(0..4));
+  }
+  let (compressed, meta) = compress_w_meta(
+    &nums,
+    &ChunkConfig::default()
+      .with_mode_spec(ModeSpec::TryIntMult(1000))
+      .with_delta_spec(DeltaSpec::None)
+      .with_latent_compression_levels(vec![None, Some(0)]),
+  )?;
+  assert_eq!(
+    meta.mode,
+    Mode::IntMult(DynLatent::U32(1000_u32))
+  );
+  let secondary = meta.per_latent_var.secondary.as_ref().unwrap();
+  assert_eq!(
+    secondary.bins.downcast_ref::<u32>().unwrap().len(),
+    1
+  );
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(
+    &decompressed,
+    &nums,
+    "latent compression levels",
+  )?;
+
+  // too many levels
+  let config = ChunkConfig::default().with_latent_compression_levels(vec![None; 3]);
+  assert!(simple_compress(&nums, &config).is_err());
+  Ok(())
+}
+
 #[test]
 fn test_sparse_islands() -> PcoResult<()> {
   let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
@@ -376,3 +454,368 @@ fn test_lookback_delta_encoding() -> PcoResult<()> {
   assert_nums_eq(&decompressed, &nums, "trivial_first_latent")?;
   Ok(())
 }
+
+#[test]
+fn test_constant_chunk() -> PcoResult<()> {
+  let nums = vec![-7.5_f64; 1 << 20];
+  let (compressed, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert!(meta.is_constant());
+  assert_eq!(meta.mode, Mode::Classic);
+  assert!(compressed.len() < 100);
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "constant")?;
+
+  // explicit specs are still honored
+  let config = ChunkConfig::default()
+    .with_mode_spec(ModeSpec::TryFloatMult(2.5))
+    .with_delta_spec(DeltaSpec::TryLookback);
+  let (compressed, meta) = compress_w_meta(&nums, &config)?;
+  assert_eq!(meta.mode, Mode::float_mult(2.5_f64));
+  assert!(matches!(
+    meta.delta_encoding,
+    DeltaEncoding::Lookback(_)
+  ));
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "constant w/ specs")?;
+
+  let mut nums = nums;
+  nums[77] = 0.0;
+  let (_, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert!(!meta.is_constant());
+  Ok(())
+}
+
+#[test]
+fn test_analysis_seed() -> PcoResult<()> {
+  let nums = (0..10_000_u64)
+    .map(|i| (i * 7919 % 1000) * 8)
+    .collect::<Vec<_>>();
+  let mut compresseds = Vec::new();
+  for seed in [0, 1, 0] {
+    let config = ChunkConfig::default().with_analysis_seed(seed);
+    let (compressed, meta) = compress_w_meta(&nums, &config)?;
+    assert_eq!(meta.mode, Mode::int_mult(8_u64));
+    let decompressed = simple_decompress(&compressed)?;
+    assert_nums_eq(&decompressed, &nums, "seeded")?;
+    compresseds.push(compressed);
+  }
+  assert_eq!(compresseds[0], compresseds[2]);
+  Ok(())
+}
+
+#[test]
+fn test_linear_chunk() -> PcoResult<()> {
+  let nums = (0..1_i64 << 20).map(|i| 3 * i).collect::<Vec<_>>();
+  let (compressed, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert!(meta.is_linear());
+  assert!(!meta.is_constant());
+  assert_eq!(meta.mode, Mode::Classic);
+  assert!(compressed.len() < 100);
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "linear")?;
+
+  // a decreasing sequence that wraps around the latent space
+  let nums = (0..1000_u32)
+    .map(|i| 5_u32.wrapping_sub(7 * i))
+    .collect::<Vec<_>>();
+  let (compressed, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert!(meta.is_linear());
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "wrapping linear")?;
+
+  let mut nums = (0..1000_i64).map(|i| 3 * i).collect::<Vec<_>>();
+  nums[500] += 1;
+  let (_, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert!(!meta.is_linear());
+  Ok(())
+}
+
+#[test]
+fn test_all_zero_delta_chunk() -> PcoResult<()> {
+  let consecutive_order = |meta: &ChunkMeta| match meta.delta_encoding {
+    DeltaEncoding::Consecutive(config) => config.order,
+    _ => 0,
+  };
+
+  // a million row numbers, across several chunks
+  let nums = (0..1_000_000_i64).map(|i| 5 * i - 77).collect::<Vec<_>>();
+  let compressed = simple_compress(&nums, &ChunkConfig::default())?;
+  assert!(compressed.len() < 300);
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "million linear")?;
+
+  // a quadratic sequence has all-zero 3rd order deltas
+  let nums = (0..10_000_i64).map(|i| i * i - 3 * i).collect::<Vec<_>>();
+  let (compressed, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert!(meta.is_constant_after_delta());
+  assert!(!meta.is_linear());
+  assert_eq!(consecutive_order(&meta), 2);
+  assert!(compressed.len() < 100);
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "quadratic")?;
+
+  let config = ChunkConfig::default().with_delta_spec(DeltaSpec::TryConsecutive(4));
+  let (compressed, meta) = compress_w_meta(&nums, &config)?;
+  assert!(meta.is_constant_after_delta());
+  assert_eq!(consecutive_order(&meta), 4);
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "quadratic at order 4")?;
+
+  let config = ChunkConfig::default().with_delta_spec(DeltaSpec::TryConsecutive(1));
+  let (_, meta) = compress_w_meta(&nums, &config)?;
+  assert!(!meta.is_constant_after_delta());
+
+  let mut nums = nums;
+  nums[5000] += 1;
+  let (_, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert!(!meta.is_constant_after_delta());
+  Ok(())
+}
+
+#[test]
+fn test_wrapping_counter() -> PcoResult<()> {
+  // nanosecond timestamps with jitter that roll over once mid-chunk
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let n = 100_000;
+  let start = u64::MAX - 1000 * (n as u64 / 2);
+  let mut counter = start;
+  let nums = (0..n)
+    .map(|_| {
+      counter = counter.wrapping_add(rng.gen_range(990..1010));
+      counter
+    })
+    .collect::<Vec<_>>();
+  assert!(nums[0] > nums[n - 1]);
+
+  for delta_spec in [DeltaSpec::Auto, DeltaSpec::TryConsecutive(1)] {
+    let config = ChunkConfig::default().with_delta_spec(delta_spec);
+    let (compressed, meta) = compress_w_meta(&nums, &config)?;
+    assert!(matches!(
+      meta.delta_encoding,
+      DeltaEncoding::Consecutive(_)
+    ));
+    // about 5 bits of jitter per number
+    assert!(compressed.len() < n * 6 / 8);
+    let decompressed = simple_decompress(&compressed)?;
+    assert_nums_eq(&decompressed, &nums, "wrapping counter")?;
+
+    // the rollover costs about the same as any other delta
+    let unwrapped = nums
+      .iter()
+      .map(|x| x.wrapping_sub(start))
+      .collect::<Vec<_>>();
+    let unwrapped_size = simple_compress(&unwrapped, &config)?.len();
+    assert!(compressed.len() <= unwrapped_size + 8);
+  }
+  Ok(())
+}
+
+#[test]
+fn test_xor_delta_encoding() -> PcoResult<()> {
+  // a CPU utilization-like trace: a slow random walk of busy percentages,
+  // whose low mantissa bits are noise from averaging many samples
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut busy = 50.0_f64;
+  let mut nums = Vec::new();
+  for _ in 0..10000 {
+    busy = (busy + rng.gen_range(-0.01..0.01)).clamp(1.0, 99.0);
+    let noise = rng.gen::<u64>() >> 20;
+    nums.push(f64::from_bits(
+      (busy.to_bits() >> 44 << 44) | noise,
+    ));
+  }
+
+  let (xor_compressed, meta) = compress_w_meta(
+    &nums,
+    &ChunkConfig::default().with_delta_spec(DeltaSpec::TryXor),
+  )?;
+  assert_eq!(meta.delta_encoding, DeltaEncoding::Xor);
+  let decompressed = simple_decompress(&xor_compressed)?;
+  assert_nums_eq(&decompressed, &nums, "xor")?;
+
+  // auto chooses xor here, which needs format version 4
+  let (auto_compressed, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert_eq!(meta.delta_encoding, DeltaEncoding::Xor);
+  assert_eq!(format_version(&auto_compressed)?, 4);
+  let v3_compressed = simple_compress(
+    &nums,
+    &ChunkConfig::default().with_delta_spec(DeltaSpec::TryConsecutive(1)),
+  )?;
+  assert_eq!(format_version(&v3_compressed)?, 3);
+  assert!(auto_compressed.len() < v3_compressed.len());
+  Ok(())
+}
+
+#[test]
+fn test_plateaus() -> PcoResult<()> {
+  // a step function, like a sensor with long flat plateaus
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut nums = Vec::new();
+  let mut x = 0_i64;
+  while nums.len() < 100_000 {
+    x += rng.gen_range(-1000..1000);
+    nums.extend(vec![x; 1000]);
+  }
+  let (compressed, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert!(matches!(
+    meta.delta_encoding,
+    DeltaEncoding::Consecutive(_)
+  ));
+  // each repeated value costs only a small fraction of a bit, so we don't
+  // need a separate run-length encoding
+  let bits_per_num = compressed.len() as f64 * 8.0 / nums.len() as f64;
+  assert!(bits_per_num < 0.05, "{}", bits_per_num);
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "plateaus")?;
+  Ok(())
+}
+
+#[test]
+fn test_epoch_seconds_with_ms() -> PcoResult<()> {
+  // timestamps in seconds since epoch, recorded to the millisecond
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut ms = 1_700_000_000_000_i64;
+  let mut nums = Vec::new();
+  for _ in 0..100_000 {
+    ms += rng.gen_range(0..2000);
+    nums.push(ms as f64 / 1000.0);
+  }
+
+  let (classic_compressed, _) = compress_w_meta(
+    &nums,
+    &ChunkConfig::default().with_mode_spec(ModeSpec::Classic),
+  )?;
+  for (mode_spec, name) in [
+    (ModeSpec::Auto, "auto"),
+    (ModeSpec::TryFloatMult(0.001), "forced"),
+  ] {
+    let (compressed, meta) = compress_w_meta(
+      &nums,
+      &ChunkConfig::default().with_mode_spec(mode_spec),
+    )?;
+    assert_eq!(
+      meta.mode,
+      Mode::FloatMult(DynLatent::U64(0.001_f64.to_latent_ordered())),
+      "{}",
+      name
+    );
+    assert!(compressed.len() < classic_compressed.len() * 3 / 5);
+    let decompressed = simple_decompress(&compressed)?;
+    assert_nums_eq(&decompressed, &nums, name)?;
+  }
+  Ok(())
+}
+
+#[test]
+fn test_low_cardinality_categories() -> PcoResult<()> {
+  // enum discriminants of a categorical column with 4 equally likely values,
+  // widened to u16 since there is no 8-bit number type
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let categories = [3_u16, 17, 40, 200];
+  let nums = (0..100_000)
+    .map(|_| categories[rng.gen_range(0..categories.len())])
+    .collect::<Vec<_>>();
+  let (compressed, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert_eq!(meta.delta_encoding, DeltaEncoding::None);
+  // each category gets its own single-valued bin, so the cost is just the
+  // entropy of 2 bits per value
+  let bits_per_num = compressed.len() as f64 * 8.0 / nums.len() as f64;
+  assert!(bits_per_num < 2.01, "{}", bits_per_num);
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "categories")?;
+  Ok(())
+}
+
+// Float modes must preserve every bit of special values, including signed
+// zeros and NaN payloads.
+fn assert_float_modes_preserve_specials<T: Float>(
+  regular: impl Fn(usize) -> T,
+  specials: &[T],
+  float_quant_k: Bitlen,
+  name: &str,
+) -> PcoResult<()> {
+  // mostly regular numbers so that the modes don't fall back to classic
+  let mut nums = (0..800).map(regular).collect::<Vec<_>>();
+  for (i, &special) in specials.iter().cycle().take(200).enumerate() {
+    nums.insert(i * 5, special);
+  }
+
+  for (mode_spec, expected_mode) in [
+    (
+      ModeSpec::TryFloatMult(0.25),
+      Mode::float_mult(T::from_f64(0.25)),
+    ),
+    (
+      ModeSpec::TryFloatQuant(float_quant_k),
+      Mode::FloatQuant(float_quant_k),
+    ),
+  ] {
+    let config = ChunkConfig::default().with_mode_spec(mode_spec);
+    let (compressed, meta) = compress_w_meta(&nums, &config)?;
+    let name = format!("{} {:?}", name, mode_spec);
+    assert_eq!(meta.mode, expected_mode, "{}", name);
+    let decompressed = simple_decompress::<T>(&compressed)?;
+    assert_nums_eq(&decompressed, &nums, &name)?;
+  }
+  Ok(())
+}
+
+#[test]
+fn test_f16_specials_in_float_modes() -> PcoResult<()> {
+  let specials = [
+    0x0000, 0x8000, 0x7c00, 0xfc00, 0x7e00, 0xfe00, 0x7e01, 0x7c01, 0x0001, 0x8001,
+  ]
+  .map(f16::from_bits);
+  assert_float_modes_preserve_specials(
+    |i| f16::from_f32((i % 64) as f32 * 0.25),
+    &specials,
+    2,
+    "f16",
+  )
+}
+
+#[test]
+fn test_f32_specials_in_float_modes() -> PcoResult<()> {
+  let specials = [
+    0x0000_0000,
+    0x8000_0000,
+    0x7f80_0000,
+    0xff80_0000,
+    0x7fc0_0000,
+    0xffc0_0000,
+    0x7fc1_2345,
+    0x7f80_0001,
+    0x0000_0001,
+    0x8000_0001,
+  ]
+  .map(f32::from_bits);
+  assert_float_modes_preserve_specials(
+    |i| (i % 1000) as f32 * 0.25,
+    &specials,
+    8,
+    "f32",
+  )
+}
+
+#[test]
+fn test_f64_specials_in_float_modes() -> PcoResult<()> {
+  let specials = [
+    0x0000_0000_0000_0000,
+    0x8000_0000_0000_0000,
+    0x7ff0_0000_0000_0000,
+    0xfff0_0000_0000_0000,
+    0x7ff8_0000_0000_0000,
+    0xfff8_0000_0000_0000,
+    0x7ff8_0123_4567_89ab,
+    0x7ff0_0000_0000_0001,
+    0x0000_0000_0000_0001,
+    0x8000_0000_0000_0001,
+  ]
+  .map(f64::from_bits);
+  assert_float_modes_preserve_specials(
+    |i| (i % 1000) as f64 * 0.25,
+    &specials,
+    20,
+    "f64",
+  )
+}
@@ -35,13 +35,8 @@ fn assert_panic_safe<T: Number>(nums: Vec<T>) -> PcoResult<ChunkMeta> {
 
 #[test]
 fn test_insufficient_data_short_bins() -> PcoResult<()> {
-  let mut nums = Vec::new();
-  for _ in 0..50 {
-    nums.push(0);
-  }
-  for _ in 0..50 {
-    nums.push(1000);
-  }
+  let mut nums = vec![0; 50];
+  nums.extend(vec![1000; 50]);
 
   let meta = assert_panic_safe(nums)?;
   assert!(meta.per_latent_var.delta.is_none());
@@ -62,9 +57,7 @@ fn test_insufficient_data_short_bins() -> PcoResult<()> {
 #[test]
 fn test_insufficient_data_sparse() -> PcoResult<()> {
   let mut nums = vec![0];
-  for _ in 0..(1 << 16) + 1 {
-    nums.push(1);
-  }
+  nums.extend(vec![1; (1 << 16) + 1]);
 
   let meta = assert_panic_safe(nums)?;
   assert!(meta.per_latent_var.delta.is_none());
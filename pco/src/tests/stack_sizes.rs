@@ -2,7 +2,7 @@ use crate::latent_batch_dissector::LatentBatchDissector;
 use crate::latent_chunk_compressor::LatentChunkCompressor;
 use crate::latent_page_decompressor::{DynLatentPageDecompressor, LatentPageDecompressor};
 use crate::metadata::PerLatentVar;
-use crate::wrapped::{ChunkCompressor, ChunkDecompressor, PageDecompressor};
+use crate::wrapped::{ChunkDecompressor, PageDecompressor};
 use std::mem;
 
 #[test]
@@ -20,7 +20,12 @@ fn test_stack_sizes() {
     136
   );
   assert_eq!(mem::size_of::<ChunkDecompressor<u64>>(), 168);
-  assert_eq!(mem::size_of::<ChunkCompressor>(), 624);
+  // these features add fields for recording extra information
+  #[cfg(not(any(feature = "debug_sample", feature = "telemetry")))]
+  assert_eq!(
+    mem::size_of::<crate::wrapped::ChunkCompressor>(),
+    624
+  );
 
   // decompression
   assert_eq!(
@@ -1,5 +1,5 @@
 use crate::bit_writer::BitWriter;
-use crate::chunk_config::DeltaSpec;
+use crate::chunk_config::{DeltaSpec, ModeSpec, Objective};
 use crate::compression_intermediates::{BinCompressionInfo, PageInfoVar};
 use crate::compression_intermediates::{DissectedPage, PageInfo};
 use crate::constants::{
@@ -7,25 +7,30 @@ use crate::constants::{
   MAX_ENTRIES, OVERSHOOT_PADDING, PAGE_PADDING,
 };
 use crate::data_types::SplitLatents;
-use crate::data_types::{Latent, LatentType, Number};
+use crate::data_types::{Latent, LatentType, Number, NumberType};
 use crate::delta::DeltaState;
 use crate::errors::{PcoError, PcoResult};
 use crate::histograms::histogram;
 use crate::latent_chunk_compressor::{
   DynLatentChunkCompressor, LatentChunkCompressor, TrainedBins,
 };
-use crate::macros::match_latent_enum;
+use crate::macros::{match_latent_enum, trace_debug, trace_info};
 use crate::metadata::chunk_latent_var::ChunkLatentVarMeta;
 use crate::metadata::delta_encoding::{DeltaConsecutiveConfig, DeltaLookbackConfig};
 use crate::metadata::dyn_bins::DynBins;
 use crate::metadata::dyn_latents::DynLatents;
+use crate::metadata::format_version::FormatVersion;
 use crate::metadata::page::PageMeta;
 use crate::metadata::page_latent_var::PageLatentVarMeta;
 use crate::metadata::per_latent_var::{LatentVarKey, PerLatentVar, PerLatentVarBuilder};
 use crate::metadata::{Bin, ChunkMeta, DeltaEncoding, Mode};
+#[cfg(feature = "debug_sample")]
+use crate::sampling;
+use crate::telemetry::{self, Stage};
 use crate::wrapped::guarantee;
 use crate::{
-  ans, bin_optimization, bits, data_types, delta, ChunkConfig, PagingSpec, FULL_BATCH_N,
+  ans, bin_layout, bin_optimization, bits, data_types, delta, BinLayout, ChunkConfig, PagingSpec,
+  FULL_BATCH_N,
 };
 use std::cmp::min;
 use std::io::Write;
@@ -38,6 +43,9 @@ const DELTA_GROUP_SIZE: usize = 200;
 const LOOKBACK_MAX_WINDOW_N_LOG: Bitlen = 15;
 const LOOKBACK_MIN_WINDOW_N_LOG: Bitlen = 4;
 const LOOKBACK_REQUIRED_BYTE_SAVINGS_PER_N: f32 = 0.25;
+// XOR delta is experimental and its sample estimates are noisy relative to
+// consecutive delta, so we only choose it when it clearly wins.
+const XOR_REQUIRED_BYTE_SAVINGS_PER_N: f32 = 0.05;
 
 // TODO taking deltas of secondary latents has been proven to help slightly
 // in some cases, so we should consider it in the future
@@ -71,6 +79,7 @@ fn quantize_weights<L: Latent>(
 fn train_infos<L: Latent>(
   mut latents: Vec<L>,
   unoptimized_bins_log: Bitlen,
+  soft_max_offset_bits: Option<Bitlen>,
 ) -> PcoResult<TrainedBins<L>> {
   if latents.is_empty() {
     return Ok(TrainedBins::default());
@@ -97,8 +106,11 @@ fn train_infos<L: Latent>(
     n_log_ceil,
   );
 
-  let mut optimized_infos =
-    bin_optimization::optimize_bins(&unoptimized_bins, estimated_ans_size_log);
+  let mut optimized_infos = bin_optimization::optimize_bins(
+    &unoptimized_bins,
+    estimated_ans_size_log,
+    soft_max_offset_bits,
+  );
 
   let counts = optimized_infos
     .iter()
@@ -117,19 +129,35 @@ fn train_infos<L: Latent>(
   })
 }
 
+// The sample used for mode analysis, kept as latents since this struct
+// isn't generic to the number type.
+#[cfg(feature = "debug_sample")]
+#[derive(Clone, Debug)]
+struct AnalysisSample {
+  indices: Vec<usize>,
+  number_type_byte: u8,
+  latents: DynLatents,
+}
+
 /// Holds metadata about a chunk and supports compression.
 #[derive(Clone, Debug)]
 pub struct ChunkCompressor {
   meta: ChunkMeta,
   latent_chunk_compressors: PerLatentVar<DynLatentChunkCompressor>,
   page_infos: Vec<PageInfo>,
+  #[cfg(feature = "debug_sample")]
+  analysis_sample: Option<AnalysisSample>,
+  #[cfg(feature = "telemetry")]
+  telemetry: telemetry::ChunkTelemetry,
+  #[cfg(feature = "telemetry")]
+  entropy_coding_nanos: telemetry::AtomicNanos,
 }
 
 fn bins_from_compression_infos<L: Latent>(infos: &[BinCompressionInfo<L>]) -> Vec<Bin<L>> {
   infos.iter().cloned().map(Bin::from).collect()
 }
 
-fn validate_config(config: &ChunkConfig) -> PcoResult<()> {
+pub(crate) fn validate_config(config: &ChunkConfig) -> PcoResult<()> {
   let compression_level = config.compression_level;
   if compression_level > MAX_COMPRESSION_LEVEL {
     return Err(PcoError::invalid_argument(format!(
@@ -138,7 +166,24 @@ fn validate_config(config: &ChunkConfig) -> PcoResult<()> {
     )));
   }
 
-  if let DeltaSpec::TryConsecutive(order) = config.delta_spec {
+  if config.latent_compression_levels.len() > 2 {
+    return Err(PcoError::invalid_argument(format!(
+      "at most 2 latent compression levels may be specified (was {})",
+      config.latent_compression_levels.len(),
+    )));
+  }
+  for &level in config.latent_compression_levels.iter().flatten() {
+    if level > MAX_COMPRESSION_LEVEL {
+      return Err(PcoError::invalid_argument(format!(
+        "latent compression level may not exceed {} (was {})",
+        MAX_COMPRESSION_LEVEL, level,
+      )));
+    }
+  }
+
+  if let DeltaSpec::TryConsecutive(order) | DeltaSpec::TryConsecutivePerPage(order) =
+    config.delta_spec
+  {
     if order > MAX_DELTA_ENCODING_ORDER {
       return Err(PcoError::invalid_argument(format!(
         "delta encoding order may not exceed {} (was {})",
@@ -147,6 +192,43 @@ fn validate_config(config: &ChunkConfig) -> PcoResult<()> {
     }
   }
 
+  match &config.paging_spec {
+    // large maxes just mean no cap, since chunks are at most MAX_ENTRIES
+    PagingSpec::EqualPagesUpTo(0) => {
+      return Err(PcoError::invalid_argument(
+        "max page count must be positive",
+      ));
+    }
+    PagingSpec::Exact(n_per_page) => {
+      let n = n_per_page
+        .iter()
+        .try_fold(0_usize, |n, &page_n| n.checked_add(page_n));
+      if !matches!(n, Some(n) if n <= MAX_ENTRIES) {
+        return Err(PcoError::invalid_argument(format!(
+          "exact page counts may not sum to more than the max chunk count of {} (was {})",
+          MAX_ENTRIES,
+          n.map_or("overflow".to_string(), |n| n.to_string()),
+        )));
+      }
+    }
+    _ => (),
+  }
+
+  Ok(())
+}
+
+fn validate_delta_spec_for_version(
+  delta_spec: DeltaSpec,
+  version: &FormatVersion,
+) -> PcoResult<()> {
+  if !version.supports_delta_spec(delta_spec) {
+    return Err(PcoError::invalid_argument(format!(
+      "delta spec {:?} is not supported by format version {}; consider \
+      FileCompressor::with_format_version",
+      delta_spec, version.0,
+    )));
+  }
+
   Ok(())
 }
 
@@ -204,14 +286,24 @@ fn delta_encode_and_build_page_infos(
   for &page_n in n_per_page {
     let end_idx = start_idx + page_n;
 
+    let delta_order = match delta_encoding {
+      DeltaEncoding::ConsecutivePerPage(config) => Some(delta::choose_consecutive_order(
+        config.order,
+        &latents.primary,
+        start_idx..end_idx,
+      )),
+      _ => None,
+    };
+    let page_delta_encoding = delta_encoding.for_page(delta_order);
+
     let page_delta_latents = delta::compute_delta_latent_var(
-      delta_encoding,
+      page_delta_encoding,
       &mut latents.primary,
       start_idx..end_idx,
     );
 
     let mut per_latent_var = latents.as_mut().map(|key, var_latents| {
-      let encoding_for_var = delta_encoding.for_latent_var(key);
+      let encoding_for_var = page_delta_encoding.for_latent_var(key);
       let delta_state = delta::encode_in_place(
         encoding_for_var,
         page_delta_latents.as_ref(),
@@ -242,6 +334,7 @@ fn delta_encode_and_build_page_infos(
 
     page_infos.push(PageInfo {
       page_n,
+      delta_order,
       per_latent_var,
     });
 
@@ -258,13 +351,16 @@ fn new_candidate_w_split_and_delta_encoding(
   mode: Mode,
   delta_encoding: DeltaEncoding,
   unoptimized_bins_log: Bitlen,
+  secondary_unoptimized_bins_log: Bitlen,
+  soft_max_offset_bits: Option<Bitlen>,
 ) -> PcoResult<(ChunkCompressor, PerLatentVar<Vec<Weight>>)> {
   let chunk_n = latents.primary.len();
   let n_per_page = paging_spec.n_per_page(chunk_n)?;
 
   // delta encoding
-  let (latents, page_infos) =
-    delta_encode_and_build_page_infos(delta_encoding, &n_per_page, latents);
+  let (latents, page_infos) = telemetry::time(Stage::Delta, || {
+    delta_encode_and_build_page_infos(delta_encoding, &n_per_page, latents)
+  });
 
   // training bins
   let mut var_metas = PerLatentVarBuilder::default();
@@ -276,18 +372,20 @@ fn new_candidate_w_split_and_delta_encoding(
       // delta latents typically have a small number of discrete values, so
       // aren't slow to optimize anyway
       LatentVarKey::Delta | LatentVarKey::Primary => unoptimized_bins_log,
-      // secondary latents should be compressed faster
-      LatentVarKey::Secondary => min(
-        unoptimized_bins_log,
-        LIMITED_UNOPTIMIZED_BINS_LOG,
-      ),
+      LatentVarKey::Secondary => secondary_unoptimized_bins_log,
     };
 
     let (var_meta, lcc, bin_counts) = match_latent_enum!(
       latents,
       DynLatents<L>(latents) => {
         let contiguous_deltas = collect_contiguous_latents(&latents, &page_infos, key);
-        let trained = train_infos(contiguous_deltas, unoptimized_bins_log)?;
+        let trained = telemetry::time(Stage::BinOptimization, || {
+          train_infos(
+            contiguous_deltas,
+            unoptimized_bins_log,
+            soft_max_offset_bits,
+          )
+        })?;
 
         let bins = bins_from_compression_infos(&trained.infos);
 
@@ -321,6 +419,12 @@ fn new_candidate_w_split_and_delta_encoding(
     meta,
     latent_chunk_compressors,
     page_infos,
+    #[cfg(feature = "debug_sample")]
+    analysis_sample: None,
+    #[cfg(feature = "telemetry")]
+    telemetry: Default::default(),
+    #[cfg(feature = "telemetry")]
+    entropy_coding_nanos: Default::default(),
   };
 
   Ok((chunk_compressor, bin_countss))
@@ -333,11 +437,10 @@ fn choose_delta_sample(
 ) -> DynLatents {
   let n = primary_latents.len();
   let nominal_sample_size = (n_extra_groups + 1) * group_size;
-  let group_padding = if n_extra_groups == 0 {
-    0
-  } else {
-    n.saturating_sub(nominal_sample_size) / n_extra_groups
-  };
+  let group_padding = n
+    .saturating_sub(nominal_sample_size)
+    .checked_div(n_extra_groups)
+    .unwrap_or(0);
 
   let mut i = group_size;
 
@@ -371,6 +474,8 @@ fn calculate_compressed_sample_size(
     Mode::Classic,
     delta_encoding,
     unoptimized_bins_log,
+    unoptimized_bins_log,
+    None,
   )?;
   let size = sample_cc.chunk_meta_size_hint() + sample_cc.page_size_hint_inner(0, 1.0);
   Ok(size as f32)
@@ -378,8 +483,10 @@ fn calculate_compressed_sample_size(
 
 #[inline(never)]
 fn choose_delta_encoding(
+  mode: Mode,
   primary_latents: &DynLatents,
   unoptimized_bins_log: Bitlen,
+  version: &FormatVersion,
 ) -> PcoResult<DeltaEncoding> {
   let n = primary_latents.len();
   let sample = choose_delta_sample(
@@ -425,6 +532,18 @@ fn choose_delta_encoding(
     }
   }
 
+  // XORs only make sense on the bits of the numbers themselves
+  if mode == Mode::Classic && version.supports_xor_delta() {
+    let cost = calculate_compressed_sample_size(
+      &sample,
+      unoptimized_bins_log,
+      DeltaEncoding::Xor,
+    )? + XOR_REQUIRED_BYTE_SAVINGS_PER_N * sample_n as f32;
+    if cost < best_cost {
+      best_encoding = DeltaEncoding::Xor;
+    }
+  }
+
   Ok(best_encoding)
 }
 
@@ -447,17 +566,53 @@ fn new_candidate_w_split(
   mode: Mode,
   latents: SplitLatents,
   config: &ChunkConfig,
+  version: &FormatVersion,
 ) -> PcoResult<(ChunkCompressor, PerLatentVar<Vec<Weight>>)> {
   let n = latents.primary.len();
-  let unoptimized_bins_log = choose_unoptimized_bins_log(config.compression_level, n);
+  let level_for_latent_var = |key| {
+    if latents.secondary.is_some() {
+      config.latent_compression_level_override(key)
+    } else {
+      None
+    }
+  };
+  let unoptimized_bins_log = choose_unoptimized_bins_log(
+    level_for_latent_var(LatentVarKey::Primary).unwrap_or(config.compression_level),
+    n,
+  );
+  let secondary_unoptimized_bins_log = match level_for_latent_var(LatentVarKey::Secondary) {
+    Some(level) => choose_unoptimized_bins_log(level, n),
+    // secondary latents should be compressed faster by default
+    None => min(
+      choose_unoptimized_bins_log(config.compression_level, n),
+      LIMITED_UNOPTIMIZED_BINS_LOG,
+    ),
+  };
   let delta_encoding = match config.delta_spec {
-    DeltaSpec::Auto => choose_delta_encoding(&latents.primary, unoptimized_bins_log)?,
+    DeltaSpec::Auto => telemetry::time(Stage::Delta, || {
+      choose_delta_encoding(
+        mode,
+        &latents.primary,
+        unoptimized_bins_log,
+        version,
+      )
+    })?,
     DeltaSpec::None | DeltaSpec::TryConsecutive(0) => DeltaEncoding::None,
     DeltaSpec::TryConsecutive(order) => DeltaEncoding::Consecutive(DeltaConsecutiveConfig {
       order,
       secondary_uses_delta: false,
     }),
+    // a lone number has nothing to look back at
+    DeltaSpec::TryLookback if n <= 1 => DeltaEncoding::None,
     DeltaSpec::TryLookback => new_lookback_delta_encoding(n),
+    DeltaSpec::TryXor => DeltaEncoding::Xor,
+    DeltaSpec::TryConsecutivePerPage(0) => DeltaEncoding::None,
+    DeltaSpec::TryConsecutivePerPage(order) => {
+      DeltaEncoding::ConsecutivePerPage(DeltaConsecutiveConfig {
+        order,
+        secondary_uses_delta: false,
+      })
+    }
   };
 
   new_candidate_w_split_and_delta_encoding(
@@ -466,6 +621,8 @@ fn new_candidate_w_split(
     mode,
     delta_encoding,
     unoptimized_bins_log,
+    secondary_unoptimized_bins_log,
+    config.soft_max_offset_bits,
   )
 }
 
@@ -510,34 +667,361 @@ fn fallback_chunk_compressor(
       secondary: None,
     },
     page_infos,
+    #[cfg(feature = "debug_sample")]
+    analysis_sample: None,
+    #[cfg(feature = "telemetry")]
+    telemetry: Default::default(),
+    #[cfg(feature = "telemetry")]
+    entropy_coding_nanos: Default::default(),
+  })
+}
+
+// Compresses with a fixed mode, delta encoding, and bins, skipping all
+// analysis.
+pub(crate) fn new_with_layout<T: Number>(
+  nums: &[T],
+  config: &ChunkConfig,
+  layout: &BinLayout,
+  version: &FormatVersion,
+) -> PcoResult<ChunkCompressor> {
+  validate_config(config)?;
+  if !version.supports_delta_encoding(&layout.meta.delta_encoding) {
+    return Err(PcoError::invalid_argument(format!(
+      "layout's delta encoding of {:?} is not supported by format version {}",
+      layout.meta.delta_encoding, version.0,
+    )));
+  }
+  let n = nums.len();
+  validate_chunk_size(n)?;
+  if let Some(value_range) = &config.value_range {
+    value_range.validate(nums)?;
+  }
+
+  let mode_config = config.clone().with_mode_spec(layout.mode_spec);
+  let (mode, latents) = T::choose_mode_and_split_latents(nums, &mode_config)?;
+  if mode != layout.meta.mode {
+    return Err(PcoError::invalid_argument(format!(
+      "layout's mode of {:?} does not apply to numbers of type {:?}",
+      layout.meta.mode,
+      NumberType::from_descriminant(T::NUMBER_TYPE_BYTE),
+    )));
+  }
+
+  let n_per_page = config.paging_spec.n_per_page(n)?;
+  let (latents, page_infos) = delta_encode_and_build_page_infos(
+    layout.meta.delta_encoding,
+    &n_per_page,
+    latents,
+  );
+
+  let mut latent_chunk_compressors = PerLatentVarBuilder::default();
+  for (key, (latents, var_meta)) in latents
+    .zip_exact(layout.meta.per_latent_var.as_ref())
+    .enumerated()
+  {
+    let lcc = match_latent_enum!(
+      latents,
+      DynLatents<L>(latents) => {
+        let Some(bins) = var_meta.bins.downcast_ref::<L>() else {
+          return Err(PcoError::invalid_argument(
+            "layout's bins have a different latent type than the numbers",
+          ));
+        };
+        let infos = bins
+          .iter()
+          .enumerate()
+          .map(|(symbol, bin)| BinCompressionInfo {
+            weight: bin.weight,
+            lower: bin.lower,
+            upper: bin_layout::bin_upper(bin),
+            offset_bits: bin.offset_bits,
+            symbol: symbol as ans::Symbol,
+          })
+          .collect();
+        let trained = TrainedBins {
+          infos,
+          ans_size_log: var_meta.ans_size_log,
+          counts: bins.iter().map(|bin| bin.weight).collect(),
+        };
+        DynLatentChunkCompressor::new(
+          LatentChunkCompressor::new(trained, bins, latents)?
+        ).unwrap()
+      }
+    );
+    latent_chunk_compressors.set(key, lcc);
+  }
+
+  Ok(ChunkCompressor {
+    meta: layout.meta.clone(),
+    latent_chunk_compressors: latent_chunk_compressors.into(),
+    page_infos,
+    #[cfg(feature = "debug_sample")]
+    analysis_sample: None,
+    #[cfg(feature = "telemetry")]
+    telemetry: Default::default(),
+    #[cfg(feature = "telemetry")]
+    entropy_coding_nanos: Default::default(),
   })
 }
 
+fn is_constant<T: Number>(nums: &[T]) -> bool {
+  match nums.first() {
+    Some(first) => {
+      let first = first.to_latent_ordered();
+      nums.iter().all(|num| num.to_latent_ordered() == first)
+    }
+    None => false,
+  }
+}
+
+// A constant chunk compresses best as a single classic bin with 0 offset
+// bits, which reduces to just the value and count, so we skip all the mode
+// and delta search.
+fn constant_chunk_compressor<T: Number>(
+  nums: &[T],
+  config: &ChunkConfig,
+) -> PcoResult<ChunkCompressor> {
+  let (cc, _) = new_candidate_w_split_and_delta_encoding(
+    data_types::split_latents_classic(nums),
+    &config.paging_spec,
+    Mode::Classic,
+    DeltaEncoding::None,
+    0,
+    0,
+    None,
+  )?;
+  Ok(cc)
+}
+
+// Returns whether the consecutive deltas of this order (with wrapping) are
+// all equal, stopping at the first one that differs.
+// This only keeps the latest delta of each lower order, so it doesn't
+// allocate.
+fn has_constant_deltas<L: Latent>(latents: impl Iterator<Item = L>, order: usize) -> bool {
+  let mut latest = [L::ZERO; MAX_DELTA_ENCODING_ORDER];
+  let mut first_delta = None;
+  for (i, latent) in latents.enumerate() {
+    let mut delta = latent;
+    for latest_delta in &mut latest[..min(i, order)] {
+      let prev = *latest_delta;
+      *latest_delta = delta;
+      delta = delta.wrapping_sub(prev);
+    }
+    if i < order {
+      latest[i] = delta;
+      continue;
+    }
+    match first_delta {
+      None => first_delta = Some(delta),
+      Some(first_delta) if delta != first_delta => return false,
+      _ => (),
+    }
+  }
+  true
+}
+
+// Returns the lowest consecutive delta order up to max_order whose deltas
+// (with wrapping) are constant, i.e. whose next order deltas are all zero.
+// Order 1 covers arithmetic sequences like row numbers, and higher orders
+// cover polynomial sequences.
+// Each order's check stops at the first delta that differs, so this is cheap
+// for typical data.
+fn all_zero_delta_order<T: Number>(nums: &[T], max_order: usize) -> Option<usize> {
+  let latents = || nums.iter().map(|num| num.to_latent_ordered());
+  (1..max_order + 1).find(|&order| {
+    // we need at least 2 deltas of this order to call them constant
+    nums.len() >= order + 2 && has_constant_deltas(latents(), order)
+  })
+}
+
+// Like a constant chunk, a chunk whose consecutive deltas of some order are
+// constant compresses best with that order of delta encoding, which leaves a single
+// classic bin with 0 offset bits, so each page reduces to its delta state.
+fn all_zero_delta_chunk_compressor<T: Number>(
+  nums: &[T],
+  config: &ChunkConfig,
+  order: usize,
+) -> PcoResult<ChunkCompressor> {
+  let (cc, _) = new_candidate_w_split_and_delta_encoding(
+    data_types::split_latents_classic(nums),
+    &config.paging_spec,
+    Mode::Classic,
+    DeltaEncoding::Consecutive(DeltaConsecutiveConfig {
+      order,
+      secondary_uses_delta: false,
+    }),
+    0,
+    0,
+    None,
+  )?;
+  Ok(cc)
+}
+
+// Tries dropping whichever of the mode and delta encoding were chosen
+// automatically, then picks the candidate that decodes fastest among those
+// close enough in size to the smallest.
+fn choose_by_objective<T: Number>(
+  nums: &[T],
+  candidate: ChunkCompressor,
+  config: &ChunkConfig,
+  version: &FormatVersion,
+) -> PcoResult<ChunkCompressor> {
+  let meta = &candidate.meta;
+  let mut mode_specs = vec![config.mode_spec];
+  if config.mode_spec == ModeSpec::Auto && meta.mode != Mode::Classic {
+    mode_specs.push(ModeSpec::Classic);
+  }
+  let mut delta_specs = vec![config.delta_spec];
+  if config.delta_spec == DeltaSpec::Auto && meta.delta_encoding != DeltaEncoding::None {
+    delta_specs.push(DeltaSpec::None);
+  }
+
+  let ratio_config = config.clone().with_objective(Objective::Ratio);
+  let mut candidates = vec![candidate];
+  for &mode_spec in &mode_specs {
+    for &delta_spec in &delta_specs {
+      if mode_spec == config.mode_spec && delta_spec == config.delta_spec {
+        continue;
+      }
+      let alt_config = ratio_config
+        .clone()
+        .with_mode_spec(mode_spec)
+        .with_delta_spec(delta_spec);
+      candidates.push(new_untimed(nums, &alt_config, version)?);
+    }
+  }
+
+  let n = nums.len();
+  let sizes = candidates
+    .iter()
+    .map(|candidate| candidate.estimated_size())
+    .collect::<Vec<_>>();
+  let max_size = *sizes.iter().min().unwrap() as f64 * config.objective.max_size_ratio();
+  let best_idx = (0..candidates.len())
+    .filter(|&idx| sizes[idx] as f64 <= max_size)
+    .min_by_key(|&idx| {
+      (
+        candidates[idx].meta.estimated_decode_cost(n),
+        sizes[idx],
+      )
+    })
+    .unwrap();
+  Ok(candidates.swap_remove(best_idx))
+}
+
 // Should this take nums as a slice of slices instead of having a config.paging_spec?
-pub(crate) fn new<T: Number>(nums: &[T], config: &ChunkConfig) -> PcoResult<ChunkCompressor> {
+pub(crate) fn new<T: Number>(
+  nums: &[T],
+  config: &ChunkConfig,
+  version: &FormatVersion,
+) -> PcoResult<ChunkCompressor> {
+  trace_debug!(n = nums.len(), "compressing chunk");
+  #[cfg(feature = "telemetry")]
+  let cc = {
+    let (res, telemetry) = telemetry::capture(|| new_untimed(nums, config, version));
+    let mut cc = res?;
+    cc.telemetry = telemetry;
+    cc
+  };
+  #[cfg(not(feature = "telemetry"))]
+  let cc = new_untimed(nums, config, version)?;
+  trace_info!(
+    n = nums.len(),
+    mode = ?cc.meta.mode,
+    delta_encoding = ?cc.meta.delta_encoding,
+    n_pages = cc.page_infos.len(),
+    "chose chunk encoding",
+  );
+  Ok(cc)
+}
+
+fn new_untimed<T: Number>(
+  nums: &[T],
+  config: &ChunkConfig,
+  version: &FormatVersion,
+) -> PcoResult<ChunkCompressor> {
   validate_config(config)?;
+  validate_delta_spec_for_version(config.delta_spec, version)?;
   let n = nums.len();
   validate_chunk_size(n)?;
+  if let Some(value_range) = &config.value_range {
+    value_range.validate(nums)?;
+  }
 
-  let (mode, latents) = T::choose_mode_and_split_latents(nums, config)?;
+  if matches!(
+    config.mode_spec,
+    ModeSpec::Auto | ModeSpec::Classic
+  ) {
+    if is_constant(nums)
+      && matches!(
+        config.delta_spec,
+        DeltaSpec::Auto | DeltaSpec::None | DeltaSpec::TryConsecutive(0)
+      )
+    {
+      return constant_chunk_compressor(nums, config);
+    }
+    // If lower order deltas are constant, higher order ones are all zero, so
+    // a specified order works too.
+    let all_zero_order = match config.delta_spec {
+      DeltaSpec::Auto => all_zero_delta_order(nums, MAX_DELTA_ENCODING_ORDER),
+      DeltaSpec::TryConsecutive(order) if order > 0 => {
+        all_zero_delta_order(nums, order).map(|_| order)
+      }
+      _ => None,
+    };
+    if let Some(order) = all_zero_order {
+      return all_zero_delta_chunk_compressor(nums, config, order);
+    }
+  }
+
+  let choose_mode_and_split_latents = || {
+    telemetry::time(Stage::Mode, || {
+      T::choose_mode_and_split_latents(nums, config)
+    })
+  };
+  #[cfg(feature = "debug_sample")]
+  let (mode_and_latents, analysis_sample_indices) =
+    sampling::capture_sample_indices(choose_mode_and_split_latents);
+  #[cfg(not(feature = "debug_sample"))]
+  let mode_and_latents = choose_mode_and_split_latents();
+  let (mode, latents) = mode_and_latents?;
   if !T::mode_is_valid(mode) {
-    return Err(PcoError::invalid_argument(
-      "The chosen mode of {:?} was invalid for type {}. \
+    return Err(PcoError::invalid_argument(format!(
+      "The chosen mode of {:?} was invalid for type {:?}. \
       This is most likely due to an invalid argument, but if using Auto mode \
       spec, it could also be a bug in pco.",
-    ));
+      mode,
+      NumberType::from_descriminant(T::NUMBER_TYPE_BYTE),
+    )));
   }
 
-  let (candidate, bin_counts) = new_candidate_w_split(mode, latents, config)?;
+  let (mut candidate, bin_counts) = new_candidate_w_split(mode, latents, config, version)?;
   if candidate.should_fallback(
     LatentType::new::<T::L>().unwrap(),
     n,
     bin_counts,
   ) {
     let split_latents = data_types::split_latents_classic(nums);
-    return fallback_chunk_compressor(split_latents, config);
+    candidate = fallback_chunk_compressor(split_latents, config)?;
+  }
+  if config.objective != Objective::Ratio {
+    candidate = choose_by_objective(nums, candidate, config, version)?;
   }
 
+  #[cfg(feature = "debug_sample")]
+  {
+    candidate.analysis_sample = analysis_sample_indices.map(|indices| {
+      let latents = indices
+        .iter()
+        .map(|&i| nums[i].to_latent_ordered())
+        .collect::<Vec<_>>();
+      AnalysisSample {
+        indices,
+        number_type_byte: T::NUMBER_TYPE_BYTE,
+        latents: DynLatents::new(latents).unwrap(),
+      }
+    });
+  }
   Ok(candidate)
 }
 
@@ -593,6 +1077,90 @@ impl ChunkCompressor {
     &self.meta
   }
 
+  /// Returns the indices of the numbers pco sampled to choose the chunk's
+  /// mode, in the order they were sampled.
+  ///
+  /// This is `None` if no sample was taken, e.g. because the mode spec
+  /// didn't require analysis or the chunk was too small.
+  /// Sampling is deterministic, so these indices are reproducible for the
+  /// same input.
+  /// Only available with the `debug_sample` feature.
+  #[cfg(feature = "debug_sample")]
+  pub fn analysis_sample_indices(&self) -> Option<&[usize]> {
+    self
+      .analysis_sample
+      .as_ref()
+      .map(|sample| sample.indices.as_slice())
+  }
+
+  /// Returns the numbers pco sampled to choose the chunk's mode, in the same
+  /// order as [`analysis_sample_indices`][Self::analysis_sample_indices].
+  ///
+  /// This is also `None` if `T` isn't the number type the chunk was
+  /// compressed from.
+  /// Only available with the `debug_sample` feature.
+  #[cfg(feature = "debug_sample")]
+  pub fn analysis_sample<T: Number>(&self) -> Option<Vec<T>> {
+    let sample = self
+      .analysis_sample
+      .as_ref()
+      .filter(|sample| sample.number_type_byte == T::NUMBER_TYPE_BYTE)?;
+    let latents = sample.latents.downcast_ref::<T::L>()?;
+    Some(
+      latents
+        .iter()
+        .map(|&latent| T::from_latent_ordered(latent))
+        .collect(),
+    )
+  }
+
+  /// Returns nanosecond timings of each stage of compressing this chunk.
+  ///
+  /// Entropy coding time accumulates as pages get written, so it only
+  /// accounts for pages written so far.
+  /// Only available with the `telemetry` feature.
+  #[cfg(feature = "telemetry")]
+  pub fn telemetry(&self) -> telemetry::ChunkTelemetry {
+    telemetry::ChunkTelemetry {
+      entropy_coding_nanos: self.entropy_coding_nanos.get(),
+      ..self.telemetry
+    }
+  }
+
+  /// Returns empirical entropy estimates of each of the chunk's latent
+  /// variables, alongside the bits actually spent encoding them.
+  ///
+  /// This dissects every page, so it costs about as much as writing them.
+  /// Only available with the `entropy` feature.
+  #[cfg(feature = "entropy")]
+  pub fn latent_entropy(&self) -> PcoResult<PerLatentVar<crate::wrapped::LatentEntropy>> {
+    let dissected_pages = (0..self.page_infos.len())
+      .map(|page_idx| self.dissect_page(page_idx))
+      .collect::<PcoResult<Vec<_>>>()?;
+    Ok(
+      self.latent_chunk_compressors.as_ref().map(|key, lcc| {
+        let dissected_vars = dissected_pages
+          .iter()
+          .map(|page| page.per_latent_var.get(key).unwrap().clone())
+          .collect::<Vec<_>>();
+        match_latent_enum!(
+          lcc,
+          DynLatentChunkCompressor<L>(inner) => {
+            crate::entropy::latent_entropy(inner.latents(), &dissected_vars)
+          }
+        )
+      }),
+    )
+  }
+
+  // the chunk's metadata plus its pages, without overestimation
+  fn estimated_size(&self) -> usize {
+    self.meta.exact_size()
+      + (0..self.page_infos.len())
+        .map(|page_idx| self.page_size_hint_inner(page_idx, 1.0))
+        .sum::<usize>()
+  }
+
   /// Returns an estimate of the overall size of the chunk.
   ///
   /// This can be useful when building the file as a `Vec<u8>` in memory;
@@ -708,10 +1276,19 @@ impl ChunkCompressor {
       )));
     }
 
+    #[cfg(feature = "telemetry")]
+    let start = std::time::Instant::now();
+
     let mut writer = BitWriter::new(dst, PAGE_PADDING);
 
     let dissected_page = self.dissect_page(page_idx)?;
     let page_info = &self.page_infos[page_idx];
+    trace_debug!(
+      page_idx,
+      n = page_info.page_n,
+      delta_order = page_info.delta_order,
+      "writing page",
+    );
 
     let ans_default_state_and_size_log = self.latent_chunk_compressors.as_ref().map(|_, lcc| {
       match_latent_enum!(
@@ -736,7 +1313,10 @@ impl ChunkCompressor {
         }
       });
 
-    let page_meta = PageMeta { per_latent_var };
+    let page_meta = PageMeta {
+      delta_order: page_info.delta_order,
+      per_latent_var,
+    };
     let ans_size_logs = ans_default_state_and_size_log.map(|_, (_, size_log)| size_log);
     unsafe { page_meta.write_to(ans_size_logs, &mut writer) };
 
@@ -744,6 +1324,8 @@ impl ChunkCompressor {
 
     writer.finish_byte();
     writer.flush()?;
+    #[cfg(feature = "telemetry")]
+    self.entropy_coding_nanos.add_since(start);
     Ok(writer.into_inner())
   }
 }
@@ -751,6 +1333,34 @@ impl ChunkCompressor {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::errors::ErrorKind;
+  use crate::ValueRange;
+  use rand::Rng;
+  use rand_xoshiro::rand_core::SeedableRng;
+  use rand_xoshiro::Xoroshiro128PlusPlus;
+
+  fn new<T: Number>(nums: &[T], config: &ChunkConfig) -> PcoResult<ChunkCompressor> {
+    super::new(nums, config, &FormatVersion::default())
+  }
+
+  #[test]
+  fn test_all_zero_delta_order() {
+    let squares = (0..100_i64).map(|i| i * i).collect::<Vec<_>>();
+    assert_eq!(all_zero_delta_order(&squares, 7), Some(2));
+    assert_eq!(all_zero_delta_order(&squares, 1), None);
+    let wrapping = (0..100_u32)
+      .map(|i| i.wrapping_mul(3_000_000_000))
+      .collect::<Vec<_>>();
+    assert_eq!(all_zero_delta_order(&wrapping, 7), Some(1));
+    let cubes = (0..100_i64).map(|i| i * i * i).collect::<Vec<_>>();
+    assert_eq!(all_zero_delta_order(&cubes, 7), Some(3));
+    // too short to have 2 deltas of order 2
+    assert_eq!(all_zero_delta_order(&[0_u32, 1, 4], 7), None);
+
+    let mut nearly_linear = (0..1000_u32).collect::<Vec<_>>();
+    nearly_linear[900] = 0;
+    assert_eq!(all_zero_delta_order(&nearly_linear, 7), None);
+  }
 
   #[test]
   fn test_choose_delta_sample() {
@@ -784,4 +1394,163 @@ mod tests {
       vec![0, 1, 3, 4, 6, 7]
     );
   }
+
+  #[test]
+  fn test_validate_paging_spec() {
+    let validate =
+      |paging_spec| validate_config(&ChunkConfig::default().with_paging_spec(paging_spec));
+    assert!(validate(PagingSpec::EqualPagesUpTo(MAX_ENTRIES)).is_ok());
+    assert!(validate(PagingSpec::EqualPagesUpTo(usize::MAX)).is_ok());
+    assert!(validate(PagingSpec::Exact(vec![MAX_ENTRIES / 2; 2])).is_ok());
+    assert!(validate(PagingSpec::EqualPagesUpTo(0)).is_err());
+    for paging_spec in [
+      PagingSpec::Exact(vec![MAX_ENTRIES, 1]),
+      PagingSpec::Exact(vec![usize::MAX, 1]),
+    ] {
+      let err = validate(paging_spec).unwrap_err();
+      assert!(matches!(
+        err.kind,
+        ErrorKind::InvalidArgument
+      ));
+      assert!(err.message.contains(&MAX_ENTRIES.to_string()));
+    }
+  }
+
+  #[test]
+  fn test_objective() -> PcoResult<()> {
+    // a bounded random walk, for which delta encoding barely helps
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(1);
+    let mut x = 0_i64;
+    let nums = (0..100_000)
+      .map(|_| {
+        x = (x + rng.gen_range(-500..500)).clamp(0, 2000);
+        x
+      })
+      .collect::<Vec<_>>();
+    let compress = |objective| {
+      new(
+        &nums,
+        &ChunkConfig::default().with_objective(objective),
+      )
+    };
+
+    let ratio = compress(Objective::Ratio)?;
+    assert!(matches!(
+      ratio.meta.delta_encoding,
+      DeltaEncoding::Consecutive(_)
+    ));
+    // dropping delta encoding costs more than BalancedSpeed's tolerance
+    assert_eq!(
+      compress(Objective::BalancedSpeed)?.meta,
+      ratio.meta
+    );
+    let decode_speed = compress(Objective::DecodeSpeed)?;
+    assert_eq!(
+      decode_speed.meta.delta_encoding,
+      DeltaEncoding::None
+    );
+    assert!(decode_speed.estimated_size() as f64 <= 1.1 * ratio.estimated_size() as f64);
+    assert!(
+      decode_speed.meta.estimated_decode_cost(nums.len())
+        < ratio.meta.estimated_decode_cost(nums.len())
+    );
+
+    // explicit specs are honored
+    let config = ChunkConfig::default()
+      .with_objective(Objective::DecodeSpeed)
+      .with_delta_spec(DeltaSpec::TryConsecutive(1));
+    assert_eq!(new(&nums, &config)?.meta, ratio.meta);
+    Ok(())
+  }
+
+  #[test]
+  fn test_value_range() -> PcoResult<()> {
+    let nums = (0..1000)
+      .map(|i| (i % 200) as f32 - 100.0)
+      .collect::<Vec<_>>();
+    let config = |min: f32, max: f32| {
+      ChunkConfig::default().with_value_range(Some(ValueRange::new(min, max).unwrap()))
+    };
+    let unbounded = new(&nums, &ChunkConfig::default())?;
+    let bounded = new(&nums, &config(-100.0, 99.0))?;
+    assert_eq!(bounded.meta(), unbounded.meta());
+
+    let err = new(&nums, &config(-100.0, 98.0)).unwrap_err();
+    assert!(matches!(
+      err.kind,
+      ErrorKind::InvalidArgument
+    ));
+    assert!(err.message.contains("index 199"));
+
+    let wrong_type = ChunkConfig::default().with_value_range(Some(ValueRange::new(0_i32, 5)?));
+    assert!(new(&nums, &wrong_type).is_err());
+    assert!(ValueRange::new(1.0_f32, -1.0).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_absolute_timestamps_need_no_rebasing() -> PcoResult<()> {
+    // non-monotonic microsecond timestamps clustered within a second
+    let epoch_micros = 1_700_000_000_000_000_i64;
+    let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+    let nums = (0..10_000)
+      .map(|_| epoch_micros + rng.gen_range(0..1_000_000))
+      .collect::<Vec<_>>();
+    let min = *nums.iter().min().unwrap();
+    let rebased = nums.iter().map(|&x| x - min).collect::<Vec<_>>();
+
+    let config = ChunkConfig::default();
+    let absolute_size = new(&nums, &config)?.estimated_size();
+    let rebased_size = new(&rebased, &config)?.estimated_size();
+    assert!(absolute_size <= rebased_size + rebased_size / 1000);
+    Ok(())
+  }
+
+  #[cfg(feature = "telemetry")]
+  #[test]
+  fn test_telemetry() -> PcoResult<()> {
+    let nums = (0..100_000).map(|x| (x as f64).sqrt()).collect::<Vec<_>>();
+    let cc = new(&nums, &ChunkConfig::default())?;
+    let telemetry = cc.telemetry();
+    assert!(telemetry.mode_nanos > 0);
+    assert!(telemetry.delta_nanos > 0);
+    assert!(telemetry.bin_optimization_nanos > 0);
+    assert_eq!(telemetry.entropy_coding_nanos, 0);
+
+    cc.write_page(0, Vec::new())?;
+    let entropy_coding_nanos = cc.telemetry().entropy_coding_nanos;
+    assert!(entropy_coding_nanos > 0);
+    assert_eq!(
+      cc.clone().telemetry().entropy_coding_nanos,
+      entropy_coding_nanos
+    );
+    Ok(())
+  }
+
+  #[cfg(feature = "debug_sample")]
+  #[test]
+  fn test_analysis_sample_indices() -> PcoResult<()> {
+    let nums = (0..1000).map(|x| x as f32).collect::<Vec<_>>();
+    let cc = new(&nums, &ChunkConfig::default())?;
+    let indices = cc.analysis_sample_indices().unwrap().to_vec();
+    // 0.0 isn't a normal float, so it can never be sampled
+    assert!(!indices.is_empty());
+    assert!(indices.iter().all(|&i| i > 0 && i < 1000));
+    let values = indices.iter().map(|&i| nums[i]).collect::<Vec<_>>();
+    assert_eq!(cc.analysis_sample::<f32>(), Some(values));
+    assert_eq!(cc.analysis_sample::<u32>(), None);
+    // sampling is deterministic
+    let cc = new(&nums, &ChunkConfig::default())?;
+    assert_eq!(
+      cc.analysis_sample_indices().unwrap(),
+      indices
+    );
+
+    let cc = new(
+      &nums,
+      &ChunkConfig::default().with_mode_spec(crate::ModeSpec::Classic),
+    )?;
+    assert!(cc.analysis_sample_indices().is_none());
+    Ok(())
+  }
 }
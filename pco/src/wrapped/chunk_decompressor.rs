@@ -1,10 +1,13 @@
 use better_io::BetterBufRead;
 use std::marker::PhantomData;
+use std::ops::Range;
 
 use crate::data_types::Number;
 use crate::errors::{PcoError, PcoResult};
 use crate::metadata::ChunkMeta;
+use crate::wrapped::zone_map::{self, ZonedPage};
 use crate::wrapped::PageDecompressor;
+use crate::FULL_BATCH_N;
 
 /// Holds metadata about a chunk and can produce page decompressors.
 #[derive(Clone, Debug)]
@@ -46,4 +49,67 @@ impl<T: Number> ChunkDecompressor<T> {
   ) -> PcoResult<PageDecompressor<T, R>> {
     PageDecompressor::<T, R>::new(src, &self.meta, n)
   }
+
+  /// Returns the first number of each page, decoding only each page's first
+  /// batch.
+  ///
+  /// Each page is given as its bytes and count of numbers, in the same way as
+  /// for [`page_decompressor`][Self::page_decompressor].
+  /// This costs a page header and up to 256 numbers per page, so it gives a
+  /// cheap coarse view of the chunk, e.g. for building a rough index.
+  /// The values are exact, not approximations: each is precisely the number
+  /// that fully decompressing the page would produce first.
+  ///
+  /// Will return an error if corruptions or insufficient data are found.
+  pub fn page_first_values(&self, pages: &[(&[u8], usize)]) -> PcoResult<Vec<T>> {
+    pages
+      .iter()
+      .map(|&(src, n)| self.page_decompressor(src, n)?.decompress_first())
+      .collect()
+  }
+
+  /// Appends each number within the range to the destination, along with
+  /// its index within the chunk, returning the count of pages decompressed.
+  ///
+  /// Pages whose zone maps exclude the range are skipped without reading
+  /// their bytes at all.
+  /// The rest are fully decompressed and filtered.
+  /// Pages must be given in order, and indices count the numbers of skipped
+  /// pages too.
+  ///
+  /// Will return an error if corruptions or insufficient data are found.
+  pub fn decompress_matching(
+    &self,
+    pages: &[ZonedPage<T>],
+    predicate: Range<T>,
+    dst: &mut Vec<(usize, T)>,
+  ) -> PcoResult<usize> {
+    let mut n_pages_decompressed = 0;
+    let mut page_start = 0;
+    let mut batch = vec![T::default(); FULL_BATCH_N];
+    for page in pages {
+      let may_overlap = page.zone_map.map_or(true, |zone_map| {
+        zone_map.may_overlap(&predicate)
+      });
+      if may_overlap {
+        let mut pd = self.page_decompressor(page.src, page.n)?;
+        let mut idx = page_start;
+        loop {
+          let progress = pd.decompress(&mut batch)?;
+          for &num in &batch[..progress.n_processed] {
+            if zone_map::range_contains(&predicate, num) {
+              dst.push((idx, num));
+            }
+            idx += 1;
+          }
+          if progress.finished {
+            break;
+          }
+        }
+        n_pages_decompressed += 1;
+      }
+      page_start += page.n;
+    }
+    Ok(n_pages_decompressed)
+  }
 }
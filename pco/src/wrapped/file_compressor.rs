@@ -5,9 +5,10 @@ use crate::constants::HEADER_PADDING;
 use crate::data_types::Number;
 use crate::errors::PcoResult;
 use crate::metadata::format_version::FormatVersion;
+use crate::metadata::ChunkMeta;
 use crate::wrapped::chunk_compressor;
 use crate::wrapped::chunk_compressor::ChunkCompressor;
-use crate::ChunkConfig;
+use crate::{BinLayout, ChunkConfig};
 
 /// The top-level struct for compressing wrapped pco files.
 ///
@@ -49,12 +50,53 @@ pub struct FileCompressor {
 }
 
 impl FileCompressor {
+  /// Sets the format version to write (default: 3).
+  ///
+  /// Version 4 is needed for the Xor and per-page delta encodings:
+  /// [`DeltaSpec::TryXor`][crate::DeltaSpec::TryXor] and
+  /// [`DeltaSpec::TryConsecutivePerPage`][crate::DeltaSpec::TryConsecutivePerPage]
+  /// are only allowed with it, and only with it may
+  /// [`DeltaSpec::Auto`][crate::DeltaSpec::Auto] choose Xor.
+  /// However, pco 0.4.6 and older can't decompress version 4.
+  ///
+  /// Writing a header or creating a chunk compressor will return an error
+  /// if the version is older than 3 or newer than this release of pco
+  /// supports.
+  pub fn with_format_version(mut self, version: u8) -> Self {
+    self.format_version = FormatVersion(version);
+    self
+  }
+
+  /// Sets the format version to the oldest one that can hold a chunk with
+  /// this metadata, as well as any other chunks compressed with this config.
+  ///
+  /// To let [`DeltaSpec::Auto`][crate::DeltaSpec::Auto] choose any delta
+  /// encoding without making every file need a recent release of pco to
+  /// decompress, compress the first chunk with
+  /// [`CURRENT_FORMAT_VERSION`][crate::CURRENT_FORMAT_VERSION], then call
+  /// this before compressing the rest.
+  pub fn with_oldest_format_version(self, config: &ChunkConfig, meta: &ChunkMeta) -> Self {
+    let version = FormatVersion::default()
+      .max(FormatVersion::oldest_supporting(
+        &meta.delta_encoding,
+      ))
+      .max(FormatVersion::oldest_supporting_spec(
+        config.delta_spec,
+      ));
+    self.with_format_version(version.0)
+  }
+
+  pub(crate) fn format_version(&self) -> PcoResult<FormatVersion> {
+    FormatVersion::for_writing(self.format_version.0)
+  }
+
   /// Writes a short header to the destination.
   ///
-  /// Will return an error if the provided `Write` errors.
+  /// Will return an error if the provided `Write` errors or the format
+  /// version is invalid.
   pub fn write_header<W: Write>(&self, dst: W) -> PcoResult<W> {
     let mut writer = BitWriter::new(dst, HEADER_PADDING);
-    self.format_version.write_to(&mut writer)?;
+    self.format_version()?.write_to(&mut writer)?;
     writer.flush()?;
     Ok(writer.into_inner())
   }
@@ -71,6 +113,27 @@ impl FileCompressor {
     nums: &[T],
     config: &ChunkConfig,
   ) -> PcoResult<ChunkCompressor> {
-    chunk_compressor::new(nums, config)
+    chunk_compressor::new(nums, config, &self.format_version()?)
+  }
+
+  /// Creates a `ChunkCompressor` like
+  /// [`chunk_compressor`][Self::chunk_compressor], but using the mode, delta
+  /// encoding, and bins of a previously learned layout instead of analyzing
+  /// the numbers.
+  ///
+  /// This is much faster, and the numbers may lie outside the range the
+  /// layout was learned from, but compression ratio suffers if they are
+  /// distributed very differently.
+  /// Only the config's paging spec is used.
+  ///
+  /// Will return an error if any arguments provided are invalid or the
+  /// layout was learned from a different number type.
+  pub fn chunk_compressor_with_layout<T: Number>(
+    &self,
+    nums: &[T],
+    config: &ChunkConfig,
+    layout: &BinLayout,
+  ) -> PcoResult<ChunkCompressor> {
+    chunk_compressor::new_with_layout(nums, config, layout, &self.format_version()?)
   }
 }
@@ -7,6 +7,7 @@ use crate::bit_reader::BitReaderBuilder;
 use crate::constants::{CHUNK_META_PADDING, HEADER_PADDING};
 use crate::data_types::{LatentType, Number};
 use crate::errors::PcoResult;
+use crate::macros::trace_info;
 use crate::metadata::chunk::ChunkMeta;
 use crate::metadata::format_version::FormatVersion;
 use crate::wrapped::chunk_decompressor::ChunkDecompressor;
@@ -15,7 +16,7 @@ use crate::wrapped::chunk_decompressor::ChunkDecompressor;
 #[derive(Clone, Debug)]
 #[non_exhaustive]
 pub struct FileDecompressor {
-  format_version: FormatVersion,
+  pub(crate) format_version: FormatVersion,
 }
 
 impl FileDecompressor {
@@ -57,6 +58,11 @@ impl FileDecompressor {
         latent_type,
       )?
     };
+    trace_info!(
+      mode = ?chunk_meta.mode,
+      delta_encoding = ?chunk_meta.delta_encoding,
+      "read chunk metadata",
+    );
     let cd = ChunkDecompressor::new(chunk_meta)?;
     Ok((cd, reader_builder.into_inner()))
   }
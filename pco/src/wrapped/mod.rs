@@ -1,10 +1,16 @@
+#[cfg(feature = "entropy")]
+pub use crate::entropy::LatentEntropy;
+#[cfg(feature = "telemetry")]
+pub use crate::telemetry::ChunkTelemetry;
+pub(crate) use chunk_compressor::validate_config;
 pub use chunk_compressor::ChunkCompressor;
 pub use chunk_decompressor::ChunkDecompressor;
 pub use file_compressor::FileCompressor;
 pub use file_decompressor::FileDecompressor;
 pub use page_decompressor::PageDecompressor;
+pub use zone_map::{ZoneMap, ZonedPage};
 
-mod chunk_compressor;
+pub(crate) mod chunk_compressor;
 mod chunk_decompressor;
 mod file_compressor;
 mod file_decompressor;
@@ -12,3 +18,4 @@ mod file_decompressor;
 /// like header and chunk metadata.
 pub mod guarantee;
 mod page_decompressor;
+mod zone_map;
@@ -10,7 +10,7 @@ use crate::constants::{FULL_BATCH_N, PAGE_PADDING};
 use crate::data_types::Number;
 use crate::errors::{PcoError, PcoResult};
 use crate::latent_page_decompressor::DynLatentPageDecompressor;
-use crate::macros::match_latent_enum;
+use crate::macros::{match_latent_enum, trace_debug};
 use crate::metadata::page::PageMeta;
 use crate::metadata::per_latent_var::{PerLatentVar, PerLatentVarBuilder};
 use crate::metadata::{ChunkMeta, DeltaEncoding, DynBins, DynLatents, Mode};
@@ -71,6 +71,7 @@ fn make_latent_decompressors(
   page_meta: &PageMeta,
   n: usize,
 ) -> PcoResult<PerLatentVar<DynLatentPageDecompressor>> {
+  let delta_encoding = page_meta.delta_encoding(chunk_meta);
   let mut states = PerLatentVarBuilder::default();
   for (key, (chunk_latent_var_meta, page_latent_var_meta)) in chunk_meta
     .per_latent_var
@@ -78,7 +79,7 @@ fn make_latent_decompressors(
     .zip_exact(page_meta.per_latent_var.as_ref())
     .enumerated()
   {
-    let var_delta_encoding = chunk_meta.delta_encoding.for_latent_var(key);
+    let var_delta_encoding = delta_encoding.for_latent_var(key);
     let n_in_body = n.saturating_sub(var_delta_encoding.n_latents_per_state());
     let state = match_latent_enum!(
       &chunk_latent_var_meta.bins,
@@ -118,6 +119,11 @@ impl<R: BetterBufRead> PageDecompressorInner<R> {
 
     let page_meta =
       reader_builder.with_reader(|reader| unsafe { PageMeta::read_from(reader, chunk_meta) })?;
+    trace_debug!(
+      n,
+      delta_order = page_meta.delta_order,
+      "reading page",
+    );
 
     let mode = chunk_meta.mode;
     let latent_decompressors = make_latent_decompressors(chunk_meta, &page_meta, n)?;
@@ -129,7 +135,7 @@ impl<R: BetterBufRead> PageDecompressorInner<R> {
     Ok(Self {
       n,
       mode,
-      delta_encoding: chunk_meta.delta_encoding,
+      delta_encoding: page_meta.delta_encoding(chunk_meta),
       reader_builder,
       n_processed: 0,
       latent_decompressors,
@@ -280,6 +286,248 @@ impl<T: Number, R: BetterBufRead> PageDecompressor<T, R> {
     })
   }
 
+  // Decodes the rest of the page's latents as the entropy stage produced
+  // them, before any delta decoding or mode join, returning them along with
+  // the rest of the source.
+  #[cfg(feature = "debug_latents")]
+  pub(crate) fn decompress_latents(mut self) -> PcoResult<(PerLatentVar<DynLatents>, R)> {
+    use crate::metadata::LatentVarKey;
+
+    let inner = &mut self.inner;
+    let delta_encoding = inner.delta_encoding;
+    let n_remaining = inner.n_remaining();
+    // Delta latents line up with the pre-delta length of the other latents,
+    // and a latent variable without delta encoding has no state to exclude.
+    let n_pre_delta = move |key: LatentVarKey, n_remaining: usize| {
+      let n_per_state = match key {
+        LatentVarKey::Delta => delta_encoding.n_latents_per_state(),
+        _ => delta_encoding.for_latent_var(key).n_latents_per_state(),
+      };
+      n_remaining.saturating_sub(n_per_state)
+    };
+    let mut res = inner.latent_decompressors.as_ref().map(|key, lpd| {
+      let n_pre_delta = n_pre_delta(key, n_remaining);
+      match_latent_enum!(
+        lpd,
+        DynLatentPageDecompressor<L>(_lpd) => {
+          DynLatents::new(Vec::<L>::with_capacity(n_pre_delta)).unwrap()
+        }
+      )
+    });
+
+    while inner.n_remaining() > 0 {
+      let n_remaining = inner.n_remaining();
+      let batch_n = min(n_remaining, FULL_BATCH_N);
+      // the order matches decompress_batch, since the latent variables'
+      // batches are interleaved
+      for (key, (dyn_lpd, dst)) in inner
+        .latent_decompressors
+        .as_mut()
+        .zip_exact(res.as_mut())
+        .enumerated()
+      {
+        let limit = min(n_pre_delta(key, n_remaining), batch_n);
+        inner.reader_builder.with_reader(|reader| unsafe {
+          match_latent_enum!(
+            dyn_lpd,
+            DynLatentPageDecompressor<L>(lpd) => {
+              let dst = dst.downcast_mut::<L>().unwrap();
+              let start = dst.len();
+              // constant latent variables occupy no bits in the page
+              dst.resize(start + limit, lpd.maybe_constant_value.unwrap_or_default());
+              if lpd.maybe_constant_value.is_none() {
+                lpd.decompress_batch_pre_delta(reader, &mut dst[start..]);
+              }
+            }
+          );
+          Ok(())
+        })?;
+      }
+      inner.n_processed += batch_n;
+    }
+
+    inner.reader_builder.with_reader(|reader| {
+      reader.drain_empty_byte("expected trailing bits at end of page to be empty")
+    })?;
+    Ok((res, self.into_src()))
+  }
+
+  // Decodes only the page's first batch and returns its first number.
+  // Within a batch, all ANS symbols precede all offsets, so we can't stop
+  // after a single number.
+  pub(crate) fn decompress_first(mut self) -> PcoResult<T> {
+    let mut dst = [T::default(); FULL_BATCH_N];
+    let batch_n = min(self.inner.n, FULL_BATCH_N);
+    self.decompress_batch(&mut dst[..batch_n])?;
+    Ok(dst[0])
+  }
+
+  // Decodes a batch without delta encoding, writing only the numbers at the
+  // selected indices into the batch to dst.
+  fn decompress_selected_batch(
+    &mut self,
+    batch_n: usize,
+    selected: &[usize],
+    dst: &mut [T],
+  ) -> PcoResult<()> {
+    let inner = &mut self.inner;
+    let n_selected = selected.len();
+
+    // PRIMARY LATENTS
+    inner.reader_builder.with_reader(|reader| unsafe {
+      let primary_dst = T::transmute_to_latents(dst);
+      let dyn_lpd = inner
+        .latent_decompressors
+        .primary
+        .downcast_mut::<T::L>()
+        .unwrap();
+      dyn_lpd.decompress_selected_pre_delta(reader, batch_n, selected, primary_dst);
+      Ok(())
+    })?;
+
+    // SECONDARY LATENTS
+    if let Some(LatentScratch {
+      is_constant: false,
+      dst,
+    }) = &mut inner.secondary_scratch
+    {
+      let dyn_lpd = inner.latent_decompressors.secondary.as_mut().unwrap();
+      inner.reader_builder.with_reader(|reader| unsafe {
+        match_latent_enum!(
+          dyn_lpd,
+          DynLatentPageDecompressor<L>(lpd) => {
+            lpd.decompress_selected_pre_delta(
+              reader,
+              batch_n,
+              selected,
+              &mut dst.downcast_mut::<L>().unwrap()[..n_selected],
+            )
+          }
+        );
+        Ok(())
+      })?;
+    }
+
+    T::join_latents(
+      inner.mode,
+      T::transmute_to_latents(dst),
+      inner.secondary_scratch.as_ref().map(|scratch| &scratch.dst),
+    );
+    convert_from_latents_to_numbers(dst);
+
+    inner.n_processed += batch_n;
+    if inner.n_processed == inner.n {
+      inner.reader_builder.with_reader(|reader| {
+        reader.drain_empty_byte("expected trailing bits at end of page to be empty")
+      })?;
+    }
+    Ok(())
+  }
+
+  // Decompresses the rest of the page, appending only the numbers at the
+  // given index into the page and every `stride`th one after it to dst.
+  // Without delta encoding, we skip reading the offsets of the other numbers
+  // and skip batches that have none of them; with it, we decode everything.
+  pub(crate) fn decompress_strided_remaining(
+    &mut self,
+    mut next_idx: usize,
+    stride: usize,
+    dst: &mut Vec<T>,
+  ) -> PcoResult<()> {
+    let can_skip = self.inner.delta_encoding == DeltaEncoding::None;
+    let mut batch = [T::default(); FULL_BATCH_N];
+    let mut selected = Vec::with_capacity(FULL_BATCH_N);
+    while self.inner.n_remaining() > 0 {
+      let batch_start = self.inner.n_processed;
+      let batch_n = min(self.inner.n_remaining(), FULL_BATCH_N);
+      selected.clear();
+      while next_idx < batch_start + batch_n {
+        selected.push(next_idx - batch_start);
+        next_idx = next_idx.saturating_add(stride);
+      }
+
+      if !can_skip {
+        self.decompress_batch(&mut batch[..batch_n])?;
+        dst.extend(selected.iter().map(|&i| batch[i]));
+      } else if selected.is_empty() {
+        self.skip_batch(batch_n)?;
+      } else {
+        let batch = &mut batch[..selected.len()];
+        self.decompress_selected_batch(batch_n, &selected, batch)?;
+        dst.extend_from_slice(batch);
+      }
+    }
+    Ok(())
+  }
+
+  fn skip_batch(&mut self, batch_n: usize) -> PcoResult<()> {
+    let inner = &mut self.inner;
+    let n_remaining = inner.n_remaining();
+    let delta_n_latents_per_state = inner.delta_encoding.n_latents_per_state();
+
+    for (lpd, is_delta) in [
+      (
+        inner
+          .latent_decompressors
+          .delta
+          .as_mut()
+          .filter(|_| matches!(&inner.delta_scratch, Some(scratch) if !scratch.is_constant)),
+        true,
+      ),
+      (
+        Some(&mut inner.latent_decompressors.primary),
+        false,
+      ),
+      (
+        inner
+          .latent_decompressors
+          .secondary
+          .as_mut()
+          .filter(|_| matches!(&inner.secondary_scratch, Some(scratch) if !scratch.is_constant)),
+        false,
+      ),
+    ] {
+      let Some(dyn_lpd) = lpd else {
+        continue;
+      };
+      inner.reader_builder.with_reader(|reader| unsafe {
+        match_latent_enum!(
+          dyn_lpd,
+          DynLatentPageDecompressor<L>(lpd) => {
+            if is_delta {
+              // same limit as in decompress_batch
+              let limit = min(
+                n_remaining.saturating_sub(delta_n_latents_per_state),
+                batch_n,
+              );
+              lpd.skip_batch_pre_delta(reader, limit);
+            } else {
+              lpd.skip_batch(n_remaining, reader, batch_n);
+            }
+          }
+        );
+        Ok(())
+      })?;
+    }
+
+    inner.n_processed += batch_n;
+    if inner.n_processed == inner.n {
+      inner.reader_builder.with_reader(|reader| {
+        reader.drain_empty_byte("expected trailing bits at end of page to be empty")
+      })?;
+    }
+    Ok(())
+  }
+
+  // Advances past the rest of the page without producing numbers, e.g. to
+  // find where the next chunk begins.
+  pub(crate) fn skip_remaining(&mut self) -> PcoResult<()> {
+    while self.inner.n_remaining() > 0 {
+      self.skip_batch(min(self.inner.n_remaining(), FULL_BATCH_N))?;
+    }
+    Ok(())
+  }
+
   /// Returns the rest of the compressed data source.
   pub fn into_src(self) -> R {
     self.inner.reader_builder.into_inner()
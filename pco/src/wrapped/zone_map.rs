@@ -0,0 +1,147 @@
+use std::ops::Range;
+
+use crate::data_types::Number;
+
+/// The minimum and maximum of a page's numbers, which can be stored in a
+/// wrapping format alongside the page to skip it during range scans.
+///
+/// Numbers are ordered as they are during compression, so floats follow IEEE
+/// total order: e.g. -0.0 sorts before 0.0, and positive NaNs after
+/// infinity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ZoneMap<T: Number> {
+  /// The least number in the page.
+  pub min: T,
+  /// The greatest number in the page.
+  pub max: T,
+}
+
+impl<T: Number> ZoneMap<T> {
+  /// Computes the zone map of a page's numbers, or `None` if there are
+  /// none.
+  pub fn new(nums: &[T]) -> Option<Self> {
+    let first = *nums.first()?;
+    let (min, max) = nums.iter().fold((first, first), |(min, max), &num| {
+      let latent = num.to_latent_ordered();
+      (
+        if latent < min.to_latent_ordered() {
+          num
+        } else {
+          min
+        },
+        if latent > max.to_latent_ordered() {
+          num
+        } else {
+          max
+        },
+      )
+    });
+    Some(Self { min, max })
+  }
+
+  /// Returns whether any number in the page might lie within the range.
+  pub fn may_overlap(&self, range: &Range<T>) -> bool {
+    let start = range.start.to_latent_ordered();
+    let end = range.end.to_latent_ordered();
+    start < end && self.min.to_latent_ordered() < end && self.max.to_latent_ordered() >= start
+  }
+}
+
+pub(crate) fn range_contains<T: Number>(range: &Range<T>, num: T) -> bool {
+  let latent = num.to_latent_ordered();
+  latent >= range.start.to_latent_ordered() && latent < range.end.to_latent_ordered()
+}
+
+/// One page of a chunk to be scanned by
+/// [`ChunkDecompressor::decompress_matching`][crate::wrapped::ChunkDecompressor::decompress_matching].
+#[derive(Clone, Copy, Debug)]
+pub struct ZonedPage<'a, T: Number> {
+  /// The page's compressed bytes.
+  pub src: &'a [u8],
+  /// The count of numbers in the page.
+  pub n: usize,
+  /// The page's zone map, if one was stored.
+  /// Pages without one are always decompressed.
+  pub zone_map: Option<ZoneMap<T>>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::errors::PcoResult;
+  use crate::wrapped::{FileCompressor, FileDecompressor};
+  use crate::{ChunkConfig, PagingSpec};
+
+  #[test]
+  fn test_zone_map() {
+    assert_eq!(ZoneMap::<i32>::new(&[]), None);
+    let zone_map = ZoneMap::new(&[3.0, -0.0, f32::NEG_INFINITY, 7.5, 0.0]).unwrap();
+    assert_eq!(zone_map.min, f32::NEG_INFINITY);
+    assert_eq!(zone_map.max, 7.5);
+    assert!(zone_map.may_overlap(&(7.5..8.0)));
+    assert!(!zone_map.may_overlap(&(7.6..8.0)));
+    assert!(!zone_map.may_overlap(&(-1.0..-1.0)));
+  }
+
+  #[test]
+  fn test_decompress_matching_skips_pages() -> PcoResult<()> {
+    let nums = (0..4000).map(|i| i * 2 + i % 3).collect::<Vec<i64>>();
+    let n_per_page = vec![1000; 4];
+    let config = ChunkConfig::default().with_paging_spec(PagingSpec::Exact(n_per_page.clone()));
+
+    let fc = FileCompressor::default();
+    let header = fc.write_header(Vec::new())?;
+    let cc = fc.chunk_compressor(&nums, &config)?;
+    let chunk_meta = cc.write_chunk_meta(Vec::new())?;
+    let pages = (0..n_per_page.len())
+      .map(|page_idx| cc.write_page(page_idx, Vec::new()))
+      .collect::<PcoResult<Vec<_>>>()?;
+
+    let (fd, _) = FileDecompressor::new(header.as_slice())?;
+    let (cd, _) = fd.chunk_decompressor::<i64, _>(chunk_meta.as_slice())?;
+    let zoned_pages = |include: &dyn Fn(usize) -> bool| {
+      pages
+        .iter()
+        .enumerate()
+        .map(|(page_idx, page)| {
+          let page_nums = &nums[page_idx * 1000..(page_idx + 1) * 1000];
+          ZonedPage {
+            // pages that shouldn't be touched get no bytes at all
+            src: if include(page_idx) { page } else { &[] },
+            n: page_nums.len(),
+            zone_map: ZoneMap::new(page_nums),
+          }
+        })
+        .collect::<Vec<_>>()
+    };
+
+    let predicate = 2500..3000;
+    let expected = nums
+      .iter()
+      .copied()
+      .enumerate()
+      .filter(|(_, num)| predicate.contains(num))
+      .collect::<Vec<_>>();
+    let mut matches = Vec::new();
+    let n_decompressed = cd.decompress_matching(
+      &zoned_pages(&|page_idx| page_idx == 1),
+      predicate.clone(),
+      &mut matches,
+    )?;
+    assert_eq!(n_decompressed, 1);
+    assert_eq!(matches, expected);
+
+    // pages without zone maps are always decompressed
+    let mut unzoned = zoned_pages(&|_| true);
+    for page in &mut unzoned {
+      page.zone_map = None;
+    }
+    let mut matches = Vec::new();
+    assert_eq!(
+      cd.decompress_matching(&unzoned, predicate, &mut matches)?,
+      4
+    );
+    assert_eq!(matches, expected);
+    Ok(())
+  }
+}
@@ -0,0 +1,44 @@
+use std::convert::TryInto;
+
+use clap::Parser;
+
+use crate::bench::codecs::{utils, CodecInternal};
+use crate::dtypes::PcoNumber;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Lz4Config {}
+
+impl CodecInternal for Lz4Config {
+  fn name(&self) -> &'static str {
+    "lz4"
+  }
+
+  fn get_confs(&self) -> Vec<(&'static str, String)> {
+    vec![]
+  }
+
+  fn compress<T: PcoNumber>(&self, nums: &[T]) -> Vec<u8> {
+    let mut res = Vec::new();
+    res.extend((nums.len() as u32).to_le_bytes());
+    unsafe {
+      res.extend(lz4_flex::block::compress(
+        utils::num_slice_to_bytes(nums),
+      ));
+    }
+    res
+  }
+
+  fn decompress<T: PcoNumber>(&self, bytes: &[u8]) -> Vec<T> {
+    let len = u32::from_le_bytes(bytes[0..4].try_into().unwrap()) as usize;
+    let mut res = Vec::<T>::with_capacity(len);
+    unsafe {
+      res.set_len(len);
+      lz4_flex::block::decompress_into(
+        &bytes[4..],
+        utils::num_slice_to_bytes_mut(res.as_mut_slice()),
+      )
+      .unwrap();
+    }
+    res
+  }
+}
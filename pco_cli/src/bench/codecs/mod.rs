@@ -23,6 +23,8 @@ use clap::{CommandFactory, FromArgMatches};
 mod blosc;
 #[cfg(feature = "full_bench")]
 mod brotli;
+#[cfg(feature = "lz4")]
+mod lz4;
 mod parquet;
 mod pco;
 #[cfg(feature = "full_bench")]
@@ -250,6 +252,8 @@ impl FromStr for CodecConfig {
       "blosc" | "blosc2" => blosc::BloscConfig::from_kv_args(&clap_kv_args),
       #[cfg(feature = "full_bench")]
       "brotli" => brotli::BrotliConfig::from_kv_args(&clap_kv_args),
+      #[cfg(feature = "lz4")]
+      "lz4" => lz4::Lz4Config::from_kv_args(&clap_kv_args),
       "parquet" => ParquetConfig::from_kv_args(&clap_kv_args),
       "pco" | "pcodec" => ChunkConfigOpt::from_kv_args(&clap_kv_args),
       #[cfg(feature = "full_bench")]
@@ -264,7 +268,7 @@ impl FromStr for CodecConfig {
       "zstd" | "zstandard" => ZstdConfig::from_kv_args(&clap_kv_args),
       _ => {
         return Err(anyhow!(
-          "Unknown codec: {}. Perhaps rebuild with the full_bench feature?",
+          "Unknown codec: {}. Perhaps rebuild with the full_bench or lz4 feature?",
           name
         ))
       }
@@ -1,4 +1,4 @@
-use pco::{ChunkConfig, DeltaSpec, ModeSpec};
+use pco::{DeltaSpec, ModeSpec};
 
 use crate::bench::codecs::CodecInternal;
 use crate::chunk_config_opt::ChunkConfigOpt;
@@ -9,7 +9,9 @@ fn unparse_delta_spec(spec: &DeltaSpec) -> String {
     DeltaSpec::Auto => "Auto".to_string(),
     DeltaSpec::None => "None".to_string(),
     DeltaSpec::TryConsecutive(order) => format!("Consecutive@{}", order),
+    DeltaSpec::TryConsecutivePerPage(order) => format!("ConsecutivePerPage@{}", order),
     DeltaSpec::TryLookback => "Lookback".to_string(),
+    DeltaSpec::TryXor => "Xor".to_string(),
     _ => "Unknown".to_string(),
   }
 }
@@ -35,12 +37,26 @@ impl CodecInternal for ChunkConfigOpt {
       ("level", self.level.to_string()),
       ("delta", unparse_delta_spec(&self.delta)),
       ("mode", unparse_mode_spec(&self.mode)),
-      ("chunk-n", self.chunk_n.to_string()),
+      (
+        "chunk-n",
+        self
+          .chunk_n
+          .map(|n| n.to_string())
+          .unwrap_or("default".to_string()),
+      ),
+      (
+        "soft-max-offset-bits",
+        self
+          .soft_max_offset_bits
+          .map(|bits| bits.to_string())
+          .unwrap_or("none".to_string()),
+      ),
+      ("seed", self.seed.to_string()),
     ]
   }
 
   fn compress<T: PcoNumber>(&self, nums: &[T]) -> Vec<u8> {
-    let chunk_config = ChunkConfig::from(self);
+    let chunk_config = self.to_config::<T>();
     pco::standalone::simple_compress(nums, &chunk_config).expect("invalid config")
   }
 
@@ -3,7 +3,7 @@
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::fs;
-use std::ops::AddAssign;
+use std::ops::{AddAssign, RangeInclusive};
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
@@ -12,12 +12,15 @@ use anyhow::{anyhow, Result};
 use arrow::datatypes::{DataType, Schema};
 use clap::{Args, Parser, ValueEnum};
 use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
 use tabled::settings::object::Columns;
 use tabled::settings::{Alignment, Modify, Style};
 use tabled::{Table, Tabled};
 
 use pco::data_types::NumberType;
 use pco::match_number_enum;
+use pco::standalone::Compressor;
+use pco::ChunkConfig;
 
 use crate::bench::codecs::CodecConfig;
 use crate::input::{Format, InputColumnOpt, InputFileOpt};
@@ -91,10 +94,22 @@ pub struct BenchOpt {
   /// input_name, codec, compression_time/s, decompress_time/s, compressed_size/bytes
   #[arg(long)]
   pub results_csv: Option<PathBuf>,
+  /// JSON file to write the results of this command to, including both
+  /// durations and their inverses, and aggregates over all datasets.
+  #[arg(long)]
+  pub results_json: Option<PathBuf>,
+  /// Benchmark each pco codec at every compression level in this inclusive
+  /// range, e.g. `0:12`, instead of only its configured level.
+  ///
+  /// Each dataset is loaded into memory once and reused for every level.
+  /// Other codecs are benchmarked as usual.
+  #[arg(long, value_parser = parse::level_range)]
+  pub sweep_levels: Option<RangeInclusive<usize>>,
   /// What units to print the results table in.
-  /// Does not affect the results CSV.
-  #[arg(short, long, default_value = "linear")]
-  pub units: Units,
+  /// Does not affect the results CSV or JSON.
+  /// Defaults to linear, or inverse when sweeping levels.
+  #[arg(short, long)]
+  pub units: Option<Units>,
   /// Aggregate all columns or sub-datasets of the input data and use this
   /// dataset name in the  --results-csv output.
   /// If a results CSV is specified but this is unspecified, disaggregated stats
@@ -246,6 +261,79 @@ impl PrintStat {
   }
 }
 
+#[derive(Serialize)]
+struct JsonStat<'a> {
+  dataset: &'a str,
+  codec: &'a str,
+  compress_dt_s: f32,
+  decompress_dt_s: f32,
+  compressed_size: usize,
+  uncompressed_size: usize,
+  compress_mb_per_s: f32,
+  decompress_mb_per_s: f32,
+  compression_ratio: f32,
+}
+
+impl<'a> From<&'a PrintStat> for JsonStat<'a> {
+  fn from(stat: &'a PrintStat) -> Self {
+    Self {
+      dataset: &stat.dataset,
+      codec: &stat.codec,
+      compress_dt_s: stat.bench_stat.compress_dt.as_secs_f32(),
+      decompress_dt_s: stat.bench_stat.decompress_dt.as_secs_f32(),
+      compressed_size: stat.bench_stat.compressed_size,
+      uncompressed_size: stat.bench_stat.uncompressed_size,
+      compress_mb_per_s: stat.inv_stat.compress_mb_per_s,
+      decompress_mb_per_s: stat.inv_stat.decompress_mb_per_s,
+      compression_ratio: stat.inv_stat.compression_ratio,
+    }
+  }
+}
+
+fn write_results_json(stats: &[PrintStat], opt: &BenchOpt) -> Result<()> {
+  let Some(results_json) = opt.results_json.as_ref() else {
+    return Ok(());
+  };
+
+  let json_stats = stats.iter().map(JsonStat::from).collect::<Vec<_>>();
+  fs::write(
+    results_json,
+    serde_json::to_vec_pretty(&json_stats)?,
+  )?;
+  Ok(())
+}
+
+// Replaces each pco codec with one copy per level, keeping its other
+// configurations.
+fn sweep_levels(codecs: &[CodecConfig], levels: RangeInclusive<usize>) -> Result<Vec<CodecConfig>> {
+  for level in levels.clone() {
+    Compressor::<i32>::new(&ChunkConfig::default().with_compression_level(level))
+      .map_err(|e| anyhow!("cannot sweep level {}: {}", level, e))?;
+  }
+
+  let mut res = Vec::new();
+  for codec in codecs {
+    if codec.name() != "pco" {
+      res.push(codec.clone());
+      continue;
+    }
+
+    let other_confs = codec
+      .details(false)
+      .split(':')
+      .filter(|conf| !conf.is_empty() && !conf.starts_with("level="))
+      .map(|conf| format!(":{}", conf))
+      .collect::<String>();
+    for level in levels.clone() {
+      res.push(CodecConfig::from_str(&format!(
+        "pco{}:level={}",
+        other_confs, level
+      ))?);
+    }
+  }
+  Ok(res)
+}
+
 fn core_dtype_to_str(dtype: NumberType) -> String {
   match_number_enum!(
     dtype,
@@ -396,8 +484,14 @@ fn print_stats(disaggregated_stats: Vec<PrintStat>, opt: &BenchOpt) -> Result<()
     })
     .collect::<Vec<_>>();
   let stats = [disaggregated_stats.clone(), aggregated_stats.clone()].concat();
+  write_results_json(&stats, opt)?;
   let mut table_builder = Table::builder(stats);
-  let unit_columns_to_keep: Vec<usize> = match opt.units {
+  let default_units = if opt.sweep_levels.is_some() {
+    Units::Inverse
+  } else {
+    Units::Linear
+  };
+  let unit_columns_to_keep: Vec<usize> = match opt.units.as_ref().unwrap_or(&default_units) {
     // we expect these to be sorted
     Units::All => (2..8).collect(),
     Units::Linear => (2..5).collect(),
@@ -427,6 +521,10 @@ pub fn bench(mut opt: BenchOpt) -> Result<()> {
     ));
   }
 
+  if let Some(levels) = opt.sweep_levels.clone() {
+    opt.codecs = sweep_levels(&opt.codecs, levels)?;
+  }
+
   let input = &mut opt.input;
   if input.input.is_none() && input.input_format.is_none() {
     input.input = Some(PathBuf::from(DEFAULT_BINARY_DIR));
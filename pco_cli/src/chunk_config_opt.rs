@@ -1,6 +1,7 @@
 use clap::Parser;
 
-use pco::{ChunkConfig, DeltaSpec, ModeSpec, PagingSpec};
+use pco::data_types::Number;
+use pco::{standalone, ChunkConfig, DeltaSpec, ModeSpec, PagingSpec};
 
 use crate::parse;
 
@@ -12,7 +13,8 @@ pub struct ChunkConfigOpt {
   // We fully quality `Option` to use a value parser that returns Option<usize>
   // instead of just usize. See
   // https://github.com/clap-rs/clap/issues/5536#issuecomment-2179646989
-  /// Can be "Auto", "None", "Consecutive@<order>", or "Lookback".
+  /// Can be "Auto", "None", "Consecutive@<order>",
+  /// "ConsecutivePerPage@<max order>", "Lookback", or "Xor".
   #[arg(long, default_value = "Auto", value_parser = parse::delta_spec)]
   pub delta: DeltaSpec,
   /// Can be "Auto", "Classic", "FloatMult@<base>", "FloatQuant@<k>", or
@@ -22,16 +24,39 @@ pub struct ChunkConfigOpt {
   /// classic if the given mode is especially bad.
   #[arg(long, default_value = "Auto", value_parser = parse::mode_spec)]
   pub mode: ModeSpec,
-  #[arg(long, default_value_t = pco::DEFAULT_MAX_PAGE_N)]
-  pub chunk_n: usize,
+  /// Count of numbers per chunk.
+  ///
+  /// Defaults to one giving about the same byte footprint for every data
+  /// type, e.g. 2^18 for 64-bit types and 2^20 for 16-bit ones.
+  #[arg(long)]
+  pub chunk_n: Option<usize>,
+  /// If set, prefers bins with offsets no wider than this many bits,
+  /// trading a little compression ratio for decompression speed.
+  #[arg(long)]
+  pub soft_max_offset_bits: Option<u32>,
+  /// Seed for the random subsampling done while choosing a mode.
+  ///
+  /// The same input, config, and seed always produce the same bytes.
+  #[arg(long, default_value = "0")]
+  pub seed: u64,
 }
 
-impl From<&ChunkConfigOpt> for ChunkConfig {
-  fn from(opt: &ChunkConfigOpt) -> Self {
+impl ChunkConfigOpt {
+  pub fn chunk_n<T: Number>(&self) -> usize {
+    self
+      .chunk_n
+      .unwrap_or_else(standalone::default_chunk_size::<T>)
+  }
+
+  pub fn to_config<T: Number>(&self) -> ChunkConfig {
     ChunkConfig::default()
-      .with_compression_level(opt.level)
-      .with_delta_spec(opt.delta)
-      .with_mode_spec(opt.mode)
-      .with_paging_spec(PagingSpec::EqualPagesUpTo(opt.chunk_n))
+      .with_compression_level(self.level)
+      .with_delta_spec(self.delta)
+      .with_mode_spec(self.mode)
+      .with_paging_spec(PagingSpec::EqualPagesUpTo(
+        self.chunk_n::<T>(),
+      ))
+      .with_soft_max_offset_bits(self.soft_max_offset_bits)
+      .with_analysis_seed(self.seed)
   }
 }
@@ -0,0 +1,133 @@
+use anyhow::Result;
+
+use pco::data_types::Number;
+use pco::standalone::{self, FileDecompressor, MaybeChunkDecompressor};
+use pco::{ChunkConfig, DeltaSpec, ModeSpec, PagingSpec};
+
+use crate::chunk_config_opt::ChunkConfigOpt;
+
+// how many numbers to buffer and trial-compress before advising
+pub const ADVICE_SAMPLE_N: usize = 1 << 16;
+// We only advise a setting that compresses the sample at least this many
+// times smaller, so that advisories aren't noise.
+const MIN_ADVISED_RATIO: f64 = 1.1;
+
+fn config_with_chunk_n<T: Number>(opt: &ChunkConfigOpt, chunk_n: usize) -> ChunkConfig {
+  opt
+    .to_config::<T>()
+    .with_paging_spec(PagingSpec::EqualPagesUpTo(chunk_n))
+}
+
+// the mode and delta encoding pco chose for the first chunk
+fn describe_first_chunk<T: Number>(compressed: &[u8]) -> Result<String> {
+  let (fd, src) = FileDecompressor::new(compressed)?;
+  let description = match fd.chunk_decompressor::<T, _>(src)? {
+    MaybeChunkDecompressor::Some(cd) => format!(
+      "mode {:?} and delta encoding {:?}",
+      cd.meta().mode,
+      cd.meta().delta_encoding,
+    ),
+    MaybeChunkDecompressor::EndOfData(_) => "no chunks".to_string(),
+  };
+  Ok(description)
+}
+
+fn advisory(
+  setting: &str,
+  n: usize,
+  size: usize,
+  better_size: usize,
+  detail: &str,
+) -> Option<String> {
+  (size as f64 >= better_size as f64 * MIN_ADVISED_RATIO).then(|| {
+    format!(
+      "{} would compress a sample of the first {} numbers to {} bytes instead of \
+       {} ({:.2}x smaller){}",
+      setting,
+      n,
+      better_size,
+      size,
+      size as f64 / better_size as f64,
+      detail,
+    )
+  })
+}
+
+/// Returns advisories for forced settings that compress the sample much
+/// worse than what pco would choose.
+///
+/// Only settings the user gave explicitly are checked: a delta spec or mode
+/// spec other than Auto, and `chunk_n` if it is `Some`.
+pub fn advise<T: Number>(
+  sample: &[T],
+  opt: &ChunkConfigOpt,
+  chunk_n: Option<usize>,
+) -> Result<Vec<String>> {
+  let mut advisories = Vec::new();
+  if sample.is_empty() {
+    return Ok(advisories);
+  }
+
+  let n = sample.len();
+  let config = config_with_chunk_n::<T>(
+    opt,
+    chunk_n.unwrap_or_else(|| opt.chunk_n::<T>()),
+  );
+  let size = standalone::simple_compress(sample, &config)?.len();
+
+  if opt.delta != DeltaSpec::Auto {
+    let auto_compressed = standalone::simple_compress(
+      sample,
+      &config.clone().with_delta_spec(DeltaSpec::Auto),
+    )?;
+    let detail = format!(
+      ", using {}",
+      describe_first_chunk::<T>(&auto_compressed)?
+    );
+    advisories.extend(advisory(
+      "--delta Auto",
+      n,
+      size,
+      auto_compressed.len(),
+      &detail,
+    ));
+  }
+
+  if opt.mode != ModeSpec::Auto {
+    let auto_compressed = standalone::simple_compress(
+      sample,
+      &config.clone().with_mode_spec(ModeSpec::Auto),
+    )?;
+    let detail = format!(
+      ", using {}",
+      describe_first_chunk::<T>(&auto_compressed)?
+    );
+    advisories.extend(advisory(
+      "--mode Auto",
+      n,
+      size,
+      auto_compressed.len(),
+      &detail,
+    ));
+  }
+
+  if let Some(chunk_n) = chunk_n {
+    let suggested = standalone::suggest_chunk_size(sample, &config)?;
+    if suggested > chunk_n && suggested <= n {
+      let suggested_size = standalone::simple_compress(
+        sample,
+        &config_with_chunk_n::<T>(opt, suggested),
+      )?
+      .len();
+      advisories.extend(advisory(
+        &format!("--chunk-n {}", suggested),
+        n,
+        size,
+        suggested_size,
+        "",
+      ));
+    }
+  }
+
+  Ok(advisories)
+}
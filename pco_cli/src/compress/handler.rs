@@ -1,48 +1,71 @@
 use std::cmp::min;
-use std::fs::OpenOptions;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use arrow::datatypes::Schema;
 
-use pco::standalone::FileCompressor;
-use pco::ChunkConfig;
+use pco::standalone::{self, ChunkConfigSummary, FileCompressor};
 
 use crate::arrow_handlers::ArrowHandlerImpl;
-use crate::compress::CompressOpt;
+use crate::compress::{self, advice, CompressOpt};
 use crate::dtypes::ArrowNumber;
 use crate::{input, utils};
 
+// how many numbers to buffer before suggesting a chunk size for --auto-chunk
+const AUTO_CHUNK_SAMPLE_N: usize = 1 << 20;
+
 pub trait CompressHandler {
   fn compress(&self, opt: &CompressOpt, schema: &Schema) -> Result<()>;
 }
 
 impl<P: ArrowNumber> CompressHandler for ArrowHandlerImpl<P> {
   fn compress(&self, opt: &CompressOpt, schema: &Schema) -> Result<()> {
-    let mut open_options = OpenOptions::new();
-    open_options.write(true);
-    if opt.overwrite {
-      open_options.create(true);
-      open_options.truncate(true);
-    } else {
-      open_options.create_new(true);
-    }
-    let file = open_options.open(&opt.path)?;
-
-    let config = ChunkConfig::from(&opt.chunk_config);
-    let chunk_size = opt.chunk_config.chunk_n;
-    let fc = FileCompressor::default();
-    fc.write_header(&file)?;
+    let file = utils::create_output_file(&opt.path, opt.overwrite)?;
 
+    let config = opt.chunk_config.to_config::<P::Pco>();
+    let mut chunk_size = (!opt.auto_chunk).then(|| opt.chunk_config.chunk_n::<P::Pco>());
     let col_idx = utils::find_col_idx(
       schema,
       opt.input_column.col_idx,
       &opt.input_column.col_name,
     )?;
+    // the header gets written along with the first chunk, so that it can
+    // contain a manifest and the oldest format version the chunk supports
+    let mut fc = FileCompressor::default()
+      .with_format_version(pco::CURRENT_FORMAT_VERSION)
+      .with_source_info(compress::source_info(
+        opt,
+        schema.field(col_idx),
+      )?);
+    let mut wrote_header = false;
+    let mut advised = opt.quiet;
+
     let reader = input::new_column_reader(schema, col_idx, &opt.input_file)?;
     let mut num_buffer = Vec::<P::Pco>::new();
 
-    let write_chunks = |num_buffer: &mut Vec<P::Pco>, finish: bool| -> Result<()> {
+    let mut write_chunks = |num_buffer: &mut Vec<P::Pco>, finish: bool| -> Result<()> {
       let n = num_buffer.len();
+      if !advised {
+        if !finish && n < advice::ADVICE_SAMPLE_N {
+          return Ok(());
+        }
+        let explicit_chunk_n = opt.chunk_config.chunk_n.filter(|_| !opt.auto_chunk);
+        for advisory in advice::advise(
+          &num_buffer[..min(n, advice::ADVICE_SAMPLE_N)],
+          &opt.chunk_config,
+          explicit_chunk_n,
+        )? {
+          eprintln!("advisory: {}", advisory);
+        }
+        advised = true;
+      }
+      let chunk_size = match chunk_size {
+        Some(chunk_size) => chunk_size,
+        None if !finish && n < AUTO_CHUNK_SAMPLE_N => return Ok(()),
+        None => *chunk_size.insert(standalone::suggest_chunk_size(
+          &num_buffer[..min(n, AUTO_CHUNK_SAMPLE_N)],
+          &config,
+        )?),
+      };
       let n_chunks = if finish {
         n.div_ceil(chunk_size)
       } else {
@@ -52,8 +75,19 @@ impl<P: ArrowNumber> CompressHandler for ArrowHandlerImpl<P> {
       let mut end = 0;
       for _ in 0..n_chunks {
         end = min(start + chunk_size, num_buffer.len());
-        fc.chunk_compressor(&num_buffer[start..end], &config)?
-          .write_chunk(&file)?;
+        let cc = fc.chunk_compressor(&num_buffer[start..end], &config)?;
+        if !wrote_header {
+          fc = fc.clone().with_oldest_format_version(&config, cc.meta());
+          if opt.manifest {
+            fc = fc.clone().with_manifest(Some(ChunkConfigSummary::new(
+              &config,
+              cc.meta(),
+            )));
+          }
+          fc.write_header(&file)?;
+          wrote_header = true;
+        }
+        cc.write_chunk(&file)?;
         start = end;
       }
       num_buffer.drain(..end);
@@ -62,12 +96,25 @@ impl<P: ArrowNumber> CompressHandler for ArrowHandlerImpl<P> {
 
     for array_result in reader {
       let array = array_result?;
+      // .pco files have no notion of validity, so we'd silently compress
+      // whatever garbage values sit behind the nulls
+      if array.null_count() > 0 {
+        return Err(anyhow!(
+          "column contains {} nulls, which .pco files can't represent; \
+          consider the pco library's compress_optionals instead",
+          array.null_count(),
+        ));
+      }
       num_buffer.extend(utils::arrow_to_nums::<P>(array));
       write_chunks(&mut num_buffer, false)?;
     }
 
     write_chunks(&mut num_buffer, true)?;
 
+    if !wrote_header {
+      fc = fc.with_format_version(pco::DEFAULT_FORMAT_VERSION);
+      fc.write_header(&file)?;
+    }
     fc.write_footer(&file)?;
     Ok(())
   }
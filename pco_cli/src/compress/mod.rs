@@ -1,15 +1,25 @@
 use std::path::PathBuf;
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{anyhow, Result};
+use arrow::datatypes::Field;
+use clap::{Parser, ValueEnum};
+
+use pco::standalone::SourceInfo;
 
 use crate::input::{InputColumnOpt, InputFileOpt};
 use crate::utils;
-use crate::{arrow_handlers, chunk_config_opt, input};
+use crate::{arrow_handlers, chunk_config_opt, input, logical_type, string_dict};
 
+pub mod advice;
 pub mod handler;
 
 /// Compress from a different format into standalone .pco
+///
+/// String columns are compressed as dictionary indices, with the dictionary
+/// written alongside the output in a .pco.dict.json file.
+/// Timestamp and date columns similarly get their dtype written to a
+/// .pco.dtype.json file, as do UInt32 columns of Unicode code points marked
+/// with the Arrow extension name "char".
 #[derive(Clone, Debug, Parser)]
 pub struct CompressOpt {
   /// Overwrite the output path (if it exists) instead of failing.
@@ -21,11 +31,48 @@ pub struct CompressOpt {
   pub input_column: InputColumnOpt,
   #[command(flatten)]
   pub chunk_config: chunk_config_opt::ChunkConfigOpt,
+  /// Record a summary of the settings used (level, and the mode and delta
+  /// encoding chosen for the first chunk) in the file's header.
+  #[arg(long)]
+  pub manifest: bool,
+  /// Record the input format and column name in the file's header, so that
+  /// `pcodec decompress` can default to writing a similar file.
+  ///
+  /// Not supported for string columns.
+  #[arg(long)]
+  pub record_source: bool,
+  /// Choose the chunk size by trial-compressing the start of the input,
+  /// overriding --chunk-n.
+  #[arg(long)]
+  pub auto_chunk: bool,
+  /// Don't print advisories to stderr.
+  ///
+  /// By default, an explicit --delta, --mode, or --chunk-n that compresses
+  /// a sample from the start of the input much worse than another setting
+  /// gets an advisory suggesting that setting.
+  /// Advisories never change the output.
+  #[arg(long)]
+  pub quiet: bool,
 
   /// Output .pco path to write to.
   pub path: PathBuf,
 }
 
+/// Returns the source info to record in the header, if --record-source was
+/// given.
+pub fn source_info(opt: &CompressOpt, field: &Field) -> Result<Option<SourceInfo>> {
+  if !opt.record_source {
+    return Ok(None);
+  }
+
+  let format = opt.input_file.format()?;
+  let format_name = format.to_possible_value().unwrap().get_name().to_string();
+  Ok(Some(SourceInfo::new(
+    format_name,
+    field.name(),
+  )))
+}
+
 pub fn compress(opt: CompressOpt) -> Result<()> {
   let schema = input::get_schema(&opt.input_column, &opt.input_file)?;
   let col_idx = utils::find_col_idx(
@@ -33,7 +80,17 @@ pub fn compress(opt: CompressOpt) -> Result<()> {
     opt.input_column.col_idx,
     &opt.input_column.col_name,
   )?;
-  let dtype = schema.field(col_idx).data_type();
+  let field = schema.field(col_idx);
+  let dtype = field.data_type();
+  if string_dict::is_string_dtype(dtype) {
+    if opt.record_source {
+      return Err(anyhow!(
+        "--record-source is not supported for string columns"
+      ));
+    }
+    return string_dict::compress(&opt, &schema, col_idx);
+  }
   let handler = arrow_handlers::from_dtype(dtype)?;
-  handler.compress(&opt, &schema)
+  handler.compress(&opt, &schema)?;
+  logical_type::write_sidecar(&opt.path, field, opt.overwrite)
 }
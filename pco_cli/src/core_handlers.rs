@@ -4,7 +4,9 @@ use pco::data_types::NumberType;
 use pco::match_number_enum;
 
 use crate::decompress::handler::DecompressHandler;
+use crate::diff::handler::DiffHandler;
 use crate::dtypes::PcoNumber;
+use crate::fingerprint::handler::FingerprintHandler;
 use crate::inspect::handler::InspectHandler;
 
 fn new_boxed_handler<T: PcoNumber>() -> Box<dyn CoreHandler> {
@@ -22,7 +24,10 @@ pub fn from_dtype(dtype: NumberType) -> Box<dyn CoreHandler> {
   )
 }
 
-pub trait CoreHandler: DecompressHandler + InspectHandler {}
+pub trait CoreHandler:
+  DecompressHandler + DiffHandler + FingerprintHandler + InspectHandler
+{
+}
 
 #[derive(Clone, Debug, Default)]
 pub struct CoreHandlerImpl<T> {
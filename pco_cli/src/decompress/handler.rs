@@ -1,23 +1,30 @@
 use std::cmp::min;
-use std::fs::OpenOptions;
+use std::fs::{self, File, OpenOptions};
 use std::io::Write;
 use std::marker::PhantomData;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use arrow::array::PrimitiveArray;
 use arrow::csv::WriterBuilder as CsvWriterBuilder;
-use arrow::datatypes::{ArrowPrimitiveType, Field, Schema};
+use arrow::datatypes::{Field, Schema};
 use arrow::record_batch::RecordBatch;
+use parquet::file::properties::{WriterProperties, WriterVersion};
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
 
 use better_io::BetterBufReader;
-use pco::standalone::{FileDecompressor, MaybeChunkDecompressor};
+use pco::data_types::NumberType;
+use pco::standalone::{
+  simple_decompress_parallel, FileDecompressor, MaybeChunkDecompressor, ParallelConfig,
+};
 use pco::FULL_BATCH_N;
 
 use crate::core_handlers::CoreHandlerImpl;
 use crate::decompress::DecompressOpt;
 use crate::decompress::OutputKind::*;
 use crate::dtypes::PcoNumber;
+use crate::{logical_type, utils};
 
 pub trait DecompressHandler {
   fn decompress(&self, opt: &DecompressOpt) -> Result<()>;
@@ -25,10 +32,15 @@ pub trait DecompressHandler {
 
 impl<T: PcoNumber> DecompressHandler for CoreHandlerImpl<T> {
   fn decompress(&self, opt: &DecompressOpt) -> Result<()> {
+    if opt.threads > 1 {
+      return decompress_parallel::<T>(opt);
+    }
+
     let file = OpenOptions::new().read(true).open(&opt.path)?;
     let src = BetterBufReader::from_read_simple(file);
     let (fd, mut src) = FileDecompressor::new(src)?;
 
+    let logical_type = logical_type::read_sidecar(&opt.path)?;
     let mut writer = new_column_writer::<T>(opt)?;
     let mut remaining_limit = opt.limit.unwrap_or(usize::MAX);
     let mut nums = Vec::new();
@@ -46,12 +58,8 @@ impl<T: PcoNumber> DecompressHandler for CoreHandlerImpl<T> {
         nums.resize(pco_size, T::default());
         let _ = cd.decompress(&mut nums)?;
         src = cd.into_src();
-        let arrow_nums = nums
-          .iter()
-          .take(batch_size)
-          .map(|&x| T::to_arrow_native(x))
-          .collect::<Vec<_>>();
-        writer.write(arrow_nums)?;
+        logical_type::validate(&nums[..batch_size], logical_type.as_ref())?;
+        writer.write(&nums[..batch_size])?;
         remaining_limit -= batch_size;
       } else {
         break;
@@ -63,17 +71,33 @@ impl<T: PcoNumber> DecompressHandler for CoreHandlerImpl<T> {
   }
 }
 
+fn decompress_parallel<T: PcoNumber>(opt: &DecompressOpt) -> Result<()> {
+  let src = fs::read(&opt.path)?;
+  let parallel_config = ParallelConfig::default().with_n_threads(opt.threads);
+  let mut nums = simple_decompress_parallel::<T>(&src, &parallel_config)?;
+  nums.truncate(opt.limit.unwrap_or(usize::MAX));
+  logical_type::validate(
+    &nums,
+    logical_type::read_sidecar(&opt.path)?.as_ref(),
+  )?;
+
+  let mut writer = new_column_writer::<T>(opt)?;
+  writer.write(&nums)?;
+  writer.close()?;
+  Ok(())
+}
+
 fn new_column_writer<T: PcoNumber>(opt: &DecompressOpt) -> Result<Box<dyn ColumnWriter<T>>> {
-  // eventually we'll likely have a txt writer and a parquet writer, etc.
-  let writer: Box<dyn ColumnWriter<T>> = match opt.output {
+  let writer: Box<dyn ColumnWriter<T>> = match opt.output_kind() {
     Txt => Box::<TxtWriter<T>>::default(),
     Binary => Box::<BinaryWriter<T>>::default(),
+    Parquet => Box::new(ParquetWriter::<T>::new(opt)?),
   };
   Ok(writer)
 }
 
 trait ColumnWriter<T: PcoNumber> {
-  fn write(&mut self, nums: Vec<<T::Arrow as ArrowPrimitiveType>::Native>) -> Result<()>;
+  fn write(&mut self, nums: &[T]) -> Result<()>;
   fn close(&mut self) -> Result<()>;
 }
 
@@ -83,9 +107,10 @@ struct TxtWriter<T: PcoNumber> {
 }
 
 impl<T: PcoNumber> ColumnWriter<T> for TxtWriter<T> {
-  fn write(&mut self, arrow_natives: Vec<<T::Arrow as ArrowPrimitiveType>::Native>) -> Result<()> {
+  fn write(&mut self, nums: &[T]) -> Result<()> {
     let schema = Schema::new(vec![Field::new("c0", T::ARROW_DTYPE, false)]);
-    let c0 = PrimitiveArray::<T::Arrow>::from_iter_values(arrow_natives);
+    let c0 =
+      PrimitiveArray::<T::Arrow>::from_iter_values(nums.iter().map(|&x| x.to_arrow_native()));
     let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(c0)])?;
     let mut stdout_bytes = Vec::<u8>::new();
     {
@@ -109,10 +134,12 @@ struct BinaryWriter<T: PcoNumber> {
 }
 
 impl<T: PcoNumber> ColumnWriter<T> for BinaryWriter<T> {
-  fn write(&mut self, arrow_natives: Vec<<T::Arrow as ArrowPrimitiveType>::Native>) -> Result<()> {
+  fn write(&mut self, nums: &[T]) -> Result<()> {
     let mut out = std::io::stdout();
-    for &x in &arrow_natives {
-      out.write_all(&T::arrow_native_to_bytes(x))?;
+    for &x in nums {
+      out.write_all(&T::arrow_native_to_bytes(
+        x.to_arrow_native(),
+      ))?;
     }
     Ok(())
   }
@@ -122,3 +149,78 @@ impl<T: PcoNumber> ColumnWriter<T> for BinaryWriter<T> {
     Ok(())
   }
 }
+
+// Whether a column name can go in a Parquet message type string as is.
+fn is_parquet_schema_name(name: &str) -> bool {
+  !name.is_empty()
+    && name
+      .chars()
+      .all(|c| !c.is_whitespace() && !";{}(),=".contains(c))
+}
+
+// Writes one row group per pco chunk.
+struct ParquetWriter<T: PcoNumber> {
+  writer: Option<SerializedFileWriter<File>>,
+  phantom: PhantomData<T>,
+}
+
+impl<T: PcoNumber> ParquetWriter<T> {
+  fn new(opt: &DecompressOpt) -> Result<Self> {
+    let Some(output_path) = &opt.output_path else {
+      return Err(anyhow!(
+        "parquet output requires an --output-path"
+      ));
+    };
+
+    let logical_type = logical_type::read_sidecar(&opt.path)?;
+    let number_type = NumberType::from_descriminant(T::NUMBER_TYPE_BYTE).unwrap();
+    let col_name = utils::read_standalone_source_info(&opt.path)?
+      .map(|source_info| source_info.name)
+      .filter(|name| is_parquet_schema_name(name))
+      .unwrap_or_else(|| "c0".to_string());
+    let message_type = format!(
+      "message schema {{ REQUIRED {} {} {}; }}",
+      T::PARQUET_DTYPE_STR,
+      col_name,
+      logical_type::parquet_annotation(number_type, logical_type.as_ref()),
+    );
+    let schema = Arc::new(parse_message_type(&message_type)?);
+    let properties = WriterProperties::builder()
+      .set_writer_version(WriterVersion::PARQUET_2_0)
+      .build();
+    let file = utils::create_output_file(output_path, opt.overwrite)?;
+    let writer = SerializedFileWriter::new(file, schema, Arc::new(properties))?;
+    Ok(Self {
+      writer: Some(writer),
+      phantom: PhantomData,
+    })
+  }
+}
+
+impl<T: PcoNumber> ColumnWriter<T> for ParquetWriter<T> {
+  fn write(&mut self, nums: &[T]) -> Result<()> {
+    let writer = self.writer.as_mut().unwrap();
+    let mut row_group_writer = writer.next_row_group()?;
+    let mut col_writer = row_group_writer.next_column()?.unwrap();
+    let parquet_vec;
+    let parquet_slice = if T::TRANSMUTABLE {
+      T::transmute_nums_to_parquet(nums)
+    } else {
+      parquet_vec = T::copy_nums_to_parquet(nums);
+      &parquet_vec
+    };
+    col_writer
+      .typed::<T::Parquet>()
+      .write_batch(parquet_slice, None, None)?;
+    col_writer.close()?;
+    row_group_writer.close()?;
+    Ok(())
+  }
+
+  fn close(&mut self) -> Result<()> {
+    if let Some(writer) = self.writer.take() {
+      writer.close()?;
+    }
+    Ok(())
+  }
+}
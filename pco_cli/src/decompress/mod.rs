@@ -1,11 +1,9 @@
-use std::fs::OpenOptions;
-use std::io::{ErrorKind, Read};
 use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::{Parser, ValueEnum};
 
-use crate::{core_handlers, utils};
+use crate::{core_handlers, string_dict, utils};
 
 pub mod handler;
 
@@ -13,34 +11,78 @@ pub mod handler;
 pub enum OutputKind {
   Txt,
   Binary,
+  Parquet,
 }
 
-/// Decompress from standalone .pco into stdout.
+/// Decompress from standalone .pco into stdout or another format.
+///
+/// Parquet output is a single-column file, restoring timestamp and date
+/// types from a .pco.dtype.json file if one was written during compression.
+///
+/// If the file was compressed with --record-source, the output defaults to
+/// Parquet for Parquet sources, written next to the .pco file, and Parquet
+/// output names its column after the original one.
 #[derive(Clone, Debug, Parser)]
 pub struct DecompressOpt {
   #[arg(long)]
   pub limit: Option<usize>,
-  #[arg(short, long, default_value = "txt")]
-  pub output: OutputKind,
+  /// Defaults to the recorded source's format if there is one, or txt.
+  #[arg(short, long)]
+  pub output: Option<OutputKind>,
+  /// Path to write to, for outputs that go to a file instead of stdout.
+  /// Required for parquet output, unless it defaulted to parquet from the
+  /// recorded source.
+  #[arg(long)]
+  pub output_path: Option<PathBuf>,
+  /// Overwrite the output path (if it exists) instead of failing.
+  #[arg(long)]
+  pub overwrite: bool,
+  /// How many threads to decompress chunks on.
+  /// With more than 1, the whole file is read into memory first.
+  #[arg(long, default_value = "1")]
+  pub threads: usize,
 
   pub path: PathBuf,
 }
 
+impl DecompressOpt {
+  pub fn output_kind(&self) -> OutputKind {
+    self.output.unwrap_or(OutputKind::Txt)
+  }
+
+  // fills in the output kind and path from the recorded source, if any
+  fn with_source_defaults(mut self) -> Result<Self> {
+    if self.output.is_some() {
+      return Ok(self);
+    }
+
+    let source_info = utils::read_standalone_source_info(&self.path)?;
+    if let Some(source_info) = source_info {
+      let output = match source_info.format.as_str() {
+        "parquet" => OutputKind::Parquet,
+        "binary" => OutputKind::Binary,
+        // CSVs and the rest are best recreated as text
+        _ => OutputKind::Txt,
+      };
+      if matches!(output, OutputKind::Parquet) && self.output_path.is_none() {
+        self.output_path = Some(self.path.with_extension("parquet"));
+      }
+      self.output = Some(output);
+    }
+    Ok(self)
+  }
+}
+
 pub fn decompress(opt: DecompressOpt) -> Result<()> {
-  let mut initial_bytes = vec![0; pco::standalone::guarantee::header_size() + 1];
-  match OpenOptions::new()
-    .read(true)
-    .open(&opt.path)?
-    .read_exact(&mut initial_bytes)
-  {
-    Ok(()) => (),
-    Err(e) if matches!(e.kind(), ErrorKind::UnexpectedEof) => (),
-    other => other?,
-  };
-  let Some(dtype) = utils::get_standalone_dtype(&initial_bytes)? else {
+  let dictionary_path = string_dict::dictionary_path(&opt.path);
+  if dictionary_path.exists() {
+    return string_dict::decompress(&opt, &dictionary_path);
+  }
+
+  let Some(dtype) = utils::peek_standalone_dtype(&opt.path)? else {
     // file terminated; nothing to decompress
     return Ok(());
   };
   let handler = core_handlers::from_dtype(dtype);
-  handler.decompress(&opt)
+  handler.decompress(&opt.with_source_defaults()?)
 }
@@ -0,0 +1,127 @@
+use std::fs::OpenOptions;
+use std::path::Path;
+
+use anyhow::Result;
+
+use better_io::BetterBufReader;
+use pco::standalone::{ChunkDecompressor, FileDecompressor, MaybeChunkDecompressor};
+use pco::FULL_BATCH_N;
+
+use crate::core_handlers::CoreHandlerImpl;
+use crate::diff::DiffOpt;
+use crate::dtypes::PcoNumber;
+
+type Src = BetterBufReader<std::fs::File>;
+
+pub trait DiffHandler {
+  fn diff(&self, opt: &DiffOpt) -> Result<bool>;
+}
+
+enum StreamState<T: PcoNumber> {
+  BetweenChunks(Src),
+  InChunk(Box<ChunkDecompressor<T, Src>>),
+  Finished,
+}
+
+// Yields the numbers of a standalone file one at a time, decompressing a
+// batch at a time.
+struct NumStream<T: PcoNumber> {
+  fd: FileDecompressor,
+  state: StreamState<T>,
+  batch: Vec<T>,
+  batch_len: usize,
+  batch_pos: usize,
+}
+
+impl<T: PcoNumber> NumStream<T> {
+  fn new(path: &Path) -> Result<Self> {
+    let file = OpenOptions::new().read(true).open(path)?;
+    let src = BetterBufReader::from_read_simple(file);
+    let (fd, src) = FileDecompressor::new(src)?;
+    Ok(Self {
+      fd,
+      state: StreamState::BetweenChunks(src),
+      batch: vec![T::default(); FULL_BATCH_N],
+      batch_len: 0,
+      batch_pos: 0,
+    })
+  }
+
+  fn next(&mut self) -> Result<Option<T>> {
+    while self.batch_pos == self.batch_len {
+      match std::mem::replace(&mut self.state, StreamState::Finished) {
+        StreamState::BetweenChunks(src) => {
+          if let MaybeChunkDecompressor::Some(cd) = self.fd.chunk_decompressor::<T, _>(src)? {
+            self.state = StreamState::InChunk(Box::new(cd));
+          }
+        }
+        StreamState::InChunk(mut cd) => {
+          let progress = cd.decompress(&mut self.batch)?;
+          self.batch_len = progress.n_processed;
+          self.batch_pos = 0;
+          self.state = if progress.finished {
+            StreamState::BetweenChunks(cd.into_src())
+          } else {
+            StreamState::InChunk(cd)
+          };
+        }
+        StreamState::Finished => return Ok(None),
+      }
+    }
+
+    let num = self.batch[self.batch_pos];
+    self.batch_pos += 1;
+    Ok(Some(num))
+  }
+}
+
+fn nums_equal<T: PcoNumber>(a: T, b: T, tol: Option<f64>) -> bool {
+  match tol {
+    // checking bitwise equality first makes infinities equal to themselves
+    Some(tol) => {
+      a.to_latent_ordered() == b.to_latent_ordered() || (a.to_f64() - b.to_f64()).abs() <= tol
+    }
+    None => a.to_latent_ordered() == b.to_latent_ordered(),
+  }
+}
+
+impl<T: PcoNumber> DiffHandler for CoreHandlerImpl<T> {
+  fn diff(&self, opt: &DiffOpt) -> Result<bool> {
+    let mut a_stream = NumStream::<T>::new(&opt.a)?;
+    let mut b_stream = NumStream::<T>::new(&opt.b)?;
+
+    let mut idx = 0;
+    loop {
+      match (a_stream.next()?, b_stream.next()?) {
+        (Some(a), Some(b)) => {
+          if !nums_equal(a, b, opt.tol) {
+            println!(
+              "files differ at index {}: {:?} vs {:?}",
+              idx, a, b
+            );
+            return Ok(false);
+          }
+        }
+        (None, None) => {
+          println!("files are equal: {} numbers", idx);
+          return Ok(true);
+        }
+        (a, _) => {
+          let (longer, shorter) = if a.is_some() {
+            (&opt.a, &opt.b)
+          } else {
+            (&opt.b, &opt.a)
+          };
+          println!(
+            "files differ in length: {} ends after {} numbers, but {} continues",
+            shorter.display(),
+            idx,
+            longer.display(),
+          );
+          return Ok(false);
+        }
+      }
+      idx += 1;
+    }
+  }
+}
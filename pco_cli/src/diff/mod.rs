@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+use std::process;
+
+use anyhow::Result;
+use clap::Parser;
+
+use crate::core_handlers;
+use crate::utils;
+
+pub mod handler;
+
+/// Compare the numbers in two standalone .pco files.
+///
+/// Both files are decompressed in a streaming fashion, stopping at the first
+/// difference.
+/// Exits with code 0 if the files are logically equal, 1 if they differ, and
+/// 2 if an error occurred.
+#[derive(Clone, Debug, Parser)]
+pub struct DiffOpt {
+  /// An absolute tolerance for comparing numbers.
+  /// By default, numbers must be bitwise identical.
+  #[arg(long)]
+  pub tol: Option<f64>,
+
+  pub a: PathBuf,
+  pub b: PathBuf,
+}
+
+fn diff_inner(opt: &DiffOpt) -> Result<bool> {
+  let a_dtype = utils::peek_standalone_dtype(&opt.a)?;
+  let b_dtype = utils::peek_standalone_dtype(&opt.b)?;
+  let dtype = match (a_dtype, b_dtype) {
+    (Some(a), Some(b)) if a != b => {
+      println!("data types differ: {:?} vs {:?}", a, b);
+      return Ok(false);
+    }
+    (Some(dtype), _) | (None, Some(dtype)) => dtype,
+    (None, None) => {
+      println!("files are equal: both are empty");
+      return Ok(true);
+    }
+  };
+
+  let handler = core_handlers::from_dtype(dtype);
+  handler.diff(opt)
+}
+
+pub fn diff(opt: DiffOpt) -> Result<()> {
+  match diff_inner(&opt) {
+    Ok(true) => Ok(()),
+    Ok(false) => process::exit(1),
+    Err(e) => {
+      eprintln!("Error: {:?}", e);
+      process::exit(2);
+    }
+  }
+}
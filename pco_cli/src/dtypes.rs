@@ -55,6 +55,7 @@ pub trait Arrowable: Sized {
   type Arrow: ArrowPrimitiveType;
 
   fn to_arrow_native(self) -> <Self::Arrow as ArrowPrimitiveType>::Native;
+  fn to_f64(self) -> f64;
   fn make_num_vec(nums: Vec<Self>) -> NumVec;
   fn arrow_native_to_bytes(x: <Self::Arrow as ArrowPrimitiveType>::Native) -> Vec<u8>;
 }
@@ -123,6 +124,10 @@ macro_rules! trivial {
         self as Self
       }
 
+      fn to_f64(self) -> f64 {
+        self as f64
+      }
+
       fn make_num_vec(nums: Vec<Self>) -> NumVec {
         NumVec::$name(nums)
       }
@@ -264,6 +269,10 @@ impl Arrowable for f16 {
     self as Self
   }
 
+  fn to_f64(self) -> f64 {
+    f16::to_f64(self)
+  }
+
   fn make_num_vec(nums: Vec<Self>) -> NumVec {
     NumVec::F16(nums)
   }
@@ -0,0 +1,33 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::mem;
+
+use anyhow::Result;
+
+use pco::data_types::Latent;
+use pco::standalone;
+
+use crate::core_handlers::CoreHandlerImpl;
+use crate::dtypes::PcoNumber;
+use crate::fingerprint::{new_hasher, FingerprintOpt};
+
+pub trait FingerprintHandler {
+  fn fingerprint(&self, opt: &FingerprintOpt) -> Result<u128>;
+}
+
+impl<T: PcoNumber> FingerprintHandler for CoreHandlerImpl<T> {
+  fn fingerprint(&self, opt: &FingerprintOpt) -> Result<u128> {
+    let mut src = BufReader::new(File::open(&opt.path)?);
+    let mut hasher = new_hasher(T::NUMBER_TYPE_BYTE);
+    let num_size = mem::size_of::<T>();
+    let mut bytes = Vec::new();
+    for page in standalone::decompress_pages_from_reader::<T, _>(&mut src)? {
+      bytes.clear();
+      for num in page? {
+        bytes.extend_from_slice(&num.transmute_to_latent().to_u64().to_le_bytes()[..num_size]);
+      }
+      hasher.write(&bytes);
+    }
+    Ok(hasher.finish_128())
+  }
+}
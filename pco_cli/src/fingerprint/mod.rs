@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use twox_hash::XxHash3_128;
+
+use crate::core_handlers;
+use crate::utils;
+
+pub mod handler;
+
+// Bump this whenever the hashed bytes change, so old fingerprints are never
+// confused with new ones.
+pub const FINGERPRINT_VERSION: u8 = 1;
+
+/// Print a hash of the numbers in a standalone .pco file.
+///
+/// The hash covers only the data type and the decompressed numbers' bits, so
+/// files with identical contents have the same fingerprint regardless of how
+/// they were compressed.
+/// It uses XXH3-128, which is fast but not cryptographic.
+#[derive(Clone, Debug, Parser)]
+pub struct FingerprintOpt {
+  pub path: PathBuf,
+}
+
+pub fn new_hasher(number_type_byte: u8) -> XxHash3_128 {
+  let mut hasher = XxHash3_128::new();
+  hasher.write(&[FINGERPRINT_VERSION, number_type_byte]);
+  hasher
+}
+
+pub fn fingerprint(opt: FingerprintOpt) -> Result<()> {
+  let fingerprint = match utils::peek_standalone_dtype(&opt.path)? {
+    Some(dtype) => core_handlers::from_dtype(dtype).fingerprint(&opt)?,
+    // empty files have no data type
+    None => new_hasher(0).finish_128(),
+  };
+  println!("{:032x}", fingerprint);
+  Ok(())
+}
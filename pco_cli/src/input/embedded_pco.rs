@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use arrow::array::{Array, ArrayRef, AsArray, PrimitiveBuilder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use half::f16;
+use pco::data_types::NumberType;
+use pco::standalone::simple_decompress;
+
+use crate::dtypes::{self, PcoNumber};
+use crate::utils;
+
+// Some systems store numeric columns as a binary column where each element is
+// a standalone pco file. We decode each element and concatenate the results
+// into a single numeric column.
+
+fn is_container(dtype: &DataType) -> bool {
+  matches!(
+    dtype,
+    DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_)
+  )
+}
+
+// Null elements yield None. For FixedSizeBinary, arrow still hands us the
+// (garbage) bytes behind nulls, so we have to check validity ourselves.
+fn elements(array: &dyn Array) -> Box<dyn Iterator<Item = Option<&[u8]>> + '_> {
+  match array.data_type() {
+    DataType::Binary => Box::new(array.as_binary::<i32>().iter()),
+    DataType::LargeBinary => Box::new(array.as_binary::<i64>().iter()),
+    DataType::FixedSizeBinary(_) => Box::new(array.as_fixed_size_binary().iter()),
+    other => panic!("{:?} is not a container of pco files", other),
+  }
+}
+
+fn peek_number_type(array: &dyn Array) -> Option<NumberType> {
+  let first = elements(array).flatten().next()?;
+  utils::get_standalone_dtype(first).ok().flatten()
+}
+
+/// Replaces binary fields whose first non-null element is a standalone pco
+/// file with the numeric type that file contains.
+///
+/// Binary fields that don't look like pco are left as they are.
+pub fn resolve_schema(schema: Schema, first_batch: Option<&RecordBatch>) -> Schema {
+  let Some(batch) = first_batch else {
+    return schema;
+  };
+
+  let fields = schema
+    .fields()
+    .iter()
+    .enumerate()
+    .map(|(col_idx, field)| {
+      if !is_container(field.data_type()) {
+        return field.as_ref().clone();
+      }
+
+      match peek_number_type(batch.column(col_idx).as_ref()) {
+        Some(number_type) => Field::new(
+          field.name(),
+          dtypes::to_arrow(number_type),
+          field.is_nullable(),
+        ),
+        None => field.as_ref().clone(),
+      }
+    })
+    .collect::<Vec<_>>();
+  Schema::new_with_metadata(fields, schema.metadata().clone())
+}
+
+fn decode_typed<T: PcoNumber>(array: &dyn Array) -> Result<ArrayRef> {
+  let is_fixed_size = matches!(
+    array.data_type(),
+    DataType::FixedSizeBinary(_)
+  );
+  let mut builder = PrimitiveBuilder::<T::Arrow>::new();
+  // For FixedSizeBinary, every element must decode the same number of
+  // elements, which we learn from the first non-null one. Null elements then
+  // become that many nulls, so validity lines up with the other elements.
+  let mut expected_count = None;
+  let mut pending_nulls = 0;
+  for (i, element) in elements(array).enumerate() {
+    let Some(src) = element else {
+      match (is_fixed_size, expected_count) {
+        (true, Some(count)) => builder.append_nulls(count),
+        (true, None) => pending_nulls += 1,
+        // we have no way to know how many numbers a null variable-length
+        // element stands for, so it contributes none
+        (false, _) => (),
+      }
+      continue;
+    };
+
+    let nums = simple_decompress::<T>(src).map_err(|e| {
+      anyhow!(
+        "failed to decode embedded pco file at element {}: {}",
+        i,
+        e
+      )
+    })?;
+    if is_fixed_size {
+      match expected_count {
+        Some(count) if count != nums.len() => {
+          return Err(anyhow!(
+            "embedded pco file at element {} decoded {} numbers, but earlier elements decoded {}",
+            i,
+            nums.len(),
+            count,
+          ));
+        }
+        Some(_) => (),
+        None => {
+          expected_count = Some(nums.len());
+          builder.append_nulls(pending_nulls * nums.len());
+        }
+      }
+    }
+    for x in nums {
+      builder.append_value(x.to_arrow_native());
+    }
+  }
+
+  Ok(Arc::new(builder.finish()))
+}
+
+/// If `array` is a binary column, decodes each element as a standalone pco
+/// file of `number_type` and concatenates them. Otherwise returns `array`
+/// as is.
+pub fn decode_if_container(array: ArrayRef, number_type: &DataType) -> Result<ArrayRef> {
+  if !is_container(array.data_type()) {
+    return Ok(array);
+  }
+
+  use NumberType::*;
+  let array = array.as_ref();
+  match dtypes::from_arrow(number_type)? {
+    F16 => decode_typed::<f16>(array),
+    F32 => decode_typed::<f32>(array),
+    F64 => decode_typed::<f64>(array),
+    I16 => decode_typed::<i16>(array),
+    I32 => decode_typed::<i32>(array),
+    I64 => decode_typed::<i64>(array),
+    U16 => decode_typed::<u16>(array),
+    U32 => decode_typed::<u32>(array),
+    U64 => decode_typed::<u64>(array),
+    other => Err(anyhow!(
+      "number type {:?} not yet supported in pco_cli",
+      other
+    )),
+  }
+}
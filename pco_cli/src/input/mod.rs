@@ -2,30 +2,26 @@ use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 
 use anyhow::{anyhow, Context, Result};
-use arrow::array::{
-  ArrayData, ArrayRef, Float16Array, Float32Array, Float64Array, Int16Array, Int32Array,
-  Int64Array, UInt16Array, UInt32Array, UInt64Array,
-};
+use arrow::array::{ArrayData, ArrayRef};
 use arrow::buffer::Buffer;
 use arrow::csv;
 use arrow::csv::Reader as CsvReader;
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::ipc::reader::FileReader as IpcFileReader;
 use arrow::record_batch::RecordBatchReader;
+use bytes::Bytes;
 use clap::Parser;
-use half::f16;
 use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
 use parquet::arrow::{ArrowSchemaConverter, ProjectionMask};
+use parquet::file::reader::ChunkReader;
 
-use pco::data_types::NumberType;
-use pco::standalone::simple_decompress;
-
-use crate::{dtypes, parse, utils};
+use crate::{dtypes, num_vec, parse, utils};
 
 #[cfg(feature = "audio")]
 mod audio;
+mod embedded_pco;
 
 const MAX_INFER_SCHEMA_RECORDS: usize = 1000;
 
@@ -45,6 +41,7 @@ pub struct InputColumnOpt {
 
 #[derive(clap::ValueEnum, Clone, Copy, Debug, Parser)]
 pub enum Format {
+  ArrowIpc,
   Binary,
   Csv,
   Parquet,
@@ -66,18 +63,71 @@ pub struct InputFileOpt {
   pub csv_delimiter: char,
 }
 
+// An external compression wrapping a whole input file, e.g. .parquet.gz.
+#[derive(Clone, Copy, Debug)]
+enum Wrapping {
+  Gzip,
+  Zstd,
+}
+
+impl Wrapping {
+  fn from_path(path: &Path) -> Option<Self> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+      Some("gz") => Some(Wrapping::Gzip),
+      Some("zst") => Some(Wrapping::Zstd),
+      _ => None,
+    }
+  }
+
+  fn read_unwrapped(self, path: &Path) -> Result<Bytes> {
+    let file = File::open(path)?;
+    let bytes = match self {
+      Wrapping::Gzip => read_gunzipped(file)?,
+      Wrapping::Zstd => zstd::decode_all(file)?,
+    };
+    Ok(Bytes::from(bytes))
+  }
+}
+
+#[cfg(feature = "gzip")]
+fn read_gunzipped(file: File) -> Result<Vec<u8>> {
+  use std::io::Read;
+
+  let mut res = Vec::new();
+  flate2::read::MultiGzDecoder::new(file).read_to_end(&mut res)?;
+  Ok(res)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn read_gunzipped(_file: File) -> Result<Vec<u8>> {
+  Err(anyhow!("not compiled with gzip feature"))
+}
+
 impl InputFileOpt {
-  fn format(&self) -> Result<Format> {
-    if let Some(format) = self.input_format {
-      return Ok(format);
+  pub fn format(&self) -> Result<Format> {
+    let wrapping = self.input.as_deref().and_then(Wrapping::from_path);
+    let format = match self.input_format {
+      Some(format) => format,
+      None => self.infer_format(wrapping.is_some())?,
+    };
+    if wrapping.is_some() && !matches!(format, Format::Parquet) {
+      return Err(anyhow!(
+        "only Parquet inputs can be gzip or zstd compressed, but got {:?}",
+        format,
+      ));
     }
+    Ok(format)
+  }
 
-    let ext = self
-      .input
-      .as_ref()
-      .and_then(|path| path.extension())
-      .and_then(|ext| ext.to_str());
+  fn infer_format(&self, is_wrapped: bool) -> Result<Format> {
+    let mut path = self.input.clone().unwrap_or_default();
+    if is_wrapped {
+      // e.g. look at the .parquet in .parquet.gz
+      path.set_extension("");
+    }
+    let ext = path.extension().and_then(|ext| ext.to_str());
     let format = match ext {
+      Some("arrow" | "feather" | "ipc") => Format::ArrowIpc,
       Some("csv") => Format::Csv,
       Some("parquet") => Format::Parquet,
       Some("pco") => Format::Pco,
@@ -195,10 +245,34 @@ fn infer_csv_schema(col_opt: &InputColumnOpt, file_opt: &InputFileOpt) -> Result
   Ok(Schema::new(fields))
 }
 
+fn build_parquet_reader<R: ChunkReader + 'static>(
+  reader: R,
+  projection: Option<ProjectionMask>,
+) -> Result<ParquetRecordBatchReader> {
+  let mut builder = ParquetRecordBatchReaderBuilder::try_new(reader)?;
+  if let Some(projection) = projection {
+    builder = builder.with_projection(projection);
+  }
+  Ok(builder.build()?)
+}
+
+// Gzip and zstd wrapped files get decompressed fully into memory, for every
+// column read.
+fn new_parquet_reader(
+  path: &Path,
+  projection: Option<ProjectionMask>,
+) -> Result<ParquetRecordBatchReader> {
+  match Wrapping::from_path(path) {
+    Some(wrapping) => build_parquet_reader(wrapping.read_unwrapped(path)?, projection),
+    None => build_parquet_reader(File::open(path)?, projection),
+  }
+}
+
 fn infer_parquet_schema(col_opt: &InputColumnOpt, path: &Path) -> Result<Schema> {
-  let file = File::open(path)?;
-  let reader = ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+  let mut reader = new_parquet_reader(path, None)?;
   let schema = reader.schema().as_ref().clone();
+  let first_batch = reader.next().transpose()?;
+  let schema = embedded_pco::resolve_schema(schema, first_batch.as_ref());
 
   if let Some(arrow_dtype) = &col_opt.dtype {
     let col_idx = utils::find_col_idx(&schema, col_opt.col_idx, &col_opt.col_name)?;
@@ -214,6 +288,26 @@ fn infer_parquet_schema(col_opt: &InputColumnOpt, path: &Path) -> Result<Schema>
   Ok(schema)
 }
 
+fn infer_arrow_ipc_schema(col_opt: &InputColumnOpt, path: &Path) -> Result<Schema> {
+  let mut reader = IpcFileReader::try_new(File::open(path)?, None)?;
+  let schema = reader.schema().as_ref().clone();
+  let first_batch = reader.next().transpose()?;
+  let schema = embedded_pco::resolve_schema(schema, first_batch.as_ref());
+
+  if let Some(arrow_dtype) = &col_opt.dtype {
+    let col_idx = utils::find_col_idx(&schema, col_opt.col_idx, &col_opt.col_name)?;
+    let field = schema.field(col_idx);
+    if field.data_type() != arrow_dtype {
+      return Err(anyhow!(
+        "optionally specified dtype {:?} did not match arrow IPC schema {:?}",
+        arrow_dtype,
+        field.data_type(),
+      ));
+    }
+  }
+  Ok(schema)
+}
+
 fn get_pco_field(path: &Path) -> Result<Option<Field>> {
   // horribly inefficient, but we're not making performance a concern here yet
   let compressed = fs::read(path)?;
@@ -245,6 +339,7 @@ pub fn get_schema(col_opt: &InputColumnOpt, file_opt: &InputFileOpt) -> Result<S
     .ok_or_else(|| anyhow!("no input was provided"))?;
   match file_opt.format()? {
     // maybe one day I should structure this better
+    Format::ArrowIpc => infer_arrow_ipc_schema(col_opt, path),
     Format::Binary => infer_binary_schema(path),
     Format::Csv => infer_csv_schema(col_opt, file_opt),
     Format::Parquet => infer_parquet_schema(col_opt, path),
@@ -281,6 +376,9 @@ pub fn new_column_reader(
 ) -> Result<Box<dyn Iterator<Item = Result<ArrayRef>>>> {
   let path = opt.input.as_ref().unwrap();
   let res: Box<dyn Iterator<Item = Result<ArrayRef>>> = match opt.format()? {
+    Format::ArrowIpc => Box::new(ArrowIpcColumnReader::new(
+      schema, path, col_idx,
+    )?),
     Format::Binary => Box::new(BinaryColumnReader::new(schema, col_idx)?),
     Format::Csv => Box::new(CsvColumnReader::new(
       schema, path, col_idx, opt,
@@ -341,20 +439,26 @@ impl Iterator for BinaryColumnReader {
   }
 }
 
-struct ParquetColumnReader(ParquetRecordBatchReader);
+struct ParquetColumnReader {
+  batch_reader: ParquetRecordBatchReader,
+  dtype: DataType,
+}
 
 impl ParquetColumnReader {
   fn new(schema: &Schema, path: &Path, col_idx: usize) -> Result<Self> {
-    let file = File::open(path)?;
-    let batch_reader_builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
     let parquet_schema = ArrowSchemaConverter::default().convert(schema)?;
-    let batch_reader = batch_reader_builder
-      .with_projection(ProjectionMask::leaves(
+    let batch_reader = new_parquet_reader(
+      path,
+      Some(ProjectionMask::leaves(
         &parquet_schema,
         vec![col_idx],
-      ))
-      .build()?;
-    Ok(Self(batch_reader))
+      )),
+    )?;
+    let dtype = schema.field(col_idx).data_type().clone();
+    Ok(Self {
+      batch_reader,
+      dtype,
+    })
   }
 }
 
@@ -362,10 +466,35 @@ impl Iterator for ParquetColumnReader {
   type Item = Result<ArrayRef>;
 
   fn next(&mut self) -> Option<Result<ArrayRef>> {
-    self.0.next().map(|batch_result| {
+    self.batch_reader.next().map(|batch_result| {
       let batch = batch_result?;
       // 0 because we told arrow to only read the exact column we want
-      Ok(batch.column(0).clone())
+      embedded_pco::decode_if_container(batch.column(0).clone(), &self.dtype)
+    })
+  }
+}
+
+struct ArrowIpcColumnReader {
+  reader: IpcFileReader<File>,
+  dtype: DataType,
+}
+
+impl ArrowIpcColumnReader {
+  fn new(schema: &Schema, path: &Path, col_idx: usize) -> Result<Self> {
+    let reader = IpcFileReader::try_new(File::open(path)?, Some(vec![col_idx]))?;
+    let dtype = schema.field(col_idx).data_type().clone();
+    Ok(Self { reader, dtype })
+  }
+}
+
+impl Iterator for ArrowIpcColumnReader {
+  type Item = Result<ArrayRef>;
+
+  fn next(&mut self) -> Option<Result<ArrayRef>> {
+    self.reader.next().map(|batch_result| {
+      let batch = batch_result?;
+      // 0 because we told arrow to only read the exact column we want
+      embedded_pco::decode_if_container(batch.column(0).clone(), &self.dtype)
     })
   }
 }
@@ -402,17 +531,14 @@ impl Iterator for CsvColumnReader {
 
 struct PcoColumnReader {
   col_path: PathBuf,
-  dtype: NumberType,
   did_read: bool,
 }
 
 impl PcoColumnReader {
   fn new(schema: &Schema, col_idx: usize) -> Result<Self> {
     let col_path = PathBuf::from(schema.metadata.get(&col_idx.to_string()).unwrap());
-    let dtype = dtypes::from_arrow(&schema.field(col_idx).data_type().clone())?;
     Ok(PcoColumnReader {
       col_path,
-      dtype,
       did_read: false,
     })
   }
@@ -420,45 +546,8 @@ impl PcoColumnReader {
 
 impl PcoColumnReader {
   fn get_array(&self) -> Result<ArrayRef> {
-    use NumberType::*;
-
     let compressed = fs::read(&self.col_path)?;
-    let array: ArrayRef = match self.dtype {
-      F16 => Arc::new(Float16Array::from(simple_decompress::<f16>(
-        &compressed,
-      )?)),
-      F32 => Arc::new(Float32Array::from(simple_decompress::<f32>(
-        &compressed,
-      )?)),
-      F64 => Arc::new(Float64Array::from(simple_decompress::<f64>(
-        &compressed,
-      )?)),
-      I16 => Arc::new(Int16Array::from(simple_decompress::<i16>(
-        &compressed,
-      )?)),
-      I32 => Arc::new(Int32Array::from(simple_decompress::<i32>(
-        &compressed,
-      )?)),
-      I64 => Arc::new(Int64Array::from(simple_decompress::<i64>(
-        &compressed,
-      )?)),
-      U16 => Arc::new(UInt16Array::from(simple_decompress::<u16>(
-        &compressed,
-      )?)),
-      U32 => Arc::new(UInt32Array::from(simple_decompress::<u32>(
-        &compressed,
-      )?)),
-      U64 => Arc::new(UInt64Array::from(simple_decompress::<u64>(
-        &compressed,
-      )?)),
-      other => {
-        return Err(anyhow!(
-          "number type {:?} not yet supported in pco_cli",
-          other
-        ))
-      }
-    };
-    Ok(array)
+    Ok(num_vec::decompress_dynamic(&compressed)?.to_arrow())
   }
 }
 
@@ -8,7 +8,7 @@ use tabled::{Table, Tabled};
 
 use pco::data_types::{Latent, Number};
 use pco::match_latent_enum;
-use pco::metadata::{ChunkMeta, DynBins, DynLatent, LatentVarKey};
+use pco::metadata::{ChunkMeta, DeltaEncoding, DynBins, DynLatent, LatentVarKey, Mode};
 use pco::standalone::{FileDecompressor, MaybeChunkDecompressor};
 
 use crate::core_handlers::CoreHandlerImpl;
@@ -23,6 +23,8 @@ pub trait InspectHandler {
 #[derive(Serialize)]
 pub struct CompressionSummary {
   pub ratio: f64,
+  // compressed bits per number, including all framing
+  pub bits_per_value: f64,
   pub total_size: usize,
   pub header_size: usize,
   pub meta_size: usize,
@@ -53,28 +55,173 @@ pub struct ChunkSummary {
   n: usize,
   mode: String,
   delta_encoding: String,
+  // whether each page holds nothing but its delta state, e.g. because the
+  // chunk's deltas are all zero
+  constant_after_delta: bool,
   // using BTreeMaps to preserve ordering
   latent_vars: BTreeMap<String, LatentVarSummary>,
 }
 
+#[derive(Serialize)]
+pub struct ManifestSummary {
+  compression_level: usize,
+  mode: String,
+  delta_encoding: String,
+}
+
+#[derive(Serialize)]
+pub struct SourceSummary {
+  format: String,
+  name: String,
+}
+
+#[derive(Serialize)]
+pub struct FixedRangeSummary {
+  lower: f64,
+  upper: f64,
+  bit_depth: u32,
+  max_error: f64,
+}
+
 #[derive(Serialize)]
 pub struct Output {
   pub filename: String,
   pub data_type: String,
   pub format_version: u8,
+  pub lossless: bool,
   pub n: usize,
   pub n_chunks: usize,
   pub uncompressed_size: usize,
   pub compressed: CompressionSummary,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub manifest: Option<ManifestSummary>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub source: Option<SourceSummary>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub fixed_range: Option<FixedRangeSummary>,
   pub chunks: Vec<ChunkSummary>,
 }
 
+// Bump this whenever the --stats-json schema changes.
+pub const STATS_JSON_VERSION: u32 = 2;
+
+#[derive(Serialize)]
+pub struct LatentVarStats {
+  n_bins: usize,
+  ans_size_log: u32,
+}
+
+#[derive(Serialize)]
+pub struct ChunkStats {
+  n: usize,
+  mode: String,
+  delta_encoding: String,
+  // null for delta encodings that have no order, e.g. lookback
+  delta_order: Option<usize>,
+  compressed_bytes: usize,
+  latent_vars: BTreeMap<String, LatentVarStats>,
+}
+
+/// The stable, machine-readable output of `inspect --stats-json`.
+#[derive(Serialize)]
+pub struct StatsJson {
+  pub version: u32,
+  pub data_type: String,
+  pub format_version: u8,
+  pub lossless: bool,
+  pub n: usize,
+  pub n_chunks: usize,
+  pub compressed_bytes: usize,
+  pub chunks: Vec<ChunkStats>,
+}
+
+pub fn manifest_summary(fd: &FileDecompressor) -> Option<ManifestSummary> {
+  fd.manifest().map(|manifest| ManifestSummary {
+    compression_level: manifest.compression_level,
+    mode: format!("{:?}", manifest.mode),
+    delta_encoding: format!("{:?}", manifest.delta_encoding),
+  })
+}
+
+pub fn source_summary(fd: &FileDecompressor) -> Option<SourceSummary> {
+  fd.source_info().map(|source_info| SourceSummary {
+    format: source_info.format.clone(),
+    name: source_info.name.clone(),
+  })
+}
+
+pub fn fixed_range_summary(fd: &FileDecompressor) -> Option<FixedRangeSummary> {
+  fd.fixed_range().map(|fixed_range| FixedRangeSummary {
+    lower: fixed_range.lower,
+    upper: fixed_range.upper,
+    bit_depth: fixed_range.bit_depth,
+    max_error: fixed_range.max_error(),
+  })
+}
+
 fn measure_bytes_read(src: &[u8], prev_src_len: &mut usize) -> usize {
   let res = *prev_src_len - src.len();
   *prev_src_len = src.len();
   res
 }
 
+fn latent_var_key_name(key: LatentVarKey) -> &'static str {
+  match key {
+    LatentVarKey::Delta => "delta",
+    LatentVarKey::Primary => "primary",
+    LatentVarKey::Secondary => "secondary",
+  }
+}
+
+fn mode_name(mode: &Mode) -> String {
+  match mode {
+    Mode::Classic => "classic".to_string(),
+    Mode::IntMult(_) => "int_mult".to_string(),
+    Mode::FloatMult(_) => "float_mult".to_string(),
+    Mode::FloatQuant(_) => "float_quant".to_string(),
+    other => format!("{:?}", other),
+  }
+}
+
+fn delta_encoding_name(delta_encoding: &DeltaEncoding) -> String {
+  match delta_encoding {
+    DeltaEncoding::None => "none".to_string(),
+    DeltaEncoding::Consecutive(_) => "consecutive".to_string(),
+    DeltaEncoding::ConsecutivePerPage(_) => "consecutive_per_page".to_string(),
+    DeltaEncoding::Lookback(_) => "lookback".to_string(),
+    DeltaEncoding::Xor => "xor".to_string(),
+    other => format!("{:?}", other),
+  }
+}
+
+fn delta_order(delta_encoding: &DeltaEncoding) -> Option<usize> {
+  match delta_encoding {
+    DeltaEncoding::None => Some(0),
+    DeltaEncoding::Consecutive(config) => Some(config.order),
+    // the max order; each page may use a lower one
+    DeltaEncoding::ConsecutivePerPage(config) => Some(config.order),
+    _ => None,
+  }
+}
+
+fn build_latent_var_stats(meta: &ChunkMeta) -> BTreeMap<String, LatentVarStats> {
+  let mut stats = BTreeMap::new();
+  for (key, latent_var_meta) in meta.per_latent_var.as_ref().enumerated() {
+    let n_bins = match_latent_enum!(
+      &latent_var_meta.bins,
+      DynBins<L>(bins) => { bins.len() }
+    );
+    stats.insert(
+      latent_var_key_name(key).to_string(),
+      LatentVarStats {
+        n_bins,
+        ans_size_log: latent_var_meta.ans_size_log,
+      },
+    );
+  }
+  stats
+}
+
 fn build_latent_var_summaries<T: Number>(meta: &ChunkMeta) -> BTreeMap<String, LatentVarSummary> {
   let describers = T::get_latent_describers(meta);
   let mut summaries = BTreeMap::new();
@@ -118,13 +265,7 @@ fn build_latent_var_summaries<T: Number>(meta: &ChunkMeta) -> BTreeMap<String, L
       bins: bins_table.to_string(),
     };
 
-    let key_name = match key {
-      LatentVarKey::Delta => "delta",
-      LatentVarKey::Primary => "primary",
-      LatentVarKey::Secondary => "secondary",
-    };
-
-    summaries.insert(key_name.to_string(), summary);
+    summaries.insert(latent_var_key_name(key).to_string(), summary);
   }
 
   summaries
@@ -132,6 +273,7 @@ fn build_latent_var_summaries<T: Number>(meta: &ChunkMeta) -> BTreeMap<String, L
 
 impl<T: PcoNumber> InspectHandler for CoreHandlerImpl<T> {
   fn inspect(&self, opt: &InspectOpt, src: &[u8]) -> Result<()> {
+    let bits_per_value = FileDecompressor::bits_per_value(src)?;
     let mut prev_src_len_val = src.len();
     let prev_src_len = &mut prev_src_len_val;
     let (fd, mut src) = FileDecompressor::new(src)?;
@@ -141,6 +283,7 @@ impl<T: PcoNumber> InspectHandler for CoreHandlerImpl<T> {
     let mut page_size = 0;
     let mut footer_size = 0;
     let mut chunk_ns = Vec::new();
+    let mut chunk_sizes = Vec::new();
     let mut metas = Vec::new();
     let mut void = Vec::new();
     loop {
@@ -150,7 +293,9 @@ impl<T: PcoNumber> InspectHandler for CoreHandlerImpl<T> {
         MaybeChunkDecompressor::Some(cd) => {
           chunk_ns.push(cd.n());
           metas.push(cd.meta().clone());
-          meta_size += measure_bytes_read(cd.into_src(), prev_src_len);
+          let chunk_meta_size = measure_bytes_read(cd.into_src(), prev_src_len);
+          meta_size += chunk_meta_size;
+          chunk_sizes.push(chunk_meta_size);
         }
         MaybeChunkDecompressor::EndOfData(rest) => {
           src = rest;
@@ -164,7 +309,9 @@ impl<T: PcoNumber> InspectHandler for CoreHandlerImpl<T> {
           void.resize(cd.n(), T::default());
           let _ = cd.decompress(&mut void)?;
           src = cd.into_src();
-          page_size += measure_bytes_read(src, prev_src_len);
+          let chunk_page_size = measure_bytes_read(src, prev_src_len);
+          page_size += chunk_page_size;
+          *chunk_sizes.last_mut().unwrap() += chunk_page_size;
         }
         _ => panic!("unreachable"),
       }
@@ -175,6 +322,33 @@ impl<T: PcoNumber> InspectHandler for CoreHandlerImpl<T> {
     let compressed_size = header_size + meta_size + page_size + footer_size;
     let unknown_trailing_bytes = src.len();
 
+    if opt.stats_json {
+      let chunks = metas
+        .iter()
+        .enumerate()
+        .map(|(idx, meta)| ChunkStats {
+          n: chunk_ns[idx],
+          mode: mode_name(&meta.mode),
+          delta_encoding: delta_encoding_name(&meta.delta_encoding),
+          delta_order: delta_order(&meta.delta_encoding),
+          compressed_bytes: chunk_sizes[idx],
+          latent_vars: build_latent_var_stats(meta),
+        })
+        .collect();
+      let stats = StatsJson {
+        version: STATS_JSON_VERSION,
+        data_type: utils::dtype_name::<T>(),
+        format_version: fd.format_version(),
+        lossless: fd.is_lossless(),
+        n,
+        n_chunks: metas.len(),
+        compressed_bytes: compressed_size,
+        chunks,
+      };
+      println!("{}", serde_json::to_string_pretty(&stats)?);
+      return Ok(());
+    }
+
     let mut chunks = Vec::new();
     for (idx, meta) in metas.iter().enumerate() {
       let latent_vars = build_latent_var_summaries::<T>(meta);
@@ -183,6 +357,7 @@ impl<T: PcoNumber> InspectHandler for CoreHandlerImpl<T> {
         n: chunk_ns[idx],
         mode: format!("{:?}", meta.mode),
         delta_encoding: format!("{:?}", meta.delta_encoding),
+        constant_after_delta: meta.is_constant_after_delta(),
         latent_vars,
       });
     }
@@ -191,11 +366,13 @@ impl<T: PcoNumber> InspectHandler for CoreHandlerImpl<T> {
       filename: opt.path.to_str().unwrap().to_string(),
       data_type: utils::dtype_name::<T>(),
       format_version: fd.format_version(),
+      lossless: fd.is_lossless(),
       n,
       n_chunks: metas.len(),
       uncompressed_size,
       compressed: CompressionSummary {
         ratio: uncompressed_size as f64 / compressed_size as f64,
+        bits_per_value,
         total_size: compressed_size,
         header_size,
         meta_size,
@@ -203,6 +380,9 @@ impl<T: PcoNumber> InspectHandler for CoreHandlerImpl<T> {
         footer_size,
         unknown_trailing_bytes,
       },
+      manifest: manifest_summary(&fd),
+      source: source_summary(&fd),
+      fixed_range: fixed_range_summary(&fd),
       chunks,
     };
 
@@ -7,7 +7,10 @@ use clap::Parser;
 use pco::standalone::{FileDecompressor, MaybeChunkDecompressor};
 
 use crate::core_handlers;
-use crate::inspect::handler::{CompressionSummary, Output};
+use crate::inspect::handler::{
+  fixed_range_summary, manifest_summary, source_summary, CompressionSummary, Output, StatsJson,
+  STATS_JSON_VERSION,
+};
 use crate::utils;
 
 pub mod handler;
@@ -15,6 +18,11 @@ pub mod handler;
 /// Print metadata about a standalone .pco file.
 #[derive(Clone, Debug, Parser)]
 pub struct InspectOpt {
+  /// Print a versioned JSON document of stats instead of the human-readable
+  /// summary.
+  #[arg(long)]
+  pub stats_json: bool,
+
   pub path: PathBuf,
 }
 
@@ -28,15 +36,32 @@ fn trivial_inspect(opt: &InspectOpt, src: &[u8]) -> Result<()> {
     MaybeChunkDecompressor::EndOfData(src) => src,
   };
 
+  if opt.stats_json {
+    let stats = StatsJson {
+      version: STATS_JSON_VERSION,
+      data_type: "<none>".to_string(),
+      format_version: fd.format_version(),
+      lossless: fd.is_lossless(),
+      n: 0,
+      n_chunks: 0,
+      compressed_bytes: start_len - src.len(),
+      chunks: Vec::new(),
+    };
+    println!("{}", serde_json::to_string_pretty(&stats)?);
+    return Ok(());
+  }
+
   let summary = Output {
     filename: opt.path.to_str().unwrap().to_string(),
     data_type: "<none>".to_string(),
     format_version: fd.format_version(),
+    lossless: fd.is_lossless(),
     n: 0,
     n_chunks: 0,
     uncompressed_size: 0,
     compressed: CompressionSummary {
       ratio: 0.0,
+      bits_per_value: f64::NAN,
       total_size: start_len - src.len(),
       header_size,
       meta_size: 0,
@@ -44,6 +69,9 @@ fn trivial_inspect(opt: &InspectOpt, src: &[u8]) -> Result<()> {
       footer_size: 1,
       unknown_trailing_bytes: src.len(),
     },
+    manifest: manifest_summary(&fd),
+    source: source_summary(&fd),
+    fixed_range: fixed_range_summary(&fd),
     chunks: Vec::new(),
   };
   println!("{}", toml::to_string_pretty(&summary)?);
@@ -0,0 +1,137 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use arrow::datatypes::{DataType, Field, TimeUnit};
+use serde::{Deserialize, Serialize};
+
+use pco::data_types::{Latent, Number, NumberType};
+
+use crate::utils;
+
+// A .pco file only knows its number type, so columns whose Arrow type carries
+// more meaning (like timestamps) get that meaning stored alongside the .pco
+// file in this file. Outputs that can represent it, like Parquet, restore it.
+pub fn sidecar_path(pco_path: &Path) -> PathBuf {
+  let mut path = OsString::from(pco_path);
+  path.push(".dtype.json");
+  PathBuf::from(path)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum LogicalType {
+  Timestamp {
+    unit: String,
+    timezone: Option<String>,
+  },
+  Date32,
+  Date64,
+  // Unicode scalar values stored as u32 code points
+  Char,
+}
+
+// Arrow has no char type, so we accept UInt32 fields marked with this
+// extension name.
+const CHAR_EXTENSION_NAME: &str = "char";
+const EXTENSION_NAME_KEY: &str = "ARROW:extension:name";
+
+impl LogicalType {
+  pub fn from_arrow(field: &Field) -> Option<Self> {
+    let res = match field.data_type() {
+      DataType::UInt32
+        if field.metadata().get(EXTENSION_NAME_KEY).map(String::as_str)
+          == Some(CHAR_EXTENSION_NAME) =>
+      {
+        LogicalType::Char
+      }
+      DataType::Timestamp(unit, timezone) => {
+        let unit = match unit {
+          TimeUnit::Second => "s",
+          TimeUnit::Millisecond => "ms",
+          TimeUnit::Microsecond => "us",
+          TimeUnit::Nanosecond => "ns",
+        };
+        LogicalType::Timestamp {
+          unit: unit.to_string(),
+          timezone: timezone.as_ref().map(|tz| tz.to_string()),
+        }
+      }
+      DataType::Date32 => LogicalType::Date32,
+      DataType::Date64 => LogicalType::Date64,
+      _ => return None,
+    };
+    Some(res)
+  }
+}
+
+/// Writes the sidecar next to `pco_path` if `field` carries more meaning than
+/// its number type.
+pub fn write_sidecar(pco_path: &Path, field: &Field, overwrite: bool) -> Result<()> {
+  if let Some(logical_type) = LogicalType::from_arrow(field) {
+    utils::create_output_file(&sidecar_path(pco_path), overwrite)?
+      .write_all(&serde_json::to_vec(&logical_type)?)?;
+  }
+  Ok(())
+}
+
+pub fn read_sidecar(pco_path: &Path) -> Result<Option<LogicalType>> {
+  let path = sidecar_path(pco_path);
+  if !path.exists() {
+    return Ok(None);
+  }
+  Ok(Some(serde_json::from_slice(&fs::read(
+    path,
+  )?)?))
+}
+
+/// Checks that decompressed numbers are valid for their logical type, e.g.
+/// that chars are Unicode scalar values rather than surrogates.
+pub fn validate<T: Number>(nums: &[T], logical_type: Option<&LogicalType>) -> Result<()> {
+  if logical_type != Some(&LogicalType::Char) {
+    return Ok(());
+  }
+
+  if NumberType::from_descriminant(T::NUMBER_TYPE_BYTE) != Some(NumberType::U32) {
+    return Err(anyhow!(
+      "char columns must be stored as u32 code points"
+    ));
+  }
+  for &num in nums {
+    let code_point = num.to_latent_ordered().to_u64();
+    if char::from_u32(code_point as u32).is_none() {
+      return Err(anyhow!(
+        "decompressed code point {:#x} is not a valid char; the file may be corrupt",
+        code_point,
+      ));
+    }
+  }
+  Ok(())
+}
+
+/// Returns the annotation to put after a Parquet column's physical type so
+/// that readers see the original dtype, e.g. `(TIMESTAMP(MILLIS,true))`.
+pub fn parquet_annotation(number_type: NumberType, logical_type: Option<&LogicalType>) -> String {
+  let annotation = match (number_type, logical_type) {
+    (NumberType::I64, Some(LogicalType::Timestamp { unit, timezone })) => {
+      let unit = match unit.as_str() {
+        "ms" => "MILLIS",
+        "us" => "MICROS",
+        "ns" => "NANOS",
+        // Parquet has no second-precision timestamp, so seconds stay plain
+        // integers
+        _ => return String::new(),
+      };
+      format!("TIMESTAMP({},{})", unit, timezone.is_some())
+    }
+    (NumberType::I32, Some(LogicalType::Date32)) => "DATE".to_string(),
+    (NumberType::I16, _) => "INTEGER(16,true)".to_string(),
+    (NumberType::U16, _) => "INTEGER(16,false)".to_string(),
+    (NumberType::U32, _) => "INTEGER(32,false)".to_string(),
+    (NumberType::U64, _) => "INTEGER(64,false)".to_string(),
+    _ => return String::new(),
+  };
+  format!("({})", annotation)
+}
@@ -9,12 +9,16 @@ mod chunk_config_opt;
 mod compress;
 mod core_handlers;
 mod decompress;
+mod diff;
 mod dtypes;
+mod fingerprint;
 mod input;
 mod inspect;
+mod logical_type;
 pub mod num_vec;
 mod opt;
 mod parse;
+mod string_dict;
 mod utils;
 
 fn main() -> Result<()> {
@@ -23,6 +27,8 @@ fn main() -> Result<()> {
     Opt::Bench(bench_opt) => bench::bench(bench_opt),
     Opt::Compress(compress_opt) => compress::compress(compress_opt),
     Opt::Decompress(decompress_opt) => decompress::decompress(decompress_opt),
+    Opt::Diff(diff_opt) => diff::diff(diff_opt),
+    Opt::Fingerprint(fingerprint_opt) => fingerprint::fingerprint(fingerprint_opt),
     Opt::Inspect(inspect_opt) => inspect::inspect(inspect_opt),
   }
 }
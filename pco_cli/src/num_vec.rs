@@ -1,6 +1,11 @@
+use std::sync::Arc;
+
 use crate::dtypes::PcoNumber;
+use crate::utils;
 use anyhow::{anyhow, Result};
+use arrow::array::{ArrayRef, PrimitiveArray};
 use pco::data_types::{Number, NumberType};
+use pco::standalone::simple_decompress;
 use pco::{define_number_enum, match_number_enum};
 
 fn check_equal<T: PcoNumber>(recovered: &[T], original: &[T]) -> Result<()> {
@@ -25,6 +30,13 @@ fn check_equal<T: PcoNumber>(recovered: &[T], original: &[T]) -> Result<()> {
   Ok(())
 }
 
+fn to_arrow<T: PcoNumber>(nums: &[T]) -> ArrayRef {
+  let natives = nums.iter().map(|&x| x.to_arrow_native());
+  Arc::new(PrimitiveArray::<T::Arrow>::from_iter_values(
+    natives,
+  ))
+}
+
 define_number_enum!(
   #[derive()]
   pub NumVec(Vec)
@@ -64,4 +76,24 @@ impl NumVec {
     );
     Ok(())
   }
+
+  pub fn to_arrow(&self) -> ArrayRef {
+    match_number_enum!(
+      self,
+      NumVec<T>(nums) => { to_arrow(nums) }
+    )
+  }
+}
+
+/// Decompresses a standalone .pco file of whichever number type it holds.
+pub fn decompress_dynamic(src: &[u8]) -> Result<NumVec> {
+  let dtype = utils::get_standalone_dtype(src)?
+    .ok_or_else(|| anyhow!("pco file contains no chunks, so its dtype is unknown"))?;
+  let num_vec = match_number_enum!(
+    dtype,
+    NumberType<T> => {
+      NumVec::new(simple_decompress::<T>(src)?).unwrap()
+    }
+  );
+  Ok(num_vec)
 }
@@ -3,10 +3,12 @@ use clap::{Parser, Subcommand};
 use crate::bench::BenchOpt;
 use crate::compress::CompressOpt;
 use crate::decompress::DecompressOpt;
+use crate::diff::DiffOpt;
+use crate::fingerprint::FingerprintOpt;
 use crate::inspect::InspectOpt;
 
 #[derive(Clone, Debug, Parser)]
-#[command(about = "compress, decompress, and inspect .pco files")]
+#[command(about = "compress, decompress, diff, fingerprint, and inspect .pco files")]
 pub struct OptWrapper {
   #[command(subcommand)]
   pub opt: Opt,
@@ -17,5 +19,7 @@ pub enum Opt {
   Bench(BenchOpt),
   Compress(CompressOpt),
   Decompress(DecompressOpt),
+  Diff(DiffOpt),
+  Fingerprint(FingerprintOpt),
   Inspect(InspectOpt),
 }
@@ -1,3 +1,5 @@
+use std::ops::RangeInclusive;
+
 use anyhow::anyhow;
 use arrow::datatypes::{DataType, TimeUnit};
 
@@ -8,6 +10,7 @@ pub fn delta_spec(s: &str) -> anyhow::Result<DeltaSpec> {
     "auto" => DeltaSpec::Auto,
     "none" => DeltaSpec::None,
     "lookback" => DeltaSpec::TryLookback,
+    "xor" => DeltaSpec::TryXor,
     other => {
       let mut parts = other.split('@');
       let name = parts.next().unwrap();
@@ -15,6 +18,7 @@ pub fn delta_spec(s: &str) -> anyhow::Result<DeltaSpec> {
       let value = parts.next().ok_or_else(err)?;
       match name {
         "consecutive" => DeltaSpec::TryConsecutive(value.parse()?),
+        "consecutiveperpage" => DeltaSpec::TryConsecutivePerPage(value.parse()?),
         _ => return Err(err()),
       }
     }
@@ -42,6 +46,16 @@ pub fn mode_spec(s: &str) -> anyhow::Result<ModeSpec> {
   Ok(spec)
 }
 
+// parses an inclusive range like 0:12, or a single level like 5
+pub fn level_range(s: &str) -> anyhow::Result<RangeInclusive<usize>> {
+  let (start, end) = s.split_once(':').unwrap_or((s, s));
+  let range = start.trim().parse()?..=end.trim().parse()?;
+  if range.is_empty() {
+    return Err(anyhow!("invalid level range: {}", s));
+  }
+  Ok(range)
+}
+
 pub fn arrow_dtype(s: &str) -> anyhow::Result<DataType> {
   let name_pairs = [
     ("f16", DataType::Float16),
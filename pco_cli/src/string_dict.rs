@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use arrow::array::{Array, ArrayRef, AsArray, StringArray};
+use arrow::csv::WriterBuilder as CsvWriterBuilder;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+
+use pco::standalone::{simple_compress, simple_decompress};
+
+use crate::compress::CompressOpt;
+use crate::decompress::{DecompressOpt, OutputKind};
+use crate::{input, utils};
+
+// String columns get compressed as a pco file of u32 dictionary indices, with
+// the dictionary of distinct strings stored alongside it in this file.
+pub fn dictionary_path(pco_path: &Path) -> PathBuf {
+  let mut path = OsString::from(pco_path);
+  path.push(".dict.json");
+  PathBuf::from(path)
+}
+
+pub fn is_string_dtype(dtype: &DataType) -> bool {
+  matches!(dtype, DataType::Utf8 | DataType::LargeUtf8)
+}
+
+fn extend_indices(
+  array: &ArrayRef,
+  dictionary: &mut Vec<String>,
+  index_by_string: &mut HashMap<String, u32>,
+  indices: &mut Vec<u32>,
+) -> Result<()> {
+  if array.null_count() > 0 {
+    return Err(anyhow!(
+      "string column contains {} nulls, which pco can't represent",
+      array.null_count(),
+    ));
+  }
+
+  let mut push = |s: &str| {
+    let idx = match index_by_string.get(s) {
+      Some(&idx) => idx,
+      None => {
+        let idx = dictionary.len() as u32;
+        dictionary.push(s.to_string());
+        index_by_string.insert(s.to_string(), idx);
+        idx
+      }
+    };
+    indices.push(idx);
+  };
+  match array.data_type() {
+    DataType::Utf8 => array
+      .as_string::<i32>()
+      .iter()
+      .flatten()
+      .for_each(&mut push),
+    DataType::LargeUtf8 => array
+      .as_string::<i64>()
+      .iter()
+      .flatten()
+      .for_each(&mut push),
+    other => return Err(anyhow!("not a string dtype: {:?}", other)),
+  }
+  Ok(())
+}
+
+pub fn compress(opt: &CompressOpt, schema: &Schema, col_idx: usize) -> Result<()> {
+  let mut dictionary = Vec::new();
+  let mut index_by_string = HashMap::new();
+  let mut indices = Vec::new();
+  for array_result in input::new_column_reader(schema, col_idx, &opt.input_file)? {
+    extend_indices(
+      &array_result?,
+      &mut dictionary,
+      &mut index_by_string,
+      &mut indices,
+    )?;
+  }
+
+  let config = opt.chunk_config.to_config::<u32>();
+  let compressed = simple_compress(&indices, &config)?;
+  // check both paths before creating either, so that a collision doesn't
+  // leave a .pco file without its dictionary
+  let dictionary_path = dictionary_path(&opt.path);
+  if !opt.overwrite {
+    for path in [&opt.path, &dictionary_path] {
+      if path.exists() {
+        return Err(anyhow!(
+          "{} already exists; consider --overwrite",
+          path.display(),
+        ));
+      }
+    }
+  }
+  let mut file = utils::create_output_file(&opt.path, opt.overwrite)?;
+  let mut dictionary_file = utils::create_output_file(&dictionary_path, opt.overwrite)?;
+  file.write_all(&compressed)?;
+  dictionary_file.write_all(&serde_json::to_vec(&dictionary)?)?;
+  Ok(())
+}
+
+pub fn decompress(opt: &DecompressOpt, dictionary_path: &Path) -> Result<()> {
+  if !matches!(opt.output_kind(), OutputKind::Txt) {
+    return Err(anyhow!(
+      "only txt output is supported for string columns"
+    ));
+  }
+
+  let dictionary: Vec<String> = serde_json::from_slice(&fs::read(dictionary_path)?)?;
+  let mut indices = simple_decompress::<u32>(&fs::read(&opt.path)?)?;
+  if let Some(limit) = opt.limit {
+    indices.truncate(limit);
+  }
+  let strings = indices
+    .iter()
+    .map(|&idx| {
+      dictionary
+        .get(idx as usize)
+        .map(|s| s.as_str())
+        .ok_or_else(|| anyhow!("dictionary index {} is out of range", idx))
+    })
+    .collect::<Result<Vec<_>>>()?;
+
+  let schema = Schema::new(vec![Field::new("c0", DataType::Utf8, false)]);
+  let c0 = StringArray::from(strings);
+  let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(c0)])?;
+  let mut stdout_bytes = Vec::<u8>::new();
+  {
+    let mut writer = CsvWriterBuilder::new()
+      .with_header(false)
+      .build(&mut stdout_bytes);
+    writer.write(&batch)?;
+  }
+  print!("{}", String::from_utf8(stdout_bytes)?);
+  Ok(())
+}
@@ -1,11 +1,15 @@
 use std::any;
+use std::fs::{File, OpenOptions};
+use std::io::Read;
+use std::path::Path;
 
 use anyhow::{anyhow, Result};
 use arrow::array::{ArrayRef, AsArray};
 use arrow::datatypes::Schema;
 
+use better_io::BetterBufReader;
 use pco::data_types::{Number, NumberType};
-use pco::standalone::FileDecompressor;
+use pco::standalone::{FileDecompressor, SourceInfo};
 
 use crate::dtypes::ArrowNumber;
 
@@ -20,6 +24,40 @@ pub fn get_standalone_dtype(initial_bytes: &[u8]) -> Result<Option<NumberType>>
   }
 }
 
+// Reads just enough of a standalone file to learn its number type.
+pub fn peek_standalone_dtype(path: &Path) -> Result<Option<NumberType>> {
+  // the header may also have source info of 2 length bytes and 2 strings
+  let max_header_size =
+    pco::standalone::guarantee::header_size() + 2 * (1 + SourceInfo::MAX_STRING_LEN);
+  let mut initial_bytes = Vec::with_capacity(max_header_size + 1);
+  OpenOptions::new()
+    .read(true)
+    .open(path)?
+    .take(max_header_size as u64 + 1)
+    .read_to_end(&mut initial_bytes)?;
+  get_standalone_dtype(&initial_bytes)
+}
+
+// Reads a standalone file's header to learn where its numbers came from, if
+// recorded.
+pub fn read_standalone_source_info(path: &Path) -> Result<Option<SourceInfo>> {
+  let file = OpenOptions::new().read(true).open(path)?;
+  let (fd, _) = FileDecompressor::new(BetterBufReader::from_read_simple(file))?;
+  Ok(fd.source_info().cloned())
+}
+
+pub fn create_output_file(path: &Path, overwrite: bool) -> Result<File> {
+  let mut open_options = OpenOptions::new();
+  open_options.write(true);
+  if overwrite {
+    open_options.create(true);
+    open_options.truncate(true);
+  } else {
+    open_options.create_new(true);
+  }
+  Ok(open_options.open(path)?)
+}
+
 pub fn find_col_idx(
   schema: &Schema,
   col_idx: Option<usize>,
@@ -64,11 +64,25 @@ impl PyDeltaSpec {
     Self(DeltaSpec::TryConsecutive(order))
   }
 
+  /// :returns: a DeltaSpec that lets each page use its own delta encoding
+  ///   order, up to the specified one.
+  #[staticmethod]
+  fn try_consecutive_per_page(max_order: usize) -> Self {
+    Self(DeltaSpec::TryConsecutivePerPage(max_order))
+  }
+
   /// :returns: a DeltaSpec that tries to use delta lookbacks, if possible.
   #[staticmethod]
   fn try_lookback() -> Self {
     Self(DeltaSpec::TryLookback)
   }
+
+  /// :returns: a (experimental) DeltaSpec that tries to XOR consecutive
+  ///   numbers' bits, if possible.
+  #[staticmethod]
+  fn try_xor() -> Self {
+    Self(DeltaSpec::TryXor)
+  }
 }
 
 #[pyclass(name = "PagingSpec")]
@@ -91,6 +105,12 @@ impl PyPagingSpec {
   fn exact_page_sizes(sizes: Vec<usize>) -> Self {
     Self(PagingSpec::Exact(sizes))
   }
+
+  /// :returns: a PagingSpec putting all numbers into a single page.
+  #[staticmethod]
+  fn single_page() -> Self {
+    Self(PagingSpec::SinglePage)
+  }
 }
 
 #[pyclass(get_all, set_all, name = "ChunkConfig")]
@@ -5,7 +5,7 @@ use numpy::{
 };
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
-use pyo3::types::{PyBytes, PyModule, PyNone};
+use pyo3::types::{PyBytes, PyModule};
 use pyo3::{pyfunction, wrap_pyfunction, Bound, PyObject, PyResult, Python};
 
 use pco::data_types::{Number, NumberType};
@@ -130,12 +130,13 @@ pub fn register(m: &Bound<PyModule>) -> PyResult<()> {
               .allow_threads(|| file_decompressor.simple_decompress::<T>(src))
               .map_err(pco_err_to_py)?
               .into_pyarray(py)
-              .to_object(py);
+              .into_any()
+              .unbind();
             Ok(res)
           }
         )
       }
-      Termination => Ok(PyNone::get(py).to_object(py)),
+      Termination => Ok(py.None()),
       Unknown(other) => Err(PyRuntimeError::new_err(format!(
         "unrecognized number type byte {:?}",
         other,
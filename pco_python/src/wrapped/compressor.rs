@@ -40,11 +40,16 @@ impl PyFc {
 
 #[pymethods]
 impl PyFc {
+  /// :param format_version: the format version to write. Version 4 is
+  ///   needed for the Xor and per-page delta encodings, but pco 0.4.6 and
+  ///   older can't decompress it.
+  ///
   /// :returns: a new FileCompressor.
   #[new]
-  pub fn new() -> PyFc {
+  #[pyo3(signature = (format_version=pco::DEFAULT_FORMAT_VERSION))]
+  pub fn new(format_version: u8) -> PyFc {
     PyFc {
-      inner: FileCompressor::default(),
+      inner: FileCompressor::default().with_format_version(format_version),
     }
   }
 
@@ -42,6 +42,15 @@ def test_round_trip_simple_decompress(length, dtype):
     np.testing.assert_array_equal(data, out)
 
 
+def test_single_page():
+    data = np.arange(1000)
+    compressed = standalone.simple_compress(
+        data, ChunkConfig(paging_spec=PagingSpec.single_page())
+    )
+    out = standalone.simple_decompress(compressed)
+    np.testing.assert_array_equal(data, out)
+
+
 def test_inexact_decompression():
     data = np.random.uniform(size=300)
     compressed = standalone.simple_compress(data, ChunkConfig())
@@ -109,7 +118,12 @@ def test_compression_options():
 
     # this is mostly just to check that there is no error, but these settings
     # should give worse compression than the defaults
-    for delta_spec in [DeltaSpec.try_consecutive(1), DeltaSpec.try_lookback()]:
+    for delta_spec in [
+        DeltaSpec.try_consecutive(1),
+        DeltaSpec.try_consecutive_per_page(2),
+        DeltaSpec.try_lookback(),
+        DeltaSpec.try_xor(),
+    ]:
         compressed = standalone.simple_compress(
             data,
             ChunkConfig(
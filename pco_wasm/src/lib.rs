@@ -0,0 +1,71 @@
+use js_sys::{
+  BigInt64Array, BigUint64Array, Float32Array, Float64Array, Int16Array, Int32Array, Uint16Array,
+  Uint32Array,
+};
+use wasm_bindgen::prelude::*;
+
+use pco::data_types::NumberType;
+use pco::standalone::simple_decompress;
+
+fn parse_dtype(dtype: &str) -> Result<NumberType, JsError> {
+  use NumberType::*;
+
+  let number_type = match dtype {
+    "f16" => F16,
+    "f32" => F32,
+    "f64" => F64,
+    "i16" => I16,
+    "i32" => I32,
+    "i64" => I64,
+    "u16" => U16,
+    "u32" => U32,
+    "u64" => U64,
+    other => {
+      return Err(JsError::new(&format!(
+        "unknown dtype: {}",
+        other
+      )))
+    }
+  };
+  Ok(number_type)
+}
+
+fn decompress_typed(number_type: NumberType, src: &[u8]) -> Result<JsValue, JsError> {
+  use NumberType::*;
+
+  let array: JsValue = match number_type {
+    // JS has no widely supported 16-bit float array, so we return the bits
+    F16 => {
+      let nums = simple_decompress::<half::f16>(src)?;
+      let bits = nums.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+      Uint16Array::from(bits.as_slice()).into()
+    }
+    F32 => Float32Array::from(simple_decompress::<f32>(src)?.as_slice()).into(),
+    F64 => Float64Array::from(simple_decompress::<f64>(src)?.as_slice()).into(),
+    I16 => Int16Array::from(simple_decompress::<i16>(src)?.as_slice()).into(),
+    I32 => Int32Array::from(simple_decompress::<i32>(src)?.as_slice()).into(),
+    I64 => BigInt64Array::from(simple_decompress::<i64>(src)?.as_slice()).into(),
+    U16 => Uint16Array::from(simple_decompress::<u16>(src)?.as_slice()).into(),
+    U32 => Uint32Array::from(simple_decompress::<u32>(src)?.as_slice()).into(),
+    U64 => BigUint64Array::from(simple_decompress::<u64>(src)?.as_slice()).into(),
+    other => {
+      return Err(JsError::new(&format!(
+        "number type {:?} not yet supported in pco_wasm",
+        other
+      )))
+    }
+  };
+  Ok(array)
+}
+
+/// Decompresses a standalone pco file into a JS typed array.
+///
+/// `dtype` must match the file's number type and be one of "f16", "f32",
+/// "f64", "i16", "i32", "i64", "u16", "u32", or "u64".
+/// 64-bit integers are returned as `BigInt64Array`s or `BigUint64Array`s,
+/// and 16-bit floats are returned as a `Uint16Array` of their bits.
+#[wasm_bindgen]
+pub fn decompress(dtype: &str, bytes: &[u8]) -> Result<JsValue, JsError> {
+  let number_type = parse_dtype(dtype)?;
+  decompress_typed(number_type, bytes)
+}
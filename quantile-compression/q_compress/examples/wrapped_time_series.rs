@@ -66,7 +66,8 @@ fn compress_time_series(series: &TimeSeries) -> QCompressResult<Vec<u8>> {
   res.extend(v_compressor.drain_bytes());
 
   let mut idx = 0;
-  for page_idx in 0..((series.len() - 1) / DATA_PAGE_SIZE) {
+  let n_pages = (series.len() - 1) / DATA_PAGE_SIZE;
+  for &page_size in data_page_sizes.iter().take(n_pages) {
     // Each page consists of
     // 1. count
     // 2. timestamp min and max (for fast decompression filtering)
@@ -76,7 +77,6 @@ fn compress_time_series(series: &TimeSeries) -> QCompressResult<Vec<u8>> {
     // 6. values page
 
     // 1.
-    let page_size = data_page_sizes[page_idx];
     res.extend((page_size as u32).to_be_bytes());
 
     // 2.